@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -12,6 +13,10 @@ use thiserror::Error;
 pub enum AuditOperation {
     Ingest,
     Query,
+    /// A durable backup snapshot was written.
+    Snapshot,
+    /// In-memory state was rebuilt from a backup snapshot.
+    Restore,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -54,6 +59,18 @@ impl AuditEvent {
     }
 }
 
+/// Metadata key carrying a stable digest of the audited request.
+pub const REQUEST_FINGERPRINT_KEY: &str = "request_fingerprint";
+
+/// Hex digest of a serialized request, for [`REQUEST_FINGERPRINT_KEY`].
+pub fn request_fingerprint(payload: &str) -> String {
+    Sha256::digest(payload.as_bytes())
+        .iter()
+        .take(16)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 #[derive(Debug, Error)]
 pub enum AuditError {
     #[error("audit sink lock poisoned")]
@@ -136,6 +153,77 @@ impl AuditSink for JsonlAuditSink {
     }
 }
 
+/// Outcomes a [`SamplingAuditSink`] forwards regardless of the sample rate.
+pub const ALWAYS_RECORDED_OUTCOMES: [AuditOutcome; 2] =
+    [AuditOutcome::Denied, AuditOutcome::Failed];
+
+/// Forwards a fraction of successful events to `inner`, and every denied or
+/// failed one.
+///
+/// The keep/drop decision hashes the event's [`REQUEST_FINGERPRINT_KEY`], so
+/// the same request is always sampled the same way. Events without a
+/// fingerprint hash their arrival order instead, so a fraction of each
+/// actor's events is kept rather than all or none.
+pub struct SamplingAuditSink {
+    inner: Arc<dyn AuditSink>,
+    success_sample_rate: f64,
+    /// Arrival counter for events without a fingerprint.
+    unfingerprinted: AtomicU64,
+}
+
+impl SamplingAuditSink {
+    /// `success_sample_rate` is clamped to `[0.0, 1.0]`; NaN keeps every event.
+    pub fn new(inner: Arc<dyn AuditSink>, success_sample_rate: f64) -> Self {
+        let success_sample_rate = if success_sample_rate.is_nan() {
+            1.0
+        } else {
+            success_sample_rate.clamp(0.0, 1.0)
+        };
+        Self {
+            inner,
+            success_sample_rate,
+            unfingerprinted: AtomicU64::new(0),
+        }
+    }
+
+    pub fn success_sample_rate(&self) -> f64 {
+        self.success_sample_rate
+    }
+
+    fn should_forward(&self, event: &AuditEvent) -> bool {
+        if ALWAYS_RECORDED_OUTCOMES.contains(&event.outcome) || self.success_sample_rate >= 1.0 {
+            return true;
+        }
+        let fingerprint = match event.metadata.get(REQUEST_FINGERPRINT_KEY) {
+            Some(fingerprint) => fingerprint.clone(),
+            None => format!(
+                "{:?}#{}",
+                event.operation,
+                self.unfingerprinted.fetch_add(1, Ordering::Relaxed)
+            ),
+        };
+        sample_point(&fingerprint) < self.success_sample_rate
+    }
+}
+
+impl AuditSink for SamplingAuditSink {
+    fn record(&self, event: AuditEvent) -> Result<(), AuditError> {
+        if self.should_forward(&event) {
+            self.inner.record(event)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Position of `fingerprint` in `[0.0, 1.0)`.
+fn sample_point(fingerprint: &str) -> f64 {
+    let digest = Sha256::digest(fingerprint.as_bytes());
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) >> 11) as f64 / (1u64 << 53) as f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +250,78 @@ mod tests {
         assert_eq!(events[1].sequence, 2);
     }
 
+    #[test]
+    fn sampling_sink_at_zero_rate_keeps_only_denied_and_failed() {
+        let inner = Arc::new(InMemoryAuditSink::default());
+        let sink = SamplingAuditSink::new(inner.clone(), 0.0);
+
+        for (index, outcome) in [
+            AuditOutcome::Succeeded,
+            AuditOutcome::Denied,
+            AuditOutcome::Succeeded,
+            AuditOutcome::Failed,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut event = AuditEvent::new(AuditOperation::Query, outcome);
+            event
+                .metadata
+                .insert(REQUEST_FINGERPRINT_KEY.to_string(), index.to_string());
+            sink.record(event).unwrap();
+        }
+
+        let outcomes: Vec<AuditOutcome> = inner
+            .events()
+            .unwrap()
+            .into_iter()
+            .map(|event| event.outcome)
+            .collect();
+        assert_eq!(outcomes, vec![AuditOutcome::Denied, AuditOutcome::Failed]);
+    }
+
+    #[test]
+    fn sampling_sink_decision_is_stable_per_fingerprint() {
+        let inner = Arc::new(InMemoryAuditSink::default());
+        let sink = SamplingAuditSink::new(inner.clone(), 0.5);
+
+        let kept_per_fingerprint: Vec<usize> = (0..32)
+            .map(|index| {
+                let before = inner.events().unwrap().len();
+                for _ in 0..3 {
+                    let mut event = AuditEvent::new(AuditOperation::Query, AuditOutcome::Succeeded);
+                    event.metadata.insert(
+                        REQUEST_FINGERPRINT_KEY.to_string(),
+                        request_fingerprint(&format!("request-{index}")),
+                    );
+                    sink.record(event).unwrap();
+                }
+                inner.events().unwrap().len() - before
+            })
+            .collect();
+
+        assert!(kept_per_fingerprint
+            .iter()
+            .all(|kept| *kept == 0 || *kept == 3));
+        assert!(kept_per_fingerprint.contains(&0));
+        assert!(kept_per_fingerprint.contains(&3));
+    }
+
+    #[test]
+    fn sampling_sink_spreads_events_without_fingerprint() {
+        let inner = Arc::new(InMemoryAuditSink::default());
+        let sink = SamplingAuditSink::new(inner.clone(), 0.5);
+
+        for _ in 0..64 {
+            let mut event = AuditEvent::new(AuditOperation::Ingest, AuditOutcome::Succeeded);
+            event.actor = Some("svc-ingest".to_string());
+            sink.record(event).unwrap();
+        }
+
+        let kept = inner.events().unwrap().len();
+        assert!(kept > 0 && kept < 64, "kept {kept} of 64");
+    }
+
     #[test]
     fn jsonl_sink_writes_operation_and_model_id() {
         let dir = tempdir().unwrap();
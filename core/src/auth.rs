@@ -1,4 +1,5 @@
 use crate::error::{AlayasikiError, ErrorCode};
+use crate::model::Node;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -413,6 +414,13 @@ impl Authorizer {
     }
 }
 
+/// Row-level read check run per node after an action has been authorized,
+/// e.g. limiting analysts to nodes whose metadata classification matches
+/// their attributes.
+pub trait RowAuthorizer: Send + Sync {
+    fn can_read(&self, principal: &Principal, node: &Node) -> bool;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,7 @@
+use crate::error::{AlayasikiError, ErrorCode};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
 
 pub fn deterministic_embedding(text: &str, model_id: &str, dims: usize) -> Vec<f32> {
     let dims = dims.max(1);
@@ -34,6 +37,260 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
     Some(dot / (norm_a * norm_b))
 }
 
+/// Per-dimension terms of [`cosine_similarity`]: `a[i] * b[i] / (|a| * |b|)`.
+/// The terms sum to the similarity. `None` under the same conditions as
+/// [`cosine_similarity`]; all zeros when either vector has zero norm.
+pub fn cosine_contributions(a: &[f32], b: &[f32]) -> Option<Vec<f32>> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return Some(vec![0.0; a.len()]);
+    }
+
+    let norm = norm_a * norm_b;
+    Some(a.iter().zip(b.iter()).map(|(x, y)| x * y / norm).collect())
+}
+
+/// Dimension and version an embedding model id stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingModelSpec {
+    pub dimension: usize,
+    pub version: String,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EmbeddingModelError {
+    #[error("unknown embedding model: {0}")]
+    UnknownModel(String),
+    #[error(
+        "embedding model {model_id} produces {model_dimension}-dim vectors but the repository holds {repository_dimension}-dim vectors"
+    )]
+    DimensionMismatch {
+        model_id: String,
+        model_dimension: usize,
+        repository_dimension: usize,
+    },
+    #[error(
+        "embedding model {model_id} produces {model_dimension}-dim vectors but its projection expects {projection_dimension}-dim input"
+    )]
+    ProjectionDimensionMismatch {
+        model_id: String,
+        model_dimension: usize,
+        projection_dimension: usize,
+    },
+    #[error(
+        "embedding model {model_id} differs from the indexed model {indexed_model_id} and no projection between them is registered"
+    )]
+    ModelMismatch {
+        model_id: String,
+        indexed_model_id: String,
+    },
+}
+
+impl AlayasikiError for EmbeddingModelError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            EmbeddingModelError::UnknownModel(_) => ErrorCode::NotFound,
+            EmbeddingModelError::DimensionMismatch { .. } => ErrorCode::InvalidArgument,
+            EmbeddingModelError::ProjectionDimensionMismatch { .. } => ErrorCode::InvalidArgument,
+            EmbeddingModelError::ModelMismatch { .. } => ErrorCode::InvalidArgument,
+        }
+    }
+}
+
+/// Linear map from one model's embedding space into another's: one row per
+/// target dimension, one column per source dimension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingProjection {
+    rows: Vec<Vec<f32>>,
+}
+
+impl EmbeddingProjection {
+    /// `None` when `rows` is empty or the rows differ in length.
+    pub fn new(rows: Vec<Vec<f32>>) -> Option<Self> {
+        let source_dimension = rows.first()?.len();
+        if source_dimension == 0 || rows.iter().any(|row| row.len() != source_dimension) {
+            return None;
+        }
+        Some(Self { rows })
+    }
+
+    pub fn identity(dimension: usize) -> Self {
+        let dimension = dimension.max(1);
+        let rows = (0..dimension)
+            .map(|row| {
+                (0..dimension)
+                    .map(|col| if row == col { 1.0 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        Self { rows }
+    }
+
+    pub fn source_dimension(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    pub fn target_dimension(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Project `vector`; `None` when its length is not the source dimension.
+    pub fn project(&self, vector: &[f32]) -> Option<Vec<f32>> {
+        if vector.len() != self.source_dimension() {
+            return None;
+        }
+        Some(
+            self.rows
+                .iter()
+                .map(|row| row.iter().zip(vector).map(|(w, x)| w * x).sum())
+                .collect(),
+        )
+    }
+}
+
+/// Maps embedding model ids to the vector dimension and version they produce.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingModelRegistry {
+    models: HashMap<String, EmbeddingModelSpec>,
+    /// Model the repository's vectors were embedded with, when declared.
+    indexed_model_id: Option<String>,
+    /// Projections keyed by `(from_model_id, to_model_id)`.
+    projections: HashMap<(String, String), EmbeddingProjection>,
+}
+
+impl EmbeddingModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers (or replaces) `model_id`.
+    pub fn register(
+        &mut self,
+        model_id: impl Into<String>,
+        dimension: usize,
+        version: impl Into<String>,
+    ) -> &mut Self {
+        self.models.insert(
+            model_id.into(),
+            EmbeddingModelSpec {
+                dimension,
+                version: version.into(),
+            },
+        );
+        self
+    }
+
+    pub fn get(&self, model_id: &str) -> Option<&EmbeddingModelSpec> {
+        self.models.get(model_id)
+    }
+
+    /// Declares the model the repository was indexed with. Queries using a
+    /// different model then need a projection into it.
+    pub fn set_indexed_model(&mut self, model_id: impl Into<String>) -> &mut Self {
+        self.indexed_model_id = Some(model_id.into());
+        self
+    }
+
+    pub fn indexed_model(&self) -> Option<&str> {
+        self.indexed_model_id.as_deref()
+    }
+
+    /// Registers (or replaces) the projection from `from_model_id` vectors
+    /// into `to_model_id`'s space.
+    pub fn register_projection(
+        &mut self,
+        from_model_id: impl Into<String>,
+        to_model_id: impl Into<String>,
+        projection: EmbeddingProjection,
+    ) -> &mut Self {
+        self.projections
+            .insert((from_model_id.into(), to_model_id.into()), projection);
+        self
+    }
+
+    pub fn projection(
+        &self,
+        from_model_id: &str,
+        to_model_id: &str,
+    ) -> Option<&EmbeddingProjection> {
+        self.projections
+            .get(&(from_model_id.to_string(), to_model_id.to_string()))
+    }
+
+    /// Like [`Self::check_dimension`], but when an indexed model is declared
+    /// and `model_id` differs from it, returns the projection into the indexed
+    /// space, whose input must match the model's dimension and whose output
+    /// must match the repository dimension, or
+    /// [`EmbeddingModelError::ModelMismatch`] when none is registered.
+    pub fn resolve_query_projection(
+        &self,
+        model_id: &str,
+        repository_dimension: Option<usize>,
+    ) -> Result<Option<&EmbeddingProjection>, EmbeddingModelError> {
+        let Some(indexed_model_id) = self
+            .indexed_model_id
+            .as_deref()
+            .filter(|indexed| *indexed != model_id)
+        else {
+            self.check_dimension(model_id, repository_dimension)?;
+            return Ok(None);
+        };
+        let spec = self
+            .get(model_id)
+            .ok_or_else(|| EmbeddingModelError::UnknownModel(model_id.to_string()))?;
+        let projection = self.projection(model_id, indexed_model_id).ok_or_else(|| {
+            EmbeddingModelError::ModelMismatch {
+                model_id: model_id.to_string(),
+                indexed_model_id: indexed_model_id.to_string(),
+            }
+        })?;
+        if projection.source_dimension() != spec.dimension {
+            return Err(EmbeddingModelError::ProjectionDimensionMismatch {
+                model_id: model_id.to_string(),
+                model_dimension: spec.dimension,
+                projection_dimension: projection.source_dimension(),
+            });
+        }
+        match repository_dimension {
+            Some(dimension) if dimension != projection.target_dimension() => {
+                Err(EmbeddingModelError::DimensionMismatch {
+                    model_id: model_id.to_string(),
+                    model_dimension: projection.target_dimension(),
+                    repository_dimension: dimension,
+                })
+            }
+            _ => Ok(Some(projection)),
+        }
+    }
+
+    /// Resolves `model_id` and, when the repository already holds vectors,
+    /// checks that their dimension matches the model's.
+    pub fn check_dimension(
+        &self,
+        model_id: &str,
+        repository_dimension: Option<usize>,
+    ) -> Result<&EmbeddingModelSpec, EmbeddingModelError> {
+        let spec = self
+            .get(model_id)
+            .ok_or_else(|| EmbeddingModelError::UnknownModel(model_id.to_string()))?;
+        match repository_dimension {
+            Some(dimension) if dimension != spec.dimension => {
+                Err(EmbeddingModelError::DimensionMismatch {
+                    model_id: model_id.to_string(),
+                    model_dimension: spec.dimension,
+                    repository_dimension: dimension,
+                })
+            }
+            _ => Ok(spec),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +308,22 @@ mod tests {
         let b = deterministic_embedding("hello", "embedding-alt-v1", 8);
         assert_ne!(a, b);
     }
+
+    #[test]
+    fn projection_maps_between_dimensions() {
+        let projection =
+            EmbeddingProjection::new(vec![vec![1.0, 0.0, 1.0], vec![0.0, 2.0, 0.0]]).unwrap();
+        assert_eq!(projection.source_dimension(), 3);
+        assert_eq!(projection.target_dimension(), 2);
+        assert_eq!(projection.project(&[1.0, 2.0, 3.0]), Some(vec![4.0, 4.0]));
+        assert_eq!(projection.project(&[1.0, 2.0]), None);
+        assert_eq!(projection.project(&[1.0, 2.0, 3.0, 4.0]), None);
+        assert!(EmbeddingProjection::new(vec![vec![1.0], vec![1.0, 2.0]]).is_none());
+
+        let identity = EmbeddingProjection::identity(3);
+        assert_eq!(
+            identity.project(&[1.0, -2.0, 0.5]),
+            Some(vec![1.0, -2.0, 0.5])
+        );
+    }
 }
@@ -28,6 +28,12 @@ pub struct TenantGovernancePolicy {
     pub residency_region: String,
     pub retention_days: u32,
     pub encryption: EncryptionPolicy,
+    /// Embedding model used for this tenant's ingests that name no model.
+    #[serde(default)]
+    pub default_model_id: Option<String>,
+    /// Embedding models this tenant may ingest with; empty allows any.
+    #[serde(default)]
+    pub allowed_model_ids: Vec<String>,
 }
 
 impl TenantGovernancePolicy {
@@ -41,9 +47,30 @@ impl TenantGovernancePolicy {
             residency_region: residency_region.into(),
             retention_days,
             encryption: EncryptionPolicy::disabled(),
+            default_model_id: None,
+            allowed_model_ids: Vec::new(),
         }
     }
 
+    pub fn with_default_model_id(
+        mut self,
+        model_id: impl Into<String>,
+    ) -> Result<Self, GovernanceError> {
+        self.default_model_id = Some(model_id.into());
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_allowed_model_ids<I, S>(mut self, model_ids: I) -> Result<Self, GovernanceError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_model_ids = model_ids.into_iter().map(Into::into).collect();
+        self.validate()?;
+        Ok(self)
+    }
+
     pub fn with_encryption(
         mut self,
         encryption: EncryptionPolicy,
@@ -77,9 +104,28 @@ impl TenantGovernancePolicy {
             }
         }
 
+        if let Some(model_id) = &self.default_model_id {
+            self.ensure_model_allowed(model_id)?;
+        }
+
         Ok(())
     }
 
+    pub fn ensure_model_allowed(&self, model_id: &str) -> Result<(), GovernanceError> {
+        if self.allowed_model_ids.is_empty()
+            || self
+                .allowed_model_ids
+                .iter()
+                .any(|allowed| allowed == model_id)
+        {
+            return Ok(());
+        }
+        Err(GovernanceError::ModelNotAllowed {
+            tenant: self.tenant.clone(),
+            model_id: model_id.to_string(),
+        })
+    }
+
     pub fn ensure_residency(&self, region: Option<&str>) -> Result<(), GovernanceError> {
         let tenant = self.tenant.clone();
         let Some(actual_region) = region.map(str::trim).filter(|region| !region.is_empty()) else {
@@ -125,6 +171,8 @@ pub enum GovernanceError {
     },
     #[error("kms key id is required when at-rest encryption is enabled")]
     MissingKmsKeyId,
+    #[error("embedding model {model_id} is not allowed for tenant {tenant}")]
+    ModelNotAllowed { tenant: String, model_id: String },
     #[error("governance policy store lock poisoned")]
     PolicyStorePoisoned,
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +19,23 @@ pub enum IngestionRequest {
         idempotency_key: Option<String>,
         model_id: Option<String>,
     },
+    /// Chunks whose embeddings were computed outside the pipeline. They are
+    /// stored as-is: no chunking and no re-embedding. `metadata` applies to
+    /// every chunk and is overridden by a chunk's own keys.
+    PreEmbedded {
+        chunks: Vec<PreEmbeddedChunk>,
+        metadata: HashMap<String, String>,
+        idempotency_key: Option<String>,
+        model_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreEmbeddedChunk {
+    pub content: String,
+    pub embedding: Vec<f32>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
 impl IngestionRequest {
@@ -46,6 +64,15 @@ impl IngestionRequest {
         }
     }
 
+    pub fn pre_embedded(chunks: Vec<PreEmbeddedChunk>, metadata: HashMap<String, String>) -> Self {
+        Self::PreEmbedded {
+            chunks,
+            metadata,
+            idempotency_key: None,
+            model_id: None,
+        }
+    }
+
     pub fn idempotency_key(&self) -> Option<&str> {
         match self {
             IngestionRequest::Text {
@@ -54,6 +81,9 @@ impl IngestionRequest {
             IngestionRequest::File {
                 idempotency_key, ..
             } => idempotency_key.as_deref(),
+            IngestionRequest::PreEmbedded {
+                idempotency_key, ..
+            } => idempotency_key.as_deref(),
         }
     }
 
@@ -61,6 +91,7 @@ impl IngestionRequest {
         match self {
             IngestionRequest::Text { model_id, .. } => model_id.as_deref(),
             IngestionRequest::File { model_id, .. } => model_id.as_deref(),
+            IngestionRequest::PreEmbedded { model_id, .. } => model_id.as_deref(),
         }
     }
 
@@ -68,6 +99,7 @@ impl IngestionRequest {
         match self {
             IngestionRequest::Text { metadata, .. } => metadata,
             IngestionRequest::File { metadata, .. } => metadata,
+            IngestionRequest::PreEmbedded { metadata, .. } => metadata,
         }
     }
 }
@@ -85,25 +117,123 @@ pub trait ContentHash {
 
 impl ContentHash for IngestionRequest {
     fn content_hash(&self) -> String {
-        let mut hasher = Sha256::new();
         match self {
-            IngestionRequest::Text { content, .. } => {
-                hasher.update(b"text");
-                hasher.update(content.as_bytes());
-            }
+            IngestionRequest::Text { content, .. } => text_hash(b"", content),
             IngestionRequest::File {
                 content,
                 mime_type,
                 filename,
                 ..
-            } => {
-                hasher.update(b"file");
-                hasher.update(mime_type.as_bytes());
-                hasher.update(filename.as_bytes());
-                hasher.update(content);
-            }
+            } => file_hash(b"", filename, mime_type, content),
+            IngestionRequest::PreEmbedded { chunks, .. } => pre_embedded_hash(chunks),
+        }
+    }
+}
+
+/// `domain` is empty for raw content and [`ContentNormalization::hash_domain`]
+/// for normalized content.
+fn text_hash(domain: &[u8], content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(b"text");
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn file_hash(domain: &[u8], filename: &str, mime_type: &str, content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(b"file");
+    hasher.update(mime_type.as_bytes());
+    hasher.update(filename.as_bytes());
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+fn pre_embedded_hash(chunks: &[PreEmbeddedChunk]) -> String {
+    // The embeddings are part of the identity: the same text with a
+    // different vector is a different ingest.
+    let mut hasher = Sha256::new();
+    hasher.update(b"pre_embedded");
+    for chunk in chunks {
+        hasher.update((chunk.content.len() as u64).to_le_bytes());
+        hasher.update(chunk.content.as_bytes());
+        hasher.update((chunk.embedding.len() as u64).to_le_bytes());
+        for value in &chunk.embedding {
+            hasher.update(value.to_le_bytes());
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Normalization applied to content before it is hashed for idempotency, so
+/// trivially different copies of a document dedupe. Stored content is never
+/// changed. The default leaves content untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ContentNormalization {
+    /// Trim the ends and collapse every internal whitespace run to one space.
+    #[serde(default)]
+    pub collapse_whitespace: bool,
+    #[serde(default)]
+    pub lowercase: bool,
+}
+
+impl ContentNormalization {
+    pub fn is_identity(&self) -> bool {
+        !self.collapse_whitespace && !self.lowercase
+    }
+
+    /// Hashed ahead of normalized content, so a normalized hash never equals
+    /// the raw hash of a document that happens to be in normal form already.
+    fn hash_domain(&self) -> String {
+        format!(
+            "normalized:collapse_whitespace={},lowercase={};",
+            self.collapse_whitespace, self.lowercase
+        )
+    }
+
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = Cow::Borrowed(text);
+        if self.collapse_whitespace {
+            out = Cow::Owned(out.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        if self.lowercase {
+            out = Cow::Owned(out.to_lowercase());
+        }
+        out
+    }
+}
+
+impl IngestionRequest {
+    /// [`ContentHash::content_hash`] of the request after `normalization`.
+    /// Text requests and `text/*` files are normalized, in a hash domain of
+    /// their own per set of flags; other files and pre-embedded chunks hash
+    /// as-is.
+    pub fn content_hash_normalized(&self, normalization: ContentNormalization) -> String {
+        if normalization.is_identity() {
+            return self.content_hash();
+        }
+        match self {
+            IngestionRequest::Text { content, .. } => text_hash(
+                normalization.hash_domain().as_bytes(),
+                &normalization.apply(content),
+            ),
+            IngestionRequest::File {
+                filename,
+                content,
+                mime_type,
+                ..
+            } if mime_type.starts_with("text/") => match std::str::from_utf8(content) {
+                Ok(text) => file_hash(
+                    normalization.hash_domain().as_bytes(),
+                    filename,
+                    mime_type,
+                    normalization.apply(text).as_bytes(),
+                ),
+                Err(_) => self.content_hash(),
+            },
+            _ => self.content_hash(),
         }
-        format!("{:x}", hasher.finalize())
     }
 }
 
@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Cooperative cancellation flag for long-running ingests. Clones share the
+/// same flag, so one handle can be kept by the caller (e.g. a shutdown hook)
+/// while another is passed to [`IngestionPipeline::ingest_cancellable`].
+///
+/// [`IngestionPipeline::ingest_cancellable`]: crate::processor::IngestionPipeline::ingest_cancellable
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,142 @@
+//! Chunk id assignment strategies for the ingestion pipeline.
+//!
+//! Node ids are 64-bit, so any content-derived scheme can collide. The pipeline
+//! treats an id as colliding when it is already taken in the current batch or by
+//! a stored node that came from different content (a different `content_hash`).
+//! Strategies that opt into probing (the hash-based default) are then asked for
+//! a new id with an increasing salt; other strategies keep their id and the
+//! existing node is overwritten, which is what callers supplying stable ids
+//! across re-ingests want.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Inputs available to a [`ChunkIdStrategy`] when naming one chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkIdContext<'a> {
+    pub content_hash: &'a str,
+    /// Tenant the chunk is scoped to, when idempotency is tenant-scoped.
+    pub tenant: Option<&'a str>,
+    pub index: u64,
+    pub content: &'a str,
+    pub metadata: &'a HashMap<String, String>,
+}
+
+pub trait ChunkIdStrategy: Send + Sync {
+    /// Returns the id for a chunk. `salt` is `0` on the first attempt and is
+    /// incremented for each collision probe.
+    fn chunk_id(&self, ctx: &ChunkIdContext<'_>, salt: u32) -> u64;
+
+    /// Whether the pipeline should probe with a new salt when the id collides.
+    fn probe_on_collision(&self) -> bool {
+        false
+    }
+}
+
+/// Default strategy: the first 8 bytes of `sha256(content_hash || index)`, with
+/// the tenant (when scoped) and the salt (on collision probes) mixed in. Unscoped
+/// chunks at salt `0` reproduce the historical ids.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashChunkIdStrategy;
+
+impl ChunkIdStrategy for HashChunkIdStrategy {
+    fn chunk_id(&self, ctx: &ChunkIdContext<'_>, salt: u32) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(ctx.content_hash.as_bytes());
+        hasher.update(ctx.index.to_le_bytes());
+        if let Some(tenant) = ctx.tenant {
+            hasher.update(b"tenant");
+            hasher.update(tenant.as_bytes());
+        }
+        if salt > 0 {
+            hasher.update(salt.to_le_bytes());
+        }
+        let digest = hasher.finalize();
+        u64::from_le_bytes([
+            digest[0], digest[1], digest[2], digest[3], digest[4], digest[5], digest[6], digest[7],
+        ])
+    }
+
+    fn probe_on_collision(&self) -> bool {
+        true
+    }
+}
+
+/// Hands out ids from a monotonically increasing counter.
+///
+/// Ids are unique within the process only; pick `start` above any id already
+/// persisted when reopening a repository.
+#[derive(Debug)]
+pub struct SequenceChunkIdStrategy {
+    next: AtomicU64,
+}
+
+impl SequenceChunkIdStrategy {
+    pub fn new(start: u64) -> Self {
+        Self {
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl ChunkIdStrategy for SequenceChunkIdStrategy {
+    fn chunk_id(&self, _ctx: &ChunkIdContext<'_>, _salt: u32) -> u64 {
+        self.next.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// Delegates id assignment to a user-provided closure.
+pub struct FnChunkIdStrategy<F> {
+    f: F,
+}
+
+impl<F> FnChunkIdStrategy<F>
+where
+    F: Fn(&ChunkIdContext<'_>) -> u64 + Send + Sync,
+{
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F> ChunkIdStrategy for FnChunkIdStrategy<F>
+where
+    F: Fn(&ChunkIdContext<'_>) -> u64 + Send + Sync,
+{
+    fn chunk_id(&self, ctx: &ChunkIdContext<'_>, _salt: u32) -> u64 {
+        (self.f)(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx<'a>(metadata: &'a HashMap<String, String>) -> ChunkIdContext<'a> {
+        ChunkIdContext {
+            content_hash: "abc",
+            tenant: None,
+            index: 3,
+            content: "chunk",
+            metadata,
+        }
+    }
+
+    #[test]
+    fn hash_strategy_is_stable_and_salt_changes_id() {
+        let metadata = HashMap::new();
+        let strategy = HashChunkIdStrategy;
+        let first = strategy.chunk_id(&ctx(&metadata), 0);
+        assert_eq!(first, strategy.chunk_id(&ctx(&metadata), 0));
+        assert_ne!(first, strategy.chunk_id(&ctx(&metadata), 1));
+    }
+
+    #[test]
+    fn sequence_strategy_counts_up_from_start() {
+        let metadata = HashMap::new();
+        let strategy = SequenceChunkIdStrategy::new(100);
+        assert_eq!(strategy.chunk_id(&ctx(&metadata), 0), 100);
+        assert_eq!(strategy.chunk_id(&ctx(&metadata), 0), 101);
+    }
+}
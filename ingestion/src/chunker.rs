@@ -1,11 +1,18 @@
 use alayasiki_core::ingest::Chunk;
 use std::collections::HashMap;
+use std::ops::Range;
 use text_splitter::TextSplitter;
 
 #[derive(Debug, Clone)]
 pub struct ChunkingConfig {
     pub max_chars: usize,
     pub overlap_chars: usize,
+    /// Fewest chunks a document may produce; ingesting content that yields
+    /// fewer (e.g. blank text) fails with `ExtractionFailed`.
+    pub min_chunks: usize,
+    /// Most chunks a document may produce. Adjacent pieces are merged, so
+    /// chunks may exceed `max_chars` once the cap is reached.
+    pub max_chunks: Option<usize>,
 }
 
 impl Default for ChunkingConfig {
@@ -13,6 +20,8 @@ impl Default for ChunkingConfig {
         Self {
             max_chars: 1000,
             overlap_chars: 100,
+            min_chunks: 1,
+            max_chunks: None,
         }
     }
 }
@@ -20,6 +29,14 @@ impl Default for ChunkingConfig {
 use std::future::Future;
 use std::pin::Pin;
 
+/// Chunk metadata key holding the byte offset where the chunk's own text
+/// starts in the chunked document, i.e. the text after PII redaction (not the
+/// raw input). Overlap carried over from the previous chunk is not included.
+pub const DOC_CHAR_START_KEY: &str = "doc_char_start";
+/// Chunk metadata key holding the byte offset where the chunk's text ends in
+/// the chunked (redacted) document, exclusive.
+pub const DOC_CHAR_END_KEY: &str = "doc_char_end";
+
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 pub trait Chunker: Send + Sync {
@@ -28,6 +45,11 @@ pub trait Chunker: Send + Sync {
         content: &'a str,
         base_metadata: HashMap<String, String>,
     ) -> BoxFuture<'a, Vec<Chunk>>;
+
+    /// Fewest chunks a document must produce to be ingested.
+    fn min_chunks(&self) -> usize {
+        1
+    }
 }
 
 pub struct SemanticChunker {
@@ -60,14 +82,23 @@ impl Chunker for SemanticChunker {
             let max_chars = self.config.max_chars.max(1);
             let overlap_chars = self.config.overlap_chars.min(max_chars);
 
-            let base_chunks: Vec<String> = self
+            let pieces: Vec<(usize, usize)> = self
                 .splitter
-                .chunks(content, max_chars)
-                .map(|chunk| chunk.to_string())
+                .chunk_indices(content, max_chars)
+                .map(|(offset, chunk)| (offset, chunk.len()))
                 .collect();
+            let base_chunks: Vec<(usize, String)> =
+                merge_groups(pieces.len(), self.config.max_chunks)
+                    .into_iter()
+                    .map(|group| {
+                        let start = pieces[group.start].0;
+                        let (last_offset, last_len) = pieces[group.end - 1];
+                        (start, content[start..last_offset + last_len].to_string())
+                    })
+                    .collect();
 
             let mut out = Vec::with_capacity(base_chunks.len());
-            for (i, text) in base_chunks.iter().enumerate() {
+            for (i, (offset, text)) in base_chunks.iter().enumerate() {
                 let mut chunk_text = text.clone();
                 if overlap_chars > 0 && i > 0 {
                     // Logic simplification: if we need overlap, we just grab prev.
@@ -78,7 +109,7 @@ impl Chunker for SemanticChunker {
                     // 'base_metadata' moved.
 
                     if i > 0 {
-                        let prev = &base_chunks[i - 1];
+                        let prev = &base_chunks[i - 1].1;
                         let overlap = tail_chars(prev, overlap_chars);
                         if !overlap.is_empty() {
                             chunk_text = format!("{}{}", overlap, chunk_text);
@@ -90,6 +121,7 @@ impl Chunker for SemanticChunker {
                 metadata.insert("chunk_index".to_string(), i.to_string());
                 metadata.insert("chunk_chars".to_string(), chunk_text.len().to_string());
                 metadata.insert("chunk_overlap".to_string(), overlap_chars.to_string());
+                insert_doc_offsets(&mut metadata, *offset, text.len());
 
                 out.push(Chunk {
                     content: chunk_text,
@@ -101,6 +133,24 @@ impl Chunker for SemanticChunker {
             out
         })
     }
+
+    fn min_chunks(&self) -> usize {
+        self.config.min_chunks
+    }
+}
+
+/// Splits `count` consecutive pieces into at most `max_groups` contiguous,
+/// evenly sized groups.
+fn merge_groups(count: usize, max_groups: Option<usize>) -> Vec<Range<usize>> {
+    let groups = max_groups.map_or(count, |max| max.max(1).min(count));
+    (0..groups)
+        .map(|group| group * count / groups..(group + 1) * count / groups)
+        .collect()
+}
+
+fn insert_doc_offsets(metadata: &mut HashMap<String, String>, start: usize, len: usize) {
+    metadata.insert(DOC_CHAR_START_KEY.to_string(), start.to_string());
+    metadata.insert(DOC_CHAR_END_KEY.to_string(), (start + len).to_string());
 }
 
 fn tail_chars(text: &str, count: usize) -> String {
@@ -112,3 +162,185 @@ fn tail_chars(text: &str, count: usize) -> String {
     chars.reverse();
     chars.into_iter().collect()
 }
+
+/// Chunker that follows markdown section structure.
+///
+/// Content is split at ATX heading boundaries (`#` .. `######`, ignoring fenced
+/// code blocks) and each chunk records the enclosing heading hierarchy in the
+/// `heading_path` metadata key (e.g. `Guide > Install`), and the innermost
+/// heading's `#` count in `heading_level` (0 before any heading). Sections longer than
+/// `max_chars` are further split by size, and every piece keeps the section's
+/// heading path. `overlap_chars` is not applied, so no chunk carries text from
+/// a neighbouring section.
+pub struct MarkdownChunker {
+    splitter: TextSplitter<text_splitter::Characters>,
+    config: ChunkingConfig,
+}
+
+impl MarkdownChunker {
+    pub fn new(config: ChunkingConfig) -> Self {
+        Self {
+            splitter: TextSplitter::default().with_trim_chunks(true),
+            config,
+        }
+    }
+}
+
+impl Default for MarkdownChunker {
+    fn default() -> Self {
+        Self::new(ChunkingConfig::default())
+    }
+}
+
+/// A heading's `#` count and title.
+type Heading = (usize, String);
+
+struct MarkdownSection<'a> {
+    /// Enclosing headings, outermost first.
+    headings: Vec<Heading>,
+    /// Byte offset of `text` within the chunked document.
+    start: usize,
+    text: &'a str,
+}
+
+impl Chunker for MarkdownChunker {
+    fn chunk<'a>(
+        &'a self,
+        content: &'a str,
+        base_metadata: HashMap<String, String>,
+    ) -> BoxFuture<'a, Vec<Chunk>> {
+        Box::pin(async move {
+            let max_chars = self.config.max_chars.max(1);
+
+            let sections = split_markdown_sections(content);
+            let mut pieces: Vec<(usize, usize, &[Heading])> = Vec::new();
+            for section in &sections {
+                for (offset, piece) in self.splitter.chunk_indices(section.text, max_chars) {
+                    if piece.trim().is_empty() {
+                        continue;
+                    }
+                    pieces.push((section.start + offset, piece.len(), &section.headings));
+                }
+            }
+
+            let mut out = Vec::new();
+            for group in merge_groups(pieces.len(), self.config.max_chunks) {
+                let (start, _, first_headings) = pieces[group.start];
+                let (last_start, last_len, _) = pieces[group.end - 1];
+                let text = &content[start..last_start + last_len];
+                // A merged chunk spanning sections keeps only the headings
+                // they all share.
+                let shared = pieces[group]
+                    .iter()
+                    .map(|(_, _, headings)| {
+                        headings
+                            .iter()
+                            .zip(first_headings)
+                            .take_while(|(a, b)| a == b)
+                            .count()
+                    })
+                    .min()
+                    .unwrap_or(0);
+                let shared_headings = &first_headings[..shared];
+                let heading_path = shared_headings
+                    .iter()
+                    .map(|(_, title)| title.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" > ");
+                let heading_level = shared_headings.last().map_or(0, |(level, _)| *level);
+
+                let mut metadata = base_metadata.clone();
+                metadata.insert("chunk_index".to_string(), out.len().to_string());
+                metadata.insert("chunk_chars".to_string(), text.len().to_string());
+                metadata.insert("heading_level".to_string(), heading_level.to_string());
+                if !heading_path.is_empty() {
+                    metadata.insert("heading_path".to_string(), heading_path);
+                }
+                insert_doc_offsets(&mut metadata, start, text.len());
+                out.push(Chunk {
+                    content: text.to_string(),
+                    metadata,
+                    embedding: None,
+                });
+            }
+
+            out
+        })
+    }
+
+    fn min_chunks(&self) -> usize {
+        self.config.min_chunks
+    }
+}
+
+fn split_markdown_sections(content: &str) -> Vec<MarkdownSection<'_>> {
+    let mut sections = Vec::new();
+    let mut stack: Vec<Heading> = Vec::new();
+    let mut section_start = 0;
+    let mut line_start = 0;
+    let mut in_fence = false;
+
+    for raw_line in content.split_inclusive('\n') {
+        let line = raw_line
+            .strip_suffix('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .unwrap_or(raw_line);
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+
+        let heading = if in_fence {
+            None
+        } else {
+            parse_atx_heading(line)
+        };
+        if let Some((level, title)) = heading {
+            let text = &content[section_start..line_start];
+            if !text.trim().is_empty() {
+                sections.push(MarkdownSection {
+                    headings: stack.clone(),
+                    start: section_start,
+                    text,
+                });
+            }
+            section_start = line_start;
+            while stack.last().is_some_and(|(depth, _)| *depth >= level) {
+                stack.pop();
+            }
+            stack.push((level, title));
+        }
+
+        line_start += raw_line.len();
+    }
+
+    let text = &content[section_start..];
+    if !text.trim().is_empty() {
+        sections.push(MarkdownSection {
+            headings: stack,
+            start: section_start,
+            text,
+        });
+    }
+
+    sections
+}
+
+fn parse_atx_heading(line: &str) -> Option<(usize, String)> {
+    // CommonMark allows up to three leading spaces before the opening `#`s.
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let after = &rest[level..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = after.trim().trim_end_matches('#').trim_end().to_string();
+    Some((level, title))
+}
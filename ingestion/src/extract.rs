@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::io::{Cursor, Read};
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -6,7 +7,9 @@ pub enum ContentKind {
     Text,
     Markdown,
     Json,
+    Csv,
     Pdf,
+    Docx,
     Image,
     Audio,
     Unsupported,
@@ -29,7 +32,11 @@ pub fn detect_content_kind(mime_type: &str, filename: Option<&str>) -> ContentKi
         "text/plain" => ContentKind::Text,
         "text/markdown" => ContentKind::Markdown,
         "application/json" => ContentKind::Json,
+        "text/csv" => ContentKind::Csv,
         "application/pdf" => ContentKind::Pdf,
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            ContentKind::Docx
+        }
         _ => {
             if let Some(name) = filename {
                 let ext = Path::new(name)
@@ -41,7 +48,9 @@ pub fn detect_content_kind(mime_type: &str, filename: Option<&str>) -> ContentKi
                     "txt" => ContentKind::Text,
                     "md" | "markdown" => ContentKind::Markdown,
                     "json" => ContentKind::Json,
+                    "csv" => ContentKind::Csv,
                     "pdf" => ContentKind::Pdf,
+                    "docx" => ContentKind::Docx,
                     "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" => ContentKind::Image,
                     "wav" | "mp3" | "mpeg" | "m4a" | "ogg" | "flac" => ContentKind::Audio,
                     _ => ContentKind::Unsupported,
@@ -74,6 +83,123 @@ pub fn extract_pdf_text(bytes: &[u8]) -> Option<String> {
     }
 }
 
+/// Extracts the body text of a DOCX (Office Open XML) document.
+///
+/// Paragraphs (`<w:p>`) from `word/document.xml` are emitted in document order,
+/// one per line, with their `<w:t>` runs concatenated. Returns `None` when the
+/// archive is malformed, lacks a main document part, or contains no text.
+pub fn extract_docx_text(bytes: &[u8]) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).ok()?;
+    let mut document = archive.by_name("word/document.xml").ok()?;
+    let mut xml = String::new();
+    document.read_to_string(&mut xml).ok()?;
+
+    let text = docx_paragraphs(&xml).join("\n");
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn docx_paragraphs(xml: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current: Option<String> = None;
+    let mut in_text_run = false;
+    // Paragraph properties hold layout, e.g. tab stops declared as `<w:tab/>`.
+    let mut in_properties = false;
+    let mut rest = xml;
+
+    while let Some(open) = rest.find('<') {
+        if in_text_run {
+            if let Some(paragraph) = current.as_mut() {
+                paragraph.push_str(&decode_xml_entities(&rest[..open]));
+            }
+        }
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or("");
+        match name {
+            "w:p" if !self_closing => current = Some(String::new()),
+            "w:p" => paragraphs.push(String::new()),
+            "/w:p" => {
+                if let Some(paragraph) = current.take() {
+                    paragraphs.push(paragraph);
+                }
+            }
+            "w:pPr" => in_properties = !self_closing,
+            "/w:pPr" => in_properties = false,
+            "w:t" => in_text_run = !self_closing,
+            "/w:t" => in_text_run = false,
+            "w:tab" if !in_properties => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.push('\t');
+                }
+            }
+            "w:br" | "w:cr" => {
+                if let Some(paragraph) = current.as_mut() {
+                    paragraph.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    paragraphs
+}
+
+/// Decodes the predefined XML entities and numeric character references
+/// (`&#8217;`, `&#x2019;`); anything unrecognized is kept verbatim.
+fn decode_xml_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let ch = match &rest[1..semi] {
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "amp" => '&',
+                reference => {
+                    let code = match reference.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => reference.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((ch, semi + 1))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 pub fn extract_image_text(metadata: &HashMap<String, String>) -> Option<String> {
     extract_metadata_text(
         metadata,
@@ -111,7 +237,10 @@ fn extract_metadata_text(metadata: &HashMap<String, String>, fields: &[&str]) ->
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_audio_text, extract_image_text};
+    use super::{
+        detect_content_kind, docx_paragraphs, extract_audio_text, extract_docx_text,
+        extract_image_text, ContentKind,
+    };
     use std::collections::HashMap;
 
     #[test]
@@ -148,4 +277,48 @@ mod tests {
 
         assert_eq!(text, "Tokyo pilot launch");
     }
+
+    #[test]
+    fn docx_is_detected_by_mime_or_extension() {
+        assert_eq!(
+            detect_content_kind(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                None
+            ),
+            ContentKind::Docx
+        );
+        assert_eq!(
+            detect_content_kind("application/octet-stream", Some("Report.DOCX")),
+            ContentKind::Docx
+        );
+    }
+
+    #[test]
+    fn docx_paragraphs_preserve_order_and_decode_entities() {
+        let xml = concat!(
+            r#"<w:document><w:body>"#,
+            r#"<w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Q3 </w:t></w:r>"#,
+            r#"<w:r><w:t xml:space="preserve">R&amp;D plan</w:t></w:r></w:p>"#,
+            r#"<w:p/>"#,
+            r#"<w:p><w:pPr><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr>"#,
+            r#"<w:r><w:t>a</w:t><w:tab/><w:t>b</w:t></w:r></w:p>"#,
+            r#"<w:p><w:r><w:t>It&#8217;s &#x201C;done&#x201D; &amp;#38;</w:t></w:r></w:p>"#,
+            r#"</w:body></w:document>"#
+        );
+
+        assert_eq!(
+            docx_paragraphs(xml),
+            vec![
+                "Q3 R&D plan",
+                "",
+                "a\tb",
+                "It\u{2019}s \u{201C}done\u{201D} &#38;"
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_docx_archive_yields_none() {
+        assert!(extract_docx_text(b"not a zip archive").is_none());
+    }
 }
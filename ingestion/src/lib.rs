@@ -1,3 +1,5 @@
+pub mod cancellation;
+pub mod chunk_id;
 pub mod chunker;
 pub mod embedding;
 pub mod policy;
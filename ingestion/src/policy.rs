@@ -8,6 +8,12 @@ pub enum PolicyError {
 
 pub trait ContentPolicy: Send + Sync {
     fn apply(&self, text: &str) -> Result<String, PolicyError>;
+
+    /// Like [`Self::apply`], also returning how many spans were redacted.
+    /// Policies that never redact keep the default count of zero.
+    fn apply_with_redactions(&self, text: &str) -> Result<(String, usize), PolicyError> {
+        Ok((self.apply(text)?, 0))
+    }
 }
 
 pub struct NoOpPolicy;
@@ -34,6 +40,10 @@ impl BasicPolicy {
 
 impl ContentPolicy for BasicPolicy {
     fn apply(&self, text: &str) -> Result<String, PolicyError> {
+        self.apply_with_redactions(text).map(|(text, _)| text)
+    }
+
+    fn apply_with_redactions(&self, text: &str) -> Result<(String, usize), PolicyError> {
         let lowered = text.to_lowercase();
         for word in &self.forbidden_words {
             if lowered.contains(&word.to_lowercase()) {
@@ -44,23 +54,27 @@ impl ContentPolicy for BasicPolicy {
         if self.mask_pii {
             Ok(mask_pii(text))
         } else {
-            Ok(text.to_string())
+            Ok((text.to_string(), 0))
         }
     }
 }
 
-fn mask_pii(text: &str) -> String {
+/// Masked text and the number of masked tokens.
+fn mask_pii(text: &str) -> (String, usize) {
     let mut out = Vec::new();
+    let mut masked = 0;
     for token in text.split_whitespace() {
         if looks_like_email(token) {
             out.push("[EMAIL]".to_string());
+            masked += 1;
         } else if looks_like_phone(token) {
             out.push("[PHONE]".to_string());
+            masked += 1;
         } else {
             out.push(token.to_string());
         }
     }
-    out.join(" ")
+    (out.join(" "), masked)
 }
 
 fn looks_like_email(token: &str) -> bool {
@@ -1,8 +1,12 @@
-use crate::chunker::{Chunker, ChunkingConfig, SemanticChunker};
-use crate::embedding::{DeterministicEmbedder, Embedder};
+use crate::cancellation::CancellationToken;
+use crate::chunk_id::{ChunkIdContext, ChunkIdStrategy, HashChunkIdStrategy};
+use crate::chunker::{
+    Chunker, ChunkingConfig, SemanticChunker, DOC_CHAR_END_KEY, DOC_CHAR_START_KEY,
+};
+use crate::embedding::{BoxFuture, DeterministicEmbedder, Embedder};
 use crate::extract::{
-    detect_content_kind, extract_audio_text, extract_image_text, extract_pdf_text, extract_utf8,
-    ContentKind,
+    detect_content_kind, extract_audio_text, extract_docx_text, extract_image_text,
+    extract_pdf_text, extract_utf8, ContentKind,
 };
 use crate::policy::{ContentPolicy, NoOpPolicy, PolicyError};
 use alayasiki_core::audit::{AuditEvent, AuditOperation, AuditOutcome, AuditSink};
@@ -10,18 +14,22 @@ use alayasiki_core::auth::{
     Action, AuthError, Authorizer, AuthzError, JwtAuthenticator, Principal, ResourceContext,
 };
 use alayasiki_core::governance::{GovernanceError, GovernancePolicyStore};
-use alayasiki_core::ingest::{ContentHash, IngestionRequest};
+use alayasiki_core::ingest::{
+    Chunk, ContentHash, ContentNormalization, IngestionRequest, PreEmbeddedChunk,
+};
 use alayasiki_core::model::Node;
 use dashmap::DashMap;
+use futures::stream::{self, StreamExt};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use storage::repo::Repository;
 use storage::session::SessionOwner;
 use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
-use jobs::queue::{Job, JobQueue};
+use jobs::queue::{Job, JobQueue, QueueFull};
 
 #[derive(Error, Debug)]
 pub enum IngestionError {
@@ -45,8 +53,136 @@ pub enum IngestionError {
     Unauthenticated(#[from] AuthError),
     #[error("Governance error: {0}")]
     Governance(#[from] GovernanceError),
+    #[error("Chunk id collision could not be resolved for chunk {index} of {content_hash}")]
+    ChunkIdCollision { content_hash: String, index: u64 },
+    #[error("Request too large: {field} exceeds the limit of {limit}")]
+    RequestTooLarge { field: &'static str, limit: usize },
+    #[error("Stream read failed: {0}")]
+    Stream(String),
+    #[error("Embedding of chunk {index} has dimension {actual}, expected {expected}")]
+    EmbeddingDimensionMismatch {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Ingestion cancelled")]
+    Cancelled,
+}
+
+/// What a single [`IngestionPipeline::ingest_detailed`] call did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngestionReport {
+    pub node_ids: Vec<u64>,
+    /// Chunks created by this call; `0` when the request was deduped.
+    pub chunk_count: usize,
+    /// The idempotency key or content hash matched an earlier ingest, so its
+    /// node ids were returned and nothing was written.
+    pub deduped: bool,
+    /// Spans redacted by the content policy.
+    pub redactions: usize,
+    /// Extraction jobs accepted by the configured job queue.
+    pub jobs_enqueued: usize,
+    /// Embedding model the chunks were (or would have been) embedded with.
+    pub model_id: String,
+}
+
+/// Per-call settings for [`IngestionPipeline::ingest_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct IngestOptions {
+    /// Applied to the content before it is hashed for idempotency, so copies
+    /// differing only in whitespace or case dedupe. Stored content and chunk
+    /// ids are unaffected.
+    pub normalization: ContentNormalization,
+    /// See [`IngestionPipeline::ingest_cancellable`].
+    pub cancel: Option<CancellationToken>,
+}
+
+/// Upper bound on salted re-derivations when a chunk id collides.
+const MAX_CHUNK_ID_PROBES: u32 = 16;
+
+/// How idempotency keys and content hashes are namespaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdempotencyScope {
+    /// Keys are shared across tenants: identical content or keys resolve to the
+    /// same node set regardless of who ingests them.
+    Global,
+    /// Keys are namespaced by the ingesting tenant, so `(tenant, key)` is the
+    /// dedup unit. Requests without a tenant behave exactly like `Global`, and
+    /// entries recorded under the raw key are still honoured when all of their
+    /// nodes belong to the ingesting tenant.
+    #[default]
+    PerTenant,
 }
 
+/// Size limits checked before a request is extracted, chunked or embedded.
+/// The defaults are generous; tighten them with
+/// [`IngestionPipeline::with_limits`] when accepting untrusted uploads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestionLimits {
+    /// Maximum size of text content, in bytes.
+    pub max_text_bytes: usize,
+    /// Maximum size of a file payload, in bytes.
+    pub max_file_bytes: usize,
+    /// Maximum number of metadata entries.
+    pub max_metadata_entries: usize,
+}
+
+impl Default for IngestionLimits {
+    fn default() -> Self {
+        Self {
+            max_text_bytes: 16 * 1024 * 1024,
+            max_file_bytes: 64 * 1024 * 1024,
+            max_metadata_entries: 256,
+        }
+    }
+}
+
+impl IngestionLimits {
+    fn check(&self, request: &IngestionRequest) -> Result<(), IngestionError> {
+        match request {
+            IngestionRequest::Text { content, .. } if content.len() > self.max_text_bytes => {
+                return Err(IngestionError::RequestTooLarge {
+                    field: "content",
+                    limit: self.max_text_bytes,
+                });
+            }
+            IngestionRequest::File { content, .. } if content.len() > self.max_file_bytes => {
+                return Err(IngestionError::RequestTooLarge {
+                    field: "content",
+                    limit: self.max_file_bytes,
+                });
+            }
+            IngestionRequest::PreEmbedded { chunks, .. }
+                if chunks
+                    .iter()
+                    .map(|chunk| chunk.content.len())
+                    .sum::<usize>()
+                    > self.max_text_bytes =>
+            {
+                return Err(IngestionError::RequestTooLarge {
+                    field: "content",
+                    limit: self.max_text_bytes,
+                });
+            }
+            _ => {}
+        }
+        if request.metadata().len() > self.max_metadata_entries {
+            return Err(IngestionError::RequestTooLarge {
+                field: "metadata",
+                limit: self.max_metadata_entries,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Default upper bound on the bytes of one segment read by
+/// [`IngestionPipeline::ingest_stream`].
+pub const DEFAULT_STREAM_SEGMENT_BYTES: usize = 64 * 1024;
+
+/// Default number of chunks of one document embedded concurrently.
+pub const DEFAULT_EMBEDDING_CONCURRENCY: usize = 8;
+
 struct IdempotencyGuard {
     key: String,
     locks: Arc<DashMap<String, ()>>,
@@ -70,6 +206,11 @@ pub struct IngestionPipeline {
     job_queue: Option<Arc<dyn JobQueue>>,
     audit_sink: Option<Arc<dyn AuditSink>>,
     governance_policy_store: Option<Arc<dyn GovernancePolicyStore>>,
+    chunk_id_strategy: Arc<dyn ChunkIdStrategy>,
+    embedding_concurrency: usize,
+    idempotency_scope: IdempotencyScope,
+    limits: IngestionLimits,
+    stream_segment_bytes: usize,
 }
 
 impl IngestionPipeline {
@@ -85,6 +226,11 @@ impl IngestionPipeline {
             job_queue: None,
             audit_sink: None,
             governance_policy_store: None,
+            chunk_id_strategy: Arc::new(HashChunkIdStrategy),
+            embedding_concurrency: DEFAULT_EMBEDDING_CONCURRENCY,
+            idempotency_scope: IdempotencyScope::default(),
+            limits: IngestionLimits::default(),
+            stream_segment_bytes: DEFAULT_STREAM_SEGMENT_BYTES,
         }
     }
 
@@ -104,6 +250,11 @@ impl IngestionPipeline {
             job_queue: None,
             audit_sink: None,
             governance_policy_store: None,
+            chunk_id_strategy: Arc::new(HashChunkIdStrategy),
+            embedding_concurrency: DEFAULT_EMBEDDING_CONCURRENCY,
+            idempotency_scope: IdempotencyScope::default(),
+            limits: IngestionLimits::default(),
+            stream_segment_bytes: DEFAULT_STREAM_SEGMENT_BYTES,
         }
     }
 
@@ -125,6 +276,11 @@ impl IngestionPipeline {
             job_queue: None,
             audit_sink: None,
             governance_policy_store: None,
+            chunk_id_strategy: Arc::new(HashChunkIdStrategy),
+            embedding_concurrency: DEFAULT_EMBEDDING_CONCURRENCY,
+            idempotency_scope: IdempotencyScope::default(),
+            limits: IngestionLimits::default(),
+            stream_segment_bytes: DEFAULT_STREAM_SEGMENT_BYTES,
         }
     }
 
@@ -145,6 +301,51 @@ impl IngestionPipeline {
         self.governance_policy_store = Some(store);
     }
 
+    pub fn with_chunk_id_strategy(mut self, strategy: Arc<dyn ChunkIdStrategy>) -> Self {
+        self.chunk_id_strategy = strategy;
+        self
+    }
+
+    pub fn set_chunk_id_strategy(&mut self, strategy: Arc<dyn ChunkIdStrategy>) {
+        self.chunk_id_strategy = strategy;
+    }
+
+    /// Caps how many chunks of a single document are embedded at once. Values
+    /// below 1 are treated as 1 (sequential embedding).
+    pub fn with_embedding_concurrency(mut self, limit: usize) -> Self {
+        self.set_embedding_concurrency(limit);
+        self
+    }
+
+    pub fn set_embedding_concurrency(&mut self, limit: usize) {
+        self.embedding_concurrency = limit.max(1);
+    }
+
+    pub fn with_idempotency_scope(mut self, scope: IdempotencyScope) -> Self {
+        self.idempotency_scope = scope;
+        self
+    }
+
+    pub fn set_idempotency_scope(&mut self, scope: IdempotencyScope) {
+        self.idempotency_scope = scope;
+    }
+
+    pub fn with_limits(mut self, limits: IngestionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: IngestionLimits) {
+        self.limits = limits;
+    }
+
+    /// Caps the bytes buffered per segment by [`Self::ingest_stream`]. Values
+    /// below 1 are treated as 1.
+    pub fn with_stream_segment_bytes(mut self, bytes: usize) -> Self {
+        self.stream_segment_bytes = bytes.max(1);
+        self
+    }
+
     pub async fn ingest_authorized(
         &self,
         request: IngestionRequest,
@@ -152,7 +353,9 @@ impl IngestionPipeline {
         authorizer: &Authorizer,
         resource: &ResourceContext,
     ) -> Result<Vec<u64>, IngestionError> {
-        let model_id = effective_ingest_model_id(&request, &self.default_model_id);
+        let model_id = self
+            .embedding_model_id(request.model_id(), Some(&principal.tenant))
+            .unwrap_or_else(|_| effective_ingest_model_id(&request, &self.default_model_id));
         if let Err(err) = authorizer.authorize(principal, Action::Ingest, resource) {
             self.emit_audit_event(build_audit_event(
                 AuditOutcome::Denied,
@@ -166,8 +369,16 @@ impl IngestionPipeline {
 
         let actor = Some(principal.subject.clone());
         let tenant = Some(principal.tenant.clone());
-        self.ingest_with_audit(request, model_id, actor, tenant, None, None)
-            .await
+        self.ingest_with_audit(
+            request,
+            model_id,
+            actor,
+            tenant,
+            None,
+            &IngestOptions::default(),
+        )
+        .await
+        .map(|report| report.node_ids)
     }
 
     pub async fn ingest_to_session_authorized(
@@ -178,7 +389,9 @@ impl IngestionPipeline {
         authorizer: &Authorizer,
         resource: &ResourceContext,
     ) -> Result<Vec<u64>, IngestionError> {
-        let model_id = effective_ingest_model_id(&request, &self.default_model_id);
+        let model_id = self
+            .embedding_model_id(request.model_id(), Some(&principal.tenant))
+            .unwrap_or_else(|_| effective_ingest_model_id(&request, &self.default_model_id));
         if let Err(err) = authorizer.authorize(principal, Action::Ingest, resource) {
             self.emit_audit_event(build_audit_event(
                 AuditOutcome::Denied,
@@ -198,10 +411,11 @@ impl IngestionPipeline {
             model_id,
             actor,
             tenant,
-            Some(session_id.to_string()),
-            Some(session_owner),
+            Some((session_id.to_string(), session_owner)),
+            &IngestOptions::default(),
         )
         .await
+        .map(|report| report.node_ids)
     }
 
     pub async fn ingest_jwt_authorized(
@@ -232,9 +446,49 @@ impl IngestionPipeline {
     }
 
     pub async fn ingest(&self, request: IngestionRequest) -> Result<Vec<u64>, IngestionError> {
+        self.ingest_detailed(request)
+            .await
+            .map(|report| report.node_ids)
+    }
+
+    /// Like [`Self::ingest`], reporting chunk, dedup, redaction and job
+    /// counts alongside the node ids.
+    pub async fn ingest_detailed(
+        &self,
+        request: IngestionRequest,
+    ) -> Result<IngestionReport, IngestionError> {
+        self.ingest_with_options(request, IngestOptions::default())
+            .await
+    }
+
+    /// Like [`Self::ingest_detailed`], with per-call [`IngestOptions`].
+    pub async fn ingest_with_options(
+        &self,
+        request: IngestionRequest,
+        options: IngestOptions,
+    ) -> Result<IngestionReport, IngestionError> {
         let model_id = effective_ingest_model_id(&request, &self.default_model_id);
-        self.ingest_with_audit(request, model_id, None, None, None, None)
+        self.ingest_with_audit(request, model_id, None, None, None, &options)
+            .await
+    }
+
+    /// Like [`Self::ingest`], but stops with [`IngestionError::Cancelled`]
+    /// once `cancel` is triggered. The token is checked before each chunk is
+    /// embedded and once more before anything is persisted, so a cancelled
+    /// ingest writes no nodes or idempotency records and the same content
+    /// can be ingested again later.
+    pub async fn ingest_cancellable(
+        &self,
+        request: IngestionRequest,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<u64>, IngestionError> {
+        let options = IngestOptions {
+            cancel: Some(cancel.clone()),
+            ..IngestOptions::default()
+        };
+        self.ingest_with_options(request, options)
             .await
+            .map(|report| report.node_ids)
     }
 
     async fn ingest_with_audit(
@@ -243,15 +497,17 @@ impl IngestionPipeline {
         model_id: String,
         actor: Option<String>,
         tenant: Option<String>,
-        session_id: Option<String>,
-        session_owner: Option<SessionOwner>,
-    ) -> Result<Vec<u64>, IngestionError> {
+        session: Option<(String, SessionOwner)>,
+        options: &IngestOptions,
+    ) -> Result<IngestionReport, IngestionError> {
+        let (session_id, session_owner) = session.unzip();
         let result = self
             .ingest_internal(
                 request,
                 tenant.as_deref(),
                 session_id.as_deref(),
                 session_owner.as_ref(),
+                options,
             )
             .await;
         let outcome = match &result {
@@ -269,16 +525,29 @@ impl IngestionPipeline {
         tenant: Option<&str>,
         session_id: Option<&str>,
         session_owner: Option<&SessionOwner>,
-    ) -> Result<Vec<u64>, IngestionError> {
+        options: &IngestOptions,
+    ) -> Result<IngestionReport, IngestionError> {
+        let cancel = options.cancel.as_ref();
+        check_cancelled(cancel)?;
+        self.limits.check(&request)?;
         self.validate_governance_preflight(tenant, request.metadata())?;
 
         let content_hash = request.content_hash();
         let idempotency_key = request.idempotency_key().map(|key| key.to_string());
+        let scope_tenant = match self.idempotency_scope {
+            IdempotencyScope::Global => None,
+            IdempotencyScope::PerTenant => tenant,
+        };
+        let normalized_content_hash = request.content_hash_normalized(options.normalization);
+        let scoped_content_hash = scoped_idempotency_key(scope_tenant, &normalized_content_hash);
+        let scoped_key = idempotency_key
+            .as_deref()
+            .map(|key| scoped_idempotency_key(scope_tenant, key));
 
         // LOCKING: Prevent concurrent processing of same key
-        let lock_key = idempotency_key
+        let lock_key = scoped_key
             .clone()
-            .unwrap_or_else(|| content_hash.clone());
+            .unwrap_or_else(|| scoped_content_hash.clone());
 
         {
             if self.locks.contains_key(&lock_key) {
@@ -294,61 +563,113 @@ impl IngestionPipeline {
 
         // 1. Check Persistent Idempotency (only if NOT session ingest)
         if session_id.is_none() {
-            if let Some(key) = idempotency_key.as_deref() {
-                if let Some(ids) = self.repo.check_idempotency(key).await {
-                    return Ok(ids);
-                }
+            let mut existing = None;
+            if let Some(key) = scoped_key.as_deref() {
+                existing = self.repo.check_idempotency(key).await;
+            }
+            if existing.is_none() {
+                existing = self.repo.check_idempotency(&scoped_content_hash).await;
+            }
+            if let (None, Some(tenant)) = (&existing, scope_tenant) {
+                existing = self
+                    .unscoped_idempotency_hit(
+                        tenant,
+                        idempotency_key.as_deref(),
+                        &normalized_content_hash,
+                    )
+                    .await;
             }
-            if let Some(ids) = self.repo.check_idempotency(&content_hash).await {
-                return Ok(ids);
+            if let Some(node_ids) = existing {
+                let model_id = self
+                    .embedding_model_id(request.model_id(), tenant)
+                    .unwrap_or_else(|_| {
+                        effective_ingest_model_id(&request, &self.default_model_id)
+                    });
+                return Ok(IngestionReport {
+                    node_ids,
+                    chunk_count: 0,
+                    deduped: true,
+                    redactions: 0,
+                    jobs_enqueued: 0,
+                    model_id,
+                });
             }
         }
 
-        let embedding_model_id = request
-            .model_id()
-            .unwrap_or(&self.default_model_id)
-            .to_string();
+        let embedding_model_id = self.embedding_model_id(request.model_id(), tenant)?;
         let extraction_model_id = request
             .model_id()
             .unwrap_or(&self.default_extraction_model_id)
             .to_string();
 
-        let (text, mut metadata) = extract_request_text(request)?;
-        metadata.insert("content_hash".to_string(), content_hash.clone());
-        metadata.insert("model_id".to_string(), embedding_model_id.clone());
-        if let Some(tenant) = tenant {
-            // Enforce tenant ownership metadata for authorized ingest.
-            metadata.insert("tenant".to_string(), tenant.to_string());
-        }
-        if let Some(key) = &idempotency_key {
-            metadata.insert("idempotency_key".to_string(), key.clone());
-        }
-        self.apply_governance(tenant, &mut metadata)?;
-
-        let text = self.policy.apply(&text)?;
-
-        let chunks = self.chunker.chunk(&text, metadata).await;
-
         let mut node_ids = Vec::new();
+        let mut assigned_ids = HashSet::new();
         let mut persistent_nodes = Vec::new();
         let mut queued_extractions = Vec::new();
-        for (i, mut chunk) in chunks.into_iter().enumerate() {
-            let embedding = self
-                .embedder
-                .embed(&chunk.content, &embedding_model_id)
-                .await;
-            chunk.embedding = Some(embedding.clone());
+        let mut redactions = 0;
+        let nodes = if let IngestionRequest::PreEmbedded {
+            chunks,
+            mut metadata,
+            ..
+        } = request
+        {
+            self.stamp_document_metadata(
+                &mut metadata,
+                &content_hash,
+                &embedding_model_id,
+                tenant,
+                idempotency_key.as_deref(),
+            )?;
+            self.build_pre_embedded_nodes(
+                chunks,
+                &metadata,
+                &content_hash,
+                scope_tenant,
+                &mut assigned_ids,
+                &mut redactions,
+            )
+            .await?
+        } else {
+            let (text, mut metadata) = extract_request_text(request)?;
+            self.stamp_document_metadata(
+                &mut metadata,
+                &content_hash,
+                &embedding_model_id,
+                tenant,
+                idempotency_key.as_deref(),
+            )?;
 
-            let chunk_id = derive_chunk_id(&content_hash, i as u64);
+            let (text, redacted) = self.policy.apply_with_redactions(&text)?;
+            redactions += redacted;
 
-            let chunk_content = chunk.content.clone();
+            let chunks = self.chunker.chunk(&text, metadata).await;
+            let min_chunks = self.chunker.min_chunks();
+            if chunks.len() < min_chunks {
+                return Err(IngestionError::ExtractionFailed(if chunks.is_empty() {
+                    "empty".to_string()
+                } else {
+                    format!("{} chunks, expected at least {min_chunks}", chunks.len())
+                }));
+            }
+            let embeddings = self
+                .embed_chunks(&chunks, &embedding_model_id, cancel)
+                .await?;
+            self.build_chunk_nodes(
+                chunks,
+                embeddings,
+                &content_hash,
+                scope_tenant,
+                0,
+                &mut assigned_ids,
+            )
+            .await?
+        };
+        check_cancelled(cancel)?;
 
-            let node = Node {
-                id: chunk_id,
-                embedding,
-                data: chunk.content,
-                metadata: chunk.metadata,
-            };
+        let chunk_count = nodes.len();
+        for node in nodes {
+            let chunk_id = node.id;
+            let chunk_content = node.data.clone();
 
             if let Some(sid) = session_id {
                 if let Some(owner) = session_owner {
@@ -364,40 +685,473 @@ impl IngestionPipeline {
         }
 
         // 2. Record Idempotency persistently (only if NOT session ingest)
+        let mut jobs_enqueued = 0;
         if session_id.is_none() {
-            let mut idempotency_records = vec![(content_hash.clone(), node_ids.clone())];
-            if let Some(key) = &idempotency_key {
+            let mut idempotency_records = vec![(scoped_content_hash, node_ids.clone())];
+            if let Some(key) = scoped_key {
                 idempotency_records.push((key.clone(), node_ids.clone()));
             }
 
-            self.repo
-                .persist_ingest_batch(persistent_nodes, idempotency_records)
+            if self.job_queue.is_some() {
+                // Idempotency is recorded only once every extraction job is
+                // queued, so a retry after `QueueFull` ingests again (rewriting
+                // nothing for unchanged chunks) and re-enqueues the batch
+                // instead of being deduplicated with its jobs lost.
+                self.repo
+                    .persist_ingest_batch(persistent_nodes, Vec::new())
+                    .await?;
+                jobs_enqueued = self
+                    .enqueue_extractions(queued_extractions, &extraction_model_id)
+                    .await?;
+                self.repo
+                    .persist_ingest_batch(Vec::new(), idempotency_records)
+                    .await?;
+            } else {
+                self.repo
+                    .persist_ingest_batch(persistent_nodes, idempotency_records)
+                    .await?;
+            }
+        }
+
+        // Guard will automatically remove lock on drop
+        // self.locks.remove(&lock_key);
+
+        Ok(IngestionReport {
+            node_ids,
+            chunk_count,
+            deduped: false,
+            redactions,
+            jobs_enqueued,
+            model_id: embedding_model_id,
+        })
+    }
+
+    /// Ingests a document read incrementally from `reader`.
+    ///
+    /// Text, Markdown, JSON and CSV are cut into segments of at most
+    /// [`Self::with_stream_segment_bytes`] bytes (at the last line break when
+    /// there is one), and each segment is chunked, embedded and persisted
+    /// before the next is read, so memory stays bounded by the segment size.
+    /// Other formats (PDF, DOCX, ...) need the whole payload and are buffered
+    /// and ingested like an [`IngestionRequest::File`].
+    ///
+    /// Chunk ids are derived from the hash of the stream up to the end of each
+    /// segment, so streaming identical bytes again yields the same node ids
+    /// instead of duplicates; the hash of the whole stream is recorded for
+    /// idempotency once the stream ends. A `filename` or `idempotency_key`
+    /// metadata entry is used like the corresponding request field.
+    pub async fn ingest_stream<R>(
+        &self,
+        reader: R,
+        mime_type: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<Vec<u64>, IngestionError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let result = self
+            .ingest_stream_internal(reader, mime_type, metadata)
+            .await;
+        let outcome = match &result {
+            Ok(_) => AuditOutcome::Succeeded,
+            Err(_) => AuditOutcome::Failed,
+        };
+        let error = result.as_ref().err().map(|err| err.to_string());
+        self.emit_audit_event(build_audit_event(
+            outcome,
+            &self.default_model_id,
+            None,
+            None,
+            error,
+        ));
+        result
+    }
+
+    async fn ingest_stream_internal<R>(
+        &self,
+        mut reader: R,
+        mime_type: &str,
+        mut metadata: HashMap<String, String>,
+    ) -> Result<Vec<u64>, IngestionError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        // Reject on governance grounds before consuming any of the stream.
+        self.validate_governance_preflight(None, &metadata)?;
+
+        let filename = metadata.get("filename").cloned();
+        let idempotency_key = metadata.remove("idempotency_key");
+        let kind = detect_content_kind(mime_type, filename.as_deref());
+        if !matches!(
+            kind,
+            ContentKind::Text | ContentKind::Markdown | ContentKind::Json | ContentKind::Csv
+        ) {
+            let mut content = Vec::new();
+            reader
+                .read_to_end(&mut content)
+                .await
+                .map_err(|err| IngestionError::Stream(err.to_string()))?;
+            return self
+                .ingest(IngestionRequest::File {
+                    filename: filename.unwrap_or_default(),
+                    content,
+                    mime_type: mime_type.to_string(),
+                    metadata,
+                    idempotency_key,
+                    model_id: None,
+                })
+                .await;
+        }
+        if metadata.len() > self.limits.max_metadata_entries {
+            return Err(IngestionError::RequestTooLarge {
+                field: "metadata",
+                limit: self.limits.max_metadata_entries,
+            });
+        }
+
+        // Without a key there is nothing to lock on until the stream is hashed.
+        let _guard = match idempotency_key.as_deref() {
+            Some(key) => {
+                if self.locks.contains_key(key) {
+                    return Err(IngestionError::IdempotencyConflict(key.to_string()));
+                }
+                self.locks.insert(key.to_string(), ());
+                let guard = IdempotencyGuard {
+                    key: key.to_string(),
+                    locks: self.locks.clone(),
+                };
+                if let Some(ids) = self.repo.check_idempotency(key).await {
+                    return Ok(ids);
+                }
+                Some(guard)
+            }
+            None => None,
+        };
+
+        metadata.insert("mime_type".to_string(), mime_type.to_string());
+        metadata.insert("model_id".to_string(), self.default_model_id.clone());
+        if let Some(key) = &idempotency_key {
+            metadata.insert("idempotency_key".to_string(), key.clone());
+        }
+
+        let segment_bytes = self.stream_segment_bytes;
+        let mut hasher = Sha256::new();
+        hasher.update(b"stream");
+        hasher.update(mime_type.as_bytes());
+        let mut buffer: Vec<u8> = Vec::with_capacity(segment_bytes * 2);
+        let mut read_buf = vec![0u8; segment_bytes];
+        let mut node_ids = Vec::new();
+        let mut assigned_ids = HashSet::new();
+        let mut eof = false;
+        // Byte offset of the current segment within the stream.
+        let mut stream_offset = 0;
+
+        while !eof || !buffer.is_empty() {
+            while !eof && buffer.len() < segment_bytes {
+                let read = reader
+                    .read(&mut read_buf)
+                    .await
+                    .map_err(|err| IngestionError::Stream(err.to_string()))?;
+                if read == 0 {
+                    eof = true;
+                } else {
+                    buffer.extend_from_slice(&read_buf[..read]);
+                }
+            }
+
+            let cut = if eof && buffer.len() <= segment_bytes {
+                buffer.len()
+            } else {
+                stream_segment_end(&buffer, segment_bytes)
+            };
+            let segment: Vec<u8> = buffer.drain(..cut).collect();
+            hasher.update(&segment);
+            let text = extract_utf8(&segment).map_err(|_| IngestionError::InvalidUtf8)?;
+            if text.trim().is_empty() {
+                stream_offset += text.len();
+                continue;
+            }
+
+            let segment_hash = format!("{:x}", hasher.clone().finalize());
+            let mut segment_metadata = metadata.clone();
+            segment_metadata.insert("content_hash".to_string(), segment_hash.clone());
+            let text = self.policy.apply(&text)?;
+            let segment_offset = stream_offset;
+            stream_offset += text.len();
+            let mut chunks = self.chunker.chunk(&text, segment_metadata).await;
+            shift_doc_offsets(&mut chunks, segment_offset);
+            let embeddings = self
+                .embed_chunks(&chunks, &self.default_model_id, None)
+                .await?;
+            let nodes = self
+                .build_chunk_nodes(
+                    chunks,
+                    embeddings,
+                    &segment_hash,
+                    None,
+                    node_ids.len() as u64,
+                    &mut assigned_ids,
+                )
                 .await?;
+            let extractions: Vec<(u64, String)> = nodes
+                .iter()
+                .map(|node| (node.id, node.data.clone()))
+                .collect();
+            node_ids.extend(nodes.iter().map(|node| node.id));
+            self.repo.persist_ingest_batch(nodes, Vec::new()).await?;
+            self.enqueue_extractions(extractions, &self.default_extraction_model_id)
+                .await?;
+        }
+
+        let mut idempotency_records = vec![(format!("{:x}", hasher.finalize()), node_ids.clone())];
+        if let Some(key) = idempotency_key {
+            idempotency_records.push((key, node_ids.clone()));
+        }
+        self.repo
+            .persist_ingest_batch(Vec::new(), idempotency_records)
+            .await?;
+        Ok(node_ids)
+    }
+
+    /// Embeds `chunks` concurrently, in chunk order. `cancel` is checked
+    /// before each chunk's embedding starts.
+    async fn embed_chunks(
+        &self,
+        chunks: &[Chunk],
+        embedding_model_id: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<Vec<f32>>, IngestionError> {
+        // `buffered` yields results in chunk order. The futures are collected
+        // first so the stream type carries no borrowing closure, which keeps
+        // `ingest` usable from `Send` contexts such as `async_trait` impls.
+        let pending: Vec<BoxFuture<'_, Option<Vec<f32>>>> = chunks
+            .iter()
+            .map(|chunk| -> BoxFuture<'_, Option<Vec<f32>>> {
+                Box::pin(async move {
+                    if check_cancelled(cancel).is_err() {
+                        return None;
+                    }
+                    Some(
+                        self.embedder
+                            .embed(&chunk.content, embedding_model_id)
+                            .await,
+                    )
+                })
+            })
+            .collect();
+        let embeddings: Vec<Option<Vec<f32>>> = stream::iter(pending)
+            .buffered(self.embedding_concurrency)
+            .collect()
+            .await;
+        embeddings
+            .into_iter()
+            .map(|embedding| embedding.ok_or(IngestionError::Cancelled))
+            .collect()
+    }
+
+    /// Assigns each embedded chunk an id; chunk `i` is named as index
+    /// `first_index + i` of `content_hash`.
+    async fn build_chunk_nodes(
+        &self,
+        chunks: Vec<Chunk>,
+        embeddings: Vec<Vec<f32>>,
+        content_hash: &str,
+        scope_tenant: Option<&str>,
+        first_index: u64,
+        assigned_ids: &mut HashSet<u64>,
+    ) -> Result<Vec<Node>, IngestionError> {
+        let mut nodes = Vec::with_capacity(chunks.len());
+        for (i, (chunk, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
+            let chunk_id = self
+                .assign_chunk_id(
+                    &ChunkIdContext {
+                        content_hash,
+                        tenant: scope_tenant,
+                        index: first_index + i as u64,
+                        content: &chunk.content,
+                        metadata: &chunk.metadata,
+                    },
+                    assigned_ids,
+                )
+                .await?;
+            assigned_ids.insert(chunk_id);
+            nodes.push(Node {
+                id: chunk_id,
+                embedding,
+                data: chunk.content,
+                metadata: chunk.metadata,
+            });
+        }
+        Ok(nodes)
+    }
+
+    /// Builds nodes from caller-computed embeddings without calling the
+    /// embedder. Every embedding must match the repository's dimension (or,
+    /// for an empty repository, the first chunk's).
+    async fn build_pre_embedded_nodes(
+        &self,
+        chunks: Vec<PreEmbeddedChunk>,
+        document_metadata: &HashMap<String, String>,
+        content_hash: &str,
+        scope_tenant: Option<&str>,
+        assigned_ids: &mut HashSet<u64>,
+        redactions: &mut usize,
+    ) -> Result<Vec<Node>, IngestionError> {
+        let expected = match self.repo.embedding_dimension().await {
+            Some(dimension) => Some(dimension),
+            None => chunks.first().map(|chunk| chunk.embedding.len()),
+        };
+
+        let mut nodes = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let actual = chunk.embedding.len();
+            if actual == 0 || Some(actual) != expected {
+                return Err(IngestionError::EmbeddingDimensionMismatch {
+                    index: i,
+                    expected: expected.unwrap_or_default(),
+                    actual,
+                });
+            }
+            let (content, redacted) = self.policy.apply_with_redactions(&chunk.content)?;
+            *redactions += redacted;
+            // Document metadata carries the enforced tenant, content hash,
+            // model and governance stamps, so it wins over chunk-supplied keys.
+            let mut metadata = chunk.metadata;
+            metadata.extend(
+                document_metadata
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            let chunk_id = self
+                .assign_chunk_id(
+                    &ChunkIdContext {
+                        content_hash,
+                        tenant: scope_tenant,
+                        index: i as u64,
+                        content: &content,
+                        metadata: &metadata,
+                    },
+                    assigned_ids,
+                )
+                .await?;
+            assigned_ids.insert(chunk_id);
+            nodes.push(Node {
+                id: chunk_id,
+                embedding: chunk.embedding,
+                data: content,
+                metadata,
+            });
+        }
+        Ok(nodes)
+    }
 
-            if let Some(queue) = &self.job_queue {
-                // Queue provenance should point at a durable snapshot that already includes
-                // the ingest batch, even when WAL writes are buffered.
-                self.repo.flush().await?;
-                let snapshot_id = self.repo.current_snapshot_id().await;
-                for (chunk_id, chunk_content) in queued_extractions {
-                    let job = Job::ExtractEntities {
-                        node_id: chunk_id,
-                        content: chunk_content,
-                        model_id: extraction_model_id.clone(),
-                        snapshot_id: snapshot_id.clone(),
-                    };
-                    if let Err(e) = queue.enqueue(job).await {
-                        // Best-effort: Log warning but continue ingestion to preserve idempotency
-                        tracing::warn!("Failed to enqueue job for node {}: {}", chunk_id, e);
+    /// Enqueues one extraction job per chunk and returns how many the queue
+    /// accepted.
+    async fn enqueue_extractions(
+        &self,
+        extractions: Vec<(u64, String)>,
+        extraction_model_id: &str,
+    ) -> Result<usize, IngestionError> {
+        let Some(queue) = &self.job_queue else {
+            return Ok(0);
+        };
+        // Queue provenance should point at a durable snapshot that already includes
+        // the ingest batch, even when WAL writes are buffered.
+        self.repo.flush().await?;
+        let snapshot_id = self.repo.current_snapshot_id().await;
+        let mut enqueued = 0;
+        for (chunk_id, chunk_content) in extractions {
+            let job = Job::ExtractEntities {
+                node_id: chunk_id,
+                content: chunk_content,
+                model_id: extraction_model_id.to_string(),
+                snapshot_id: snapshot_id.clone(),
+            };
+            match queue.enqueue(job).await {
+                Ok(()) => enqueued += 1,
+                Err(e) => {
+                    // A queue rejecting work under backpressure fails the ingest
+                    // before its idempotency is recorded, so retrying re-enqueues
+                    // the whole batch; other failures stay best-effort.
+                    if e.is::<QueueFull>() {
+                        return Err(IngestionError::JobQueue(e));
                     }
+                    // Best-effort: Log warning but continue ingestion to preserve idempotency
+                    tracing::warn!("Failed to enqueue job for node {}: {}", chunk_id, e);
                 }
             }
         }
+        Ok(enqueued)
+    }
 
-        // Guard will automatically remove lock on drop
-        // self.locks.remove(&lock_key);
+    /// Resolves the id for one chunk, probing with a salt while the candidate is
+    /// already used in this batch or by a stored node from different content.
+    async fn assign_chunk_id(
+        &self,
+        ctx: &ChunkIdContext<'_>,
+        assigned_ids: &HashSet<u64>,
+    ) -> Result<u64, IngestionError> {
+        let strategy = &self.chunk_id_strategy;
+        for salt in 0..MAX_CHUNK_ID_PROBES {
+            let candidate = strategy.chunk_id(ctx, salt);
+            if !strategy.probe_on_collision()
+                || !self.chunk_id_collides(ctx, candidate, assigned_ids).await
+            {
+                return Ok(candidate);
+            }
+            tracing::warn!(
+                "chunk id {} collides for chunk {} of {}; probing with salt {}",
+                candidate,
+                ctx.index,
+                ctx.content_hash,
+                salt + 1
+            );
+        }
+        Err(IngestionError::ChunkIdCollision {
+            content_hash: ctx.content_hash.to_string(),
+            index: ctx.index,
+        })
+    }
 
-        Ok(node_ids)
+    async fn chunk_id_collides(
+        &self,
+        ctx: &ChunkIdContext<'_>,
+        candidate: u64,
+        assigned_ids: &HashSet<u64>,
+    ) -> bool {
+        if assigned_ids.contains(&candidate) {
+            return true;
+        }
+        match self.repo.get_node(candidate).await {
+            Ok(existing) => {
+                existing.metadata.get("content_hash").map(String::as_str) != Some(ctx.content_hash)
+                    || (ctx.tenant.is_some()
+                        && existing.metadata.get("tenant").map(String::as_str) != ctx.tenant)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Adds the pipeline-owned keys (content hash, model, tenant, idempotency
+    /// key) and the tenant's governance metadata to a document's metadata.
+    fn stamp_document_metadata(
+        &self,
+        metadata: &mut HashMap<String, String>,
+        content_hash: &str,
+        embedding_model_id: &str,
+        tenant: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<(), IngestionError> {
+        metadata.insert("content_hash".to_string(), content_hash.to_string());
+        metadata.insert("model_id".to_string(), embedding_model_id.to_string());
+        if let Some(tenant) = tenant {
+            // Enforce tenant ownership metadata for authorized ingest.
+            metadata.insert("tenant".to_string(), tenant.to_string());
+        }
+        if let Some(key) = idempotency_key {
+            metadata.insert("idempotency_key".to_string(), key.to_string());
+        }
+        self.apply_governance(tenant, metadata)
     }
 
     fn validate_governance_preflight(
@@ -417,6 +1171,28 @@ impl IngestionPipeline {
         Ok(())
     }
 
+    /// Embedding model for an ingest: the request's own model, else the tenant
+    /// policy default, else the pipeline default. The result must be in the
+    /// tenant's `allowed_model_ids` when the policy sets them.
+    fn embedding_model_id(
+        &self,
+        request_model_id: Option<&str>,
+        tenant: Option<&str>,
+    ) -> Result<String, IngestionError> {
+        let policy = match (&self.governance_policy_store, tenant) {
+            (Some(policy_store), Some(tenant)) => policy_store.get_policy(tenant)?,
+            _ => None,
+        };
+        let model_id = request_model_id
+            .or_else(|| policy.as_ref()?.default_model_id.as_deref())
+            .unwrap_or(&self.default_model_id)
+            .to_string();
+        if let Some(policy) = &policy {
+            policy.ensure_model_allowed(&model_id)?;
+        }
+        Ok(model_id)
+    }
+
     fn apply_governance(
         &self,
         tenant: Option<&str>,
@@ -449,6 +1225,32 @@ impl IngestionPipeline {
         Ok(())
     }
 
+    /// Idempotency entries recorded before keys were scoped per tenant live
+    /// under the raw key or content hash. Reuse one only when all of its nodes
+    /// belong to `tenant`, so upgrading neither re-ingests old documents nor
+    /// hands out another tenant's nodes.
+    async fn unscoped_idempotency_hit(
+        &self,
+        tenant: &str,
+        idempotency_key: Option<&str>,
+        content_hash: &str,
+    ) -> Option<Vec<u64>> {
+        for key in idempotency_key.into_iter().chain([content_hash]) {
+            let Some(node_ids) = self.repo.check_idempotency(key).await else {
+                continue;
+            };
+            let nodes = self.repo.get_nodes_by_ids(&node_ids).await;
+            if nodes.len() == node_ids.len()
+                && nodes
+                    .iter()
+                    .all(|node| node.metadata.get("tenant").map(String::as_str) == Some(tenant))
+            {
+                return Some(node_ids);
+            }
+        }
+        None
+    }
+
     fn emit_audit_event(&self, event: AuditEvent) {
         if let Some(sink) = &self.audit_sink {
             let _ = sink.record(event);
@@ -456,6 +1258,62 @@ impl IngestionPipeline {
     }
 }
 
+/// Make chunk document offsets relative to the whole (redacted) stream rather
+/// than the segment they were chunked from.
+fn shift_doc_offsets(chunks: &mut [Chunk], base: usize) {
+    if base == 0 {
+        return;
+    }
+    for chunk in chunks {
+        for key in [DOC_CHAR_START_KEY, DOC_CHAR_END_KEY] {
+            if let Some(value) = chunk.metadata.get_mut(key) {
+                if let Ok(offset) = value.parse::<usize>() {
+                    *value = (offset + base).to_string();
+                }
+            }
+        }
+    }
+}
+
+/// End of the next stream segment within `buffer`: just past the last line
+/// break in the first `max_bytes` bytes, else the last UTF-8 character boundary
+/// at or before `max_bytes`.
+fn stream_segment_end(buffer: &[u8], max_bytes: usize) -> usize {
+    let limit = max_bytes.min(buffer.len());
+    if let Some(newline) = buffer[..limit].iter().rposition(|byte| *byte == b'\n') {
+        return newline + 1;
+    }
+    let mut end = limit;
+    while end > 0 && end < buffer.len() && (buffer[end] & 0xC0) == 0x80 {
+        end -= 1;
+    }
+    if end == 0 {
+        // A single character longer than the segment; take it whole.
+        end = limit;
+        while end < buffer.len() && (buffer[end] & 0xC0) == 0x80 {
+            end += 1;
+        }
+    }
+    end
+}
+
+/// Namespaces an idempotency key (or content hash) by tenant. Without a tenant the
+/// key is returned unchanged so single-tenant deployments keep their existing keys.
+fn scoped_idempotency_key(tenant: Option<&str>, key: &str) -> String {
+    match tenant {
+        // Length-prefix the tenant so a `:` inside it cannot alias another tenant's key.
+        Some(tenant) => format!("tenant:{}:{tenant}:{key}", tenant.len()),
+        None => key.to_string(),
+    }
+}
+
+fn check_cancelled(cancel: Option<&CancellationToken>) -> Result<(), IngestionError> {
+    match cancel {
+        Some(token) if token.is_cancelled() => Err(IngestionError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
 fn effective_ingest_model_id(request: &IngestionRequest, default_model_id: &str) -> String {
     request.model_id().unwrap_or(default_model_id).to_string()
 }
@@ -477,16 +1335,6 @@ fn build_audit_event(
     event
 }
 
-fn derive_chunk_id(content_hash: &str, index: u64) -> u64 {
-    let mut hasher = Sha256::new();
-    hasher.update(content_hash.as_bytes());
-    hasher.update(index.to_le_bytes());
-    let digest = hasher.finalize();
-    u64::from_le_bytes([
-        digest[0], digest[1], digest[2], digest[3], digest[4], digest[5], digest[6], digest[7],
-    ])
-}
-
 fn current_unix_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -501,6 +1349,16 @@ fn extract_request_text(
         IngestionRequest::Text {
             content, metadata, ..
         } => Ok((content, metadata)),
+        IngestionRequest::PreEmbedded {
+            chunks, metadata, ..
+        } => {
+            let text = chunks
+                .into_iter()
+                .map(|chunk| chunk.content)
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            Ok((text, metadata))
+        }
         IngestionRequest::File {
             filename,
             content,
@@ -513,7 +1371,10 @@ fn extract_request_text(
             metadata.insert("mime_type".to_string(), mime_type.clone());
 
             match kind {
-                ContentKind::Text | ContentKind::Markdown | ContentKind::Json => {
+                ContentKind::Text
+                | ContentKind::Markdown
+                | ContentKind::Json
+                | ContentKind::Csv => {
                     let text = extract_utf8(&content).map_err(|_| IngestionError::InvalidUtf8)?;
                     Ok((text, metadata))
                 }
@@ -524,6 +1385,13 @@ fn extract_request_text(
                         Err(IngestionError::ExtractionFailed("pdf".to_string()))
                     }
                 }
+                ContentKind::Docx => {
+                    if let Some(text) = extract_docx_text(&content) {
+                        Ok((text, metadata))
+                    } else {
+                        Err(IngestionError::ExtractionFailed("docx".to_string()))
+                    }
+                }
                 ContentKind::Image => {
                     if let Some(text) = extract_image_text(&metadata) {
                         Ok((text, metadata))
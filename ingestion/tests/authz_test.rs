@@ -1,5 +1,5 @@
 use alayasiki_core::auth::{Authorizer, AuthzError, Principal, ResourceContext};
-use alayasiki_core::ingest::IngestionRequest;
+use alayasiki_core::ingest::{IngestionRequest, PreEmbeddedChunk};
 use ingestion::processor::{IngestionError, IngestionPipeline};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -82,3 +82,37 @@ async fn ingest_authorized_enforces_abac_attributes() {
     ));
     assert!(repo.list_node_ids().await.is_empty());
 }
+
+#[tokio::test]
+async fn ingest_authorized_pre_embedded_chunks_cannot_claim_another_tenant() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("auth_chunk_tenant.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone());
+
+    let principal = Principal::new("user-1", "acme").with_roles(["ingestor"]);
+    let authorizer = Authorizer::default();
+    let resource = ResourceContext::new("acme");
+    let request = IngestionRequest::pre_embedded(
+        vec![PreEmbeddedChunk {
+            content: "chunk pretending to belong elsewhere".to_string(),
+            embedding: vec![0.1, 0.2, 0.3],
+            metadata: HashMap::from([
+                ("tenant".to_string(), "globex".to_string()),
+                ("content_hash".to_string(), "forged".to_string()),
+                ("page".to_string(), "7".to_string()),
+            ]),
+        }],
+        HashMap::new(),
+    );
+
+    let ids = pipeline
+        .ingest_authorized(request, &principal, &authorizer, &resource)
+        .await
+        .unwrap();
+
+    let node = repo.get_node(ids[0]).await.unwrap();
+    assert_eq!(node.metadata["tenant"], "acme");
+    assert_ne!(node.metadata["content_hash"], "forged");
+    assert_eq!(node.metadata["page"], "7");
+}
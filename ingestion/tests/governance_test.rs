@@ -156,3 +156,193 @@ async fn ingest_authorized_validates_region_even_when_idempotent_key_exists() {
         IngestionError::Governance(GovernanceError::ResidencyViolation { .. })
     ));
 }
+
+#[tokio::test]
+async fn idempotency_is_scoped_per_tenant() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("governance_tenant_idempotency.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone());
+    let authorizer = Authorizer::default();
+
+    let mut ids_by_tenant = HashMap::new();
+    for tenant in ["acme", "globex"] {
+        let principal = Principal::new("ingestor-1", tenant).with_roles(["ingestor"]);
+        let resource = ResourceContext::new(tenant);
+        let ids = pipeline
+            .ingest_authorized(
+                make_request_with_idempotency("ap-northeast-1", "shared-key"),
+                &principal,
+                &authorizer,
+                &resource,
+            )
+            .await
+            .unwrap();
+
+        // A retry within the same tenant still dedups to the same node set.
+        let retried = pipeline
+            .ingest_authorized(
+                make_request_with_idempotency("ap-northeast-1", "shared-key"),
+                &principal,
+                &authorizer,
+                &resource,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids, retried);
+        ids_by_tenant.insert(tenant, ids);
+    }
+
+    let acme = &ids_by_tenant["acme"];
+    let globex = &ids_by_tenant["globex"];
+    assert!(acme.iter().all(|id| !globex.contains(id)));
+    for (tenant, ids) in &ids_by_tenant {
+        for id in ids {
+            let node = repo.get_node(*id).await.unwrap();
+            assert_eq!(
+                node.metadata.get("tenant").map(String::as_str),
+                Some(*tenant)
+            );
+        }
+    }
+
+    // Unscoped ingests keep using the raw key.
+    let unscoped = pipeline
+        .ingest(make_request_with_idempotency(
+            "ap-northeast-1",
+            "shared-key",
+        ))
+        .await
+        .unwrap();
+    assert!(repo.check_idempotency("shared-key").await.is_some());
+    assert!(unscoped.iter().all(|id| !acme.contains(id)));
+}
+
+#[tokio::test]
+async fn tenants_without_request_model_use_policy_default_models() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("governance_default_model.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+
+    let mut pipeline = IngestionPipeline::new(repo.clone());
+    let store = Arc::new(InMemoryGovernancePolicyStore::default());
+    store
+        .upsert_policy(
+            TenantGovernancePolicy::new("acme", "ap-northeast-1", 30)
+                .with_allowed_model_ids(["embedding-acme-v2", "embedding-default-v1"])
+                .unwrap()
+                .with_default_model_id("embedding-acme-v2")
+                .unwrap(),
+        )
+        .unwrap();
+    store
+        .upsert_policy(
+            TenantGovernancePolicy::new("globex", "ap-northeast-1", 30)
+                .with_default_model_id("embedding-globex-v1")
+                .unwrap(),
+        )
+        .unwrap();
+    pipeline.set_governance_policy_store(store);
+    let authorizer = Authorizer::default();
+
+    for (tenant, expected_model) in [
+        ("acme", "embedding-acme-v2"),
+        ("globex", "embedding-globex-v1"),
+    ] {
+        let principal = Principal::new("ingestor-1", tenant).with_roles(["ingestor"]);
+        let ids = pipeline
+            .ingest_authorized(
+                make_request("ap-northeast-1"),
+                &principal,
+                &authorizer,
+                &ResourceContext::new(tenant),
+            )
+            .await
+            .unwrap();
+        let node = repo.get_node(ids[0]).await.unwrap();
+        assert_eq!(
+            node.metadata.get("model_id").map(String::as_str),
+            Some(expected_model)
+        );
+    }
+
+    // An explicit request model outside the tenant's allow-list is rejected.
+    let principal = Principal::new("ingestor-1", "acme").with_roles(["ingestor"]);
+    let request = IngestionRequest::Text {
+        content: "governed content from another model".to_string(),
+        metadata: HashMap::from([("region".to_string(), "ap-northeast-1".to_string())]),
+        idempotency_key: None,
+        model_id: Some("embedding-globex-v1".to_string()),
+    };
+    let err = pipeline
+        .ingest_authorized(
+            request,
+            &principal,
+            &authorizer,
+            &ResourceContext::new("acme"),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        IngestionError::Governance(GovernanceError::ModelNotAllowed { .. })
+    ));
+}
+
+#[test]
+fn policy_default_model_must_be_in_allowed_models() {
+    let err = TenantGovernancePolicy::new("acme", "ap-northeast-1", 30)
+        .with_allowed_model_ids(["embedding-acme-v2"])
+        .unwrap()
+        .with_default_model_id("embedding-other")
+        .unwrap_err();
+    assert!(matches!(err, GovernanceError::ModelNotAllowed { .. }));
+}
+
+#[tokio::test]
+async fn per_tenant_idempotency_reuses_unscoped_entries_of_the_same_tenant() {
+    use ingestion::processor::IdempotencyScope;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("governance_legacy_idempotency.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let authorizer = Authorizer::default();
+    let acme = Principal::new("ingestor-1", "acme").with_roles(["ingestor"]);
+    let globex = Principal::new("ingestor-1", "globex").with_roles(["ingestor"]);
+
+    // Recorded under the raw key, as before keys were scoped per tenant.
+    let legacy = IngestionPipeline::new(repo.clone())
+        .with_idempotency_scope(IdempotencyScope::Global)
+        .ingest_authorized(
+            make_request_with_idempotency("ap-northeast-1", "legacy-key"),
+            &acme,
+            &authorizer,
+            &ResourceContext::new("acme"),
+        )
+        .await
+        .unwrap();
+
+    let pipeline = IngestionPipeline::new(repo.clone());
+    let retried = pipeline
+        .ingest_authorized(
+            make_request_with_idempotency("ap-northeast-1", "legacy-key"),
+            &acme,
+            &authorizer,
+            &ResourceContext::new("acme"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(retried, legacy);
+
+    // Another tenant never resolves to acme's unscoped entry.
+    let other = pipeline
+        .ingest_authorized(
+            make_request_with_idempotency("ap-northeast-1", "legacy-key"),
+            &globex,
+            &authorizer,
+            &ResourceContext::new("globex"),
+        )
+        .await
+        .unwrap();
+    assert!(other.iter().all(|id| !legacy.contains(id)));
+}
@@ -1,8 +1,11 @@
 use alayasiki_core::ingest::{Chunk, IngestionRequest};
-use ingestion::chunker::{BoxFuture, Chunker, SemanticChunker};
+use ingestion::cancellation::CancellationToken;
+use ingestion::chunker::{
+    BoxFuture, Chunker, ChunkingConfig, SemanticChunker, DOC_CHAR_END_KEY, DOC_CHAR_START_KEY,
+};
 use ingestion::embedding::DeterministicEmbedder;
 use ingestion::policy::BasicPolicy;
-use ingestion::processor::IngestionPipeline;
+use ingestion::processor::{IngestOptions, IngestionError, IngestionPipeline};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -206,6 +209,104 @@ async fn test_ingestion_pdf_extract() {
     assert!(node.data.contains("Dummy PDF file"));
 }
 
+fn build_docx(paragraphs: &[&str]) -> Vec<u8> {
+    use std::io::Write;
+
+    let body: String = paragraphs
+        .iter()
+        .map(|text| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>"))
+        .collect();
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}</w:body></w:document>"#
+    );
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    writer.start_file("[Content_Types].xml", options).unwrap();
+    writer.write_all(b"<Types/>").unwrap();
+    writer.start_file("word/document.xml", options).unwrap();
+    writer.write_all(document.as_bytes()).unwrap();
+    writer.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_ingestion_docx_extract() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("docx.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone());
+
+    let request = IngestionRequest::File {
+        filename: "minutes.docx".to_string(),
+        content: build_docx(&["Quarterly review", "Revenue grew in Tokyo"]),
+        mime_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            .to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+
+    let node_ids = pipeline.ingest(request).await.unwrap();
+    let node = repo.get_node(node_ids[0]).await.unwrap();
+    assert!(node
+        .data
+        .contains("Quarterly review\nRevenue grew in Tokyo"));
+}
+
+#[tokio::test]
+async fn test_ingestion_malformed_docx_fails_extraction() {
+    use ingestion::processor::IngestionError;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("docx_bad.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo);
+
+    let request = IngestionRequest::File {
+        filename: "broken.docx".to_string(),
+        content: b"PK\x03\x04 truncated".to_vec(),
+        mime_type: "application/octet-stream".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+
+    let err = pipeline.ingest(request).await.unwrap_err();
+    assert!(matches!(err, IngestionError::ExtractionFailed(kind) if kind == "docx"));
+}
+
+#[tokio::test]
+async fn test_ingestion_rejects_oversized_file_before_extraction() {
+    use ingestion::processor::{IngestionError, IngestionLimits};
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("limits.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone()).with_limits(IngestionLimits {
+        max_file_bytes: 16,
+        ..IngestionLimits::default()
+    });
+
+    let request = IngestionRequest::File {
+        filename: "notes.txt".to_string(),
+        content: vec![b'a'; 17],
+        mime_type: "text/plain".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+
+    let err = pipeline.ingest(request).await.unwrap_err();
+    assert!(matches!(
+        err,
+        IngestionError::RequestTooLarge {
+            field: "content",
+            limit: 16
+        }
+    ));
+    assert!(repo.list_node_ids().await.is_empty());
+}
+
 #[tokio::test]
 async fn test_ingestion_with_job_queue() {
     use jobs::queue::ChannelJobQueue;
@@ -282,6 +383,51 @@ impl jobs::queue::JobQueue for CapturingQueue {
     }
 }
 
+#[tokio::test]
+async fn test_queue_full_ingest_can_be_retried_without_losing_jobs() {
+    use jobs::queue::{BackpressurePolicy, ChannelJobQueue, Job, JobQueue};
+
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("queue_full.wal"))
+            .await
+            .unwrap(),
+    );
+    let (queue, mut receiver) = ChannelJobQueue::bounded(1, BackpressurePolicy::Reject);
+    let queue = Arc::new(queue);
+    let mut pipeline = IngestionPipeline::new(repo.clone());
+    pipeline.set_job_queue(queue.clone());
+
+    queue
+        .enqueue(Job::ExtractEntities {
+            node_id: 0,
+            content: "occupies the only slot".to_string(),
+            model_id: "m".to_string(),
+            snapshot_id: "wal-lsn-0".to_string(),
+        })
+        .await
+        .unwrap();
+    let request = IngestionRequest::Text {
+        content: "Backpressure should not lose extraction".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: Some("doc-1".to_string()),
+        model_id: None,
+    };
+    let err = pipeline.ingest(request.clone()).await.unwrap_err();
+    assert!(matches!(err, IngestionError::JobQueue(_)), "{err:?}");
+
+    receiver.recv().await.unwrap();
+    let report = pipeline.ingest_detailed(request.clone()).await.unwrap();
+    assert!(!report.deduped);
+    assert_eq!(report.jobs_enqueued, 1);
+    match receiver.recv().await.unwrap() {
+        Job::ExtractEntities { node_id, .. } => assert_eq!(node_id, report.node_ids[0]),
+    }
+
+    // Once the jobs are queued, repeats dedupe as usual.
+    assert!(pipeline.ingest_detailed(request).await.unwrap().deduped);
+}
+
 #[tokio::test]
 async fn test_ingestion_enqueues_fixed_model_and_snapshot_for_reproducibility() {
     let dir = tempdir().unwrap();
@@ -319,6 +465,57 @@ async fn test_ingestion_enqueues_fixed_model_and_snapshot_for_reproducibility()
     }
 }
 
+#[tokio::test]
+async fn test_ingest_detailed_reports_chunks_redactions_and_jobs() {
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("report.wal"))
+            .await
+            .unwrap(),
+    );
+    let captured = Arc::new(Mutex::new(Vec::new()));
+
+    let mut pipeline = IngestionPipeline::with_components(
+        repo.clone(),
+        Box::new(FixedChunker {
+            chunks: vec![
+                "chunk-a".to_string(),
+                "chunk-b".to_string(),
+                "chunk-c".to_string(),
+            ],
+        }),
+        Box::new(DeterministicEmbedder::default()),
+        Box::new(BasicPolicy::new(Vec::new(), true)),
+        "embedding-default-v1",
+    );
+    pipeline.set_job_queue(Arc::new(CapturingQueue {
+        jobs: captured.clone(),
+    }));
+
+    let request = IngestionRequest::Text {
+        content: "Contact alice@example.com or 090-1234-5678 for details".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: Some("report-key".to_string()),
+        model_id: None,
+    };
+
+    let report = pipeline.ingest_detailed(request.clone()).await.unwrap();
+    assert_eq!(report.node_ids.len(), 3);
+    assert_eq!(report.chunk_count, 3);
+    assert!(!report.deduped);
+    assert_eq!(report.redactions, 2);
+    assert_eq!(report.jobs_enqueued, 3);
+    assert_eq!(report.model_id, "embedding-default-v1");
+    assert_eq!(captured.lock().await.len(), 3);
+
+    let replay = pipeline.ingest_detailed(request).await.unwrap();
+    assert_eq!(replay.node_ids, report.node_ids);
+    assert!(replay.deduped);
+    assert_eq!(replay.chunk_count, 0);
+    assert_eq!(replay.jobs_enqueued, 0);
+    assert_eq!(captured.lock().await.len(), 3);
+}
+
 #[tokio::test]
 async fn test_ingestion_flushes_buffered_wal_before_enqueuing_snapshot() {
     let dir = tempdir().unwrap();
@@ -421,3 +618,600 @@ async fn test_ingestion_is_failsafe_when_extraction_model_fails() {
         "failed extraction must not break ingestion and should produce no graph edges"
     );
 }
+
+#[tokio::test]
+async fn test_hash_chunk_id_collision_probes_with_salt() {
+    use alayasiki_core::ingest::ContentHash;
+    use alayasiki_core::model::Node;
+    use ingestion::chunk_id::{ChunkIdContext, ChunkIdStrategy, HashChunkIdStrategy};
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("chunk_id_collision.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::with_chunker(
+        repo.clone(),
+        Box::new(FixedChunker {
+            chunks: vec!["fresh chunk".to_string()],
+        }),
+    );
+
+    let request = IngestionRequest::text("fresh document".to_string(), HashMap::new());
+    let metadata = HashMap::new();
+    let colliding_id = HashChunkIdStrategy.chunk_id(
+        &ChunkIdContext {
+            content_hash: &request.content_hash(),
+            tenant: None,
+            index: 0,
+            content: "fresh chunk",
+            metadata: &metadata,
+        },
+        0,
+    );
+    // Occupy the id the hash strategy will derive with a node from other content.
+    repo.put_node(Node::new(
+        colliding_id,
+        vec![0.1; 4],
+        "squatter".to_string(),
+    ))
+    .await
+    .unwrap();
+
+    let node_ids = pipeline.ingest(request).await.unwrap();
+
+    assert_eq!(node_ids.len(), 1);
+    assert_ne!(node_ids[0], colliding_id);
+    assert_eq!(repo.get_node(colliding_id).await.unwrap().data, "squatter");
+    assert_eq!(
+        repo.get_node(node_ids[0]).await.unwrap().data,
+        "fresh chunk"
+    );
+}
+
+#[tokio::test]
+async fn test_custom_chunk_id_strategy_assigns_ids() {
+    use ingestion::chunk_id::FnChunkIdStrategy;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("chunk_id_custom.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::with_chunker(
+        repo.clone(),
+        Box::new(FixedChunker {
+            chunks: vec!["first".to_string(), "second".to_string()],
+        }),
+    )
+    .with_chunk_id_strategy(Arc::new(FnChunkIdStrategy::new(|ctx| 9_000 + ctx.index)));
+
+    let node_ids = pipeline
+        .ingest(IngestionRequest::text(
+            "custom ids".to_string(),
+            HashMap::new(),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(node_ids, vec![9_000, 9_001]);
+    assert_eq!(repo.get_node(9_001).await.unwrap().data, "second");
+}
+
+#[derive(Default)]
+struct ConcurrencyProbeEmbedder {
+    in_flight: std::sync::atomic::AtomicUsize,
+    max_in_flight: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl ingestion::embedding::Embedder for ConcurrencyProbeEmbedder {
+    fn embed<'a>(
+        &'a self,
+        text: &'a str,
+        _model_id: &'a str,
+    ) -> ingestion::embedding::BoxFuture<'a, Vec<f32>> {
+        use std::sync::atomic::Ordering;
+
+        Box::pin(async move {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            // Earlier chunks finish last so ordering must come from the pipeline.
+            let index: u64 = text.trim_start_matches("chunk-").parse().unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(40 - index * 10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            vec![index as f32, 1.0]
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_chunks_are_embedded_concurrently_in_order() {
+    use std::sync::atomic::Ordering;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("parallel_embed.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let embedder = ConcurrencyProbeEmbedder::default();
+    let max_in_flight = embedder.max_in_flight.clone();
+
+    let chunks: Vec<String> = (0..4).map(|i| format!("chunk-{i}")).collect();
+    let pipeline = IngestionPipeline::with_components(
+        repo.clone(),
+        Box::new(FixedChunker {
+            chunks: chunks.clone(),
+        }),
+        Box::new(embedder),
+        Box::new(ingestion::policy::NoOpPolicy),
+        "probe-model",
+    )
+    .with_embedding_concurrency(3);
+
+    let node_ids = pipeline
+        .ingest(IngestionRequest::text(
+            "parallel".to_string(),
+            HashMap::new(),
+        ))
+        .await
+        .unwrap();
+
+    let max_in_flight = max_in_flight.load(Ordering::SeqCst);
+    assert!(max_in_flight > 1, "expected parallel embedding");
+    assert!(max_in_flight <= 3, "concurrency limit exceeded");
+
+    assert_eq!(node_ids.len(), chunks.len());
+    for (index, node_id) in node_ids.iter().enumerate() {
+        let node = repo.get_node(*node_id).await.unwrap();
+        assert_eq!(node.data, chunks[index]);
+        assert_eq!(node.embedding[0], index as f32);
+    }
+}
+
+/// Cancels `token` while embedding the `cancel_at`-th chunk (1-based).
+struct CancellingEmbedder {
+    token: CancellationToken,
+    cancel_at: usize,
+    calls: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl ingestion::embedding::Embedder for CancellingEmbedder {
+    fn embed<'a>(
+        &'a self,
+        text: &'a str,
+        model_id: &'a str,
+    ) -> ingestion::embedding::BoxFuture<'a, Vec<f32>> {
+        Box::pin(async move {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            if call == self.cancel_at {
+                self.token.cancel();
+            }
+            alayasiki_core::embedding::deterministic_embedding(text, model_id, 4)
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_ingest_stops_embedding_and_can_be_retried() {
+    use std::sync::atomic::Ordering;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("cancel_ingest.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let token = CancellationToken::new();
+    let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let chunks: Vec<String> = (0..5).map(|i| format!("chunk-{i}")).collect();
+    let pipeline = IngestionPipeline::with_components(
+        repo.clone(),
+        Box::new(FixedChunker {
+            chunks: chunks.clone(),
+        }),
+        Box::new(CancellingEmbedder {
+            token: token.clone(),
+            cancel_at: 2,
+            calls: calls.clone(),
+        }),
+        Box::new(ingestion::policy::NoOpPolicy),
+        "cancel-model",
+    )
+    .with_embedding_concurrency(1);
+    let request = || IngestionRequest::text("long document".to_string(), HashMap::new());
+
+    let result = pipeline.ingest_cancellable(request(), &token).await;
+    assert!(matches!(result, Err(IngestionError::Cancelled)));
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        2,
+        "no chunk embedded after cancel"
+    );
+    assert!(repo.list_node_ids().await.is_empty());
+
+    let already_cancelled = pipeline.ingest_cancellable(request(), &token).await;
+    assert!(matches!(already_cancelled, Err(IngestionError::Cancelled)));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let report = pipeline.ingest_detailed(request()).await.unwrap();
+    assert!(!report.deduped);
+    assert_eq!(report.node_ids.len(), chunks.len());
+    assert_eq!(repo.list_node_ids().await.len(), chunks.len());
+}
+
+/// Emits one chunk per non-empty line and records the largest input it saw.
+struct LineChunker {
+    max_input_bytes: Arc<std::sync::atomic::AtomicUsize>,
+    calls: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl Chunker for LineChunker {
+    fn chunk<'a>(
+        &'a self,
+        content: &'a str,
+        base_metadata: HashMap<String, String>,
+    ) -> BoxFuture<'a, Vec<Chunk>> {
+        use std::sync::atomic::Ordering;
+        self.max_input_bytes
+            .fetch_max(content.len(), Ordering::SeqCst);
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Chunk {
+                    content: line.to_string(),
+                    metadata: base_metadata.clone(),
+                    embedding: None,
+                })
+                .collect()
+        })
+    }
+}
+
+/// Generates `total` numbered lines on demand without materializing the file.
+struct SyntheticLines {
+    next: usize,
+    total: usize,
+    pending: Vec<u8>,
+}
+
+impl tokio::io::AsyncRead for SyntheticLines {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        if self.pending.is_empty() && self.next < self.total {
+            let line = format!("line {} of the synthetic corpus\n", self.next);
+            self.pending = line.into_bytes();
+            self.next += 1;
+        }
+        let take = self.pending.len().min(buf.remaining());
+        let chunk: Vec<u8> = self.pending.drain(..take).collect();
+        buf.put_slice(&chunk);
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_ingest_stream_processes_large_text_segment_by_segment() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("stream.wal"))
+            .await
+            .unwrap(),
+    );
+    let max_input_bytes = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let chunker = LineChunker {
+        max_input_bytes: max_input_bytes.clone(),
+        calls: calls.clone(),
+    };
+    let pipeline = IngestionPipeline::with_chunker(repo.clone(), Box::new(chunker))
+        .with_stream_segment_bytes(2048);
+
+    let total_lines = 1_500;
+    let reader = SyntheticLines {
+        next: 0,
+        total: total_lines,
+        pending: Vec::new(),
+    };
+    let ids = pipeline
+        .ingest_stream(reader, "text/plain", HashMap::new())
+        .await
+        .unwrap();
+
+    assert_eq!(ids.len(), total_lines);
+    assert_eq!(repo.list_node_ids().await.len(), total_lines);
+    assert!(max_input_bytes.load(Ordering::SeqCst) <= 2048);
+    assert!(calls.load(Ordering::SeqCst) > 20);
+    let first = repo.get_node(ids[0]).await.unwrap();
+    assert_eq!(first.data, "line 0 of the synthetic corpus");
+    let last = repo.get_node(ids[total_lines - 1]).await.unwrap();
+    assert_eq!(last.data, "line 1499 of the synthetic corpus");
+
+    // Streaming the same bytes again reuses the node ids without rewriting
+    // the unchanged nodes.
+    let snapshot = repo.current_snapshot_id().await;
+    let again = pipeline
+        .ingest_stream(
+            SyntheticLines {
+                next: 0,
+                total: total_lines,
+                pending: Vec::new(),
+            },
+            "text/plain",
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(again, ids);
+    assert_eq!(repo.list_node_ids().await.len(), total_lines);
+    assert_eq!(repo.current_snapshot_id().await, snapshot);
+}
+
+/// Replaces every `secret` with a longer marker, leaving whitespace alone.
+struct SecretPolicy;
+
+impl ingestion::policy::ContentPolicy for SecretPolicy {
+    fn apply(&self, text: &str) -> Result<String, ingestion::policy::PolicyError> {
+        Ok(text.replace("secret", "[REDACTED]"))
+    }
+}
+
+#[tokio::test]
+async fn test_ingest_stream_doc_offsets_index_the_redacted_stream() {
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("stream_offsets.wal"))
+            .await
+            .unwrap(),
+    );
+    let pipeline = IngestionPipeline::with_components(
+        repo.clone(),
+        Box::new(SemanticChunker::new(ChunkingConfig {
+            max_chars: 60,
+            overlap_chars: 0,
+            ..Default::default()
+        })),
+        Box::new(DeterministicEmbedder::default()),
+        Box::new(SecretPolicy),
+        "embedding-default-v1",
+    )
+    .with_stream_segment_bytes(64);
+
+    let content: String = (0..12)
+        .map(|i| format!("Line {i} mentions a secret code today.\n"))
+        .collect();
+    let redacted = content.replace("secret", "[REDACTED]");
+
+    let ids = pipeline
+        .ingest_stream(content.as_bytes(), "text/plain", HashMap::new())
+        .await
+        .unwrap();
+    assert!(ids.len() > 1);
+    for id in ids {
+        let node = repo.get_node(id).await.unwrap();
+        let start: usize = node.metadata[DOC_CHAR_START_KEY].parse().unwrap();
+        let end: usize = node.metadata[DOC_CHAR_END_KEY].parse().unwrap();
+        assert_eq!(&redacted[start..end], node.data);
+    }
+}
+
+/// Fails the test if the pipeline tries to embed anything.
+struct UnreachableEmbedder;
+
+impl ingestion::embedding::Embedder for UnreachableEmbedder {
+    fn embed<'a>(
+        &'a self,
+        text: &'a str,
+        _model_id: &'a str,
+    ) -> ingestion::embedding::BoxFuture<'a, Vec<f32>> {
+        panic!("pre-embedded chunk {text:?} was re-embedded")
+    }
+}
+
+#[tokio::test]
+async fn test_pre_embedded_chunks_are_stored_without_re_embedding() {
+    use alayasiki_core::ingest::PreEmbeddedChunk;
+    use ingestion::processor::IngestionError;
+
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("pre_embedded.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::with_components(
+        repo.clone(),
+        Box::new(FixedChunker { chunks: Vec::new() }),
+        Box::new(UnreachableEmbedder),
+        Box::new(ingestion::policy::NoOpPolicy),
+        "gpu-embedder-v2",
+    );
+
+    let chunks = vec![
+        PreEmbeddedChunk {
+            content: "first precomputed chunk".to_string(),
+            embedding: vec![0.125, -0.5, 0.333_333_34],
+            metadata: HashMap::from([("page".to_string(), "1".to_string())]),
+        },
+        PreEmbeddedChunk {
+            content: "second precomputed chunk".to_string(),
+            embedding: vec![1.0e-7, 0.75, -0.999_999],
+            metadata: HashMap::new(),
+        },
+    ];
+    let request = IngestionRequest::PreEmbedded {
+        chunks: chunks.clone(),
+        metadata: HashMap::from([("source".to_string(), "gpu-batch".to_string())]),
+        idempotency_key: Some("batch-1".to_string()),
+        model_id: None,
+    };
+
+    let node_ids = pipeline.ingest(request.clone()).await.unwrap();
+    assert_eq!(node_ids.len(), 2);
+    for (node_id, chunk) in node_ids.iter().zip(&chunks) {
+        let node = repo.get_node(*node_id).await.unwrap();
+        assert_eq!(node.data, chunk.content);
+        assert_eq!(node.embedding, chunk.embedding);
+        assert_eq!(node.metadata.get("source").unwrap(), "gpu-batch");
+        assert_eq!(node.metadata.get("model_id").unwrap(), "gpu-embedder-v2");
+    }
+    let first = repo.get_node(node_ids[0]).await.unwrap();
+    assert_eq!(first.metadata.get("page").unwrap(), "1");
+
+    // Idempotency still applies to the pre-embedded path.
+    assert_eq!(pipeline.ingest(request).await.unwrap(), node_ids);
+
+    let mismatched = IngestionRequest::pre_embedded(
+        vec![PreEmbeddedChunk {
+            content: "wrong dimension".to_string(),
+            embedding: vec![0.1, 0.2],
+            metadata: HashMap::new(),
+        }],
+        HashMap::new(),
+    );
+    let err = pipeline.ingest(mismatched).await.unwrap_err();
+    assert!(matches!(
+        err,
+        IngestionError::EmbeddingDimensionMismatch {
+            index: 0,
+            expected: 3,
+            actual: 2
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_blank_content_is_rejected_as_empty_extraction() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("blank.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone());
+
+    let request = IngestionRequest::Text {
+        content: "  \n\t  ".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+
+    let err = pipeline.ingest(request).await.unwrap_err();
+    assert!(matches!(err, IngestionError::ExtractionFailed(ref reason) if reason == "empty"));
+    assert!(repo.list_node_ids().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_zero_min_chunks_accepts_blank_content() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("blank_allowed.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    let chunker = SemanticChunker::new(ChunkingConfig {
+        min_chunks: 0,
+        ..Default::default()
+    });
+    let pipeline = IngestionPipeline::with_chunker(repo.clone(), Box::new(chunker));
+
+    let request = IngestionRequest::Text {
+        content: "  \n\t  ".to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+
+    assert!(pipeline.ingest(request).await.unwrap().is_empty());
+    assert!(repo.list_node_ids().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_max_chunks_merges_long_input_into_capped_chunks() {
+    let sentences: Vec<String> = (0..40)
+        .map(|i| format!("Sentence number {i} is here."))
+        .collect();
+    let content = sentences.join(" ");
+    let chunker = SemanticChunker::new(ChunkingConfig {
+        max_chars: 40,
+        overlap_chars: 0,
+        max_chunks: Some(5),
+        ..Default::default()
+    });
+
+    let uncapped = SemanticChunker::new(ChunkingConfig {
+        max_chars: 40,
+        overlap_chars: 0,
+        ..Default::default()
+    })
+    .chunk(&content, HashMap::new())
+    .await;
+    assert!(uncapped.len() > 5);
+
+    let chunks = chunker.chunk(&content, HashMap::new()).await;
+    assert_eq!(chunks.len(), 5);
+    for sentence in &sentences {
+        assert_eq!(
+            chunks
+                .iter()
+                .filter(|c| c.content.contains(sentence))
+                .count(),
+            1,
+            "{sentence} should land in exactly one chunk"
+        );
+    }
+    for (index, chunk) in chunks.iter().enumerate() {
+        assert_eq!(chunk.metadata["chunk_index"], index.to_string());
+        let start: usize = chunk.metadata[DOC_CHAR_START_KEY].parse().unwrap();
+        let end: usize = chunk.metadata[DOC_CHAR_END_KEY].parse().unwrap();
+        assert_eq!(&content[start..end], chunk.content);
+    }
+}
+
+#[tokio::test]
+async fn test_content_normalization_dedupes_whitespace_variants_only_when_enabled() {
+    use alayasiki_core::ingest::ContentNormalization;
+
+    let text = |content: &str| IngestionRequest::Text {
+        content: content.to_string(),
+        metadata: HashMap::new(),
+        idempotency_key: None,
+        model_id: None,
+    };
+    let normalized = IngestOptions {
+        normalization: ContentNormalization {
+            collapse_whitespace: true,
+            lowercase: false,
+        },
+        ..IngestOptions::default()
+    };
+
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(Repository::open(dir.path().join("norm.wal")).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo.clone());
+    let first = pipeline
+        .ingest_with_options(text("Quarterly report for Acme."), normalized.clone())
+        .await
+        .unwrap();
+    let second = pipeline
+        .ingest_with_options(text("Quarterly report for Acme.  \n"), normalized)
+        .await
+        .unwrap();
+    assert!(!first.deduped);
+    assert!(second.deduped);
+    assert_eq!(second.node_ids, first.node_ids);
+    assert_eq!(
+        repo.get_node(first.node_ids[0]).await.unwrap().data,
+        "Quarterly report for Acme."
+    );
+    // Normalized hashes live in their own domain: a raw ingest of text that
+    // is already in normal form is not mistaken for a normalized repeat.
+    let raw = pipeline
+        .ingest_detailed(text("Quarterly report for Acme."))
+        .await
+        .unwrap();
+    assert!(!raw.deduped);
+
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(Repository::open(dir.path().join("raw.wal")).await.unwrap());
+    let pipeline = IngestionPipeline::new(repo);
+    let first = pipeline
+        .ingest_detailed(text("Quarterly report for Acme."))
+        .await
+        .unwrap();
+    let second = pipeline
+        .ingest_detailed(text("Quarterly report for Acme.  \n"))
+        .await
+        .unwrap();
+    assert!(!second.deduped);
+    assert_ne!(second.node_ids, first.node_ids);
+}
@@ -0,0 +1,91 @@
+use ingestion::chunker::{Chunker, ChunkingConfig, MarkdownChunker};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn markdown_chunker_records_nested_heading_paths() {
+    let doc = "\
+Preamble before any heading.
+
+# Guide
+Overview of the guide.
+
+## Install
+Run the installer.
+
+```sh
+# not a heading
+cargo build
+```
+
+### Linux
+Use the package manager.
+
+## Usage
+Call the API.
+
+# Appendix
+Extra notes.
+
+### Errata
+Skips a heading level.
+";
+    let chunker = MarkdownChunker::default();
+    let base = HashMap::from([("source".to_string(), "guide.md".to_string())]);
+    let chunks = chunker.chunk(doc, base).await;
+
+    let paths: Vec<Option<&str>> = chunks
+        .iter()
+        .map(|chunk| chunk.metadata.get("heading_path").map(String::as_str))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            None,
+            Some("Guide"),
+            Some("Guide > Install"),
+            Some("Guide > Install > Linux"),
+            Some("Guide > Usage"),
+            Some("Appendix"),
+            Some("Appendix > Errata"),
+        ]
+    );
+
+    let install = &chunks[2];
+    assert!(install.content.starts_with("## Install"));
+    assert!(install.content.contains("# not a heading"));
+    assert_eq!(install.metadata.get("heading_level").unwrap(), "2");
+    // The level is the innermost heading's `#` count, not its nesting depth.
+    assert_eq!(chunks[6].metadata.get("heading_level").unwrap(), "3");
+    assert_eq!(install.metadata.get("source").unwrap(), "guide.md");
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        assert_eq!(
+            chunk.metadata.get("chunk_index").unwrap(),
+            &index.to_string()
+        );
+        let offset = |key: &str| chunk.metadata[key].parse::<usize>().unwrap();
+        assert_eq!(
+            &doc[offset("doc_char_start")..offset("doc_char_end")],
+            chunk.content
+        );
+    }
+}
+
+#[tokio::test]
+async fn markdown_chunker_splits_long_sections_by_size() {
+    let body = "Sentence about storage engines. ".repeat(20);
+    let doc = format!("# Storage\n## WAL\n{body}\n");
+    let chunker = MarkdownChunker::new(ChunkingConfig {
+        max_chars: 120,
+        overlap_chars: 0,
+        ..Default::default()
+    });
+
+    let chunks = chunker.chunk(&doc, HashMap::new()).await;
+
+    assert!(chunks.len() > 2);
+    assert!(chunks.iter().all(|chunk| chunk.content.len() <= 120));
+    assert!(chunks[1..]
+        .iter()
+        .all(|chunk| chunk.metadata.get("heading_path").unwrap() == "Storage > WAL"));
+}
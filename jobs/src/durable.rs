@@ -152,6 +152,7 @@ impl DurableJobQueue {
         let wal_options = WalOptions {
             recovery_mode: config.recovery_mode,
             flush_policy: WalFlushPolicy::Always,
+            ..WalOptions::default()
         };
         let mut wal = Wal::open_with_options(path, wal_options).await?;
 
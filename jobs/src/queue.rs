@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Job {
@@ -16,23 +19,135 @@ pub trait JobQueue: Send + Sync {
     async fn enqueue(&self, job: Job) -> anyhow::Result<()>;
 }
 
+/// What [`ChannelJobQueue::enqueue`] does when the channel is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait until the consumer frees capacity.
+    #[default]
+    Block,
+    /// Evict the oldest pending job to make room for the new one.
+    DropOldest,
+    /// Fail immediately with [`QueueFull`].
+    Reject,
+}
+
+/// Returned (inside `anyhow::Error`) when a [`BackpressurePolicy::Reject`]
+/// queue is full. Callers can detect it with `err.is::<QueueFull>()`.
+#[derive(Debug, Error)]
+#[error("job queue is full")]
+pub struct QueueFull;
+
+/// Consumer side of a [`ChannelJobQueue`].
+pub enum JobReceiver {
+    Channel(mpsc::Receiver<Job>),
+    /// Shared with a [`BackpressurePolicy::DropOldest`] queue so it can evict.
+    Shared(Arc<Mutex<mpsc::Receiver<Job>>>),
+}
+
+impl JobReceiver {
+    pub async fn recv(&mut self) -> Option<Job> {
+        match self {
+            JobReceiver::Channel(receiver) => receiver.recv().await,
+            JobReceiver::Shared(receiver) => receiver.lock().await.recv().await,
+        }
+    }
+}
+
+impl From<mpsc::Receiver<Job>> for JobReceiver {
+    fn from(receiver: mpsc::Receiver<Job>) -> Self {
+        JobReceiver::Channel(receiver)
+    }
+}
+
 /// Simple in-memory queue using Tokio channels
 pub struct ChannelJobQueue {
     sender: mpsc::Sender<Job>,
+    overflow: Overflow,
+}
+
+enum Overflow {
+    Block,
+    Reject,
+    /// Evicts through the receiver it shares with the consumer.
+    DropOldest(Arc<Mutex<mpsc::Receiver<Job>>>),
 }
 
 impl ChannelJobQueue {
+    /// Queue over an existing channel that blocks when it is full.
     pub fn new(sender: mpsc::Sender<Job>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            overflow: Overflow::Block,
+        }
+    }
+
+    /// Create a channel holding up to `capacity` jobs (at least 1) with the
+    /// given overflow policy. Hand the receiver to the worker.
+    pub fn bounded(capacity: usize, policy: BackpressurePolicy) -> (Self, JobReceiver) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let (overflow, receiver) = match policy {
+            BackpressurePolicy::Block => (Overflow::Block, JobReceiver::Channel(receiver)),
+            BackpressurePolicy::Reject => (Overflow::Reject, JobReceiver::Channel(receiver)),
+            BackpressurePolicy::DropOldest => {
+                let shared = Arc::new(Mutex::new(receiver));
+                (
+                    Overflow::DropOldest(shared.clone()),
+                    JobReceiver::Shared(shared),
+                )
+            }
+        };
+        (Self { sender, overflow }, receiver)
     }
+
+    pub fn policy(&self) -> BackpressurePolicy {
+        match self.overflow {
+            Overflow::Block => BackpressurePolicy::Block,
+            Overflow::Reject => BackpressurePolicy::Reject,
+            Overflow::DropOldest(_) => BackpressurePolicy::DropOldest,
+        }
+    }
+}
+
+fn closed_error() -> anyhow::Error {
+    anyhow::anyhow!("Queue send error: channel closed")
 }
 
 #[async_trait::async_trait]
 impl JobQueue for ChannelJobQueue {
     async fn enqueue(&self, job: Job) -> anyhow::Result<()> {
-        self.sender
-            .send(job)
-            .await
-            .map_err(|e| anyhow::anyhow!("Queue send error: {}", e))
+        match &self.overflow {
+            Overflow::Block => self
+                .sender
+                .send(job)
+                .await
+                .map_err(|e| anyhow::anyhow!("Queue send error: {}", e)),
+            Overflow::Reject => self.sender.try_send(job).map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => anyhow::Error::new(QueueFull),
+                mpsc::error::TrySendError::Closed(_) => closed_error(),
+            }),
+            Overflow::DropOldest(receiver) => {
+                let mut job = job;
+                loop {
+                    match self.sender.try_send(job) {
+                        Ok(()) => return Ok(()),
+                        Err(mpsc::error::TrySendError::Closed(_)) => return Err(closed_error()),
+                        Err(mpsc::error::TrySendError::Full(rejected)) => {
+                            job = rejected;
+                            if Arc::strong_count(receiver) == 1 {
+                                // The consumer's JobReceiver has been dropped.
+                                return Err(closed_error());
+                            }
+                            // A concurrent consumer may have drained a slot
+                            // already; the retry then succeeds without eviction.
+                            if let Ok(Job::ExtractEntities { node_id, .. }) =
+                                receiver.lock().await.try_recv()
+                            {
+                                warn!("Job queue full; dropped oldest job for node {node_id}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
     }
 }
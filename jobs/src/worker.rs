@@ -1,5 +1,5 @@
 use crate::durable::{DurableJobQueue, JobEnvelope};
-use crate::queue::Job;
+use crate::queue::{Job, JobReceiver};
 use sha2::{Digest, Sha256};
 use slm::ner::EntityExtractor;
 use slm::registry::ModelRegistry;
@@ -10,7 +10,7 @@ use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 pub struct Worker {
-    receiver: Option<mpsc::Receiver<Job>>,
+    receiver: Option<JobReceiver>,
     repo: Arc<Repository>,
     registry: Arc<ModelRegistry>,
     default_model_ref: String,
@@ -18,7 +18,7 @@ pub struct Worker {
 
 impl Worker {
     pub fn new(
-        receiver: mpsc::Receiver<Job>,
+        receiver: impl Into<JobReceiver>,
         repo: Arc<Repository>,
         extractor: Arc<dyn EntityExtractor>,
     ) -> Self {
@@ -31,7 +31,7 @@ impl Worker {
             .expect("legacy extractor activation must succeed");
 
         Self {
-            receiver: Some(receiver),
+            receiver: Some(receiver.into()),
             repo,
             registry: Arc::new(registry),
             default_model_ref: "legacy-default".to_string(),
@@ -59,13 +59,13 @@ impl Worker {
     }
 
     pub fn with_registry(
-        receiver: mpsc::Receiver<Job>,
+        receiver: impl Into<JobReceiver>,
         repo: Arc<Repository>,
         registry: Arc<ModelRegistry>,
         default_model_ref: impl Into<String>,
     ) -> Self {
         Self {
-            receiver: Some(receiver),
+            receiver: Some(receiver.into()),
             repo,
             registry,
             default_model_ref: default_model_ref.into(),
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use jobs::queue::{BackpressurePolicy, ChannelJobQueue, Job, JobQueue, JobReceiver, QueueFull};
+use tokio::time::timeout;
+
+fn job(node_id: u64) -> Job {
+    Job::ExtractEntities {
+        node_id,
+        content: format!("chunk {node_id}"),
+        model_id: "triplex-lite".to_string(),
+        snapshot_id: "wal-lsn-1".to_string(),
+    }
+}
+
+async fn recv_id(receiver: &mut JobReceiver) -> u64 {
+    match receiver.recv().await.expect("queue closed") {
+        Job::ExtractEntities { node_id, .. } => node_id,
+    }
+}
+
+#[tokio::test]
+async fn block_policy_waits_until_the_consumer_drains() {
+    let (queue, mut receiver) = ChannelJobQueue::bounded(1, BackpressurePolicy::Block);
+    let queue = Arc::new(queue);
+    queue.enqueue(job(1)).await.unwrap();
+
+    let pending = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.enqueue(job(2)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(
+        !pending.is_finished(),
+        "enqueue must wait while the queue is full"
+    );
+
+    assert_eq!(recv_id(&mut receiver).await, 1);
+    timeout(Duration::from_secs(1), pending)
+        .await
+        .expect("enqueue should resolve once capacity frees up")
+        .unwrap()
+        .unwrap();
+    assert_eq!(recv_id(&mut receiver).await, 2);
+}
+
+#[tokio::test]
+async fn reject_policy_returns_queue_full() {
+    let (queue, mut receiver) = ChannelJobQueue::bounded(1, BackpressurePolicy::Reject);
+    queue.enqueue(job(1)).await.unwrap();
+
+    let err = queue.enqueue(job(2)).await.unwrap_err();
+    assert!(err.is::<QueueFull>(), "{err}");
+
+    assert_eq!(recv_id(&mut receiver).await, 1);
+    queue.enqueue(job(3)).await.unwrap();
+    assert_eq!(recv_id(&mut receiver).await, 3);
+}
+
+#[tokio::test]
+async fn drop_oldest_policy_evicts_the_oldest_pending_job() {
+    let (queue, mut receiver) = ChannelJobQueue::bounded(2, BackpressurePolicy::DropOldest);
+    for node_id in 1..=4 {
+        queue.enqueue(job(node_id)).await.unwrap();
+    }
+
+    assert_eq!(recv_id(&mut receiver).await, 3);
+    assert_eq!(recv_id(&mut receiver).await, 4);
+
+    drop(receiver);
+    assert!(queue.enqueue(job(5)).await.is_ok());
+    assert!(queue.enqueue(job(6)).await.is_ok());
+    assert!(queue.enqueue(job(7)).await.is_err(), "no consumer left");
+}
@@ -1,10 +1,17 @@
+use crate::graphrag::MAX_GLOBAL_COMMUNITIES;
+pub use crate::tokenizer::TokenizerLanguage;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+pub use storage::index::SearchAccuracy;
 use thiserror::Error;
 
 const DEFAULT_DEPTH: u8 = 1;
 const DEFAULT_TOP_K: usize = 20;
+const DEFAULT_ANSWER_EVIDENCE_COUNT: usize = 3;
 const MAX_TOP_K: usize = 1_000;
-const MAX_DEPTH: u8 = 8;
+const DEFAULT_MAX_QUERY_CHARS: usize = 8_192;
+const DEFAULT_MAX_FILTER_VALUES: usize = 256;
+const DEFAULT_MAX_ANCHOR_IDS: usize = 1_024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -12,6 +19,9 @@ pub enum QueryMode {
     #[default]
     Answer,
     Evidence,
+    /// Runs the same retrieval but returns only [`crate::QueryCounts`]: no
+    /// answer, evidence or citations.
+    Count,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
@@ -24,10 +34,70 @@ pub enum SearchMode {
     Auto,
 }
 
+/// How edge confidences along an expansion path discount the confidence of
+/// the node it reaches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidencePropagation {
+    /// Node confidence is its `confidence` metadata, else its score; the path
+    /// is ignored.
+    #[default]
+    Metadata,
+    /// Multiply by the weakest edge confidence on the best path.
+    MinEdge,
+    /// Multiply by the product of edge confidences on the best path.
+    Product,
+}
+
+impl ConfidencePropagation {
+    /// Folds one more edge confidence into a path confidence.
+    pub fn extend(self, path: f32, edge: f32) -> f32 {
+        let edge = edge.clamp(0.0, 1.0);
+        match self {
+            ConfidencePropagation::Metadata => path,
+            ConfidencePropagation::MinEdge => path.min(edge),
+            ConfidencePropagation::Product => path * edge,
+        }
+    }
+}
+
+/// How graph distance discounts the score of an expanded node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HopPenalty {
+    /// Divide by `hop + 1`, regardless of the edges on the path.
+    #[default]
+    HopCount,
+    /// Multiply by the product of edge weights (and relation weights) on the
+    /// strongest shortest path from an anchor, capped at 1.0, so a strong
+    /// multi-hop path can outrank a weak single hop.
+    PathWeight,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TimeRange {
-    pub from: String,
-    pub to: String,
+    /// Lower bound (`YYYY-MM-DD`); omit for no lower bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Upper bound (`YYYY-MM-DD`); omit for no upper bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default = "default_inclusive_bound")]
+    pub inclusive_start: bool,
+    #[serde(default = "default_inclusive_bound")]
+    pub inclusive_end: bool,
+    /// Metadata key holding the node's date (`YYYY-MM-DD` or RFC3339), e.g.
+    /// `ingested_at`. Nodes without a parseable value are filtered out.
+    #[serde(default = "default_time_field")]
+    pub time_field: String,
+}
+
+fn default_time_field() -> String {
+    "timestamp".to_string()
+}
+
+fn default_inclusive_bound() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Default)]
@@ -38,14 +108,48 @@ pub struct QueryFilters {
     pub relation_type: Vec<String>,
     #[serde(default)]
     pub time_range: Option<TimeRange>,
+    /// Dotted metadata paths that must equal the given value, e.g.
+    /// `{"author.country": "JP"}`. A path matches a flat key verbatim, or
+    /// descends into a metadata value holding serialized JSON. Nodes where
+    /// the path is missing are filtered out.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Traversal {
     #[serde(default = "default_depth")]
     pub depth: u8,
     #[serde(default)]
     pub relation_types: Vec<String>,
+    /// Relations never followed. Composes with the allow-list
+    /// (`relation_types` / `filters.relation_type`): an edge must pass the
+    /// allow-list, if any, and must not be excluded, so exclusion wins when a
+    /// relation appears in both.
+    #[serde(default)]
+    pub exclude_relation_types: Vec<String>,
+    /// Cap on neighbors followed from any one node; the highest-weighted edges
+    /// are kept. `None` follows every neighbor.
+    #[serde(default)]
+    pub max_neighbors_per_node: Option<usize>,
+    /// Cap on distinct candidates (anchors included) collected before
+    /// ranking. Expansion stops once it is reached, so shallower candidates
+    /// win. `None` collects every reachable node.
+    #[serde(default)]
+    pub max_candidates: Option<usize>,
+    /// Per-relation factor applied to edge weights during expansion scoring.
+    /// Relations missing from the map use `1.0`; `0.0` prunes the relation.
+    /// The relation allow-list, when present, still applies first.
+    #[serde(default)]
+    pub relation_weights: HashMap<String, f32>,
+    /// Discounts each evidence node's confidence by the edges on its best
+    /// path from an anchor. Anchors keep their own confidence.
+    #[serde(default)]
+    pub confidence_propagation: ConfidencePropagation,
+    /// How an expanded node's score is discounted by its distance from an
+    /// anchor: by hop count (the default) or by the weights on its path.
+    #[serde(default)]
+    pub hop_penalty: HopPenalty,
 }
 
 impl Default for Traversal {
@@ -53,11 +157,17 @@ impl Default for Traversal {
         Self {
             depth: default_depth(),
             relation_types: Vec::new(),
+            exclude_relation_types: Vec::new(),
+            max_neighbors_per_node: None,
+            max_candidates: None,
+            relation_weights: HashMap::new(),
+            confidence_propagation: ConfidencePropagation::default(),
+            hop_penalty: HopPenalty::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct QueryRequest {
     pub query: String,
     #[serde(default)]
@@ -81,6 +191,65 @@ pub struct QueryRequest {
     /// When both snapshot_id and time_travel are provided, snapshot_id takes priority.
     #[serde(default)]
     pub time_travel: Option<String>,
+    /// Explicit anchor node IDs. When non-empty, expansion starts from exactly these
+    /// nodes and vector search is skipped; unknown IDs are reported as exclusions.
+    #[serde(default)]
+    pub anchor_ids: Vec<u64>,
+    /// Plan only: report the steps and effective mode without searching or expanding.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Report the normalized query tokens used for lexical scoring in
+    /// `explain.query_tokens`.
+    #[serde(default)]
+    pub debug_tokens: bool,
+    /// Report the top vector hit's per-dimension cosine contributions in
+    /// `explain.vector_explain`. Such requests bypass the result caches.
+    #[serde(default)]
+    pub explain_vector: bool,
+    /// When vector search finds nothing, return empty evidence (exclusion
+    /// `no_vector_match`) instead of seeding expansion from an arbitrary node.
+    #[serde(default)]
+    pub require_match: bool,
+    /// Vector hits scoring below this (after any centrality boost) are not
+    /// used as anchors and are reported as `anchor_below_threshold`.
+    /// Explicit `anchor_ids` are never dropped.
+    #[serde(default)]
+    pub min_anchor_score: Option<f32>,
+    /// How many of the top evidence nodes feed answer synthesis in `answer`
+    /// mode. Fewer are used when less evidence is available.
+    #[serde(default = "default_answer_evidence_count")]
+    pub answer_evidence_count: usize,
+    /// In `answer` mode, return no answer (evidence is still returned) when
+    /// groundedness is below this value in `0.0..=1.0`. Overrides the
+    /// engine's default gate.
+    #[serde(default)]
+    pub min_answer_groundedness: Option<f32>,
+    /// Disable data-dependent strategy changes so the result depends only on
+    /// the snapshot and the request: `auto` never falls back to DRIFT on thin
+    /// evidence, and DRIFT always runs all of its iterations instead of
+    /// stopping once evidence looks sufficient. This can cost recall (no
+    /// fallback) and latency (no early stop).
+    #[serde(default)]
+    pub deterministic: bool,
+    /// How many top-ranked community summaries the global reduce phase
+    /// synthesizes, in `1..=100`. Overrides the engine's default (5).
+    #[serde(default)]
+    pub global_max_communities: Option<usize>,
+    /// Weight of graph centrality when ranking vector anchors: each candidate
+    /// gains `centrality_boost * pagerank / max_pagerank`, so well-connected
+    /// nodes can overtake isolated ones with slightly higher similarity.
+    /// `0.0` (default) ranks by similarity alone.
+    #[serde(default)]
+    pub centrality_boost: f32,
+    /// `approximate` (default) searches the ANN index; `exact` scans every
+    /// vector for the true top-k, e.g. for compliance or audit queries.
+    #[serde(default)]
+    pub search_accuracy: SearchAccuracy,
+    /// Tokenization strategy for lexical scoring of this query: `auto`, `cjk`
+    /// (n-grams for every word, e.g. romanized terms) or `whitespace` (whole
+    /// words only). Unset uses the engine tokenizer's language.
+    #[serde(default)]
+    pub language: Option<TokenizerLanguage>,
 }
 
 impl Default for QueryRequest {
@@ -96,6 +265,45 @@ impl Default for QueryRequest {
             snapshot_id: None,
             session_id: None,
             time_travel: None,
+            anchor_ids: Vec::new(),
+            dry_run: false,
+            debug_tokens: false,
+            explain_vector: false,
+            require_match: false,
+            min_anchor_score: None,
+            answer_evidence_count: default_answer_evidence_count(),
+            min_answer_groundedness: None,
+            deterministic: false,
+            global_max_communities: None,
+            centrality_boost: 0.0,
+            search_accuracy: SearchAccuracy::default(),
+            language: None,
+        }
+    }
+}
+
+/// Size limits enforced by [`QueryRequest::validate_with_limits`]. The
+/// defaults are generous; deployments exposed to untrusted callers can tighten
+/// them through `QueryEngine::with_request_limits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Maximum length of `query`, in characters.
+    pub max_query_chars: usize,
+    /// Maximum entries in any one filter or traversal list
+    /// (`filters.entity_type`, `filters.relation_type`,
+    /// `traversal.relation_types`, `traversal.exclude_relation_types`,
+    /// `traversal.relation_weights`, `filters.metadata`).
+    pub max_filter_values: usize,
+    /// Maximum entries in `anchor_ids`.
+    pub max_anchor_ids: usize,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            max_query_chars: DEFAULT_MAX_QUERY_CHARS,
+            max_filter_values: DEFAULT_MAX_FILTER_VALUES,
+            max_anchor_ids: DEFAULT_MAX_ANCHOR_IDS,
         }
     }
 }
@@ -108,24 +316,60 @@ const fn default_top_k() -> usize {
     DEFAULT_TOP_K
 }
 
+const fn default_answer_evidence_count() -> usize {
+    DEFAULT_ANSWER_EVIDENCE_COUNT
+}
+
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum QueryValidationError {
     #[error("query must not be empty")]
     EmptyQuery,
+    #[error("query must be at most {0} characters")]
+    QueryTooLong(usize),
+    #[error("{field} must contain at most {max} entries")]
+    TooManyValues { field: &'static str, max: usize },
     #[error("top_k must be between 1 and {0}")]
     InvalidTopK(usize),
-    #[error("traversal.depth must be between 1 and {0}")]
-    InvalidDepth(u8),
+    #[error("answer_evidence_count must be between 1 and {0}")]
+    InvalidAnswerEvidenceCount(usize),
+    #[error("min_answer_groundedness must be between 0.0 and 1.0")]
+    InvalidMinAnswerGroundedness,
+    #[error("global_max_communities must be between 1 and {0}")]
+    InvalidGlobalMaxCommunities(usize),
+    #[error("centrality_boost must be a finite, non-negative number")]
+    InvalidCentralityBoost,
+    #[error("min_anchor_score must be a finite number")]
+    InvalidMinAnchorScore,
+    /// Depths above the engine's `max_expansion_depth` are clamped at
+    /// planning time rather than rejected.
+    #[error("traversal.depth must be at least 1")]
+    InvalidDepth,
     #[error("filters.entity_type must not contain empty values")]
     InvalidEntityTypeFilter,
     #[error("filters.relation_type must not contain empty values")]
     InvalidRelationTypeFilter,
     #[error("traversal.relation_types must not contain empty values")]
     InvalidTraversalRelationTypes,
-    #[error("filters.time_range.from/to must be YYYY-MM-DD")]
+    #[error("traversal.exclude_relation_types must not contain empty values")]
+    InvalidExcludedRelationTypes,
+    #[error("traversal.max_neighbors_per_node must be at least 1")]
+    InvalidMaxNeighborsPerNode,
+    #[error("traversal.max_candidates must be at least 1")]
+    InvalidMaxCandidates,
+    #[error(
+        "traversal.relation_weights must map non-empty relations to finite, non-negative factors"
+    )]
+    InvalidRelationWeights,
+    #[error("filters.time_range.from/to must be YYYY-MM-DD or RFC3339")]
     InvalidTimeRangeFormat,
     #[error("filters.time_range.from must be <= filters.time_range.to")]
     InvalidTimeRangeOrder,
+    #[error("filters.time_range requires from or to")]
+    MissingTimeRangeBound,
+    #[error("filters.time_range.time_field must not be empty")]
+    InvalidTimeField,
+    #[error("filters.metadata paths must not be empty")]
+    InvalidMetadataFilter,
     #[error("model_id must not be empty when provided")]
     InvalidModelId,
     #[error("snapshot_id must not be empty when provided")]
@@ -140,14 +384,77 @@ impl QueryRequest {
     }
 
     pub fn validate(&self) -> Result<(), QueryValidationError> {
+        self.validate_with_limits(&QueryLimits::default())
+    }
+
+    pub fn validate_with_limits(&self, limits: &QueryLimits) -> Result<(), QueryValidationError> {
         if self.query.trim().is_empty() {
             return Err(QueryValidationError::EmptyQuery);
         }
+        if self.query.chars().count() > limits.max_query_chars {
+            return Err(QueryValidationError::QueryTooLong(limits.max_query_chars));
+        }
+        for (field, len) in [
+            ("filters.entity_type", self.filters.entity_type.len()),
+            ("filters.relation_type", self.filters.relation_type.len()),
+            (
+                "traversal.relation_types",
+                self.traversal.relation_types.len(),
+            ),
+            (
+                "traversal.exclude_relation_types",
+                self.traversal.exclude_relation_types.len(),
+            ),
+            (
+                "traversal.relation_weights",
+                self.traversal.relation_weights.len(),
+            ),
+            ("filters.metadata", self.filters.metadata.len()),
+        ] {
+            if len > limits.max_filter_values {
+                return Err(QueryValidationError::TooManyValues {
+                    field,
+                    max: limits.max_filter_values,
+                });
+            }
+        }
+        if self.anchor_ids.len() > limits.max_anchor_ids {
+            return Err(QueryValidationError::TooManyValues {
+                field: "anchor_ids",
+                max: limits.max_anchor_ids,
+            });
+        }
         if self.top_k == 0 || self.top_k > MAX_TOP_K {
             return Err(QueryValidationError::InvalidTopK(MAX_TOP_K));
         }
-        if self.traversal.depth == 0 || self.traversal.depth > MAX_DEPTH {
-            return Err(QueryValidationError::InvalidDepth(MAX_DEPTH));
+        if self.answer_evidence_count == 0 || self.answer_evidence_count > MAX_TOP_K {
+            return Err(QueryValidationError::InvalidAnswerEvidenceCount(MAX_TOP_K));
+        }
+        if self
+            .min_answer_groundedness
+            .is_some_and(|threshold| !(0.0..=1.0).contains(&threshold))
+        {
+            return Err(QueryValidationError::InvalidMinAnswerGroundedness);
+        }
+        if self
+            .global_max_communities
+            .is_some_and(|max| max == 0 || max > MAX_GLOBAL_COMMUNITIES)
+        {
+            return Err(QueryValidationError::InvalidGlobalMaxCommunities(
+                MAX_GLOBAL_COMMUNITIES,
+            ));
+        }
+        if !self.centrality_boost.is_finite() || self.centrality_boost < 0.0 {
+            return Err(QueryValidationError::InvalidCentralityBoost);
+        }
+        if self
+            .min_anchor_score
+            .is_some_and(|threshold| !threshold.is_finite())
+        {
+            return Err(QueryValidationError::InvalidMinAnchorScore);
+        }
+        if self.traversal.depth == 0 {
+            return Err(QueryValidationError::InvalidDepth);
         }
         if has_empty_values(&self.filters.entity_type) {
             return Err(QueryValidationError::InvalidEntityTypeFilter);
@@ -155,9 +462,36 @@ impl QueryRequest {
         if has_empty_values(&self.filters.relation_type) {
             return Err(QueryValidationError::InvalidRelationTypeFilter);
         }
+        if self
+            .filters
+            .metadata
+            .keys()
+            .any(|path| path.split('.').any(|segment| segment.trim().is_empty()))
+        {
+            return Err(QueryValidationError::InvalidMetadataFilter);
+        }
         if has_empty_values(&self.traversal.relation_types) {
             return Err(QueryValidationError::InvalidTraversalRelationTypes);
         }
+        if has_empty_values(&self.traversal.exclude_relation_types) {
+            return Err(QueryValidationError::InvalidExcludedRelationTypes);
+        }
+        if self.traversal.max_neighbors_per_node == Some(0) {
+            return Err(QueryValidationError::InvalidMaxNeighborsPerNode);
+        }
+        if self.traversal.max_candidates == Some(0) {
+            return Err(QueryValidationError::InvalidMaxCandidates);
+        }
+        if self
+            .traversal
+            .relation_weights
+            .iter()
+            .any(|(relation, factor)| {
+                relation.trim().is_empty() || !factor.is_finite() || *factor < 0.0
+            })
+        {
+            return Err(QueryValidationError::InvalidRelationWeights);
+        }
         if let Some(model_id) = &self.model_id {
             if model_id.trim().is_empty() {
                 return Err(QueryValidationError::InvalidModelId);
@@ -169,10 +503,18 @@ impl QueryRequest {
             }
         }
         if let Some(range) = &self.filters.time_range {
-            let from = parse_date(&range.from)?;
-            let to = parse_date(&range.to)?;
-            if from > to {
-                return Err(QueryValidationError::InvalidTimeRangeOrder);
+            if range.from.is_none() && range.to.is_none() {
+                return Err(QueryValidationError::MissingTimeRangeBound);
+            }
+            let from = range.from.as_deref().map(parse_date).transpose()?;
+            let to = range.to.as_deref().map(parse_date).transpose()?;
+            if let (Some(from), Some(to)) = (from, to) {
+                if from > to {
+                    return Err(QueryValidationError::InvalidTimeRangeOrder);
+                }
+            }
+            if range.time_field.trim().is_empty() {
+                return Err(QueryValidationError::InvalidTimeField);
             }
         }
         if let Some(time_travel) = &self.time_travel {
@@ -184,13 +526,89 @@ impl QueryRequest {
     }
 }
 
+/// Typed construction of a validated [`QueryRequest`] for Rust callers.
+/// Fields without a setter keep their [`QueryRequest::default`] values;
+/// external JSON input still goes through [`QueryRequest::parse_json`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryRequestBuilder {
+    request: QueryRequest,
+}
+
+impl QueryRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.request.query = query.into();
+        self
+    }
+
+    pub fn mode(mut self, mode: QueryMode) -> Self {
+        self.request.mode = mode;
+        self
+    }
+
+    pub fn search_mode(mut self, search_mode: SearchMode) -> Self {
+        self.request.search_mode = search_mode;
+        self
+    }
+
+    pub fn top_k(mut self, top_k: usize) -> Self {
+        self.request.top_k = top_k;
+        self
+    }
+
+    pub fn traversal_depth(mut self, depth: u8) -> Self {
+        self.request.traversal.depth = depth;
+        self
+    }
+
+    pub fn filters(mut self, filters: QueryFilters) -> Self {
+        self.request.filters = filters;
+        self
+    }
+
+    pub fn model_id(mut self, model_id: impl Into<String>) -> Self {
+        self.request.model_id = Some(model_id.into());
+        self
+    }
+
+    pub fn snapshot_id(mut self, snapshot_id: impl Into<String>) -> Self {
+        self.request.snapshot_id = Some(snapshot_id.into());
+        self
+    }
+
+    /// The request, after [`QueryRequest::validate`].
+    pub fn build(self) -> Result<QueryRequest, QueryValidationError> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
+}
+
+impl QueryRequest {
+    pub fn builder() -> QueryRequestBuilder {
+        QueryRequestBuilder::new()
+    }
+}
+
 fn has_empty_values(values: &[String]) -> bool {
     values.iter().any(|value| value.trim().is_empty())
 }
 
-fn parse_date(input: &str) -> Result<chrono::NaiveDate, QueryValidationError> {
-    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
-        .map_err(|_| QueryValidationError::InvalidTimeRangeFormat)
+fn parse_date(input: &str) -> Result<chrono::DateTime<chrono::Utc>, QueryValidationError> {
+    parse_timestamp(input).ok_or(QueryValidationError::InvalidTimeRangeFormat)
+}
+
+/// Parse a `YYYY-MM-DD` (midnight UTC) or RFC3339 timestamp, as used by
+/// `filters.time_range` bounds and the metadata values they are compared to.
+pub(crate) fn parse_timestamp(input: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    chrono::DateTime::parse_from_rfc3339(input)
+        .ok()
+        .map(|datetime| datetime.with_timezone(&chrono::Utc))
 }
 
 /// Validate time_travel format: accepts YYYY-MM-DD or RFC3339.
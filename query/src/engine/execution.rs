@@ -1,72 +1,115 @@
 use super::synthesis::{
     collect_relation_filter, dedup_edges, dedup_exclusions, dedup_paths, lexical_similarity,
-    node_belongs_to_tenant, node_filter_exclusion_reason, node_lexical_text, node_passes_filters,
-    parse_time_range, reconstruct_path, relation_is_allowed, retention_cutoff_unix, tokenize,
+    node_belongs_to_tenant, node_filter_exclusion_reason, node_lexical_tokens, node_passes_filters,
+    parse_time_range, reconstruct_path, relation_factor, relation_is_allowed,
+    retention_cutoff_unix, select_neighbors,
 };
 use super::{
-    Anchor, ExclusionReason, ExecutionState, ExpansionPath, InternalEdge, Provenance, QueryError,
-    QueryRequest, RankedNode, ResolvedSnapshot,
+    Anchor, EdgeConfidenceSource, ExclusionCode, ExclusionReason, ExecutionState, ExpansionPath,
+    InternalEdge, Provenance, QueryEmbedder, QueryError, QueryRequest, RankedNode, ReadScope,
+    ResolvedSnapshot,
 };
+use crate::dsl::{ConfidencePropagation, HopPenalty};
 use crate::graphrag::{
-    map_community_summaries, reduce_community_summaries, DRIFT_EVIDENCE_THRESHOLD,
-    DRIFT_MAX_ITERATIONS,
+    collect_global_node_ids, map_community_summaries, reduce_community_summaries,
+    DRIFT_EVIDENCE_THRESHOLD, DRIFT_MAX_ITERATIONS,
 };
 use crate::planner::QueryPlan;
-use alayasiki_core::embedding::deterministic_embedding;
 use alayasiki_core::model::Node;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet, VecDeque};
-use storage::community::CommunitySummary;
-use storage::repo::SnapshotView;
+use std::sync::Arc;
+use storage::community::{compute_pagerank, CommunitySummary, PageRankConfig};
+use storage::index::TraversalEdge;
+use storage::repo::ReadSource;
 use storage::session::SessionGraph;
 
+const GLOBAL_TENANT_SCOPED_STEPS: [&str; 4] = [
+    "vector_search",
+    "graph_expansion",
+    "context_pruning",
+    "global_fallback_tenant_scoped",
+];
+
+const GLOBAL_NO_COMMUNITY_STEPS: [&str; 4] = [
+    "vector_search",
+    "graph_expansion",
+    "context_pruning",
+    "global_fallback_no_community_data",
+];
+
+const GLOBAL_SNAPSHOT_PINNED_STEPS: [&str; 4] = [
+    "vector_search",
+    "graph_expansion",
+    "context_pruning",
+    "global_fallback_snapshot_pinned",
+];
+
+const GLOBAL_MAP_REDUCE_STEPS: [&str; 4] = [
+    "vector_search",
+    "community_map_reduce",
+    "graph_expansion",
+    "context_pruning",
+];
+
+const DRIFT_STEPS: [&str; 4] = [
+    "vector_search",
+    "drift_iterative_expansion",
+    "graph_expansion",
+    "context_pruning",
+];
+
+fn is_empty_repository(state: &ExecutionState) -> bool {
+    state
+        .exclusions
+        .iter()
+        .any(|exclusion| exclusion.code == ExclusionCode::EmptyRepository)
+}
+
+fn has_tenant_summaries(summaries: &[CommunitySummary], tenant: &str) -> bool {
+    summaries
+        .iter()
+        .any(|summary| summary.belongs_to_tenant(tenant))
+}
+
 impl super::QueryEngine {
     pub(super) async fn execute_local_with_auto_fallback(
         &self,
         request: &QueryRequest,
         mut plan: QueryPlan,
-        embedding_model_id: &str,
-        snapshot_view: Option<&SnapshotView>,
-        tenant_scope: Option<&str>,
+        query_embedder: &QueryEmbedder,
+        source: &dyn ReadSource,
+        scope: ReadScope<'_>,
         session: Option<&SessionGraph>,
     ) -> Result<(ExecutionState, QueryPlan), QueryError> {
         let mut state = self
-            .execute_with_plan(
-                request,
-                &plan,
-                embedding_model_id,
-                snapshot_view,
-                tenant_scope,
-                session,
-            )
+            .execute_with_plan(request, &plan, query_embedder, source, scope, session)
             .await?;
 
+        if is_empty_repository(&state) {
+            return Ok((state, plan));
+        }
+
         if state.edges.is_empty() && !state.nodes.is_empty() {
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "no_graph_expansion_vector_only_fallback".to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::NoGraphExpansionVectorOnlyFallback,
+            ));
         }
 
         if request.search_mode == crate::dsl::SearchMode::Auto
+            && !request.deterministic
             && plan.effective_search_mode == crate::dsl::SearchMode::Local
             && state.nodes.len() < 2
         {
             let (drift_state, drift_plan) = self
-                .execute_drift(
-                    request,
-                    &mut plan,
-                    embedding_model_id,
-                    snapshot_view,
-                    tenant_scope,
-                    session,
-                )
+                .execute_drift(request, &mut plan, query_embedder, source, scope, session)
                 .await?;
             let mut drift_state = drift_state;
-            drift_state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "auto_fallback_to_drift_due_to_insufficient_evidence".to_string(),
-            });
+            drift_state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::AutoFallbackToDriftDueToInsufficientEvidence,
+            ));
 
             return Ok((drift_state, drift_plan));
         }
@@ -78,106 +121,75 @@ impl super::QueryEngine {
         &self,
         request: &QueryRequest,
         plan: &mut QueryPlan,
-        embedding_model_id: &str,
+        query_embedder: &QueryEmbedder,
         resolved_snapshot: &ResolvedSnapshot,
-        tenant_scope: Option<&str>,
+        scope: ReadScope<'_>,
         session: Option<&SessionGraph>,
     ) -> Result<(ExecutionState, QueryPlan, Option<String>), QueryError> {
-        let snapshot_view = resolved_snapshot.snapshot_view.as_deref();
-        if tenant_scope.is_some() {
-            plan.steps = vec![
-                "vector_search",
-                "graph_expansion",
-                "context_pruning",
-                "global_fallback_tenant_scoped",
-            ];
+        let source = self.read_source(resolved_snapshot);
+        let community_summaries = &resolved_snapshot.community_summaries;
+        // Untagged summaries may mix tenants, so a tenant-scoped caller only
+        // gets map-reduce over summaries built from its own partition.
+        if scope
+            .tenant
+            .is_some_and(|tenant| !has_tenant_summaries(community_summaries, tenant))
+        {
+            plan.steps = GLOBAL_TENANT_SCOPED_STEPS.to_vec();
             let mut state = self
-                .execute_with_plan(
-                    request,
-                    plan,
-                    embedding_model_id,
-                    snapshot_view,
-                    tenant_scope,
-                    session,
-                )
+                .execute_with_plan(request, plan, query_embedder, source, scope, session)
                 .await?;
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "global_summary_disabled_by_tenant_scope".to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::GlobalSummaryDisabledByTenantScope,
+            ));
             return Ok((state, plan.clone(), None));
         }
 
-        if self.community_summaries.is_empty() {
-            plan.steps = vec![
-                "vector_search",
-                "graph_expansion",
-                "context_pruning",
-                "global_fallback_no_community_data",
-            ];
+        if community_summaries.is_empty() {
+            plan.steps = GLOBAL_NO_COMMUNITY_STEPS.to_vec();
             let mut state = self
-                .execute_with_plan(
-                    request,
-                    plan,
-                    embedding_model_id,
-                    snapshot_view,
-                    tenant_scope,
-                    session,
-                )
+                .execute_with_plan(request, plan, query_embedder, source, scope, session)
                 .await?;
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "no_community_data_fallback_to_vector".to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::NoCommunityDataFallbackToVector,
+            ));
             return Ok((state, plan.clone(), None));
         }
 
-        let summary_candidates: Vec<CommunitySummary> = self
-            .community_summaries
+        let summary_candidates: Vec<CommunitySummary> = community_summaries
             .iter()
             .filter(|summary| {
                 summary.is_visible_at_lsn(resolved_snapshot.snapshot_lsn)
                     && (!resolved_snapshot.requires_versioned_summaries
                         || summary.snapshot_lsn_range.is_some())
+                    && scope
+                        .tenant
+                        .is_none_or(|tenant| summary.belongs_to_tenant(tenant))
             })
             .cloned()
             .collect();
         if summary_candidates.is_empty() && resolved_snapshot.requires_versioned_summaries {
-            plan.steps = vec![
-                "vector_search",
-                "graph_expansion",
-                "context_pruning",
-                "global_fallback_snapshot_pinned",
-            ];
+            plan.steps = GLOBAL_SNAPSHOT_PINNED_STEPS.to_vec();
             let mut state = self
-                .execute_with_plan(
-                    request,
-                    plan,
-                    embedding_model_id,
-                    snapshot_view,
-                    tenant_scope,
-                    session,
-                )
+                .execute_with_plan(request, plan, query_embedder, source, scope, session)
                 .await?;
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "global_summary_disabled_by_snapshot_pin".to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::GlobalSummaryDisabledBySnapshotPin,
+            ));
             return Ok((state, plan.clone(), None));
         }
 
         let mut state = self
-            .execute_with_plan(
-                request,
-                plan,
-                embedding_model_id,
-                snapshot_view,
-                tenant_scope,
-                session,
-            )
+            .execute_with_plan(request, plan, query_embedder, source, scope, session)
             .await?;
 
-        let ranked = map_community_summaries(&request.query, &summary_candidates);
+        let ranked = map_community_summaries(
+            &request.query,
+            &summary_candidates,
+            self.summary_scorer.as_ref(),
+        );
         let relation_filter = collect_relation_filter(request);
         let time_range = parse_time_range(request)?;
         let retention_cutoff = retention_cutoff_unix(request);
@@ -201,7 +213,7 @@ impl super::QueryEngine {
             out
         };
         let top_nodes = self
-            .get_nodes_by_ids_from_source(&all_top_node_ids, snapshot_view, None)
+            .get_nodes_by_ids_from_source(&all_top_node_ids, source, None)
             .await;
         let top_node_lookup: HashMap<u64, Node> =
             top_nodes.into_iter().map(|node| (node.id, node)).collect();
@@ -216,9 +228,10 @@ impl super::QueryEngine {
                                 node_passes_filters(
                                     node,
                                     &entity_filter,
+                                    &request.filters.metadata,
                                     time_range,
                                     retention_cutoff,
-                                    tenant_scope,
+                                    scope.tenant,
                                 )
                             })
                         })
@@ -228,17 +241,16 @@ impl super::QueryEngine {
             Vec::new()
         };
 
-        let max_communities = 5;
+        let max_communities = request
+            .global_max_communities
+            .unwrap_or(self.global_max_communities);
         let global_answer = if relevant_ranked.is_empty() {
-            let reason = if relation_filter.is_empty() {
-                "global_no_relevant_community_summary"
+            let code = if relation_filter.is_empty() {
+                ExclusionCode::GlobalNoRelevantCommunitySummary
             } else {
-                "global_summary_disabled_by_relation_filter"
+                ExclusionCode::GlobalSummaryDisabledByRelationFilter
             };
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: reason.to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(None, code));
             None
         } else {
             Some(reduce_community_summaries(
@@ -248,23 +260,135 @@ impl super::QueryEngine {
             ))
         };
 
-        plan.steps = vec![
-            "vector_search",
-            "community_map_reduce",
-            "graph_expansion",
-            "context_pruning",
-        ];
+        if global_answer.is_some() {
+            // Back the synthesized answer with the chosen communities' own
+            // nodes, each scored by its best-ranked community's relevance.
+            let mut community_scores: HashMap<u64, f32> = HashMap::new();
+            for (summary, score) in relevant_ranked.iter().take(max_communities) {
+                for node_id in &summary.top_nodes {
+                    community_scores.entry(*node_id).or_insert(*score);
+                }
+            }
+            let present: HashSet<u64> = state.nodes.iter().map(|node| node.id).collect();
+            for node_id in collect_global_node_ids(&relevant_ranked, max_communities) {
+                if present.contains(&node_id) {
+                    continue;
+                }
+                let Some(node) = top_node_lookup.get(&node_id) else {
+                    state.exclusions.push(ExclusionReason::new(
+                        Some(node_id),
+                        ExclusionCode::MissingNode,
+                    ));
+                    continue;
+                };
+                if let Some(code) = node_filter_exclusion_reason(
+                    node,
+                    &entity_filter,
+                    &request.filters.metadata,
+                    time_range,
+                    retention_cutoff,
+                    scope.tenant,
+                ) {
+                    state
+                        .exclusions
+                        .push(ExclusionReason::new(Some(node_id), code));
+                    continue;
+                }
+                if !self.row_readable(scope.principal, node) {
+                    state.exclusions.push(ExclusionReason::new(
+                        Some(node_id),
+                        ExclusionCode::RowAuthzDenied,
+                    ));
+                    continue;
+                }
+
+                let score = community_scores[&node_id];
+                let provenance = self.provenance_mapping.provenance(&node.metadata);
+                state.nodes.push(RankedNode {
+                    id: node_id,
+                    data: node.data.clone(),
+                    score,
+                    hop: 0,
+                    source: provenance.source,
+                    extraction_model_id: provenance.extraction_model_id,
+                    node_snapshot_id: provenance.snapshot_id,
+                    ingested_at: provenance.ingested_at,
+                    confidence: node
+                        .metadata
+                        .get("confidence")
+                        .and_then(|v| v.parse::<f32>().ok())
+                        .unwrap_or(score),
+                    doc_span: document_span(&node.metadata),
+                });
+            }
+
+            state.nodes.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.id.cmp(&b.id))
+            });
+            if state.nodes.len() > request.top_k {
+                for node in state.nodes.split_off(request.top_k) {
+                    state.exclusions.push(ExclusionReason::new(
+                        Some(node.id),
+                        ExclusionCode::PrunedByTopK,
+                    ));
+                }
+                let kept: HashSet<u64> = state.nodes.iter().map(|node| node.id).collect();
+                state
+                    .edges
+                    .retain(|edge| kept.contains(&edge.source) && kept.contains(&edge.target));
+            }
+            state.exclusions = dedup_exclusions(std::mem::take(&mut state.exclusions));
+        }
+
+        plan.steps = GLOBAL_MAP_REDUCE_STEPS.to_vec();
 
         Ok((state, plan.clone(), global_answer))
     }
 
+    /// Step list the dispatcher would report for `plan`, decided from engine and
+    /// snapshot configuration only (no search or expansion). Data-dependent
+    /// fallbacks such as Auto -> DRIFT cannot be predicted and are not reflected.
+    pub(super) fn planned_steps(
+        &self,
+        plan: &QueryPlan,
+        resolved_snapshot: &ResolvedSnapshot,
+        tenant_scope: Option<&str>,
+    ) -> Vec<&'static str> {
+        match plan.effective_search_mode {
+            crate::dsl::SearchMode::Global => {
+                let community_summaries = &resolved_snapshot.community_summaries;
+                if tenant_scope
+                    .is_some_and(|tenant| !has_tenant_summaries(community_summaries, tenant))
+                {
+                    GLOBAL_TENANT_SCOPED_STEPS.to_vec()
+                } else if community_summaries.is_empty() {
+                    GLOBAL_NO_COMMUNITY_STEPS.to_vec()
+                } else if resolved_snapshot.requires_versioned_summaries
+                    && !community_summaries.iter().any(|summary| {
+                        summary.is_visible_at_lsn(resolved_snapshot.snapshot_lsn)
+                            && summary.snapshot_lsn_range.is_some()
+                    })
+                {
+                    GLOBAL_SNAPSHOT_PINNED_STEPS.to_vec()
+                } else {
+                    GLOBAL_MAP_REDUCE_STEPS.to_vec()
+                }
+            }
+            crate::dsl::SearchMode::Drift => DRIFT_STEPS.to_vec(),
+            crate::dsl::SearchMode::Local | crate::dsl::SearchMode::Auto => plan.steps.clone(),
+        }
+    }
+
     pub(super) async fn execute_drift(
         &self,
         request: &QueryRequest,
         plan: &mut QueryPlan,
-        embedding_model_id: &str,
-        snapshot_view: Option<&SnapshotView>,
-        tenant_scope: Option<&str>,
+        query_embedder: &QueryEmbedder,
+        source: &dyn ReadSource,
+        scope: ReadScope<'_>,
         session: Option<&SessionGraph>,
     ) -> Result<(ExecutionState, QueryPlan), QueryError> {
         plan.effective_search_mode = crate::dsl::SearchMode::Drift;
@@ -274,27 +398,30 @@ impl super::QueryEngine {
 
         for iteration in 0..DRIFT_MAX_ITERATIONS {
             let mut iter_plan = plan.clone();
-            iter_plan.expansion_depth = (initial_depth + iteration as u8).min(8);
+            iter_plan.expansion_depth = initial_depth
+                .saturating_add(iteration as u8)
+                .min(plan.max_expansion_depth);
             iter_plan.vector_top_k = plan.vector_top_k.saturating_add(iteration * 2).min(50);
 
             let state = self
-                .execute_with_plan(
-                    request,
-                    &iter_plan,
-                    embedding_model_id,
-                    snapshot_view,
-                    tenant_scope,
-                    session,
-                )
+                .execute_with_plan(request, &iter_plan, query_embedder, source, scope, session)
                 .await?;
+            if is_empty_repository(&state) {
+                // Deeper iterations cannot find anything either.
+                best_state = Some(state);
+                break;
+            }
 
-            let is_sufficient = state.nodes.len() >= DRIFT_EVIDENCE_THRESHOLD
-                || (iteration > 0
-                    && best_state
-                        .as_ref()
-                        .map(|prev| state.nodes.len() <= prev.nodes.len())
-                        .unwrap_or(false));
+            let is_sufficient = !request.deterministic
+                && (state.nodes.len() >= DRIFT_EVIDENCE_THRESHOLD
+                    || (iteration > 0
+                        && best_state
+                            .as_ref()
+                            .map(|prev| state.nodes.len() <= prev.nodes.len())
+                            .unwrap_or(false)));
 
+            // Only strictly larger evidence replaces the best state, so ties
+            // always keep the earliest iteration.
             if state.nodes.len() > best_state.as_ref().map(|s| s.nodes.len()).unwrap_or(0) {
                 best_state = Some(state);
             }
@@ -307,26 +434,21 @@ impl super::QueryEngine {
         let mut state = best_state.unwrap_or(ExecutionState {
             anchors: Vec::new(),
             expansion_paths: Vec::new(),
-            exclusions: vec![ExclusionReason {
-                node_id: None,
-                reason: "drift_no_evidence_found".to_string(),
-            }],
+            exclusions: vec![ExclusionReason::new(
+                None,
+                ExclusionCode::DriftNoEvidenceFound,
+            )],
             nodes: Vec::new(),
             edges: Vec::new(),
         });
 
-        plan.steps = vec![
-            "vector_search",
-            "drift_iterative_expansion",
-            "graph_expansion",
-            "context_pruning",
-        ];
+        plan.steps = DRIFT_STEPS.to_vec();
 
         if state.nodes.is_empty() {
-            state.exclusions.push(ExclusionReason {
-                node_id: None,
-                reason: "drift_exhausted_no_evidence".to_string(),
-            });
+            state.exclusions.push(ExclusionReason::new(
+                None,
+                ExclusionCode::DriftExhaustedNoEvidence,
+            ));
         }
 
         Ok((state, plan.clone()))
@@ -336,24 +458,55 @@ impl super::QueryEngine {
         &self,
         request: &QueryRequest,
         plan: &QueryPlan,
-        embedding_model_id: &str,
-        snapshot_view: Option<&SnapshotView>,
-        tenant_scope: Option<&str>,
+        query_embedder: &QueryEmbedder,
+        source: &dyn ReadSource,
+        scope: ReadScope<'_>,
         session: Option<&SessionGraph>,
     ) -> Result<ExecutionState, QueryError> {
-        let mut vector_hits = self
-            .collect_vector_scores(
-                request,
-                plan,
-                embedding_model_id,
-                snapshot_view,
-                tenant_scope,
-                session,
-            )
-            .await;
-        if vector_hits.is_empty() {
+        if source.node_count().await == 0 && session.is_none_or(|graph| graph.nodes.is_empty()) {
+            return Ok(ExecutionState {
+                anchors: Vec::new(),
+                expansion_paths: Vec::new(),
+                exclusions: vec![ExclusionReason::new(None, ExclusionCode::EmptyRepository)],
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            });
+        }
+
+        let mut exclusions = Vec::new();
+        let explicit_anchors = !request.anchor_ids.is_empty();
+        let mut vector_hits = if explicit_anchors {
+            self.resolve_explicit_anchors(request, source, scope.tenant, session, &mut exclusions)
+                .await
+        } else {
+            self.collect_vector_scores(request, plan, query_embedder, source, scope.tenant, session)
+                .await
+        };
+        if let (Some(threshold), false) = (request.min_anchor_score, explicit_anchors) {
+            vector_hits.retain(|(node_id, score)| {
+                let keep = *score >= threshold;
+                if !keep {
+                    exclusions.push(ExclusionReason::new(
+                        Some(*node_id),
+                        ExclusionCode::AnchorBelowThreshold,
+                    ));
+                }
+                keep
+            });
+        }
+        if vector_hits.is_empty() && !explicit_anchors && request.require_match {
+            exclusions.push(ExclusionReason::new(None, ExclusionCode::NoVectorMatch));
+            return Ok(ExecutionState {
+                anchors: Vec::new(),
+                expansion_paths: Vec::new(),
+                exclusions,
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            });
+        }
+        if vector_hits.is_empty() && !explicit_anchors {
             if let Some(node_id) = self
-                .list_node_ids_from_source(snapshot_view, session)
+                .list_node_ids_from_source(source, session)
                 .await
                 .into_iter()
                 .next()
@@ -363,19 +516,21 @@ impl super::QueryEngine {
         }
 
         if vector_hits.is_empty() {
+            exclusions.push(ExclusionReason::new(None, ExclusionCode::NoNodesAvailable));
             return Ok(ExecutionState {
                 anchors: Vec::new(),
                 expansion_paths: Vec::new(),
-                exclusions: vec![ExclusionReason {
-                    node_id: None,
-                    reason: "no_nodes_available".to_string(),
-                }],
+                exclusions,
                 nodes: Vec::new(),
                 edges: Vec::new(),
             });
         }
 
-        let anchor_limit = plan.vector_top_k.min(vector_hits.len()).max(1);
+        let anchor_limit = if explicit_anchors {
+            vector_hits.len()
+        } else {
+            plan.vector_top_k.min(vector_hits.len()).max(1)
+        };
         let mut anchors: Vec<Anchor> = vector_hits
             .iter()
             .take(anchor_limit)
@@ -386,42 +541,62 @@ impl super::QueryEngine {
             .collect();
 
         let relation_filter = collect_relation_filter(request);
+        let relation_weights = &request.traversal.relation_weights;
         let mut candidate_hops: HashMap<u64, u8> = HashMap::new();
+        // Best product of weighted edge weights from any anchor; only used for
+        // scoring when relation weights or `HopPenalty::PathWeight` are
+        // requested.
+        let mut path_strengths: HashMap<u64, f32> = HashMap::new();
+        // Best propagated edge confidence from any anchor; only used when
+        // `traversal.confidence_propagation` is enabled.
+        let propagation = request.traversal.confidence_propagation;
+        let mut path_confidences: HashMap<u64, f32> = HashMap::new();
         let mut expansion_paths = Vec::new();
-        let mut exclusions = Vec::new();
         let mut traversed_edges = Vec::new();
 
-        if let Some(view) = snapshot_view {
-            for anchor in &anchors {
-                candidate_hops.entry(anchor.node_id).or_insert(0);
-
-                let mut queue = VecDeque::new();
-                let mut visited: HashMap<u64, u8> = HashMap::new();
-                let mut parents: HashMap<u64, u64> = HashMap::new();
-
-                queue.push_back(anchor.node_id);
-                visited.insert(anchor.node_id, 0);
+        let anchor_ids: HashSet<u64> = anchors.iter().map(|anchor| anchor.node_id).collect();
+        let mut candidate_count = anchor_ids.len();
+        let mut walks: Vec<AnchorWalk> = anchors
+            .iter()
+            .map(|anchor| AnchorWalk::new(anchor.node_id))
+            .collect();
 
-                while let Some(current_id) = queue.pop_front() {
-                    let current_hop = *visited.get(&current_id).unwrap_or(&0);
-                    if current_hop >= plan.expansion_depth {
-                        continue;
+        // Advance every anchor's BFS one hop level at a time so that, when
+        // `max_candidates` cuts expansion short, every kept candidate is at
+        // least as close to an anchor as any dropped one.
+        'levels: for level in 0..plan.expansion_depth {
+            for walk in &mut walks {
+                while let Some(&current_id) = walk.queue.front() {
+                    let current_hop = *walk.visited.get(&current_id).unwrap_or(&0);
+                    if current_hop > level {
+                        break;
                     }
-
-                    for (target, relation, weight) in
-                        view.neighbors_with_session(current_id, session)
-                    {
-                        if !relation_is_allowed(relation.as_str(), &relation_filter) {
-                            exclusions.push(ExclusionReason {
-                                node_id: Some(target),
-                                reason: format!("relation_filtered:{}", relation),
-                            });
-                            continue;
-                        }
-
+                    walk.queue.pop_front();
+
+                    let neighbors = select_neighbors(
+                        current_id,
+                        source.neighbors(current_id, session).await,
+                        &relation_filter,
+                        relation_weights,
+                        plan.max_neighbors_per_node,
+                        &mut exclusions,
+                    );
+                    let edge_confidences = self
+                        .path_edge_confidences(source, current_id, &neighbors, propagation)
+                        .await;
+                    for (edge, edge_confidence) in neighbors.into_iter().zip(edge_confidences) {
+                        // Record the edge as stored, so metadata lookups and
+                        // evidence see its real provenance and confidence.
+                        let (edge_source, edge_target) = edge.endpoints(current_id);
+                        let TraversalEdge {
+                            neighbor: target,
+                            relation,
+                            weight,
+                            ..
+                        } = edge;
                         traversed_edges.push(InternalEdge {
-                            source: current_id,
-                            target,
+                            source: edge_source,
+                            target: edge_target,
                             relation: relation.clone(),
                             weight,
                             provenance: Provenance::default(),
@@ -429,91 +604,78 @@ impl super::QueryEngine {
                         });
 
                         let next_hop = current_hop + 1;
-                        let should_visit = visited
+                        let should_visit = walk
+                            .visited
                             .get(&target)
                             .map(|prev_hop| next_hop < *prev_hop)
                             .unwrap_or(true);
-
-                        if should_visit {
-                            visited.insert(target, next_hop);
-                            parents.insert(target, current_id);
-                            queue.push_back(target);
-                            candidate_hops
-                                .entry(target)
-                                .and_modify(|hop| *hop = (*hop).min(next_hop))
-                                .or_insert(next_hop);
-
-                            if let Some(path) = reconstruct_path(anchor.node_id, target, &parents) {
-                                expansion_paths.push(ExpansionPath {
-                                    anchor_id: anchor.node_id,
-                                    target_id: target,
-                                    path,
-                                });
+                        let new_candidate = should_visit
+                            && !candidate_hops.contains_key(&target)
+                            && !anchor_ids.contains(&target);
+                        if new_candidate {
+                            if plan
+                                .max_candidates
+                                .is_some_and(|cap| candidate_count >= cap)
+                            {
+                                exclusions.push(ExclusionReason::new(
+                                    None,
+                                    ExclusionCode::CandidateCapReached,
+                                ));
+                                break 'levels;
                             }
+                            candidate_count += 1;
                         }
-                    }
-                }
-            }
-        } else {
-            for anchor in &anchors {
-                candidate_hops.entry(anchor.node_id).or_insert(0);
-
-                let mut queue = VecDeque::new();
-                let mut visited: HashMap<u64, u8> = HashMap::new();
-                let mut parents: HashMap<u64, u64> = HashMap::new();
-
-                queue.push_back(anchor.node_id);
-                visited.insert(anchor.node_id, 0);
 
-                while let Some(current_id) = queue.pop_front() {
-                    let current_hop = *visited.get(&current_id).unwrap_or(&0);
-                    if current_hop >= plan.expansion_depth {
-                        continue;
-                    }
-
-                    for (target, relation, weight) in self
-                        .repo
-                        .neighbors_with_session_graph(current_id, session)
-                        .await
-                    {
-                        if !relation_is_allowed(relation.as_str(), &relation_filter) {
-                            exclusions.push(ExclusionReason {
-                                node_id: Some(target),
-                                reason: format!("relation_filtered:{}", relation),
-                            });
-                            continue;
+                        let strength = walk.strengths.get(&current_id).copied().unwrap_or(1.0)
+                            * weight
+                            * relation_factor(&relation, relation_weights);
+                        let confidence = propagation.extend(
+                            walk.confidences.get(&current_id).copied().unwrap_or(1.0),
+                            edge_confidence,
+                        );
+                        if should_visit || walk.visited.get(&target) == Some(&next_hop) {
+                            let best = walk.strengths.entry(target).or_insert(strength);
+                            if should_visit || strength > *best {
+                                *best = strength;
+                            }
+                            path_strengths
+                                .entry(target)
+                                .and_modify(|best| *best = best.max(strength))
+                                .or_insert(strength);
+                            let best = walk.confidences.entry(target).or_insert(confidence);
+                            if should_visit || confidence > *best {
+                                *best = confidence;
+                            }
+                            path_confidences
+                                .entry(target)
+                                .and_modify(|best| *best = best.max(confidence))
+                                .or_insert(confidence);
                         }
 
-                        traversed_edges.push(InternalEdge {
-                            source: current_id,
-                            target,
-                            relation: relation.clone(),
-                            weight,
-                            provenance: Provenance::default(),
-                            confidence: weight,
-                        });
-
-                        let next_hop = current_hop + 1;
-                        let should_visit = visited
-                            .get(&target)
-                            .map(|prev_hop| next_hop < *prev_hop)
-                            .unwrap_or(true);
-
                         if should_visit {
-                            visited.insert(target, next_hop);
-                            parents.insert(target, current_id);
-                            queue.push_back(target);
+                            walk.visited.insert(target, next_hop);
+                            walk.parents.insert(target, current_id);
+                            walk.queue.push_back(target);
                             candidate_hops
                                 .entry(target)
                                 .and_modify(|hop| *hop = (*hop).min(next_hop))
                                 .or_insert(next_hop);
 
-                            if let Some(path) = reconstruct_path(anchor.node_id, target, &parents) {
-                                expansion_paths.push(ExpansionPath {
-                                    anchor_id: anchor.node_id,
+                            match reconstruct_path(
+                                walk.anchor_id,
+                                target,
+                                &walk.parents,
+                                plan.expansion_depth,
+                            ) {
+                                Some(path) => expansion_paths.push(ExpansionPath {
+                                    anchor_id: walk.anchor_id,
                                     target_id: target,
                                     path,
-                                });
+                                }),
+                                None => exclusions.push(ExclusionReason::new(
+                                    Some(target),
+                                    ExclusionCode::PathReconstructionFailed,
+                                )),
                             }
                         }
                     }
@@ -521,9 +683,24 @@ impl super::QueryEngine {
             }
         }
 
-        let candidate_ids: Vec<u64> = candidate_hops.keys().copied().collect();
+        // An anchor reached from a higher-scored anchor keeps that hop;
+        // otherwise it sits at hop 0.
+        for (position, anchor) in anchors.iter().enumerate() {
+            if !walks[..position]
+                .iter()
+                .any(|walk| walk.visited.contains_key(&anchor.node_id))
+            {
+                candidate_hops.insert(anchor.node_id, 0);
+            }
+        }
+
+        // Walk candidates in id order so exclusions (and the detail kept by
+        // `dedup_exclusions`) do not depend on HashMap iteration order.
+        let mut candidate_hops: Vec<(u64, u8)> = candidate_hops.into_iter().collect();
+        candidate_hops.sort_unstable_by_key(|(node_id, _)| *node_id);
+        let candidate_ids: Vec<u64> = candidate_hops.iter().map(|(node_id, _)| *node_id).collect();
         let fetched_nodes = self
-            .get_nodes_by_ids_from_source(&candidate_ids, snapshot_view, session)
+            .get_nodes_by_ids_from_source(&candidate_ids, source, session)
             .await;
         let node_lookup: HashMap<u64, Node> = fetched_nodes
             .into_iter()
@@ -534,7 +711,8 @@ impl super::QueryEngine {
             .iter()
             .map(|anchor| (anchor.node_id, anchor.score))
             .collect();
-        let query_tokens = tokenize(&request.query);
+        let tokenizer = self.request_tokenizer(request);
+        let query_tokens = tokenizer.tokenize(&request.query);
         let time_range = parse_time_range(request)?;
         let retention_cutoff = retention_cutoff_unix(request);
         let entity_filter: HashSet<&str> = request
@@ -547,51 +725,71 @@ impl super::QueryEngine {
         let mut ranked_nodes = Vec::new();
         for (node_id, hop) in candidate_hops {
             let Some(node) = node_lookup.get(&node_id) else {
-                exclusions.push(ExclusionReason {
-                    node_id: Some(node_id),
-                    reason: "missing_node".to_string(),
-                });
+                exclusions.push(ExclusionReason::new(
+                    Some(node_id),
+                    ExclusionCode::MissingNode,
+                ));
                 continue;
             };
 
-            if let Some(reason) = node_filter_exclusion_reason(
+            if let Some(code) = node_filter_exclusion_reason(
                 node,
                 &entity_filter,
+                &request.filters.metadata,
                 time_range,
                 retention_cutoff,
-                tenant_scope,
+                scope.tenant,
             ) {
-                exclusions.push(ExclusionReason {
-                    node_id: Some(node_id),
-                    reason,
-                });
+                exclusions.push(ExclusionReason::new(Some(node_id), code));
+                continue;
+            }
+            if !self.row_readable(scope.principal, node) {
+                exclusions.push(ExclusionReason::new(
+                    Some(node_id),
+                    ExclusionCode::RowAuthzDenied,
+                ));
                 continue;
             }
 
-            let lexical_score =
-                lexical_similarity(&query_tokens, &tokenize(&node_lexical_text(node)));
+            let lexical_score = lexical_similarity(
+                &query_tokens,
+                &node_lexical_tokens(node, &tokenizer, &self.lexical_weights),
+            );
             let anchor_score = anchor_scores.get(&node_id).copied().unwrap_or(0.0);
             let base_score = ((anchor_score * 0.8) + (lexical_score * 0.2))
                 .max(lexical_score)
                 .max(0.01);
-            let score = base_score / (hop as f32 + 1.0);
+            let path_strength = path_strengths.get(&node_id).copied().unwrap_or(1.0);
+            let score = match request.traversal.hop_penalty {
+                HopPenalty::HopCount if !relation_weights.is_empty() && hop > 0 => {
+                    base_score / (hop as f32 + 1.0) * path_strength
+                }
+                HopPenalty::HopCount => base_score / (hop as f32 + 1.0),
+                HopPenalty::PathWeight if hop > 0 => base_score * path_strength.clamp(0.0, 1.0),
+                HopPenalty::PathWeight => base_score,
+            };
 
-            let confidence = node
+            let mut confidence = node
                 .metadata
                 .get("confidence")
                 .and_then(|v| v.parse::<f32>().ok())
                 .unwrap_or(score);
+            if propagation != ConfidencePropagation::Metadata && hop > 0 {
+                confidence *= path_confidences.get(&node_id).copied().unwrap_or(1.0);
+            }
 
+            let provenance = self.provenance_mapping.provenance(&node.metadata);
             ranked_nodes.push(RankedNode {
                 id: node_id,
                 data: node.data.clone(),
                 score,
                 hop,
-                source: node.metadata.get("source").cloned(),
-                extraction_model_id: node.metadata.get("extraction_model_id").cloned(),
-                node_snapshot_id: node.metadata.get("snapshot_id").cloned(),
-                ingested_at: node.metadata.get("ingested_at").cloned(),
+                source: provenance.source,
+                extraction_model_id: provenance.extraction_model_id,
+                node_snapshot_id: provenance.snapshot_id,
+                ingested_at: provenance.ingested_at,
                 confidence,
+                doc_span: document_span(&node.metadata),
             });
         }
 
@@ -605,10 +803,10 @@ impl super::QueryEngine {
         if ranked_nodes.len() > request.top_k {
             let pruned = ranked_nodes.split_off(request.top_k);
             for node in pruned {
-                exclusions.push(ExclusionReason {
-                    node_id: Some(node.id),
-                    reason: "pruned_by_top_k".to_string(),
-                });
+                exclusions.push(ExclusionReason::new(
+                    Some(node.id),
+                    ExclusionCode::PrunedByTopK,
+                ));
             }
         }
 
@@ -627,18 +825,16 @@ impl super::QueryEngine {
                 .iter()
                 .map(|e| (e.source, e.target, e.relation.clone()))
                 .collect();
-            let all_meta = self
-                .get_edge_metadata_bulk_from_source(&edge_keys, snapshot_view)
-                .await;
+            let all_meta = source.get_edge_metadata_bulk(&edge_keys).await;
             for edge in &mut edges {
                 let key = (edge.source, edge.target, edge.relation.clone());
                 if let Some(meta) = all_meta.get(&key) {
-                    edge.provenance = Provenance {
-                        source: meta.get("source").cloned(),
-                        extraction_model_id: meta.get("extraction_model_id").cloned(),
-                        snapshot_id: meta.get("snapshot_id").cloned(),
-                        ingested_at: meta.get("ingested_at").cloned(),
-                    };
+                    edge.provenance = self.provenance_mapping.provenance(meta);
+                    if self.edge_confidence_source == EdgeConfidenceSource::MetadataOrWeight {
+                        if let Some(confidence) = metadata_confidence(meta) {
+                            edge.confidence = confidence;
+                        }
+                    }
                 }
             }
         }
@@ -662,48 +858,100 @@ impl super::QueryEngine {
         })
     }
 
+    /// Seeds anchors from `request.anchor_ids` with a full score, in request order.
+    /// IDs that are unknown (or outside the tenant scope) are excluded.
+    async fn resolve_explicit_anchors(
+        &self,
+        request: &QueryRequest,
+        source: &dyn ReadSource,
+        tenant_scope: Option<&str>,
+        session: Option<&SessionGraph>,
+        exclusions: &mut Vec<ExclusionReason>,
+    ) -> Vec<(u64, f32)> {
+        let mut seen = HashSet::new();
+        let requested: Vec<u64> = request
+            .anchor_ids
+            .iter()
+            .copied()
+            .filter(|node_id| seen.insert(*node_id))
+            .collect();
+        let available: HashSet<u64> = self
+            .get_nodes_by_ids_from_source(&requested, source, session)
+            .await
+            .into_iter()
+            .filter(|node| tenant_scope.is_none_or(|tenant| node_belongs_to_tenant(node, tenant)))
+            .map(|node| node.id)
+            .collect();
+
+        let mut hits = Vec::with_capacity(available.len());
+        for node_id in requested {
+            if available.contains(&node_id) {
+                hits.push((node_id, 1.0));
+            } else {
+                exclusions.push(ExclusionReason::new(
+                    Some(node_id),
+                    ExclusionCode::AnchorNotFound,
+                ));
+            }
+        }
+        hits
+    }
+
     pub(super) async fn collect_vector_scores(
         &self,
         request: &QueryRequest,
         plan: &QueryPlan,
-        embedding_model_id: &str,
-        snapshot_view: Option<&SnapshotView>,
+        query_embedder: &QueryEmbedder,
+        source: &dyn ReadSource,
         tenant_scope: Option<&str>,
         session: Option<&SessionGraph>,
     ) -> Vec<(u64, f32)> {
-        let embedding_dim = match snapshot_view {
-            Some(view) => view.embedding_dimension(),
-            None => self.repo.embedding_dimension().await,
-        }
-        .or_else(|| session.and_then(SessionGraph::embedding_dimension));
+        let embedding_dim = source
+            .embedding_dimension()
+            .await
+            .or_else(|| session.and_then(SessionGraph::embedding_dimension));
         let Some(embedding_dim) = embedding_dim else {
             return Vec::new();
         };
 
-        let query_embedding =
-            deterministic_embedding(&request.query, embedding_model_id, embedding_dim);
+        let query_embedding = query_embedder.embed(&request.query, embedding_dim);
         let vector_limit = match plan.effective_search_mode {
             crate::dsl::SearchMode::Global => plan.vector_top_k.saturating_mul(2),
             _ => plan.vector_top_k,
         }
         .max(1);
-
-        let raw_hits = match snapshot_view {
-            Some(view) => view.search_vector_with_session(&query_embedding, vector_limit, session),
-            None => {
-                self.repo
-                    .search_vector_with_session_graph(&query_embedding, vector_limit, session)
-                    .await
-            }
+        let boost_centrality = request.centrality_boost > 0.0;
+        // Over-fetch so the centrality boost can promote candidates just past
+        // the similarity cut-off.
+        let search_limit = if boost_centrality {
+            vector_limit.saturating_mul(2)
+        } else {
+            vector_limit
         };
 
+        let mut raw_hits = source
+            .search_vector(
+                &query_embedding,
+                search_limit,
+                session,
+                request.search_accuracy,
+            )
+            .await;
+        // Equal similarities keep a stable node-id order for anchor selection.
+        sort_hits(&mut raw_hits);
+        if boost_centrality {
+            let pagerank = self.pagerank(source).await;
+            raw_hits = boost_by_centrality(raw_hits, &pagerank, request.centrality_boost);
+            raw_hits.truncate(vector_limit);
+        }
+
         let Some(tenant) = tenant_scope else {
             return raw_hits;
         };
 
         let candidate_ids: Vec<u64> = raw_hits.iter().map(|(node_id, _)| *node_id).collect();
         let allowed_ids: HashSet<u64> = self
-            .get_nodes_by_ids_from_source(&candidate_ids, snapshot_view, session)
+            .get_nodes_by_ids_from_source(&candidate_ids, source, session)
             .await
             .into_iter()
             .filter(|node| node_belongs_to_tenant(node, tenant))
@@ -715,4 +963,133 @@ impl super::QueryEngine {
             .filter(|(node_id, _)| allowed_ids.contains(node_id))
             .collect()
     }
+
+    /// PageRank of `source`'s graph, computed once per state of its data
+    /// rather than on every boosted query.
+    async fn pagerank(&self, source: &dyn ReadSource) -> Arc<HashMap<u64, f64>> {
+        let state_id = source.state_id().await;
+        if let Some((cached_state_id, ranks)) = self
+            .pagerank_cache
+            .lock()
+            .expect("pagerank cache lock poisoned")
+            .as_ref()
+        {
+            if *cached_state_id == state_id {
+                return ranks.clone();
+            }
+        }
+
+        let ranks = Arc::new(compute_pagerank(
+            &*source.graph_index().await,
+            &PageRankConfig::default(),
+        ));
+        *self
+            .pagerank_cache
+            .lock()
+            .expect("pagerank cache lock poisoned") = Some((state_id, ranks.clone()));
+        ranks
+    }
+
+    /// Confidence of each of `edges` (expanded from `from`) as folded into
+    /// path confidence: the same value [`super::EvidenceEdge::confidence`]
+    /// reports for the edge. Edge metadata is only read when propagation is
+    /// on and the engine prefers metadata confidence.
+    async fn path_edge_confidences(
+        &self,
+        source: &dyn ReadSource,
+        from: u64,
+        edges: &[TraversalEdge],
+        propagation: ConfidencePropagation,
+    ) -> Vec<f32> {
+        if propagation == ConfidencePropagation::Metadata
+            || self.edge_confidence_source == EdgeConfidenceSource::Weight
+        {
+            return edges.iter().map(|edge| edge.weight).collect();
+        }
+        let keys: Vec<(u64, u64, String)> = edges
+            .iter()
+            .map(|edge| {
+                let (edge_source, edge_target) = edge.endpoints(from);
+                (edge_source, edge_target, edge.relation.clone())
+            })
+            .collect();
+        let metadata = source.get_edge_metadata_bulk(&keys).await;
+        edges
+            .iter()
+            .zip(&keys)
+            .map(|(edge, key)| {
+                metadata
+                    .get(key)
+                    .and_then(metadata_confidence)
+                    .unwrap_or(edge.weight)
+            })
+            .collect()
+    }
+}
+
+/// The edge's `confidence` metadata, when it parses as a number.
+fn metadata_confidence(metadata: &HashMap<String, String>) -> Option<f32> {
+    metadata.get("confidence")?.parse().ok()
+}
+
+/// BFS state of graph expansion from a single anchor.
+struct AnchorWalk {
+    anchor_id: u64,
+    queue: VecDeque<u64>,
+    visited: HashMap<u64, u8>,
+    parents: HashMap<u64, u64>,
+    strengths: HashMap<u64, f32>,
+    confidences: HashMap<u64, f32>,
+}
+
+impl AnchorWalk {
+    fn new(anchor_id: u64) -> Self {
+        Self {
+            anchor_id,
+            queue: VecDeque::from([anchor_id]),
+            visited: HashMap::from([(anchor_id, 0)]),
+            parents: HashMap::new(),
+            strengths: HashMap::new(),
+            confidences: HashMap::new(),
+        }
+    }
+}
+
+/// Add `boost * pagerank / max_pagerank` to each hit's score and re-sort by
+/// score descending, then node id.
+fn boost_by_centrality(
+    hits: Vec<(u64, f32)>,
+    pagerank: &HashMap<u64, f64>,
+    boost: f32,
+) -> Vec<(u64, f32)> {
+    let max_rank = pagerank.values().copied().fold(0.0_f64, f64::max);
+    if max_rank <= 0.0 {
+        return hits;
+    }
+    let mut boosted: Vec<(u64, f32)> = hits
+        .into_iter()
+        .map(|(node_id, score)| {
+            let centrality = pagerank.get(&node_id).copied().unwrap_or(0.0) / max_rank;
+            (node_id, score + boost * centrality as f32)
+        })
+        .collect();
+    sort_hits(&mut boosted);
+    boosted
+}
+
+/// Sort hits by score descending, then node id.
+fn sort_hits(hits: &mut [(u64, f32)]) {
+    hits.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
+}
+
+/// `[doc_char_start, doc_char_end]` recorded by the chunker, if both parse
+/// and are ordered.
+fn document_span(metadata: &HashMap<String, String>) -> Option<[usize; 2]> {
+    let offset = |key: &str| metadata.get(key)?.parse::<usize>().ok();
+    let (start, end) = (offset("doc_char_start")?, offset("doc_char_end")?);
+    (start <= end).then_some([start, end])
 }
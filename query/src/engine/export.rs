@@ -0,0 +1,95 @@
+use super::{EvidenceNode, QueryResponse};
+use serde::{Deserialize, Serialize};
+
+/// Column of an evidence-node table produced by [`QueryResponse::to_csv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceField {
+    Id,
+    Score,
+    Hop,
+    Confidence,
+    /// `provenance.source`; empty when unknown.
+    Source,
+    Data,
+}
+
+impl EvidenceField {
+    pub const ALL: [EvidenceField; 6] = [
+        EvidenceField::Id,
+        EvidenceField::Score,
+        EvidenceField::Hop,
+        EvidenceField::Confidence,
+        EvidenceField::Source,
+        EvidenceField::Data,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EvidenceField::Id => "id",
+            EvidenceField::Score => "score",
+            EvidenceField::Hop => "hop",
+            EvidenceField::Confidence => "confidence",
+            EvidenceField::Source => "source",
+            EvidenceField::Data => "data",
+        }
+    }
+
+    fn value(self, node: &EvidenceNode) -> String {
+        match self {
+            EvidenceField::Id => node.id.to_string(),
+            EvidenceField::Score => node.score.to_string(),
+            EvidenceField::Hop => node.hop.to_string(),
+            EvidenceField::Confidence => node.confidence.to_string(),
+            EvidenceField::Source => node.provenance.source.clone().unwrap_or_default(),
+            EvidenceField::Data => node.data.clone(),
+        }
+    }
+}
+
+impl QueryResponse {
+    /// Evidence nodes as RFC 4180 CSV: a header row of `fields`, then one
+    /// row per node in evidence order. Values containing the delimiter,
+    /// quotes or line breaks are quoted, with quotes doubled.
+    pub fn to_csv(&self, fields: &[EvidenceField]) -> String {
+        self.to_delimited(fields, ',')
+    }
+
+    /// Same as [`QueryResponse::to_csv`] with tab-separated columns.
+    pub fn to_tsv(&self, fields: &[EvidenceField]) -> String {
+        self.to_delimited(fields, '\t')
+    }
+
+    fn to_delimited(&self, fields: &[EvidenceField], delimiter: char) -> String {
+        let mut out = String::new();
+        push_row(
+            &mut out,
+            fields.iter().map(|field| field.as_str().to_string()),
+            delimiter,
+        );
+        for node in &self.evidence.nodes {
+            push_row(
+                &mut out,
+                fields.iter().map(|field| field.value(node)),
+                delimiter,
+            );
+        }
+        out
+    }
+}
+
+fn push_row(out: &mut String, values: impl Iterator<Item = String>, delimiter: char) {
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        if value.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&value.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&value);
+        }
+    }
+    out.push_str("\r\n");
+}
@@ -1,25 +1,42 @@
 mod execution;
+mod export;
 mod planning;
 mod synthesis;
 
-use crate::dsl::{QueryRequest, SearchMode};
-use crate::semantic_cache::{SemanticCache, SemanticCacheConfig, SemanticCacheKey};
+use crate::dsl::{QueryLimits, QueryRequest, SearchMode};
+use crate::graphrag::{
+    SummaryScorer, TokenOverlapScorer, DEFAULT_GLOBAL_MAX_COMMUNITIES, MAX_GLOBAL_COMMUNITIES,
+};
+use crate::planner::MAX_EXPANSION_DEPTH;
+use crate::semantic_cache::{
+    EmbeddingCache, ExactCacheKey, ExactResultCache, SemanticCache, SemanticCacheConfig,
+    SemanticCacheKey,
+};
+use crate::tokenizer::Tokenizer;
 use alayasiki_core::audit::{AuditEvent, AuditOutcome, AuditSink};
 use alayasiki_core::auth::{
     Action, AuthError, Authorizer, AuthzError, JwtAuthenticator, Principal, ResourceContext,
+    RowAuthorizer,
+};
+use alayasiki_core::embedding::{
+    deterministic_embedding, EmbeddingModelError, EmbeddingModelRegistry, EmbeddingProjection,
 };
 use alayasiki_core::error::{AlayasikiError, ErrorCode};
 use alayasiki_core::metrics::{MetricsCollector, MetricsSnapshot};
+use alayasiki_core::model::Node;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Instant;
-use storage::community::CommunitySummary;
+use storage::community::{CommunitySummary, CommunitySummaryHandle};
 use storage::repo::{RepoError, Repository, SnapshotView};
 use storage::session::SessionOwner;
 use thiserror::Error;
 use tokio::sync::Mutex;
 
-use synthesis::{build_query_audit_event, effective_query_model_id};
+pub use export::EvidenceField;
+use synthesis::{build_query_audit_event, effective_query_model_id, query_request_fingerprint};
 
 /// Provenance metadata attached to evidence items.
 /// Captures the data lineage: where it came from and how it was extracted.
@@ -47,6 +64,12 @@ pub struct EvidenceNode {
     pub hop: u8,
     pub provenance: Provenance,
     pub confidence: f32,
+    /// Most specific (lowest-level) loaded community containing the node;
+    /// `None` when no community summaries are loaded or none contains it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub community_id: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub community_level: Option<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -68,6 +91,9 @@ pub struct EvidenceSubgraph {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Citation {
     pub source: String,
+    /// Byte range of the cited text. Within the original document when the
+    /// chunk recorded `doc_char_start`/`doc_char_end` metadata, otherwise the
+    /// whole node `data`.
     pub span: [usize; 2],
     pub node_id: u64,
     pub confidence: f32,
@@ -86,12 +112,151 @@ pub struct ExpansionPath {
     pub path: Vec<u64>,
 }
 
+/// Machine-readable category of an [`ExclusionReason`]. Serialized in
+/// `snake_case`, which is also the prefix of the legacy `reason` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionCode {
+    /// Edge relation not in the allow-list; detail is the relation.
+    RelationFiltered,
+    /// Edge relation listed in `traversal.exclude_relation_types`; detail is
+    /// the relation.
+    RelationExcluded,
+    /// Edge relation weighted to zero; detail is the relation.
+    RelationWeightZero,
+    /// Neighbor dropped by the fan-out cap; detail is the capped node id.
+    FanoutCapped,
+    /// Graph expansion stopped at `traversal.max_candidates`.
+    CandidateCapReached,
+    PathReconstructionFailed,
+    MissingNode,
+    TenantFiltered,
+    /// The engine's [`RowAuthorizer`] denied the principal read access.
+    RowAuthzDenied,
+    RetentionExpired,
+    EntityTypeFiltered,
+    TimeRangeFiltered,
+    /// A `filters.metadata` path was missing or held another value.
+    MetadataFiltered,
+    PrunedByTopK,
+    AnchorNotFound,
+    /// Vector hit scored below `min_anchor_score`; never used as an anchor.
+    AnchorBelowThreshold,
+    NoNodesAvailable,
+    /// The repository (and session) holds no nodes at all, so retrieval and
+    /// every fallback were skipped.
+    EmptyRepository,
+    NoGraphExpansionVectorOnlyFallback,
+    AutoFallbackToDriftDueToInsufficientEvidence,
+    GlobalSummaryDisabledByTenantScope,
+    NoCommunityDataFallbackToVector,
+    GlobalSummaryDisabledBySnapshotPin,
+    GlobalNoRelevantCommunitySummary,
+    GlobalSummaryDisabledByRelationFilter,
+    DriftNoEvidenceFound,
+    DriftExhaustedNoEvidence,
+    /// Vector search found nothing and `require_match` disabled the
+    /// arbitrary-node fallback.
+    NoVectorMatch,
+}
+
+impl ExclusionCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExclusionCode::RelationFiltered => "relation_filtered",
+            ExclusionCode::RelationExcluded => "relation_excluded",
+            ExclusionCode::RelationWeightZero => "relation_weight_zero",
+            ExclusionCode::FanoutCapped => "fanout_capped",
+            ExclusionCode::CandidateCapReached => "candidate_cap_reached",
+            ExclusionCode::PathReconstructionFailed => "path_reconstruction_failed",
+            ExclusionCode::MissingNode => "missing_node",
+            ExclusionCode::TenantFiltered => "tenant_filtered",
+            ExclusionCode::RowAuthzDenied => "row_authz_denied",
+            ExclusionCode::RetentionExpired => "retention_expired",
+            ExclusionCode::EntityTypeFiltered => "entity_type_filtered",
+            ExclusionCode::TimeRangeFiltered => "time_range_filtered",
+            ExclusionCode::MetadataFiltered => "metadata_filtered",
+            ExclusionCode::PrunedByTopK => "pruned_by_top_k",
+            ExclusionCode::AnchorNotFound => "anchor_not_found",
+            ExclusionCode::AnchorBelowThreshold => "anchor_below_threshold",
+            ExclusionCode::NoNodesAvailable => "no_nodes_available",
+            ExclusionCode::EmptyRepository => "empty_repository",
+            ExclusionCode::NoGraphExpansionVectorOnlyFallback => {
+                "no_graph_expansion_vector_only_fallback"
+            }
+            ExclusionCode::AutoFallbackToDriftDueToInsufficientEvidence => {
+                "auto_fallback_to_drift_due_to_insufficient_evidence"
+            }
+            ExclusionCode::GlobalSummaryDisabledByTenantScope => {
+                "global_summary_disabled_by_tenant_scope"
+            }
+            ExclusionCode::NoCommunityDataFallbackToVector => {
+                "no_community_data_fallback_to_vector"
+            }
+            ExclusionCode::GlobalSummaryDisabledBySnapshotPin => {
+                "global_summary_disabled_by_snapshot_pin"
+            }
+            ExclusionCode::GlobalNoRelevantCommunitySummary => {
+                "global_no_relevant_community_summary"
+            }
+            ExclusionCode::GlobalSummaryDisabledByRelationFilter => {
+                "global_summary_disabled_by_relation_filter"
+            }
+            ExclusionCode::DriftNoEvidenceFound => "drift_no_evidence_found",
+            ExclusionCode::DriftExhaustedNoEvidence => "drift_exhausted_no_evidence",
+            ExclusionCode::NoVectorMatch => "no_vector_match",
+        }
+    }
+}
+
+impl std::fmt::Display for ExclusionCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a node (or, with `node_id: None`, a whole stage) was left out of the
+/// result. `reason` is the human-readable `code[:detail]` string.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExclusionReason {
     pub node_id: Option<u64>,
+    pub code: ExclusionCode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
     pub reason: String,
 }
 
+impl ExclusionReason {
+    pub fn new(node_id: Option<u64>, code: ExclusionCode) -> Self {
+        Self {
+            node_id,
+            code,
+            detail: None,
+            reason: code.as_str().to_string(),
+        }
+    }
+
+    pub fn with_detail(
+        node_id: Option<u64>,
+        code: ExclusionCode,
+        detail: impl Into<String>,
+    ) -> Self {
+        let detail = detail.into();
+        Self {
+            node_id,
+            code,
+            reason: format!("{}:{}", code.as_str(), detail),
+            detail: Some(detail),
+        }
+    }
+}
+
+impl std::fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExplainPlan {
     pub steps: Vec<String>,
@@ -99,10 +264,57 @@ pub struct ExplainPlan {
     pub anchors: Vec<Anchor>,
     pub expansion_paths: Vec<ExpansionPath>,
     pub exclusions: Vec<ExclusionReason>,
+    /// Sorted lexical tokens of the query, including CJK n-grams. Only set when
+    /// the request enables `debug_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_tokens: Option<Vec<String>>,
+    /// Why the top anchor scored as it did against the query embedding. Only
+    /// set when the request enables `explain_vector`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector_explain: Option<VectorExplanation>,
+}
+
+/// Cosine similarity between the query embedding and one node, with the
+/// dimensions that contributed most to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorExplanation {
+    pub node_id: u64,
+    pub similarity: f32,
+    /// Largest contributions by magnitude, at most
+    /// [`MAX_EXPLAINED_DIMENSIONS`]. With that many dimensions or fewer they
+    /// sum to `similarity`.
+    pub dimensions: Vec<DimensionContribution>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionContribution {
+    pub dimension: usize,
+    pub query_value: f32,
+    pub node_value: f32,
+    /// `query_value * node_value / (|query| * |node|)`.
+    pub contribution: f32,
+}
+
+pub const MAX_EXPLAINED_DIMENSIONS: usize = 16;
+
+/// Whether a query produced anything to answer from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultStatus {
+    #[default]
+    Found,
+    /// Nothing matched: evidence is empty, `answer` is `None` and
+    /// groundedness is 0.
+    Empty,
+    /// The query failed; `error_code` and `error_message` say why and
+    /// `answer` is `None`.
+    Error,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryResponse {
+    #[serde(default)]
+    pub result_status: ResultStatus,
     pub answer: Option<String>,
     pub evidence: EvidenceSubgraph,
     pub citations: Vec<Citation>,
@@ -115,6 +327,23 @@ pub struct QueryResponse {
     pub latency_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_code: Option<ErrorCode>,
+    /// Set only when `result_status` is [`ResultStatus::Error`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Set only for [`QueryMode::Count`](crate::QueryMode::Count) requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<QueryCounts>,
+}
+
+/// Result sizes reported by a count query in place of the evidence itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct QueryCounts {
+    /// Nodes the same request would return as evidence.
+    pub matched: usize,
+    /// Edges the same request would return as evidence.
+    pub edges: usize,
+    /// Exclusions by reason code.
+    pub excluded: BTreeMap<String, usize>,
 }
 
 #[derive(Debug, Error)]
@@ -129,6 +358,8 @@ pub enum QueryError {
     Unauthorized(#[from] AuthzError),
     #[error("authentication error: {0}")]
     Unauthenticated(#[from] AuthError),
+    #[error("embedding model error: {0}")]
+    EmbeddingModel(#[from] EmbeddingModelError),
 }
 
 impl AlayasikiError for QueryError {
@@ -139,6 +370,7 @@ impl AlayasikiError for QueryError {
             QueryError::Repository(err) => err.error_code(),
             QueryError::Unauthorized(err) => err.error_code(),
             QueryError::Unauthenticated(err) => err.error_code(),
+            QueryError::EmbeddingModel(err) => err.error_code(),
         }
     }
 }
@@ -146,7 +378,8 @@ impl AlayasikiError for QueryError {
 impl QueryError {
     pub fn to_response(&self) -> QueryResponse {
         QueryResponse {
-            answer: Some(self.to_string()),
+            result_status: ResultStatus::Error,
+            answer: None,
             evidence: EvidenceSubgraph {
                 nodes: vec![],
                 edges: vec![],
@@ -159,26 +392,171 @@ impl QueryError {
                 anchors: vec![],
                 expansion_paths: vec![],
                 exclusions: vec![],
+                query_tokens: None,
+                vector_explain: None,
             },
             model_id: None,
             snapshot_id: None,
             time_travel: None,
             latency_ms: 0,
             error_code: Some(self.error_code()),
+            error_message: Some(self.to_string()),
+            counts: None,
+        }
+    }
+}
+
+/// Per-field weights for lexical scoring. A node token counts with the
+/// largest weight of any field it appears in; fields weighted `0.0` are left
+/// out of lexical scoring entirely. The defaults weight `data` and every
+/// metadata value equally.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicalFieldWeights {
+    pub data: f32,
+    /// Weight for metadata values whose key has no entry in `metadata_keys`.
+    pub metadata: f32,
+    pub metadata_keys: HashMap<String, f32>,
+}
+
+impl Default for LexicalFieldWeights {
+    fn default() -> Self {
+        Self {
+            data: 1.0,
+            metadata: 1.0,
+            metadata_keys: HashMap::new(),
+        }
+    }
+}
+
+impl LexicalFieldWeights {
+    pub fn with_data_weight(mut self, weight: f32) -> Self {
+        self.data = weight;
+        self
+    }
+
+    pub fn with_metadata_weight(mut self, weight: f32) -> Self {
+        self.metadata = weight;
+        self
+    }
+
+    pub fn with_metadata_key_weight(mut self, key: impl Into<String>, weight: f32) -> Self {
+        self.metadata_keys.insert(key.into(), weight);
+        self
+    }
+
+    /// Leaves the values of `key` out of lexical scoring.
+    pub fn exclude_metadata_key(self, key: impl Into<String>) -> Self {
+        self.with_metadata_key_weight(key, 0.0)
+    }
+
+    pub fn metadata_key_weight(&self, key: &str) -> f32 {
+        self.metadata_keys
+            .get(key)
+            .copied()
+            .unwrap_or(self.metadata)
+    }
+}
+
+/// Where [`EvidenceEdge::confidence`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeConfidenceSource {
+    /// The edge's `confidence` metadata (written by extraction) when it parses
+    /// as a number, else its weight. Mirrors how node confidence is read.
+    #[default]
+    MetadataOrWeight,
+    /// Always the edge weight.
+    Weight,
+}
+
+/// Metadata keys read into [`Provenance`] for evidence nodes and edges. The
+/// defaults match the keys written by the ingestion pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenanceMapping {
+    pub source: String,
+    pub extraction_model_id: String,
+    pub snapshot_id: String,
+    pub ingested_at: String,
+}
+
+impl Default for ProvenanceMapping {
+    fn default() -> Self {
+        Self {
+            source: "source".to_string(),
+            extraction_model_id: "extraction_model_id".to_string(),
+            snapshot_id: "snapshot_id".to_string(),
+            ingested_at: "ingested_at".to_string(),
+        }
+    }
+}
+
+impl ProvenanceMapping {
+    pub fn with_source_key(mut self, key: impl Into<String>) -> Self {
+        self.source = key.into();
+        self
+    }
+
+    pub fn with_extraction_model_id_key(mut self, key: impl Into<String>) -> Self {
+        self.extraction_model_id = key.into();
+        self
+    }
+
+    pub fn with_snapshot_id_key(mut self, key: impl Into<String>) -> Self {
+        self.snapshot_id = key.into();
+        self
+    }
+
+    pub fn with_ingested_at_key(mut self, key: impl Into<String>) -> Self {
+        self.ingested_at = key.into();
+        self
+    }
+
+    pub fn provenance(&self, metadata: &HashMap<String, String>) -> Provenance {
+        Provenance {
+            source: metadata.get(&self.source).cloned(),
+            extraction_model_id: metadata.get(&self.extraction_model_id).cloned(),
+            snapshot_id: metadata.get(&self.snapshot_id).cloned(),
+            ingested_at: metadata.get(&self.ingested_at).cloned(),
         }
     }
 }
 
 pub struct QueryEngine {
     repo: Arc<Repository>,
-    community_summaries: Vec<CommunitySummary>,
+    community_summaries: CommunitySummaryHandle,
+    embedding_models: Option<Arc<EmbeddingModelRegistry>>,
     audit_sink: Option<Arc<dyn AuditSink>>,
+    row_authorizer: Option<Arc<dyn RowAuthorizer>>,
     semantic_cache: Arc<Mutex<SemanticCache<QueryResponse>>>,
+    exact_cache: Option<Arc<Mutex<ExactResultCache<QueryResponse>>>>,
+    query_embed: QueryEmbedFn,
+    embedding_cache: Arc<std::sync::Mutex<EmbeddingCache>>,
+    pagerank_cache: PageRankCache,
+    tokenizer: Tokenizer,
+    request_limits: QueryLimits,
+    lexical_weights: LexicalFieldWeights,
+    provenance_mapping: ProvenanceMapping,
+    edge_confidence_source: EdgeConfidenceSource,
+    summary_scorer: Arc<dyn SummaryScorer>,
+    global_max_communities: usize,
+    max_expansion_depth: u8,
+    min_answer_groundedness: Option<f32>,
+    plan_logging: bool,
     metrics: Arc<MetricsCollector>,
 }
 
+/// `tracing` target of the per-query plan events enabled by
+/// [`QueryEngine::with_plan_logging`].
+pub const QUERY_PLAN_LOG_TARGET: &str = "alayasiki::query::plan";
+
 const DEFAULT_EMBEDDING_MODEL_ID: &str = "embedding-default-v1";
-const UNICODE_NGRAM_SIZE: usize = 2;
+const DEFAULT_EMBEDDING_CACHE_ENTRIES: usize = 256;
+
+/// PageRank for `centrality_boost`, with the source state id it was
+/// computed for.
+type PageRankCache = Arc<std::sync::Mutex<Option<(String, Arc<HashMap<u64, f64>>)>>>;
+
+/// Embeds query text as `(text, model_id, dimension) -> vector`.
+pub type QueryEmbedFn = Arc<dyn Fn(&str, &str, usize) -> Vec<f32> + Send + Sync>;
 
 #[derive(Debug, Clone)]
 pub struct RankedNode {
@@ -191,6 +569,8 @@ pub struct RankedNode {
     pub node_snapshot_id: Option<String>,
     pub ingested_at: Option<String>,
     pub confidence: f32,
+    /// `[doc_char_start, doc_char_end]` from chunk metadata, when recorded.
+    pub doc_span: Option<[usize; 2]>,
 }
 
 /// Internal edge representation during query execution (before final output).
@@ -213,6 +593,50 @@ pub struct ExecutionState {
     pub edges: Vec<InternalEdge>,
 }
 
+/// Who a query reads on behalf of: the tenant whose nodes are visible and,
+/// for authorized queries, the principal checked by the row authorizer.
+#[derive(Clone, Copy)]
+struct ReadScope<'a> {
+    tenant: Option<&'a str>,
+    principal: Option<&'a Principal>,
+}
+
+/// Turns query text into a vector in the indexed embedding space, projecting
+/// it when the request's model differs from the indexed one.
+struct QueryEmbedder {
+    model_id: String,
+    projection: Option<EmbeddingProjection>,
+    embed: QueryEmbedFn,
+    cache: Arc<std::sync::Mutex<EmbeddingCache>>,
+}
+
+impl QueryEmbedder {
+    fn embed(&self, text: &str, dimension: usize) -> Vec<f32> {
+        match &self.projection {
+            // An embedder that ignores the requested dimension yields an empty
+            // vector, which matches nothing, as an unprojected mismatch does.
+            Some(projection) => projection
+                .project(&self.embed_cached(text, projection.source_dimension()))
+                .unwrap_or_default(),
+            None => self.embed_cached(text, dimension),
+        }
+    }
+
+    fn embed_cached(&self, text: &str, dimension: usize) -> Vec<f32> {
+        let key = (text.to_string(), self.model_id.clone(), dimension);
+        if let Some(embedding) = self.lock_cache().get(&key) {
+            return embedding;
+        }
+        let embedding = (self.embed)(text, &self.model_id, dimension);
+        self.lock_cache().insert(key, embedding.clone());
+        embedding
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, EmbeddingCache> {
+        self.cache.lock().expect("embedding cache lock poisoned")
+    }
+}
+
 #[derive(Clone)]
 struct ResolvedSnapshot {
     snapshot_id: String,
@@ -220,24 +644,70 @@ struct ResolvedSnapshot {
     snapshot_view: Option<Arc<SnapshotView>>,
     time_travel: Option<String>,
     requires_versioned_summaries: bool,
+    /// Community summaries published when the query started.
+    community_summaries: Arc<Vec<CommunitySummary>>,
+    community_generation: u64,
 }
 
 impl QueryEngine {
     pub fn new(repo: Arc<Repository>) -> Self {
         Self {
             repo,
-            community_summaries: Vec::new(),
+            community_summaries: CommunitySummaryHandle::default(),
+            embedding_models: None,
             audit_sink: None,
+            row_authorizer: None,
             semantic_cache: Arc::new(Mutex::new(SemanticCache::with_config(
                 SemanticCacheConfig::default(),
             ))),
+            exact_cache: None,
+            query_embed: Arc::new(deterministic_embedding),
+            embedding_cache: Arc::new(std::sync::Mutex::new(EmbeddingCache::new(
+                DEFAULT_EMBEDDING_CACHE_ENTRIES,
+            ))),
+            pagerank_cache: PageRankCache::default(),
+            tokenizer: Tokenizer::default(),
+            request_limits: QueryLimits::default(),
+            lexical_weights: LexicalFieldWeights::default(),
+            provenance_mapping: ProvenanceMapping::default(),
+            edge_confidence_source: EdgeConfidenceSource::default(),
+            summary_scorer: Arc::new(TokenOverlapScorer),
+            global_max_communities: DEFAULT_GLOBAL_MAX_COMMUNITIES,
+            max_expansion_depth: MAX_EXPANSION_DEPTH,
+            min_answer_groundedness: None,
+            plan_logging: false,
             metrics: Arc::new(MetricsCollector::new(1000)),
         }
     }
 
     /// Attach pre-computed community summaries for global search support.
     pub fn with_community_summaries(mut self, summaries: Vec<CommunitySummary>) -> Self {
-        self.community_summaries = summaries;
+        self.community_summaries = CommunitySummaryHandle::new(summaries);
+        self
+    }
+
+    /// Read community summaries from a shared handle, e.g. one published to by
+    /// a [`storage::community_scheduler::CommunityScheduler`].
+    pub fn with_community_summary_handle(mut self, handle: CommunitySummaryHandle) -> Self {
+        self.community_summaries = handle;
+        self
+    }
+
+    /// Replace the community summaries used by global search on a running
+    /// engine. Queries already in flight keep the set they started with.
+    pub fn set_community_summaries(&self, summaries: Vec<CommunitySummary>) {
+        self.community_summaries.store(summaries);
+    }
+
+    pub fn community_summary_handle(&self) -> CommunitySummaryHandle {
+        self.community_summaries.clone()
+    }
+
+    /// Validate each query's embedding model against `registry`: unknown model
+    /// ids and models whose dimension differs from the stored vectors are
+    /// rejected. Without a registry any model id is accepted.
+    pub fn with_embedding_model_registry(mut self, registry: EmbeddingModelRegistry) -> Self {
+        self.embedding_models = Some(Arc::new(registry));
         self
     }
 
@@ -246,8 +716,146 @@ impl QueryEngine {
         self
     }
 
+    /// Row-level read check applied to every candidate node of an
+    /// authorized query; denied nodes are excluded with `row_authz_denied`.
+    /// Unauthenticated `execute` calls are not filtered.
+    pub fn with_row_authorizer(mut self, authorizer: Arc<dyn RowAuthorizer>) -> Self {
+        self.row_authorizer = Some(authorizer);
+        self
+    }
+
     pub fn with_semantic_cache_config(mut self, config: SemanticCacheConfig) -> Self {
-        self.semantic_cache = Arc::new(Mutex::new(SemanticCache::with_config(config)));
+        self.semantic_cache = Arc::new(Mutex::new(
+            SemanticCache::with_config(config).with_tokenizer(self.tokenizer.clone()),
+        ));
+        self
+    }
+
+    /// Enables the exact-repeat result cache in front of the semantic cache.
+    /// Pass the same cache to engines over replicas with identical data to
+    /// share entries between them. Each lookup hashes the repository content
+    /// (reused until the WAL moves).
+    pub fn with_exact_result_cache(
+        mut self,
+        cache: Arc<Mutex<ExactResultCache<QueryResponse>>>,
+    ) -> Self {
+        self.exact_cache = Some(cache);
+        self
+    }
+
+    /// Function that embeds query text (default [`deterministic_embedding`]).
+    pub fn with_query_embed_fn(mut self, embed: QueryEmbedFn) -> Self {
+        self.query_embed = embed;
+        self
+    }
+
+    /// Number of query embeddings kept in the LRU embedding cache (default
+    /// 256). Entries are keyed on `(query, model_id, dimension)`; `0` disables
+    /// the cache.
+    pub fn with_embedding_cache_capacity(mut self, max_entries: usize) -> Self {
+        self.embedding_cache = Arc::new(std::sync::Mutex::new(EmbeddingCache::new(max_entries)));
+        self
+    }
+
+    /// Tokenizer for lexical scoring. The semantic cache is switched to the
+    /// same tokenizer so cached and fresh queries are compared alike.
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        // The cache is only shared with in-flight queries, so a builder call
+        // always has exclusive access.
+        if let Some(cache) = Arc::get_mut(&mut self.semantic_cache) {
+            cache.get_mut().set_tokenizer(tokenizer.clone());
+        }
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    /// The engine tokenizer, switched to the request's `language` if set.
+    fn request_tokenizer(&self, request: &QueryRequest) -> Cow<'_, Tokenizer> {
+        match request.language {
+            Some(language) if language != self.tokenizer.language() => {
+                Cow::Owned(self.tokenizer.clone().with_language(language))
+            }
+            _ => Cow::Borrowed(&self.tokenizer),
+        }
+    }
+
+    /// Size limits applied to every request before planning; oversized
+    /// requests fail with `QueryError::InvalidQuery`.
+    pub fn with_request_limits(mut self, limits: QueryLimits) -> Self {
+        self.request_limits = limits;
+        self
+    }
+
+    pub fn with_lexical_field_weights(mut self, weights: LexicalFieldWeights) -> Self {
+        self.lexical_weights = weights;
+        self
+    }
+
+    pub fn lexical_field_weights(&self) -> &LexicalFieldWeights {
+        &self.lexical_weights
+    }
+
+    /// Metadata keys that populate evidence provenance.
+    pub fn with_provenance_mapping(mut self, mapping: ProvenanceMapping) -> Self {
+        self.provenance_mapping = mapping;
+        self
+    }
+
+    pub fn provenance_mapping(&self) -> &ProvenanceMapping {
+        &self.provenance_mapping
+    }
+
+    /// Whether evidence edges report their `confidence` metadata or weight.
+    /// Path confidence propagation folds in the same per-edge value.
+    pub fn with_edge_confidence_source(mut self, source: EdgeConfidenceSource) -> Self {
+        self.edge_confidence_source = source;
+        self
+    }
+
+    pub fn edge_confidence_source(&self) -> EdgeConfidenceSource {
+        self.edge_confidence_source
+    }
+
+    /// Scorer used to rank community summaries in global search (default
+    /// [`TokenOverlapScorer`]).
+    pub fn with_summary_scorer(mut self, scorer: Arc<dyn SummaryScorer>) -> Self {
+        self.summary_scorer = scorer;
+        self
+    }
+
+    /// Default for `global_max_communities` when a request leaves it unset,
+    /// clamped to `1..=`[`MAX_GLOBAL_COMMUNITIES`].
+    pub fn with_global_max_communities(mut self, max_communities: usize) -> Self {
+        self.global_max_communities = max_communities.clamp(1, MAX_GLOBAL_COMMUNITIES);
+        self
+    }
+
+    /// Server-side ceiling on graph expansion depth (default
+    /// [`MAX_EXPANSION_DEPTH`]). Deeper requests are clamped and report a
+    /// `depth_capped` step in `explain.steps`; DRIFT never widens past it.
+    pub fn with_max_expansion_depth(mut self, max_depth: u8) -> Self {
+        self.max_expansion_depth = max_depth.max(1);
+        self
+    }
+
+    /// Default for `min_answer_groundedness` when a request leaves it unset.
+    /// Answers below the threshold are withheld and reported with an
+    /// `answer_suppressed_low_groundedness` step.
+    pub fn with_min_answer_groundedness(mut self, threshold: f32) -> Self {
+        self.min_answer_groundedness = Some(threshold.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Emit a `debug` event per query (target [`QUERY_PLAN_LOG_TARGET`]) with
+    /// the effective search mode, plan parameters, anchor/evidence counts,
+    /// groundedness and whether it ran, was a dry run or hit a cache. The query is
+    /// identified by its request fingerprint, never its raw text.
+    pub fn with_plan_logging(mut self, enabled: bool) -> Self {
+        self.plan_logging = enabled;
         self
     }
 
@@ -312,6 +920,7 @@ impl QueryEngine {
                 Some(principal.subject.clone()),
                 Some(principal.tenant.clone()),
                 None,
+                query_request_fingerprint(&request).as_deref(),
                 Some(err.to_string()),
             ));
             return Err(err.into());
@@ -322,6 +931,7 @@ impl QueryEngine {
             Some(principal.subject.clone()),
             Some(principal.tenant.clone()),
             Some(principal.tenant.clone()),
+            Some(principal),
             Some(SessionOwner::new(
                 principal.tenant.clone(),
                 principal.subject.clone(),
@@ -359,6 +969,7 @@ impl QueryEngine {
                 None,
                 None,
                 None,
+                None,
                 Some(err.to_string()),
             ));
             err.into()
@@ -366,7 +977,7 @@ impl QueryEngine {
     }
 
     pub async fn execute(&self, request: QueryRequest) -> Result<QueryResponse, QueryError> {
-        self.execute_with_audit(request, None, None, None, None)
+        self.execute_with_audit(request, None, None, None, None, None)
             .await
     }
 
@@ -376,12 +987,14 @@ impl QueryEngine {
         actor: Option<String>,
         tenant: Option<String>,
         tenant_scope: Option<String>,
+        reader: Option<&Principal>,
         session_owner: Option<SessionOwner>,
     ) -> Result<QueryResponse, QueryError> {
         let start = Instant::now();
         let model_id = effective_query_model_id(&request);
+        let fingerprint = query_request_fingerprint(&request);
         let result = self
-            .execute_internal(request, start, tenant_scope, session_owner)
+            .execute_internal(request, start, tenant_scope, reader, session_owner)
             .await;
         match &result {
             Ok(response) => {
@@ -391,6 +1004,7 @@ impl QueryEngine {
                     actor,
                     tenant,
                     response.snapshot_id.clone(),
+                    fingerprint.as_deref(),
                     None,
                 ));
             }
@@ -401,6 +1015,7 @@ impl QueryEngine {
                     actor,
                     tenant,
                     None,
+                    fingerprint.as_deref(),
                     Some(err.to_string()),
                 ));
                 self.metrics
@@ -410,6 +1025,13 @@ impl QueryEngine {
         result
     }
 
+    fn row_readable(&self, reader: Option<&Principal>, node: &Node) -> bool {
+        match (&self.row_authorizer, reader) {
+            (Some(authorizer), Some(principal)) => authorizer.can_read(principal, node),
+            _ => true,
+        }
+    }
+
     fn emit_audit_event(&self, event: AuditEvent) {
         if let Some(sink) = &self.audit_sink {
             let _ = sink.record(event);
@@ -425,6 +1047,17 @@ impl QueryEngine {
         cache.lookup(key, query)
     }
 
+    async fn lookup_exact_cache(&self, key: &ExactCacheKey) -> Option<QueryResponse> {
+        let cache = self.exact_cache.as_ref()?;
+        cache.lock().await.lookup(key)
+    }
+
+    async fn insert_exact_cache(&self, key: ExactCacheKey, response: QueryResponse) {
+        if let Some(cache) = &self.exact_cache {
+            cache.lock().await.insert(key, response);
+        }
+    }
+
     async fn insert_semantic_cache(
         &self,
         key: SemanticCacheKey,
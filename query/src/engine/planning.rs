@@ -1,18 +1,26 @@
-use super::synthesis::{build_citations, generate_answer};
+use super::synthesis::{
+    build_citations, community_assignments, generate_answer, query_request_fingerprint,
+};
 use super::{
-    EvidenceEdge, EvidenceNode, EvidenceSubgraph, Provenance, QueryError, QueryRequest,
-    QueryResponse, ResolvedSnapshot, DEFAULT_EMBEDDING_MODEL_ID,
+    DimensionContribution, EvidenceEdge, EvidenceNode, EvidenceSubgraph, ExclusionReason,
+    Provenance, QueryCounts, QueryEmbedder, QueryError, QueryRequest, QueryResponse, ReadScope,
+    ResolvedSnapshot, ResultStatus, VectorExplanation, DEFAULT_EMBEDDING_MODEL_ID,
+    MAX_EXPLAINED_DIMENSIONS,
 };
 use crate::dsl::{QueryMode, SearchMode};
 use crate::graphrag::compute_groundedness;
-use crate::planner::QueryPlanner;
-use crate::semantic_cache::SemanticCacheKey;
+use crate::planner::QueryPlan;
+use crate::planner::{QueryPlanner, DEPTH_CAPPED_STEP, EXPLICIT_ANCHORS_STEP};
+use crate::semantic_cache::{ExactCacheKey, SemanticCacheKey};
+use crate::tokenizer::Tokenizer;
+use alayasiki_core::auth::Principal;
+use alayasiki_core::embedding::cosine_contributions;
 use alayasiki_core::model::Node;
 use chrono::{DateTime, NaiveDate, Utc};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
-use storage::repo::{parse_wal_snapshot_lsn, RepoError, SnapshotView};
+use storage::repo::{ReadSource, RepoError, SnapshotView, CONTENT_SNAPSHOT_ID_PREFIX};
 use storage::session::{SessionGraph, SessionOwner};
 
 impl super::QueryEngine {
@@ -21,20 +29,44 @@ impl super::QueryEngine {
         request: QueryRequest,
         start: Instant,
         tenant_scope: Option<String>,
+        reader: Option<&Principal>,
         session_owner: Option<SessionOwner>,
     ) -> Result<QueryResponse, QueryError> {
         request
-            .validate()
+            .validate_with_limits(&self.request_limits)
             .map_err(|err| QueryError::InvalidQuery(err.to_string()))?;
 
         let effective_model_id = request
             .model_id
             .clone()
             .unwrap_or_else(|| DEFAULT_EMBEDDING_MODEL_ID.to_string());
-        let mut plan = QueryPlanner::plan(&request);
+        let mut plan = QueryPlanner::plan_with_max_depth(&request, self.max_expansion_depth);
         let resolved_snapshot = self.resolve_snapshot(&request).await?;
+        let projection = match &self.embedding_models {
+            Some(registry) => {
+                let repository_dimension = self
+                    .read_source(&resolved_snapshot)
+                    .embedding_dimension()
+                    .await;
+                registry
+                    .resolve_query_projection(&effective_model_id, repository_dimension)?
+                    .cloned()
+            }
+            None => None,
+        };
+        let query_embedder = QueryEmbedder {
+            model_id: effective_model_id.clone(),
+            projection,
+            embed: self.query_embed.clone(),
+            cache: self.embedding_cache.clone(),
+        };
         let tenant_scoped = tenant_scope.is_some();
-        let cache_eligible = !tenant_scoped && request.session_id.is_none();
+        let scope = ReadScope {
+            tenant: tenant_scope.as_deref(),
+            principal: reader,
+        };
+        let cache_eligible =
+            !tenant_scoped && request.session_id.is_none() && !request.explain_vector;
 
         let session_graph = match request.session_id.as_deref() {
             Some(session_id) => self
@@ -42,18 +74,78 @@ impl super::QueryEngine {
                 .get_session_with_owner(session_id, session_owner.as_ref())?,
             None => None,
         };
-        let cache_key = SemanticCacheKey::from_request(
-            &request,
-            &effective_model_id,
-            &resolved_snapshot.snapshot_id,
-            plan.effective_search_mode,
-        );
+        let cache_key = SemanticCacheKey {
+            community_generation: resolved_snapshot.community_generation,
+            ..SemanticCacheKey::from_request(
+                &request,
+                &effective_model_id,
+                &resolved_snapshot.snapshot_id,
+                plan.effective_search_mode,
+            )
+        };
+
+        if request.dry_run {
+            let mut steps = self.planned_steps(&plan, &resolved_snapshot, tenant_scope.as_deref());
+            report_anchor_step(&request, &mut steps);
+            report_depth_cap(plan.depth_capped, &mut steps);
+            report_query_projection(&query_embedder, &mut steps);
+            steps.insert(0, crate::DRY_RUN_STEP);
+            let response = QueryResponse {
+                result_status: ResultStatus::Empty,
+                answer: None,
+                evidence: EvidenceSubgraph {
+                    nodes: Vec::new(),
+                    edges: Vec::new(),
+                },
+                citations: Vec::new(),
+                groundedness: 0.0,
+                explain: super::ExplainPlan {
+                    steps: steps.iter().map(|step| step.to_string()).collect(),
+                    effective_search_mode: plan.effective_search_mode,
+                    anchors: Vec::new(),
+                    expansion_paths: Vec::new(),
+                    exclusions: Vec::new(),
+                    query_tokens: debug_query_tokens(&self.request_tokenizer(&request), &request),
+                    vector_explain: None,
+                },
+                model_id: Some(effective_model_id),
+                snapshot_id: Some(resolved_snapshot.snapshot_id),
+                time_travel: resolved_snapshot.time_travel,
+                latency_ms: start.elapsed().as_millis() as u64,
+                error_code: None,
+                error_message: None,
+                counts: None,
+            };
+            if self.plan_logging {
+                log_query_plan(&request, &plan, &response, "dry_run");
+            }
+            return Ok(response);
+        }
+
+        let exact_key = if cache_eligible {
+            self.exact_cache_key(&request, &effective_model_id, &plan, &resolved_snapshot)
+        } else {
+            None
+        };
+        if let Some(key) = &exact_key {
+            if let Some(mut cached_response) = self.lookup_exact_cache(key).await {
+                cached_response.latency_ms = start.elapsed().as_millis() as u64;
+                if self.plan_logging {
+                    log_query_plan(&request, &plan, &cached_response, "exact_cache_hit");
+                }
+                self.metrics
+                    .record_query(start.elapsed().as_micros() as u64, true);
+                return Ok(cached_response);
+            }
+        }
 
         if cache_eligible {
             if let Some(mut cached_response) =
                 self.lookup_semantic_cache(&cache_key, &request.query).await
             {
                 cached_response.latency_ms = start.elapsed().as_millis() as u64;
+                cached_response.explain.query_tokens =
+                    debug_query_tokens(&self.request_tokenizer(&request), &request);
                 if !cached_response
                     .explain
                     .steps
@@ -65,6 +157,9 @@ impl super::QueryEngine {
                         .steps
                         .insert(0, crate::SEMANTIC_CACHE_HIT_STEP.to_string());
                 }
+                if self.plan_logging {
+                    log_query_plan(&request, &plan, &cached_response, "semantic_cache_hit");
+                }
 
                 self.metrics
                     .record_query(start.elapsed().as_micros() as u64, true);
@@ -77,9 +172,9 @@ impl super::QueryEngine {
                 self.execute_global(
                     &request,
                     &mut plan,
-                    &effective_model_id,
+                    &query_embedder,
                     &resolved_snapshot,
-                    tenant_scope.as_deref(),
+                    scope,
                     session_graph.as_ref(),
                 )
                 .await?
@@ -89,9 +184,9 @@ impl super::QueryEngine {
                     .execute_drift(
                         &request,
                         &mut plan,
-                        &effective_model_id,
-                        resolved_snapshot.snapshot_view.as_deref(),
-                        tenant_scope.as_deref(),
+                        &query_embedder,
+                        self.read_source(&resolved_snapshot),
+                        scope,
                         session_graph.as_ref(),
                     )
                     .await?;
@@ -102,9 +197,9 @@ impl super::QueryEngine {
                     .execute_local_with_auto_fallback(
                         &request,
                         plan,
-                        &effective_model_id,
-                        resolved_snapshot.snapshot_view.as_deref(),
-                        tenant_scope.as_deref(),
+                        &query_embedder,
+                        self.read_source(&resolved_snapshot),
+                        scope,
                         session_graph.as_ref(),
                     )
                     .await?;
@@ -112,6 +207,15 @@ impl super::QueryEngine {
             }
         };
 
+        let mut plan = plan;
+        report_anchor_step(&request, &mut plan.steps);
+        report_depth_cap(plan.depth_capped, &mut plan.steps);
+        report_query_projection(&query_embedder, &mut plan.steps);
+
+        let communities = community_assignments(
+            &resolved_snapshot,
+            &state.nodes.iter().map(|node| node.id).collect(),
+        );
         let evidence_nodes: Vec<EvidenceNode> = state
             .nodes
             .iter()
@@ -127,6 +231,8 @@ impl super::QueryEngine {
                     ingested_at: node.ingested_at.clone(),
                 },
                 confidence: node.confidence,
+                community_id: communities.get(&node.id).map(|(_, id)| *id),
+                community_level: communities.get(&node.id).map(|(level, _)| *level),
             })
             .collect();
 
@@ -163,20 +269,65 @@ impl super::QueryEngine {
             has_graph_support,
         });
 
-        let answer = match request.mode {
-            QueryMode::Evidence => None,
-            QueryMode::Answer => {
+        let result_status = if evidence_nodes.is_empty() && global_answer.is_none() {
+            ResultStatus::Empty
+        } else {
+            ResultStatus::Found
+        };
+        let below_groundedness_gate = request
+            .min_answer_groundedness
+            .or(self.min_answer_groundedness)
+            .is_some_and(|threshold| groundedness < threshold);
+        let answer = match (request.mode, result_status) {
+            (QueryMode::Evidence | QueryMode::Count, _)
+            | (_, ResultStatus::Empty | ResultStatus::Error) => None,
+            (QueryMode::Answer, ResultStatus::Found) if below_groundedness_gate => {
+                plan.steps.push(crate::ANSWER_SUPPRESSED_STEP);
+                None
+            }
+            (QueryMode::Answer, ResultStatus::Found) => {
                 if let Some(global_ans) = global_answer {
                     Some(global_ans)
                 } else {
-                    Some(generate_answer(&request.query, &evidence_nodes))
+                    Some(generate_answer(
+                        &request.query,
+                        &evidence_nodes,
+                        request.answer_evidence_count,
+                    ))
+                }
+            }
+        };
+
+        let vector_explain = if request.explain_vector {
+            match state.anchors.first() {
+                Some(anchor) => {
+                    self.explain_vector_hit(
+                        &request.query,
+                        &query_embedder,
+                        anchor.node_id,
+                        self.read_source(&resolved_snapshot),
+                        session_graph.as_ref(),
+                    )
+                    .await
                 }
+                None => None,
             }
+        } else {
+            None
         };
 
+        let (evidence_nodes, evidence_edges, citations, counts) =
+            if request.mode == QueryMode::Count {
+                let counts = count_results(&evidence_nodes, &evidence_edges, &state.exclusions);
+                (Vec::new(), Vec::new(), Vec::new(), Some(counts))
+            } else {
+                (evidence_nodes, evidence_edges, citations, None)
+            };
+
         let latency_ms = start.elapsed().as_millis() as u64;
 
         let response = QueryResponse {
+            result_status,
             answer,
             evidence: EvidenceSubgraph {
                 nodes: evidence_nodes,
@@ -190,14 +341,22 @@ impl super::QueryEngine {
                 anchors: state.anchors,
                 expansion_paths: state.expansion_paths,
                 exclusions: state.exclusions,
+                query_tokens: debug_query_tokens(&self.request_tokenizer(&request), &request),
+                vector_explain,
             },
             model_id: Some(effective_model_id),
             snapshot_id: Some(resolved_snapshot.snapshot_id.clone()),
             time_travel: resolved_snapshot.time_travel.clone(),
             latency_ms,
             error_code: None,
+            error_message: None,
+            counts,
         };
 
+        if self.plan_logging {
+            log_query_plan(&request, &plan, &response, "executed");
+        }
+
         self.metrics.record_query(
             start.elapsed().as_micros() as u64,
             response
@@ -211,20 +370,53 @@ impl super::QueryEngine {
             self.insert_semantic_cache(cache_key, &request.query, response.clone())
                 .await;
         }
+        if let Some(key) = exact_key {
+            self.insert_exact_cache(key, response.clone()).await;
+        }
 
         Ok(response)
     }
 
+    /// Exact-cache key for `request`, or `None` when no exact cache is
+    /// configured or the request targets a snapshot that has no content id
+    /// (a `wal-lsn-N` pin or `time_travel`). Unpinned requests are keyed on
+    /// the repository's local state id, which is free to read, so only
+    /// requests pinned to a content id share entries across replicas.
+    fn exact_cache_key(
+        &self,
+        request: &QueryRequest,
+        model_id: &str,
+        plan: &QueryPlan,
+        resolved_snapshot: &ResolvedSnapshot,
+    ) -> Option<ExactCacheKey> {
+        if self.exact_cache.is_none() || request.time_travel.is_some() {
+            return None;
+        }
+        let snapshot_key = match request.snapshot_id.as_deref() {
+            Some(snapshot_id) if snapshot_id.starts_with(CONTENT_SNAPSHOT_ID_PREFIX) => {
+                snapshot_id.to_string()
+            }
+            Some(_) => return None,
+            None => self.repo.local_state_id(),
+        };
+        let mut key = ExactCacheKey::from_request(
+            request,
+            model_id,
+            &snapshot_key,
+            plan.effective_search_mode,
+        );
+        key.request.community_generation = resolved_snapshot.community_generation;
+        Some(key)
+    }
+
     async fn resolve_snapshot(
         &self,
         request: &QueryRequest,
     ) -> Result<ResolvedSnapshot, QueryError> {
+        // One summary set per query: a concurrent swap only affects later queries.
+        let (community_generation, community_summaries) = self.community_summaries.load_versioned();
         if let Some(snapshot_id) = request.snapshot_id.clone() {
-            let snapshot_lsn = parse_wal_snapshot_lsn(&snapshot_id).ok_or_else(|| {
-                QueryError::InvalidQuery(format!(
-                    "snapshot_id must be wal-lsn-<lsn>: {snapshot_id}"
-                ))
-            })?;
+            let snapshot_lsn = self.resolve_snapshot_lsn(&snapshot_id)?;
             let snapshot_view = self.load_snapshot_view(&snapshot_id).await?;
             return Ok(ResolvedSnapshot {
                 snapshot_id,
@@ -232,6 +424,8 @@ impl super::QueryEngine {
                 snapshot_view: Some(snapshot_view),
                 time_travel: None,
                 requires_versioned_summaries: true,
+                community_summaries,
+                community_generation,
             });
         }
 
@@ -247,11 +441,7 @@ impl super::QueryEngine {
                     }
                     other => QueryError::Repository(other),
                 })?;
-            let snapshot_lsn = parse_wal_snapshot_lsn(&snapshot_id).ok_or_else(|| {
-                QueryError::InvalidQuery(format!(
-                    "snapshot_id must be wal-lsn-<lsn>: {snapshot_id}"
-                ))
-            })?;
+            let snapshot_lsn = self.resolve_snapshot_lsn(&snapshot_id)?;
             let snapshot_view = self.load_snapshot_view(&snapshot_id).await?;
             return Ok(ResolvedSnapshot {
                 snapshot_id,
@@ -259,44 +449,56 @@ impl super::QueryEngine {
                 snapshot_view: Some(snapshot_view),
                 time_travel: Some(time_travel.to_string()),
                 requires_versioned_summaries: true,
+                community_summaries,
+                community_generation,
             });
         }
 
         let snapshot_id = self.repo.current_snapshot_id().await;
-        let snapshot_lsn = parse_wal_snapshot_lsn(&snapshot_id).ok_or_else(|| {
-            QueryError::InvalidQuery(format!("snapshot_id must be wal-lsn-<lsn>: {snapshot_id}"))
-        })?;
+        let snapshot_lsn = self.resolve_snapshot_lsn(&snapshot_id)?;
         Ok(ResolvedSnapshot {
             snapshot_id,
             snapshot_lsn,
             snapshot_view: None,
             time_travel: None,
             requires_versioned_summaries: false,
+            community_summaries,
+            community_generation,
         })
     }
 
+    fn resolve_snapshot_lsn(&self, snapshot_id: &str) -> Result<u64, QueryError> {
+        self.repo
+            .resolve_snapshot_id(snapshot_id)
+            .map(|resolved| resolved.lsn())
+            .map_err(|_| invalid_snapshot_id(snapshot_id))
+    }
+
     async fn load_snapshot_view(&self, snapshot_id: &str) -> Result<Arc<SnapshotView>, QueryError> {
         match self.repo.load_snapshot_view(snapshot_id).await {
             Ok(view) => Ok(Arc::new(view)),
             Err(RepoError::SnapshotNotFound(_)) => {
                 Err(QueryError::NotFound(format!("snapshot_id `{snapshot_id}`")))
             }
-            Err(RepoError::InvalidSnapshotId(_)) => Err(QueryError::InvalidQuery(format!(
-                "snapshot_id must be wal-lsn-<lsn>: {snapshot_id}"
-            ))),
+            Err(RepoError::InvalidSnapshotId(_)) => Err(invalid_snapshot_id(snapshot_id)),
             Err(err) => Err(QueryError::Repository(err)),
         }
     }
 
+    /// The pinned snapshot when one was resolved, otherwise the live repository.
+    pub(super) fn read_source<'a>(&'a self, resolved: &'a ResolvedSnapshot) -> &'a dyn ReadSource {
+        match resolved.snapshot_view.as_deref() {
+            Some(view) => view,
+            None => self.repo.as_ref(),
+        }
+    }
+
     pub(super) async fn list_node_ids_from_source(
         &self,
-        snapshot_view: Option<&SnapshotView>,
+        source: &dyn ReadSource,
         session: Option<&SessionGraph>,
     ) -> Vec<u64> {
-        let mut out = match snapshot_view {
-            Some(view) => view.list_node_ids(),
-            None => self.repo.list_node_ids().await,
-        };
+        let mut out = source.list_node_ids().await;
         if let Some(session) = session {
             out.extend(session.nodes.keys().copied());
             out.sort_unstable();
@@ -305,10 +507,53 @@ impl super::QueryEngine {
         out
     }
 
+    /// Per-dimension cosine contributions of `node_id` against the query
+    /// embedding that vector search used.
+    async fn explain_vector_hit(
+        &self,
+        query: &str,
+        query_embedder: &QueryEmbedder,
+        node_id: u64,
+        source: &dyn ReadSource,
+        session: Option<&SessionGraph>,
+    ) -> Option<VectorExplanation> {
+        let node = self
+            .get_nodes_by_ids_from_source(&[node_id], source, session)
+            .await
+            .into_iter()
+            .next()?;
+        let query_embedding = query_embedder.embed(query, node.embedding.len());
+        let contributions = cosine_contributions(&query_embedding, &node.embedding)?;
+
+        let mut dimensions: Vec<DimensionContribution> = contributions
+            .iter()
+            .enumerate()
+            .map(|(dimension, contribution)| DimensionContribution {
+                dimension,
+                query_value: query_embedding[dimension],
+                node_value: node.embedding[dimension],
+                contribution: *contribution,
+            })
+            .collect();
+        dimensions.sort_by(|a, b| {
+            b.contribution
+                .abs()
+                .total_cmp(&a.contribution.abs())
+                .then(a.dimension.cmp(&b.dimension))
+        });
+        dimensions.truncate(MAX_EXPLAINED_DIMENSIONS);
+
+        Some(VectorExplanation {
+            node_id,
+            similarity: contributions.iter().sum(),
+            dimensions,
+        })
+    }
+
     pub(super) async fn get_nodes_by_ids_from_source(
         &self,
         ids: &[u64],
-        snapshot_view: Option<&SnapshotView>,
+        source: &dyn ReadSource,
         session: Option<&SessionGraph>,
     ) -> Vec<Node> {
         let mut results = Vec::with_capacity(ids.len());
@@ -327,27 +572,84 @@ impl super::QueryEngine {
         }
 
         if !remaining_ids.is_empty() {
-            let mut source_results = match snapshot_view {
-                Some(view) => view.get_nodes_by_ids(&remaining_ids),
-                None => self.repo.get_nodes_by_ids(&remaining_ids).await,
-            };
+            let mut source_results = source.get_nodes_by_ids(&remaining_ids).await;
             results.append(&mut source_results);
         }
         results
     }
+}
 
-    pub(super) async fn get_edge_metadata_bulk_from_source(
-        &self,
-        keys: &[(u64, u64, String)],
-        snapshot_view: Option<&SnapshotView>,
-    ) -> HashMap<(u64, u64, String), HashMap<String, String>> {
-        match snapshot_view {
-            Some(view) => view.get_edge_metadata_bulk(keys),
-            None => self.repo.get_edge_metadata_bulk(keys).await,
+/// Debug-level summary of a query's plan; `outcome` tells executed queries
+/// apart from dry runs and cache hits. Skipped (including the fingerprint
+/// digest) unless a subscriber enables `debug` for the target.
+fn log_query_plan(
+    request: &QueryRequest,
+    plan: &QueryPlan,
+    response: &QueryResponse,
+    outcome: &'static str,
+) {
+    if !tracing::enabled!(target: super::QUERY_PLAN_LOG_TARGET, tracing::Level::DEBUG) {
+        return;
+    }
+    let fingerprint = query_request_fingerprint(request);
+    tracing::debug!(
+        target: super::QUERY_PLAN_LOG_TARGET,
+        fingerprint = fingerprint.as_deref().unwrap_or_default(),
+        outcome,
+        mode = ?request.mode,
+        search_mode = ?plan.effective_search_mode,
+        vector_top_k = plan.vector_top_k,
+        expansion_depth = plan.expansion_depth,
+        depth_capped = plan.depth_capped,
+        max_neighbors_per_node = ?plan.max_neighbors_per_node,
+        max_candidates = ?plan.max_candidates,
+        anchor_count = response.explain.anchors.len(),
+        evidence_count = response.evidence.nodes.len(),
+        groundedness = response.groundedness,
+        "query plan"
+    );
+}
+
+/// Mode-specific step lists assume vector anchoring; report explicit anchors instead.
+fn report_anchor_step(request: &QueryRequest, steps: &mut [&'static str]) {
+    if request.anchor_ids.is_empty() {
+        return;
+    }
+    for step in steps {
+        if *step == "vector_search" {
+            *step = EXPLICIT_ANCHORS_STEP;
         }
     }
 }
 
+fn report_depth_cap(depth_capped: bool, steps: &mut Vec<&'static str>) {
+    if depth_capped && !steps.contains(&DEPTH_CAPPED_STEP) {
+        steps.push(DEPTH_CAPPED_STEP);
+    }
+}
+
+fn report_query_projection(query_embedder: &QueryEmbedder, steps: &mut Vec<&'static str>) {
+    if query_embedder.projection.is_some() {
+        steps.push(crate::QUERY_EMBEDDING_PROJECTED_STEP);
+    }
+}
+
+fn count_results(
+    nodes: &[EvidenceNode],
+    edges: &[EvidenceEdge],
+    exclusions: &[ExclusionReason],
+) -> QueryCounts {
+    let mut excluded = BTreeMap::new();
+    for exclusion in exclusions {
+        *excluded.entry(exclusion.reason.clone()).or_insert(0) += 1;
+    }
+    QueryCounts {
+        matched: nodes.len(),
+        edges: edges.len(),
+        excluded,
+    }
+}
+
 fn parse_time_travel_as_of_unix_ms(input: &str) -> Result<i64, QueryError> {
     if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
         return date
@@ -366,3 +668,15 @@ fn parse_time_travel_as_of_unix_ms(input: &str) -> Result<i64, QueryError> {
             QueryError::InvalidQuery("time_travel must be YYYY-MM-DD or RFC3339 format".to_string())
         })
 }
+
+fn invalid_snapshot_id(snapshot_id: &str) -> QueryError {
+    QueryError::InvalidQuery(format!("unrecognized snapshot_id: {snapshot_id}"))
+}
+
+fn debug_query_tokens(tokenizer: &Tokenizer, request: &QueryRequest) -> Option<Vec<String>> {
+    request.debug_tokens.then(|| {
+        let mut tokens: Vec<String> = tokenizer.tokenize(&request.query).into_iter().collect();
+        tokens.sort();
+        tokens
+    })
+}
@@ -1,8 +1,16 @@
-use super::{Citation, EvidenceNode, ExclusionReason, ExpansionPath, InternalEdge, RankedNode};
-use alayasiki_core::audit::{AuditEvent, AuditOperation, AuditOutcome};
+use super::{
+    Citation, EvidenceNode, ExclusionCode, ExclusionReason, ExpansionPath, InternalEdge,
+    LexicalFieldWeights, RankedNode, ResolvedSnapshot,
+};
+use crate::dsl::parse_timestamp;
+use crate::tokenizer::Tokenizer;
+use alayasiki_core::audit::{
+    request_fingerprint, AuditEvent, AuditOperation, AuditOutcome, REQUEST_FINGERPRINT_KEY,
+};
 use alayasiki_core::model::Node;
-use chrono::NaiveDate;
+use chrono::{DateTime, Utc};
 use std::collections::{BTreeSet, HashMap, HashSet};
+use storage::index::TraversalEdge;
 
 pub(super) fn node_belongs_to_tenant(node: &Node, tenant_scope: &str) -> bool {
     node.metadata
@@ -11,103 +19,180 @@ pub(super) fn node_belongs_to_tenant(node: &Node, tenant_scope: &str) -> bool {
         .unwrap_or(false)
 }
 
-pub(super) fn node_lexical_text(node: &Node) -> String {
-    format!(
-        "{} {}",
-        node.data,
+/// Tokens of `data` and the metadata values, each mapped to the largest field
+/// weight it appears under. Fields with a non-positive weight are skipped.
+pub(super) fn node_lexical_tokens(
+    node: &Node,
+    tokenizer: &Tokenizer,
+    weights: &LexicalFieldWeights,
+) -> HashMap<String, f32> {
+    let mut tokens: HashMap<String, f32> = HashMap::new();
+    let fields = std::iter::once((node.data.as_str(), weights.data)).chain(
         node.metadata
-            .values()
-            .cloned()
-            .collect::<Vec<_>>()
-            .join(" ")
-    )
-}
-
-pub(super) fn tokenize(text: &str) -> HashSet<String> {
-    let mut out = HashSet::new();
-    let mut buffer = String::new();
-
-    for ch in text.chars().flat_map(|ch| ch.to_lowercase()) {
-        if ch.is_alphanumeric() || ch == '_' {
-            buffer.push(ch);
-        } else if !buffer.is_empty() {
-            out.insert(buffer.clone());
-            buffer.clear();
+            .iter()
+            .map(|(key, value)| (value.as_str(), weights.metadata_key_weight(key))),
+    );
+    for (text, weight) in fields {
+        if weight <= 0.0 {
+            continue;
+        }
+        for token in tokenizer.tokenize(text) {
+            let entry = tokens.entry(token).or_insert(weight);
+            *entry = entry.max(weight);
         }
     }
+    tokens
+}
 
-    if !buffer.is_empty() {
-        out.insert(buffer);
+/// Weighted token overlap: matched node-token weight over the larger of the
+/// query size and the node's total token weight. With unit weights this is
+/// `|a ∩ b| / max(|a|, |b|)`.
+pub(super) fn lexical_similarity(query: &HashSet<String>, node: &HashMap<String, f32>) -> f32 {
+    if query.is_empty() || node.is_empty() {
+        return 0.0;
     }
 
-    let unicode_tokens: Vec<String> = out
-        .iter()
-        .filter(|token| !token.is_ascii())
-        .cloned()
-        .collect();
-    for token in unicode_tokens {
-        for ngram in char_ngrams(&token, super::UNICODE_NGRAM_SIZE) {
-            out.insert(ngram);
-        }
-    }
+    let matched: f32 = query.iter().filter_map(|token| node.get(token)).sum();
+    let total: f32 = node.values().sum();
+    matched / (query.len() as f32).max(total)
+}
 
-    out
+/// Relation allow-list (`filters.relation_type` + `traversal.relation_types`)
+/// and deny-list (`traversal.exclude_relation_types`) of one request.
+#[derive(Debug, Default)]
+pub(super) struct RelationFilter<'a> {
+    allowed: HashSet<&'a str>,
+    excluded: HashSet<&'a str>,
 }
 
-fn char_ngrams(token: &str, n: usize) -> Vec<String> {
-    let chars: Vec<char> = token.chars().collect();
-    if chars.is_empty() || n == 0 {
-        return Vec::new();
-    }
-    if chars.len() <= n {
-        return vec![token.to_string()];
+impl RelationFilter<'_> {
+    pub(super) fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.excluded.is_empty()
     }
 
-    chars
-        .windows(n)
-        .map(|window| window.iter().collect::<String>())
-        .collect()
+    /// Why `relation` is not followed, or `None` when it is. Exclusion is
+    /// checked first, so it wins over the allow-list.
+    pub(super) fn rejection(&self, relation: &str) -> Option<ExclusionCode> {
+        if self.excluded.contains(relation) {
+            Some(ExclusionCode::RelationExcluded)
+        } else if !self.allowed.is_empty() && !self.allowed.contains(relation) {
+            Some(ExclusionCode::RelationFiltered)
+        } else {
+            None
+        }
+    }
 }
 
-pub(super) fn lexical_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
-    if a.is_empty() || b.is_empty() {
-        return 0.0;
+pub(super) fn collect_relation_filter(request: &super::QueryRequest) -> RelationFilter<'_> {
+    RelationFilter {
+        allowed: request
+            .filters
+            .relation_type
+            .iter()
+            .map(|value| value.as_str())
+            .chain(
+                request
+                    .traversal
+                    .relation_types
+                    .iter()
+                    .map(|value| value.as_str()),
+            )
+            .collect(),
+        excluded: request
+            .traversal
+            .exclude_relation_types
+            .iter()
+            .map(|value| value.as_str())
+            .collect(),
     }
+}
 
-    let intersection = a.intersection(b).count() as f32;
-    let denominator = a.len().max(b.len()) as f32;
-    intersection / denominator
+pub(super) fn relation_is_allowed(relation: &str, relation_filter: &RelationFilter<'_>) -> bool {
+    relation_filter.rejection(relation).is_none()
 }
 
-pub(super) fn collect_relation_filter(request: &super::QueryRequest) -> HashSet<&str> {
-    request
-        .filters
-        .relation_type
-        .iter()
-        .map(|value| value.as_str())
-        .chain(
-            request
-                .traversal
-                .relation_types
-                .iter()
-                .map(|value| value.as_str()),
-        )
-        .collect()
+/// Factor applied to `relation` edges from `traversal.relation_weights`.
+pub(super) fn relation_factor(relation: &str, relation_weights: &HashMap<String, f32>) -> f32 {
+    relation_weights.get(relation).copied().unwrap_or(1.0)
 }
 
-pub(super) fn relation_is_allowed(relation: &str, relation_filter: &HashSet<&str>) -> bool {
-    relation_filter.is_empty() || relation_filter.contains(relation)
+/// Applies the relation filter and zero relation weights to `node_id`'s
+/// neighbors, then keeps at most `max_neighbors` of the survivors, highest
+/// weighted edge first (ties by target id). Dropped neighbors are recorded as
+/// exclusions.
+pub(super) fn select_neighbors(
+    node_id: u64,
+    neighbors: Vec<TraversalEdge>,
+    relation_filter: &RelationFilter<'_>,
+    relation_weights: &HashMap<String, f32>,
+    max_neighbors: Option<usize>,
+    exclusions: &mut Vec<ExclusionReason>,
+) -> Vec<TraversalEdge> {
+    let mut allowed = Vec::with_capacity(neighbors.len());
+    for edge in neighbors {
+        if let Some(code) = relation_filter.rejection(edge.relation.as_str()) {
+            exclusions.push(ExclusionReason::with_detail(
+                Some(edge.neighbor),
+                code,
+                edge.relation.clone(),
+            ));
+        } else if relation_factor(&edge.relation, relation_weights) <= 0.0 {
+            exclusions.push(ExclusionReason::with_detail(
+                Some(edge.neighbor),
+                ExclusionCode::RelationWeightZero,
+                edge.relation.clone(),
+            ));
+        } else {
+            allowed.push(edge);
+        }
+    }
+
+    let Some(max_neighbors) = max_neighbors else {
+        return allowed;
+    };
+    if allowed.len() <= max_neighbors {
+        return allowed;
+    }
+
+    let weighted =
+        |edge: &TraversalEdge| edge.weight * relation_factor(&edge.relation, relation_weights);
+    allowed.sort_by(|a, b| {
+        weighted(b)
+            .partial_cmp(&weighted(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.neighbor.cmp(&b.neighbor))
+    });
+    for edge in allowed.drain(max_neighbors..) {
+        exclusions.push(ExclusionReason::with_detail(
+            Some(edge.neighbor),
+            ExclusionCode::FanoutCapped,
+            node_id.to_string(),
+        ));
+    }
+    allowed
 }
 
+/// Extra nodes tolerated beyond `expansion_depth + 1` before a parent chain is
+/// treated as malformed.
+const PATH_LENGTH_MARGIN: usize = 2;
+
+/// Walks `parents` from `target_id` back to `anchor_id`. Returns `None` when the
+/// chain breaks or runs longer than `expansion_depth` allows (e.g. a cycle), so
+/// a malformed parent map cannot hang the query.
 pub(super) fn reconstruct_path(
     anchor_id: u64,
     target_id: u64,
     parents: &HashMap<u64, u64>,
+    expansion_depth: u8,
 ) -> Option<Vec<u64>> {
+    let max_len = expansion_depth as usize + 1 + PATH_LENGTH_MARGIN;
     let mut path = vec![target_id];
     let mut current = target_id;
 
     while current != anchor_id {
+        if path.len() >= max_len {
+            return None;
+        }
         let parent = parents.get(&current)?;
         current = *parent;
         path.push(current);
@@ -117,39 +202,80 @@ pub(super) fn reconstruct_path(
     Some(path)
 }
 
+/// Parsed `filters.time_range`: the UTC bounds and the metadata key they
+/// apply to. A bare-date bound means midnight UTC; a missing bound leaves
+/// that side open.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TimeWindow<'a> {
+    pub field: &'a str,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub inclusive_start: bool,
+    pub inclusive_end: bool,
+}
+
+impl TimeWindow<'_> {
+    fn contains(&self, instant: DateTime<Utc>) -> bool {
+        let after_start = match self.from {
+            Some(from) if self.inclusive_start => instant >= from,
+            Some(from) => instant > from,
+            None => true,
+        };
+        let before_end = match self.to {
+            Some(to) if self.inclusive_end => instant <= to,
+            Some(to) => instant < to,
+            None => true,
+        };
+        after_start && before_end
+    }
+}
+
 pub(super) fn parse_time_range(
     request: &super::QueryRequest,
-) -> Result<Option<(NaiveDate, NaiveDate)>, super::QueryError> {
+) -> Result<Option<TimeWindow<'_>>, super::QueryError> {
     let Some(range) = &request.filters.time_range else {
         return Ok(None);
     };
 
-    let from = NaiveDate::parse_from_str(&range.from, "%Y-%m-%d").map_err(|_| {
-        super::QueryError::InvalidQuery("filters.time_range.from/to must be YYYY-MM-DD".to_string())
-    })?;
-    let to = NaiveDate::parse_from_str(&range.to, "%Y-%m-%d").map_err(|_| {
-        super::QueryError::InvalidQuery("filters.time_range.from/to must be YYYY-MM-DD".to_string())
-    })?;
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|value| {
+                parse_timestamp(value).ok_or_else(|| {
+                    super::QueryError::InvalidQuery(
+                        "filters.time_range.from/to must be YYYY-MM-DD or RFC3339".to_string(),
+                    )
+                })
+            })
+            .transpose()
+    };
 
-    Ok(Some((from, to)))
+    Ok(Some(TimeWindow {
+        field: &range.time_field,
+        from: parse(&range.from)?,
+        to: parse(&range.to)?,
+        inclusive_start: range.inclusive_start,
+        inclusive_end: range.inclusive_end,
+    }))
 }
 
 pub(super) fn node_filter_exclusion_reason(
     node: &Node,
     entity_filter: &HashSet<&str>,
-    time_range: Option<(NaiveDate, NaiveDate)>,
+    metadata_filter: &HashMap<String, String>,
+    time_range: Option<TimeWindow<'_>>,
     retention_cutoff_unix: Option<u64>,
     tenant_scope: Option<&str>,
-) -> Option<String> {
+) -> Option<ExclusionCode> {
     if let Some(tenant_scope) = tenant_scope {
         if !node_belongs_to_tenant(node, tenant_scope) {
-            return Some("tenant_filtered".to_string());
+            return Some(ExclusionCode::TenantFiltered);
         }
     }
 
     if let Some(now_unix) = retention_cutoff_unix {
         if node_is_retention_expired(node, now_unix) {
-            return Some("retention_expired".to_string());
+            return Some(ExclusionCode::RetentionExpired);
         }
     }
 
@@ -159,18 +285,24 @@ pub(super) fn node_filter_exclusion_reason(
             .map(|value| !entity_filter.contains(value))
             .unwrap_or(true)
         {
-            return Some("entity_type_filtered".to_string());
+            return Some(ExclusionCode::EntityTypeFiltered);
         }
     }
 
-    if let Some((from, to)) = time_range {
-        let timestamp = node
+    if metadata_filter.iter().any(|(path, expected)| {
+        metadata_path_value(&node.metadata, path).as_deref() != Some(expected.as_str())
+    }) {
+        return Some(ExclusionCode::MetadataFiltered);
+    }
+
+    if let Some(window) = time_range {
+        let date = node
             .metadata
-            .get("timestamp")
-            .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok());
-        match timestamp {
-            Some(value) if value >= from && value <= to => {}
-            _ => return Some("time_range_filtered".to_string()),
+            .get(window.field)
+            .and_then(|value| parse_timestamp(value));
+        match date {
+            Some(value) if window.contains(value) => {}
+            _ => return Some(ExclusionCode::TimeRangeFiltered),
         }
     }
 
@@ -180,13 +312,15 @@ pub(super) fn node_filter_exclusion_reason(
 pub(super) fn node_passes_filters(
     node: &Node,
     entity_filter: &HashSet<&str>,
-    time_range: Option<(NaiveDate, NaiveDate)>,
+    metadata_filter: &HashMap<String, String>,
+    time_range: Option<TimeWindow<'_>>,
     retention_cutoff_unix: Option<u64>,
     tenant_scope: Option<&str>,
 ) -> bool {
     node_filter_exclusion_reason(
         node,
         entity_filter,
+        metadata_filter,
         time_range,
         retention_cutoff_unix,
         tenant_scope,
@@ -194,6 +328,37 @@ pub(super) fn node_passes_filters(
     .is_none()
 }
 
+/// Value at a dotted metadata `path`. A flat key equal to the whole path wins;
+/// otherwise the longest matching key prefix whose value parses as JSON is
+/// descended by the remaining segments (array elements by index). Strings
+/// resolve to their contents, numbers and booleans to their JSON text;
+/// `null`, objects and arrays resolve to nothing.
+fn metadata_path_value(metadata: &HashMap<String, String>, path: &str) -> Option<String> {
+    if let Some(value) = metadata.get(path) {
+        return Some(value.clone());
+    }
+    path.match_indices('.').rev().find_map(|(split, _)| {
+        let raw = metadata.get(&path[..split])?;
+        let mut value = serde_json::from_str::<serde_json::Value>(raw).ok()?;
+        for segment in path[split + 1..].split('.') {
+            value = match value {
+                serde_json::Value::Object(mut object) => object.remove(segment)?,
+                serde_json::Value::Array(mut items) => {
+                    let index = segment.parse::<usize>().ok()?;
+                    (index < items.len()).then(|| items.swap_remove(index))?
+                }
+                _ => return None,
+            };
+        }
+        match value {
+            serde_json::Value::String(text) => Some(text),
+            serde_json::Value::Number(number) => Some(number.to_string()),
+            serde_json::Value::Bool(flag) => Some(flag.to_string()),
+            _ => None,
+        }
+    })
+}
+
 fn node_is_retention_expired(node: &Node, now_unix: u64) -> bool {
     node.metadata
         .get("retention_until_unix")
@@ -223,12 +388,23 @@ pub(super) fn effective_query_model_id(request: &super::QueryRequest) -> String
         .unwrap_or_else(|| super::DEFAULT_EMBEDDING_MODEL_ID.to_string())
 }
 
+/// Digest of the serialized request, so audit sampling treats repeats alike.
+/// The request goes through `serde_json::Value`, whose maps are sorted, so
+/// `HashMap` fields such as relation weights and metadata filters hash the
+/// same whatever their iteration order.
+pub(super) fn query_request_fingerprint(request: &super::QueryRequest) -> Option<String> {
+    serde_json::to_value(request)
+        .ok()
+        .map(|canonical| request_fingerprint(&canonical.to_string()))
+}
+
 pub(super) fn build_query_audit_event(
     outcome: AuditOutcome,
     model_id: &str,
     actor: Option<String>,
     tenant: Option<String>,
     snapshot_id: Option<String>,
+    fingerprint: Option<&str>,
     error: Option<String>,
 ) -> AuditEvent {
     let mut event = AuditEvent::new(AuditOperation::Query, outcome);
@@ -236,21 +412,37 @@ pub(super) fn build_query_audit_event(
     event.actor = actor;
     event.tenant = tenant;
     event.snapshot_id = snapshot_id;
+    if let Some(fingerprint) = fingerprint {
+        event
+            .metadata
+            .insert(REQUEST_FINGERPRINT_KEY.to_string(), fingerprint.to_string());
+    }
     if let Some(error) = error {
         event.metadata.insert("error".to_string(), error);
     }
     event
 }
 
-pub(super) fn generate_answer(query: &str, nodes: &[EvidenceNode]) -> String {
+/// Deterministic answer from the first `evidence_count` nodes (all of them
+/// when fewer are available).
+pub(super) fn generate_answer(
+    query: &str,
+    nodes: &[EvidenceNode],
+    evidence_count: usize,
+) -> String {
     if nodes.is_empty() {
         return format!("No evidence found for query: {query}");
     }
 
+    // Each snippet drawn from a node with a source is followed by a
+    // `[source]` marker matching that source's entry in `citations`.
     let snippets = nodes
         .iter()
-        .take(3)
-        .map(|node| node.data.as_str())
+        .take(evidence_count)
+        .map(|node| match &node.provenance.source {
+            Some(source) => format!("{} [{}]", node.data, source),
+            None => node.data.clone(),
+        })
         .collect::<Vec<_>>()
         .join(" | ");
 
@@ -273,10 +465,9 @@ pub(super) fn build_citations(nodes: &[RankedNode]) -> Vec<Citation> {
             continue;
         }
 
-        let end = node.data.len();
         out.push(Citation {
             source: source.clone(),
-            span: [0, end],
+            span: node.doc_span.unwrap_or([0, node.data.len()]),
             node_id: node.id,
             confidence: node.confidence,
         });
@@ -285,6 +476,32 @@ pub(super) fn build_citations(nodes: &[RankedNode]) -> Vec<Citation> {
     out
 }
 
+/// `(level, community_id)` of the lowest-level summary containing each of
+/// `node_ids`, among the summaries global search would consider for the
+/// snapshot. Summaries without membership lists match on their top nodes.
+pub(super) fn community_assignments(
+    snapshot: &ResolvedSnapshot,
+    node_ids: &HashSet<u64>,
+) -> HashMap<u64, (usize, usize)> {
+    let mut assignments: HashMap<u64, (usize, usize)> = HashMap::new();
+    for summary in snapshot.community_summaries.iter().filter(|summary| {
+        summary.is_visible_at_lsn(snapshot.snapshot_lsn)
+            && (!snapshot.requires_versioned_summaries || summary.snapshot_lsn_range.is_some())
+    }) {
+        for node_id in summary.node_ids.iter().chain(&summary.top_nodes) {
+            if !node_ids.contains(node_id) {
+                continue;
+            }
+            let candidate = (summary.level, summary.community_id);
+            assignments
+                .entry(*node_id)
+                .and_modify(|current| *current = (*current).min(candidate))
+                .or_insert(candidate);
+        }
+    }
+    assignments
+}
+
 pub(super) fn dedup_edges(edges: Vec<InternalEdge>) -> Vec<InternalEdge> {
     let mut map: HashMap<(u64, u64, String), InternalEdge> = HashMap::new();
     for edge in edges {
@@ -308,6 +525,7 @@ pub(super) fn dedup_paths(mut paths: Vec<ExpansionPath>) -> Vec<ExpansionPath> {
         a.anchor_id
             .cmp(&b.anchor_id)
             .then(a.target_id.cmp(&b.target_id))
+            .then_with(|| a.path.cmp(&b.path))
     });
     paths
 }
@@ -318,3 +536,41 @@ pub(super) fn dedup_exclusions(mut exclusions: Vec<ExclusionReason>) -> Vec<Excl
     exclusions.sort_by(|a, b| a.node_id.cmp(&b.node_id).then(a.reason.cmp(&b.reason)));
     exclusions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconstruct_path_follows_parents_to_anchor() {
+        let parents = HashMap::from([(3, 2), (2, 1)]);
+        assert_eq!(reconstruct_path(1, 3, &parents, 2), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn reconstruct_path_terminates_on_cyclic_parents() {
+        // 4 -> 3 -> 2 -> 3 -> ... never reaches anchor 1.
+        let parents = HashMap::from([(4, 3), (3, 2), (2, 3)]);
+        assert_eq!(reconstruct_path(1, 4, &parents, 3), None);
+    }
+
+    #[test]
+    fn request_fingerprint_ignores_map_iteration_order() {
+        let request = |relations: Vec<String>| {
+            let weights: Vec<String> = relations
+                .iter()
+                .map(|relation| format!(r#""{relation}": 0.5"#))
+                .collect();
+            super::super::QueryRequest::parse_json(&format!(
+                r#"{{"query": "q", "traversal": {{"relation_weights": {{{}}}}}}}"#,
+                weights.join(", ")
+            ))
+            .unwrap()
+        };
+        let relations: Vec<String> = (0..32).map(|index| format!("rel_{index}")).collect();
+        let forward = query_request_fingerprint(&request(relations.clone()));
+        let reversed = query_request_fingerprint(&request(relations.into_iter().rev().collect()));
+        assert!(forward.is_some());
+        assert_eq!(forward, reversed);
+    }
+}
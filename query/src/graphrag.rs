@@ -8,13 +8,19 @@
 //!
 //! Also provides improved groundedness scoring.
 
+use alayasiki_core::embedding::cosine_similarity;
 use std::collections::HashSet;
+use std::sync::Arc;
 use storage::community::CommunitySummary;
 
 /// Maximum number of DRIFT feedback iterations to prevent infinite loops.
 pub const DRIFT_MAX_ITERATIONS: usize = 4;
 /// Minimum evidence count before DRIFT considers stopping early.
 pub const DRIFT_EVIDENCE_THRESHOLD: usize = 3;
+/// Community summaries synthesized by the global reduce phase by default.
+pub const DEFAULT_GLOBAL_MAX_COMMUNITIES: usize = 5;
+/// Upper bound accepted for `global_max_communities`.
+pub const MAX_GLOBAL_COMMUNITIES: usize = 100;
 
 // ---------------------------------------------------------------------------
 // Groundedness
@@ -77,15 +83,78 @@ pub fn score_community_summary(query: &str, summary: &CommunitySummary) -> f32 {
     intersection / denominator
 }
 
-/// Map phase: Score all community summaries and rank them.
+/// Relevance of a community summary to a query, used by the global map phase.
+/// Scores are expected in [0.0, 1.0]; higher ranks first.
+pub trait SummaryScorer: Send + Sync {
+    fn score(&self, query: &str, summary: &CommunitySummary) -> f32;
+
+    /// Score every summary for one query. Override when per-query work (such
+    /// as embedding the query) should happen once rather than per summary.
+    fn score_all(&self, query: &str, summaries: &[CommunitySummary]) -> Vec<f32> {
+        summaries
+            .iter()
+            .map(|summary| self.score(query, summary))
+            .collect()
+    }
+}
+
+/// Default scorer: lexical overlap via [`score_community_summary`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenOverlapScorer;
+
+impl SummaryScorer for TokenOverlapScorer {
+    fn score(&self, query: &str, summary: &CommunitySummary) -> f32 {
+        score_community_summary(query, summary)
+    }
+}
+
+/// Text-to-vector function used by [`EmbeddingSummaryScorer`].
+pub type SummaryEmbedFn = Arc<dyn Fn(&str) -> Vec<f32> + Send + Sync>;
+
+/// Scores summaries by cosine similarity between the query embedding and the
+/// summary embedding, clamped to [0.0, 1.0]. Dimension mismatches score 0.
+#[derive(Clone)]
+pub struct EmbeddingSummaryScorer {
+    embed: SummaryEmbedFn,
+}
+
+impl EmbeddingSummaryScorer {
+    pub fn new(embed: SummaryEmbedFn) -> Self {
+        Self { embed }
+    }
+
+    fn similarity(&self, query_embedding: &[f32], summary: &CommunitySummary) -> f32 {
+        let summary_embedding = (self.embed)(&summary.summary);
+        cosine_similarity(query_embedding, &summary_embedding)
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0)
+    }
+}
+
+impl SummaryScorer for EmbeddingSummaryScorer {
+    fn score(&self, query: &str, summary: &CommunitySummary) -> f32 {
+        self.similarity(&(self.embed)(query), summary)
+    }
+
+    fn score_all(&self, query: &str, summaries: &[CommunitySummary]) -> Vec<f32> {
+        let query_embedding = (self.embed)(query);
+        summaries
+            .iter()
+            .map(|summary| self.similarity(&query_embedding, summary))
+            .collect()
+    }
+}
+
+/// Map phase: Score all community summaries with `scorer` and rank them.
 /// Returns (community_summary, score) sorted by score descending.
 pub fn map_community_summaries<'a>(
     query: &str,
     summaries: &'a [CommunitySummary],
+    scorer: &dyn SummaryScorer,
 ) -> Vec<(&'a CommunitySummary, f32)> {
     let mut scored: Vec<(&CommunitySummary, f32)> = summaries
         .iter()
-        .map(|s| (s, score_community_summary(query, s)))
+        .zip(scorer.score_all(query, summaries))
         .collect();
     scored.sort_by(|a, b| {
         b.1.partial_cmp(&a.1)
@@ -96,6 +165,8 @@ pub fn map_community_summaries<'a>(
 }
 
 /// Reduce phase: Combine top community summaries into a synthesized answer.
+/// Pass the same `max_summaries` to [`collect_global_node_ids`] so the node
+/// set matches the communities the answer was built from.
 pub fn reduce_community_summaries(
     query: &str,
     ranked_summaries: &[(&CommunitySummary, f32)],
@@ -123,7 +194,7 @@ pub fn reduce_community_summaries(
     )
 }
 
-/// Collect node IDs belonging to top-ranked communities.
+/// Collect node IDs belonging to the top `max_communities` ranked communities.
 pub fn collect_global_node_ids(
     ranked_summaries: &[(&CommunitySummary, f32)],
     max_communities: usize,
@@ -213,8 +284,11 @@ mod tests {
             level: 0,
             community_id: 0,
             top_nodes: vec![1],
+            node_ids: Vec::new(),
             summary: "EV production and battery technology advances".to_string(),
             snapshot_lsn_range: None,
+            redundant_with: None,
+            tenant: None,
         };
         let score = score_community_summary("EV production", &summary);
         assert!(score > 0.0, "matching terms should produce positive score");
@@ -227,19 +301,25 @@ mod tests {
                 level: 0,
                 community_id: 0,
                 top_nodes: vec![1, 2],
+                node_ids: Vec::new(),
                 summary: "EV production and competition among automakers".to_string(),
                 snapshot_lsn_range: None,
+                redundant_with: None,
+                tenant: None,
             },
             CommunitySummary {
                 level: 0,
                 community_id: 1,
                 top_nodes: vec![3, 4],
+                node_ids: Vec::new(),
                 summary: "Government regulation and emission standards".to_string(),
                 snapshot_lsn_range: None,
+                redundant_with: None,
+                tenant: None,
             },
         ];
 
-        let ranked = map_community_summaries("EV production", &summaries);
+        let ranked = map_community_summaries("EV production", &summaries, &TokenOverlapScorer);
         assert_eq!(
             ranked[0].0.community_id, 0,
             "EV community should rank first"
@@ -249,4 +329,47 @@ mod tests {
         assert!(answer.contains("Global synthesis"));
         assert!(answer.contains("Community L0-C0"));
     }
+
+    #[test]
+    fn test_embedding_scorer_ranks_semantic_match_above_lexical_match() {
+        let summary = |community_id: usize, text: &str| CommunitySummary {
+            level: 0,
+            community_id,
+            top_nodes: vec![community_id as u64],
+            node_ids: Vec::new(),
+            summary: text.to_string(),
+            snapshot_lsn_range: None,
+            redundant_with: None,
+            tenant: None,
+        };
+        let summaries = vec![
+            summary(0, "car dealership opening hours"),
+            summary(1, "automobile makers expand battery plants"),
+        ];
+        // Toy concept embedding: [vehicles, manufacturing, retail].
+        let embed: SummaryEmbedFn = Arc::new(|text: &str| {
+            let lower = text.to_lowercase();
+            let has = |words: &[&str]| {
+                if words.iter().any(|word| lower.contains(word)) {
+                    1.0
+                } else {
+                    0.0
+                }
+            };
+            vec![
+                has(&["car", "automobile", "vehicle"]),
+                has(&["production", "makers", "plants", "factory"]),
+                has(&["dealership", "hours", "store"]),
+            ]
+        });
+        let query = "car production";
+
+        let lexical = map_community_summaries(query, &summaries, &TokenOverlapScorer);
+        assert_eq!(lexical[0].0.community_id, 0, "token overlap favors 'car'");
+
+        let semantic =
+            map_community_summaries(query, &summaries, &EmbeddingSummaryScorer::new(embed));
+        assert_eq!(semantic[0].0.community_id, 1);
+        assert!(semantic[0].1 > semantic[1].1);
+    }
 }
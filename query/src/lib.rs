@@ -3,9 +3,23 @@ pub mod engine;
 pub mod graphrag;
 pub mod planner;
 pub mod semantic_cache;
+mod stemmer;
+pub mod tokenizer;
 
-pub use dsl::{QueryMode, QueryRequest, SearchMode};
-pub use engine::{QueryEngine, QueryError, QueryResponse};
+pub use dsl::{QueryLimits, QueryMode, QueryRequest, QueryRequestBuilder, SearchMode};
+pub use engine::{
+    EdgeConfidenceSource, EvidenceField, ExclusionCode, ExclusionReason, LexicalFieldWeights,
+    ProvenanceMapping, QueryCounts, QueryEmbedFn, QueryEngine, QueryError, QueryResponse,
+    ResultStatus, QUERY_PLAN_LOG_TARGET,
+};
 pub use planner::{QueryPlan, QueryPlanner};
+pub use tokenizer::{Tokenizer, TokenizerLanguage};
 
 pub const SEMANTIC_CACHE_HIT_STEP: &str = "semantic_cache_hit";
+pub const DRY_RUN_STEP: &str = "dry_run";
+/// Step reported when an answer was withheld for falling below the
+/// groundedness gate.
+pub const ANSWER_SUPPRESSED_STEP: &str = "answer_suppressed_low_groundedness";
+/// Step reported when the query embedding was projected from the request's
+/// model into the indexed model's space.
+pub const QUERY_EMBEDDING_PROJECTED_STEP: &str = "query_embedding_projected";
@@ -13,11 +13,31 @@ const GLOBAL_KEYWORDS: [&str; 10] = [
     "summary",
 ];
 
+/// Plan step that replaces `vector_search` when the request names its anchors.
+pub const EXPLICIT_ANCHORS_STEP: &str = "explicit_anchors";
+
+/// Plan step appended when the expansion depth was clamped to the engine's
+/// `max_expansion_depth`.
+pub const DEPTH_CAPPED_STEP: &str = "depth_capped";
+
+/// Default ceiling on graph expansion depth, including DRIFT's widening.
+pub const MAX_EXPANSION_DEPTH: u8 = 8;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QueryPlan {
     pub effective_search_mode: SearchMode,
     pub vector_top_k: usize,
     pub expansion_depth: u8,
+    /// Ceiling for `expansion_depth`, also bounding DRIFT's per-iteration
+    /// deepening.
+    pub max_expansion_depth: u8,
+    /// Whether the requested (or mode-derived) depth exceeded the ceiling.
+    pub depth_capped: bool,
+    /// Per-node neighbor cap for graph expansion; `None` is unlimited.
+    pub max_neighbors_per_node: Option<usize>,
+    /// Cap on distinct candidates collected by graph expansion, anchors
+    /// included; `None` is unlimited.
+    pub max_candidates: Option<usize>,
     pub steps: Vec<&'static str>,
 }
 
@@ -25,27 +45,45 @@ pub struct QueryPlanner;
 
 impl QueryPlanner {
     pub fn plan(request: &QueryRequest) -> QueryPlan {
+        Self::plan_with_max_depth(request, MAX_EXPANSION_DEPTH)
+    }
+
+    /// Like [`Self::plan`], clamping the expansion depth to `max_depth`
+    /// (at least 1).
+    pub fn plan_with_max_depth(request: &QueryRequest, max_depth: u8) -> QueryPlan {
+        let max_expansion_depth = max_depth.max(1);
         let effective_search_mode = match request.search_mode {
             SearchMode::Auto => infer_auto_mode(&request.query),
             mode => mode,
         };
-        let expansion_depth = match effective_search_mode {
+        let requested_depth = match effective_search_mode {
             SearchMode::Global => request.traversal.depth.max(2),
-            SearchMode::Drift => request.traversal.depth.max(2).saturating_add(1).min(8),
+            SearchMode::Drift => request.traversal.depth.max(2).saturating_add(1),
             SearchMode::Local => request.traversal.depth.max(1),
             SearchMode::Auto => request.traversal.depth.max(1),
         };
+        let expansion_depth = requested_depth.min(max_expansion_depth);
         let vector_top_k = match effective_search_mode {
             SearchMode::Global => request.top_k.max(10),
             SearchMode::Drift => request.top_k.max(5),
             _ => request.top_k.max(1),
         };
 
+        let anchor_step = if request.anchor_ids.is_empty() {
+            "vector_search"
+        } else {
+            EXPLICIT_ANCHORS_STEP
+        };
+
         QueryPlan {
             effective_search_mode,
             vector_top_k,
             expansion_depth,
-            steps: vec!["vector_search", "graph_expansion", "context_pruning"],
+            max_expansion_depth,
+            depth_capped: requested_depth > max_expansion_depth,
+            max_neighbors_per_node: request.traversal.max_neighbors_per_node,
+            max_candidates: request.traversal.max_candidates,
+            steps: vec![anchor_step, "graph_expansion", "context_pruning"],
         }
     }
 }
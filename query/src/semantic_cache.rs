@@ -1,10 +1,11 @@
-use crate::dsl::{QueryMode, QueryRequest, SearchMode};
+use crate::dsl::{
+    ConfidencePropagation, HopPenalty, QueryMode, QueryRequest, SearchAccuracy, SearchMode,
+};
+use crate::tokenizer::{Tokenizer, TokenizerLanguage};
 use std::cmp::Ordering;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::{Duration, Instant};
 
-const UNICODE_NGRAM_SIZE: usize = 2;
-
 /// Eviction policy for cache entries.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum EvictionPolicy {
@@ -58,9 +59,36 @@ pub struct SemanticCacheKey {
     pub entity_type: Vec<String>,
     pub relation_type: Vec<String>,
     pub traversal_relation_types: Vec<String>,
+    pub exclude_relation_types: Vec<String>,
+    pub max_neighbors_per_node: Option<usize>,
+    pub max_candidates: Option<usize>,
+    /// Sorted `(relation, factor bits)` pairs from `traversal.relation_weights`.
+    pub relation_weights: Vec<(String, u32)>,
+    pub confidence_propagation: ConfidencePropagation,
+    pub hop_penalty: HopPenalty,
+    /// Sorted `(path, value)` pairs from `filters.metadata`.
+    pub metadata_filter: Vec<(String, String)>,
     pub time_range_from: Option<String>,
     pub time_range_to: Option<String>,
+    /// `(inclusive_start, inclusive_end)` of `filters.time_range`.
+    pub time_range_inclusive: Option<(bool, bool)>,
+    pub time_field: Option<String>,
     pub time_travel: Option<String>,
+    pub anchor_ids: Vec<u64>,
+    pub require_match: bool,
+    /// Bits of `min_anchor_score`.
+    pub min_anchor_score: Option<u32>,
+    pub answer_evidence_count: usize,
+    /// Bits of `min_answer_groundedness`.
+    pub min_answer_groundedness: Option<u32>,
+    pub deterministic: bool,
+    pub global_max_communities: Option<usize>,
+    /// Bits of `centrality_boost`.
+    pub centrality_boost: u32,
+    pub search_accuracy: SearchAccuracy,
+    pub language: Option<TokenizerLanguage>,
+    /// Generation of the community summary set the response was computed with.
+    pub community_generation: u64,
 }
 
 impl SemanticCacheKey {
@@ -82,6 +110,30 @@ impl SemanticCacheKey {
         traversal_relation_types.sort();
         traversal_relation_types.dedup();
 
+        let mut exclude_relation_types = request.traversal.exclude_relation_types.clone();
+        exclude_relation_types.sort();
+        exclude_relation_types.dedup();
+
+        let mut relation_weights: Vec<(String, u32)> = request
+            .traversal
+            .relation_weights
+            .iter()
+            .map(|(relation, factor)| (relation.clone(), factor.to_bits()))
+            .collect();
+        relation_weights.sort();
+
+        let mut metadata_filter: Vec<(String, String)> = request
+            .filters
+            .metadata
+            .iter()
+            .map(|(path, value)| (path.clone(), value.clone()))
+            .collect();
+        metadata_filter.sort();
+
+        let mut anchor_ids = request.anchor_ids.clone();
+        anchor_ids.sort_unstable();
+        anchor_ids.dedup();
+
         Self {
             model_id: model_id.to_string(),
             snapshot_id: snapshot_id.to_string(),
@@ -94,17 +146,45 @@ impl SemanticCacheKey {
             entity_type,
             relation_type,
             traversal_relation_types,
+            exclude_relation_types,
+            max_neighbors_per_node: request.traversal.max_neighbors_per_node,
+            max_candidates: request.traversal.max_candidates,
+            relation_weights,
+            confidence_propagation: request.traversal.confidence_propagation,
+            hop_penalty: request.traversal.hop_penalty,
+            metadata_filter,
             time_range_from: request
                 .filters
                 .time_range
                 .as_ref()
-                .map(|range| range.from.clone()),
+                .and_then(|range| range.from.clone()),
             time_range_to: request
                 .filters
                 .time_range
                 .as_ref()
-                .map(|range| range.to.clone()),
+                .and_then(|range| range.to.clone()),
+            time_range_inclusive: request
+                .filters
+                .time_range
+                .as_ref()
+                .map(|range| (range.inclusive_start, range.inclusive_end)),
+            time_field: request
+                .filters
+                .time_range
+                .as_ref()
+                .map(|range| range.time_field.clone()),
             time_travel: request.time_travel.clone(),
+            anchor_ids,
+            require_match: request.require_match,
+            min_anchor_score: request.min_anchor_score.map(f32::to_bits),
+            answer_evidence_count: request.answer_evidence_count,
+            min_answer_groundedness: request.min_answer_groundedness.map(f32::to_bits),
+            deterministic: request.deterministic,
+            global_max_communities: request.global_max_communities,
+            centrality_boost: request.centrality_boost.to_bits(),
+            search_accuracy: request.search_accuracy,
+            language: request.language,
+            community_generation: 0,
         }
     }
 }
@@ -123,6 +203,7 @@ struct SemanticCacheEntry<T> {
 #[derive(Debug, Clone)]
 pub struct SemanticCache<T> {
     config: SemanticCacheConfig,
+    tokenizer: Tokenizer,
     entries: VecDeque<SemanticCacheEntry<T>>,
 }
 
@@ -130,10 +211,31 @@ impl<T: Clone> SemanticCache<T> {
     pub fn with_config(config: SemanticCacheConfig) -> Self {
         Self {
             config,
+            tokenizer: Tokenizer::default(),
             entries: VecDeque::new(),
         }
     }
 
+    /// Compare queries using `tokenizer`, typically the one the query engine
+    /// scores with.
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.set_tokenizer(tokenizer);
+        self
+    }
+
+    /// Switch tokenizers, re-tokenizing cached queries so old and new entries
+    /// stay comparable.
+    pub fn set_tokenizer(&mut self, tokenizer: Tokenizer) {
+        for entry in &mut self.entries {
+            entry.query_tokens = tokenizer.tokenize(&entry.normalized_query);
+        }
+        self.tokenizer = tokenizer;
+    }
+
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
     pub fn lookup(&mut self, key: &SemanticCacheKey, query: &str) -> Option<T> {
         if !self.config.enabled {
             return None;
@@ -150,7 +252,7 @@ impl<T: Clone> SemanticCache<T> {
         if effective_query_length(&normalized_query) < self.config.min_query_length {
             return None;
         }
-        let query_tokens = tokenize(&normalized_query);
+        let query_tokens = self.tokenizer.tokenize(&normalized_query);
 
         let mut best_match: Option<(usize, f32)> = None;
 
@@ -210,7 +312,7 @@ impl<T: Clone> SemanticCache<T> {
 
         self.purge_expired_entries();
 
-        let query_tokens = tokenize(&normalized_query);
+        let query_tokens = self.tokenizer.tokenize(&normalized_query);
 
         if let Some(existing_idx) = self
             .entries
@@ -286,6 +388,178 @@ fn is_expired(created_at: Instant, ttl_seconds: Option<u64>, now: Instant) -> bo
         .is_some_and(|elapsed| elapsed >= Duration::from_secs(ttl_seconds))
 }
 
+/// Key of an [`ExactResultCache`] entry: the snapshot the result was computed
+/// against plus the normalized request. Requests pinned to a content snapshot
+/// id are keyed by content rather than LSN, so those keys are portable across
+/// restarts and across replicas holding identical data; unpinned requests use
+/// the repository's local state id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExactCacheKey {
+    /// Pinned content snapshot id, or the repository's local state id.
+    pub snapshot_key: String,
+    /// Query text with whitespace collapsed; case is preserved.
+    pub query: String,
+    /// Normalized request fields; its `snapshot_id` is the snapshot key.
+    pub request: SemanticCacheKey,
+    /// Whether the request pinned `snapshot_id` to the content id.
+    pub pinned: bool,
+    pub debug_tokens: bool,
+}
+
+impl ExactCacheKey {
+    pub fn from_request(
+        request: &QueryRequest,
+        model_id: &str,
+        snapshot_key: &str,
+        effective_search_mode: SearchMode,
+    ) -> Self {
+        Self {
+            snapshot_key: snapshot_key.to_string(),
+            query: request
+                .query
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            request: SemanticCacheKey::from_request(
+                request,
+                model_id,
+                snapshot_key,
+                effective_search_mode,
+            ),
+            pinned: request.snapshot_id.is_some(),
+            debug_tokens: request.debug_tokens,
+        }
+    }
+}
+
+/// Exact-repeat cache consulted before the [`SemanticCache`]. Every write
+/// moves the snapshot key, so a data change never serves a stale result;
+/// entries for superseded snapshots simply age out, oldest first.
+#[derive(Debug, Clone)]
+pub struct ExactResultCache<T> {
+    max_entries: usize,
+    entries: HashMap<ExactCacheKey, T>,
+    order: VecDeque<ExactCacheKey>,
+    hits: u64,
+}
+
+impl<T: Clone> ExactResultCache<T> {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+        }
+    }
+
+    pub fn lookup(&mut self, key: &ExactCacheKey) -> Option<T> {
+        let value = self.entries.get(key).cloned();
+        if value.is_some() {
+            self.hits += 1;
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: ExactCacheKey, value: T) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.max_entries {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+/// `(query, model_id, dimension)` an embedding was computed for.
+pub type EmbeddingCacheKey = (String, String, usize);
+
+/// Bounded LRU cache of query embeddings, so repeated queries under the same
+/// model and dimension are embedded once.
+#[derive(Debug, Clone)]
+pub struct EmbeddingCache {
+    max_entries: usize,
+    entries: HashMap<EmbeddingCacheKey, Vec<f32>>,
+    order: VecDeque<EmbeddingCacheKey>,
+    hits: u64,
+}
+
+impl EmbeddingCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+        }
+    }
+
+    /// Cached embedding for `key`, marking it most recently used.
+    pub fn get(&mut self, key: &EmbeddingCacheKey) -> Option<Vec<f32>> {
+        let value = self.entries.get(key).cloned()?;
+        self.hits += 1;
+        self.touch(key);
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: EmbeddingCacheKey, embedding: Vec<f32>) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), embedding).is_some() {
+            self.touch(&key);
+        } else {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.max_entries {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    fn touch(&mut self, key: &EmbeddingCacheKey) {
+        if let Some(position) = self.order.iter().position(|entry| entry == key) {
+            if let Some(entry) = self.order.remove(position) {
+                self.order.push_back(entry);
+            }
+        }
+    }
+}
+
 fn normalize_query(query: &str) -> String {
     query
         .split_whitespace()
@@ -322,52 +596,6 @@ fn query_similarity(
     intersection as f32 / union as f32
 }
 
-fn tokenize(text: &str) -> HashSet<String> {
-    let mut out = HashSet::new();
-    let mut buffer = String::new();
-
-    for ch in text.chars().flat_map(|ch| ch.to_lowercase()) {
-        if ch.is_alphanumeric() || ch == '_' {
-            buffer.push(ch);
-        } else if !buffer.is_empty() {
-            out.insert(buffer.clone());
-            buffer.clear();
-        }
-    }
-
-    if !buffer.is_empty() {
-        out.insert(buffer);
-    }
-
-    let unicode_tokens: Vec<String> = out
-        .iter()
-        .filter(|token| !token.is_ascii())
-        .cloned()
-        .collect();
-    for token in unicode_tokens {
-        for ngram in char_ngrams(&token, UNICODE_NGRAM_SIZE) {
-            out.insert(ngram);
-        }
-    }
-
-    out
-}
-
-fn char_ngrams(token: &str, n: usize) -> Vec<String> {
-    let chars: Vec<char> = token.chars().collect();
-    if chars.is_empty() || n == 0 {
-        return Vec::new();
-    }
-    if chars.len() <= n {
-        return vec![token.to_string()];
-    }
-
-    chars
-        .windows(n)
-        .map(|window| window.iter().collect::<String>())
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,9 +613,29 @@ mod tests {
             entity_type: Vec::new(),
             relation_type: Vec::new(),
             traversal_relation_types: Vec::new(),
+            exclude_relation_types: Vec::new(),
+            max_neighbors_per_node: None,
+            max_candidates: None,
+            relation_weights: Vec::new(),
+            confidence_propagation: ConfidencePropagation::Metadata,
+            hop_penalty: HopPenalty::HopCount,
+            metadata_filter: Vec::new(),
             time_range_from: None,
             time_range_to: None,
+            time_range_inclusive: None,
+            time_field: None,
             time_travel: None,
+            anchor_ids: Vec::new(),
+            require_match: false,
+            min_anchor_score: None,
+            answer_evidence_count: 3,
+            min_answer_groundedness: None,
+            deterministic: false,
+            global_max_communities: None,
+            centrality_boost: 0,
+            search_accuracy: SearchAccuracy::Approximate,
+            language: None,
+            community_generation: 0,
         }
     }
 
@@ -589,4 +837,23 @@ mod tests {
         let miss = cache.lookup(&key, "test query");
         assert_eq!(miss, None);
     }
+
+    #[test]
+    fn embedding_cache_evicts_least_recently_used() {
+        let key = |query: &str| (query.to_string(), "embedding-default-v1".to_string(), 4);
+        let mut cache = EmbeddingCache::new(2);
+        cache.insert(key("a"), vec![1.0]);
+        cache.insert(key("b"), vec![2.0]);
+        assert_eq!(cache.get(&key("a")), Some(vec![1.0]));
+
+        cache.insert(key("c"), vec![3.0]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")), Some(vec![1.0]));
+        assert_eq!(cache.hits(), 2);
+
+        let mut disabled = EmbeddingCache::new(0);
+        disabled.insert(key("a"), vec![1.0]);
+        assert!(disabled.is_empty());
+    }
 }
@@ -0,0 +1,258 @@
+//! Porter (1980) suffix-stripping stemmer for English words.
+//!
+//! Only lowercase ASCII alphabetic words are stemmed; anything else (digits,
+//! underscores, non-Latin scripts) is returned unchanged, as are words of two
+//! letters or fewer.
+
+pub(crate) fn stem(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    let mut stemmer = Stemmer {
+        b: word.as_bytes().to_vec(),
+    };
+    stemmer.step1ab();
+    stemmer.step1c();
+    stemmer.step2();
+    stemmer.step3();
+    stemmer.step4();
+    stemmer.step5();
+    String::from_utf8(stemmer.b).expect("stemmer keeps ASCII")
+}
+
+struct Stemmer {
+    b: Vec<u8>,
+}
+
+impl Stemmer {
+    fn is_consonant(&self, i: usize) -> bool {
+        match self.b[i] {
+            b'a' | b'e' | b'i' | b'o' | b'u' => false,
+            b'y' => i == 0 || !self.is_consonant(i - 1),
+            _ => true,
+        }
+    }
+
+    /// Number of vowel-consonant sequences in `b[..end]`.
+    fn measure(&self, end: usize) -> usize {
+        let mut i = 0;
+        while i < end && self.is_consonant(i) {
+            i += 1;
+        }
+        let mut n = 0;
+        loop {
+            while i < end && !self.is_consonant(i) {
+                i += 1;
+            }
+            if i >= end {
+                return n;
+            }
+            while i < end && self.is_consonant(i) {
+                i += 1;
+            }
+            n += 1;
+        }
+    }
+
+    fn has_vowel(&self, end: usize) -> bool {
+        (0..end).any(|i| !self.is_consonant(i))
+    }
+
+    /// Whether `b[..end]` ends in a doubled consonant.
+    fn ends_double_consonant(&self, end: usize) -> bool {
+        end >= 2 && self.b[end - 1] == self.b[end - 2] && self.is_consonant(end - 1)
+    }
+
+    /// Whether `b[..end]` ends consonant-vowel-consonant, the last not
+    /// `w`, `x` or `y` (e.g. "hop", not "snow").
+    fn ends_cvc(&self, end: usize) -> bool {
+        end >= 3
+            && self.is_consonant(end - 1)
+            && !self.is_consonant(end - 2)
+            && self.is_consonant(end - 3)
+            && !matches!(self.b[end - 1], b'w' | b'x' | b'y')
+    }
+
+    fn ends_with(&self, suffix: &str) -> bool {
+        self.b.ends_with(suffix.as_bytes())
+    }
+
+    /// Length of the word without `suffix`, which it must end with.
+    fn stem_len(&self, suffix: &str) -> usize {
+        self.b.len() - suffix.len()
+    }
+
+    fn replace_suffix(&mut self, suffix: &str, replacement: &str) {
+        let stem_len = self.stem_len(suffix);
+        self.b.truncate(stem_len);
+        self.b.extend_from_slice(replacement.as_bytes());
+    }
+
+    /// Replaces the first matching suffix when the remaining stem has a
+    /// measure above `min_measure`; later rules are not tried either way.
+    fn apply_rules(&mut self, rules: &[(&str, &str)], min_measure: usize) {
+        if let Some((suffix, replacement)) = rules.iter().find(|(s, _)| self.ends_with(s)) {
+            if self.measure(self.stem_len(suffix)) > min_measure {
+                self.replace_suffix(suffix, replacement);
+            }
+        }
+    }
+
+    /// Plurals and `-ed` / `-ing`.
+    fn step1ab(&mut self) {
+        if self.ends_with("sses") || self.ends_with("ies") {
+            self.b.truncate(self.b.len() - 2);
+        } else if self.ends_with("s") && !self.ends_with("ss") {
+            self.b.pop();
+        }
+
+        if self.ends_with("eed") {
+            if self.measure(self.stem_len("eed")) > 0 {
+                self.b.pop();
+            }
+            return;
+        }
+        let Some(suffix) = ["ed", "ing"]
+            .into_iter()
+            .find(|suffix| self.ends_with(suffix) && self.has_vowel(self.stem_len(suffix)))
+        else {
+            return;
+        };
+        self.b.truncate(self.stem_len(suffix));
+        let len = self.b.len();
+        if self.ends_with("at") || self.ends_with("bl") || self.ends_with("iz") {
+            self.b.push(b'e');
+        } else if self.ends_double_consonant(len) && !matches!(self.b[len - 1], b'l' | b's' | b'z')
+        {
+            self.b.pop();
+        } else if self.measure(len) == 1 && self.ends_cvc(len) {
+            self.b.push(b'e');
+        }
+    }
+
+    /// Terminal `y` to `i` when the stem has a vowel.
+    fn step1c(&mut self) {
+        if self.ends_with("y") && self.has_vowel(self.b.len() - 1) {
+            let last = self.b.len() - 1;
+            self.b[last] = b'i';
+        }
+    }
+
+    /// Double suffixes to single ones (`-ization` to `-ize`, ...).
+    fn step2(&mut self) {
+        self.apply_rules(
+            &[
+                ("ational", "ate"),
+                ("tional", "tion"),
+                ("enci", "ence"),
+                ("anci", "ance"),
+                ("izer", "ize"),
+                ("bli", "ble"),
+                ("alli", "al"),
+                ("entli", "ent"),
+                ("eli", "e"),
+                ("ousli", "ous"),
+                ("ization", "ize"),
+                ("ation", "ate"),
+                ("ator", "ate"),
+                ("alism", "al"),
+                ("iveness", "ive"),
+                ("fulness", "ful"),
+                ("ousness", "ous"),
+                ("aliti", "al"),
+                ("iviti", "ive"),
+                ("biliti", "ble"),
+                ("logi", "log"),
+            ],
+            0,
+        );
+    }
+
+    /// `-ic-`, `-full`, `-ness` and friends.
+    fn step3(&mut self) {
+        self.apply_rules(
+            &[
+                ("icate", "ic"),
+                ("ative", ""),
+                ("alize", "al"),
+                ("iciti", "ic"),
+                ("ical", "ic"),
+                ("ful", ""),
+                ("ness", ""),
+            ],
+            0,
+        );
+    }
+
+    /// Drops `-ant`, `-ence`, ... from stems with a measure above one.
+    fn step4(&mut self) {
+        const SUFFIXES: [&str; 19] = [
+            "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent", "ion",
+            "ou", "ism", "ate", "iti", "ous", "ive", "ize",
+        ];
+        let Some(suffix) = SUFFIXES.into_iter().find(|suffix| self.ends_with(suffix)) else {
+            return;
+        };
+        let stem_len = self.stem_len(suffix);
+        if suffix == "ion" && (stem_len == 0 || !matches!(self.b[stem_len - 1], b's' | b't')) {
+            return;
+        }
+        if self.measure(stem_len) > 1 {
+            self.b.truncate(stem_len);
+        }
+    }
+
+    /// Final `-e` and doubled `-ll`.
+    fn step5(&mut self) {
+        if self.ends_with("e") {
+            let stem_len = self.b.len() - 1;
+            let measure = self.measure(stem_len);
+            if measure > 1 || (measure == 1 && !self.ends_cvc(stem_len)) {
+                self.b.pop();
+            }
+        }
+        let len = self.b.len();
+        if self.ends_with("ll") && self.measure(len) > 1 {
+            self.b.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_porter_output() {
+        for (word, expected) in [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("feed", "feed"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("motoring", "motor"),
+            ("sing", "sing"),
+            ("conflated", "conflat"),
+            ("hopping", "hop"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("relational", "relat"),
+            ("generalization", "gener"),
+            ("hopeful", "hope"),
+            ("controlling", "control"),
+            ("adoption", "adopt"),
+            ("battery", "batteri"),
+            ("batteries", "batteri"),
+        ] {
+            assert_eq!(stem(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn leaves_short_and_non_alphabetic_words_alone() {
+        for word in ["is", "ev2026", "snake_case", "電池", "Caps"] {
+            assert_eq!(stem(word), word);
+        }
+    }
+}
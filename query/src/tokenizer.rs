@@ -0,0 +1,269 @@
+//! Lexical tokenization shared by query scoring and the semantic cache.
+//!
+//! Text is lowercased and split on non-alphanumeric characters. Tokens that
+//! contain non-ASCII characters are additionally broken into character n-grams
+//! (and, optionally, dictionary segments) so scripts without spaces such as
+//! Japanese, Chinese, Korean or Thai still produce overlapping tokens. A
+//! [`TokenizerLanguage`] hint overrides that per-token detection. Whole
+//! ASCII words can optionally be reduced to their English (Porter) stem so
+//! morphological variants such as "battery" and "batteries" match.
+
+use crate::stemmer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub const DEFAULT_NGRAM_SIZE: usize = 2;
+
+/// Tokenization strategy, overriding the per-token script detection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerLanguage {
+    /// N-grams (and segments) for tokens containing non-ASCII characters only.
+    #[default]
+    Auto,
+    /// Treat every token as CJK, so romanized or mixed-script words are also
+    /// broken into n-grams.
+    Cjk,
+    /// Whole words only: no n-grams and no segmentation.
+    Whitespace,
+}
+
+/// Dictionary-based segmentation hook for non-ASCII tokens.
+pub trait CjkSegmenter: Send + Sync {
+    /// Returns the words found in `token`. Segments are added alongside the
+    /// n-grams, not in place of them.
+    fn segment(&self, token: &str) -> Vec<String>;
+}
+
+#[derive(Clone)]
+pub struct Tokenizer {
+    ngram_size: usize,
+    emit_whole_tokens: bool,
+    segmenter: Option<Arc<dyn CjkSegmenter>>,
+    language: TokenizerLanguage,
+    stemming: bool,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self {
+            ngram_size: DEFAULT_NGRAM_SIZE,
+            emit_whole_tokens: true,
+            segmenter: None,
+            language: TokenizerLanguage::Auto,
+            stemming: false,
+        }
+    }
+}
+
+impl std::fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tokenizer")
+            .field("ngram_size", &self.ngram_size)
+            .field("emit_whole_tokens", &self.emit_whole_tokens)
+            .field("segmenter", &self.segmenter.is_some())
+            .field("language", &self.language)
+            .field("stemming", &self.stemming)
+            .finish()
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Character n-gram size for non-ASCII tokens. `0` disables n-grams.
+    pub fn with_ngram_size(mut self, ngram_size: usize) -> Self {
+        self.ngram_size = ngram_size;
+        self
+    }
+
+    /// Whether non-ASCII tokens are also emitted whole next to their n-grams.
+    /// ASCII tokens are always emitted whole.
+    pub fn with_whole_tokens(mut self, emit_whole_tokens: bool) -> Self {
+        self.emit_whole_tokens = emit_whole_tokens;
+        self
+    }
+
+    pub fn with_segmenter(mut self, segmenter: Arc<dyn CjkSegmenter>) -> Self {
+        self.segmenter = Some(segmenter);
+        self
+    }
+
+    pub fn with_language(mut self, language: TokenizerLanguage) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Reduce whole lowercase ASCII words to their English stem. Words broken
+    /// into n-grams (non-ASCII, or every word under
+    /// [`TokenizerLanguage::Cjk`]) are never stemmed.
+    pub fn with_stemming(mut self, stemming: bool) -> Self {
+        self.stemming = stemming;
+        self
+    }
+
+    pub fn stemming(&self) -> bool {
+        self.stemming
+    }
+
+    pub fn language(&self) -> TokenizerLanguage {
+        self.language
+    }
+
+    pub fn ngram_size(&self) -> usize {
+        self.ngram_size
+    }
+
+    pub fn tokenize(&self, text: &str) -> HashSet<String> {
+        let mut words = Vec::new();
+        let mut buffer = String::new();
+
+        for ch in text.chars().flat_map(|ch| ch.to_lowercase()) {
+            if ch.is_alphanumeric() || ch == '_' {
+                buffer.push(ch);
+            } else if !buffer.is_empty() {
+                words.push(std::mem::take(&mut buffer));
+            }
+        }
+        if !buffer.is_empty() {
+            words.push(buffer);
+        }
+
+        let mut out = HashSet::new();
+        for word in words {
+            let whole_only = match self.language {
+                TokenizerLanguage::Auto => word.is_ascii(),
+                TokenizerLanguage::Cjk => false,
+                TokenizerLanguage::Whitespace => true,
+            };
+            if whole_only {
+                out.insert(if self.stemming {
+                    stemmer::stem(&word)
+                } else {
+                    word
+                });
+                continue;
+            }
+
+            out.extend(char_ngrams(&word, self.ngram_size));
+            if let Some(segmenter) = &self.segmenter {
+                out.extend(
+                    segmenter
+                        .segment(&word)
+                        .into_iter()
+                        .filter(|segment| !segment.is_empty()),
+                );
+            }
+            if self.emit_whole_tokens {
+                out.insert(word);
+            }
+        }
+
+        out
+    }
+}
+
+fn char_ngrams(token: &str, n: usize) -> Vec<String> {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.is_empty() || n == 0 {
+        return Vec::new();
+    }
+    if chars.len() <= n {
+        return vec![token.to_string()];
+    }
+
+    chars
+        .windows(n)
+        .map(|window| window.iter().collect::<String>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+        let intersection = a.intersection(b).count();
+        intersection as f32 / (a.len() + b.len() - intersection) as f32
+    }
+
+    #[test]
+    fn default_tokenizer_emits_whole_tokens_and_bigrams() {
+        let tokens = Tokenizer::default().tokenize("Toyota 電気自動車");
+        for expected in ["toyota", "電気自動車", "電気", "気自", "自動", "動車"] {
+            assert!(tokens.contains(expected), "missing {expected}");
+        }
+    }
+
+    #[test]
+    fn trigram_tokenizer_changes_similarity() {
+        let bigram = Tokenizer::default();
+        let trigram = Tokenizer::new().with_ngram_size(3);
+        let (a, b) = ("電気自動車の生産", "電気自動車の生産量");
+
+        let bigram_score = jaccard(&bigram.tokenize(a), &bigram.tokenize(b));
+        let trigram_score = jaccard(&trigram.tokenize(a), &trigram.tokenize(b));
+        assert!((bigram_score - 0.7).abs() < 1e-6);
+        assert!((trigram_score - 6.0 / 9.0).abs() < 1e-6);
+        assert!(trigram.tokenize(a).contains("自動車"));
+    }
+
+    #[test]
+    fn segmenter_and_whole_token_switch_apply_to_non_ascii_only() {
+        struct Dictionary;
+        impl CjkSegmenter for Dictionary {
+            fn segment(&self, token: &str) -> Vec<String> {
+                ["電気", "自動車"]
+                    .into_iter()
+                    .filter(|word| token.contains(word))
+                    .map(str::to_string)
+                    .collect()
+            }
+        }
+
+        let tokens = Tokenizer::new()
+            .with_ngram_size(0)
+            .with_whole_tokens(false)
+            .with_segmenter(Arc::new(Dictionary))
+            .tokenize("EV 電気自動車");
+        let mut sorted: Vec<_> = tokens.into_iter().collect();
+        sorted.sort();
+        assert_eq!(sorted, vec!["ev", "自動車", "電気"]);
+    }
+
+    #[test]
+    fn stemming_merges_english_variants_and_skips_ngram_tokens() {
+        let stemmed = Tokenizer::new().with_stemming(true);
+        assert_eq!(stemmed.tokenize("battery"), stemmed.tokenize("Batteries"));
+        assert!(stemmed.tokenize("電池 batteries").contains("電池"));
+        assert_ne!(
+            Tokenizer::new().tokenize("battery"),
+            Tokenizer::new().tokenize("batteries")
+        );
+
+        let cjk = stemmed.with_language(TokenizerLanguage::Cjk);
+        assert!(cjk.tokenize("batteries").contains("batteries"));
+    }
+
+    #[test]
+    fn language_hint_overrides_script_detection() {
+        let sorted = |tokens: HashSet<String>| {
+            let mut tokens: Vec<_> = tokens.into_iter().collect();
+            tokens.sort();
+            tokens
+        };
+        let cjk = Tokenizer::new().with_language(TokenizerLanguage::Cjk);
+        assert_eq!(
+            sorted(cjk.tokenize("Denki 電気")),
+            vec!["de", "denki", "en", "ki", "nk", "電気"]
+        );
+        let whitespace = Tokenizer::new().with_language(TokenizerLanguage::Whitespace);
+        assert_eq!(
+            sorted(whitespace.tokenize("Denki 電気自動車")),
+            vec!["denki", "電気自動車"]
+        );
+    }
+}
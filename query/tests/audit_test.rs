@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use alayasiki_core::audit::{AuditOperation, AuditOutcome, InMemoryAuditSink};
+use alayasiki_core::audit::{
+    AuditOperation, AuditOutcome, InMemoryAuditSink, SamplingAuditSink, REQUEST_FINGERPRINT_KEY,
+};
 use alayasiki_core::auth::{Authorizer, Principal, ResourceContext};
 use alayasiki_core::embedding::deterministic_embedding;
 use alayasiki_core::model::Node;
@@ -84,3 +86,37 @@ async fn query_authorized_records_denied_audit_event() {
     assert_eq!(events[0].tenant.as_deref(), Some("acme"));
     assert!(events[0].metadata.contains_key("error"));
 }
+
+#[tokio::test]
+async fn sampled_audit_sink_drops_successes_but_keeps_denials() {
+    let repo = build_repo().await;
+    let inner = Arc::new(InMemoryAuditSink::default());
+    let engine = QueryEngine::new(repo)
+        .with_audit_sink(Arc::new(SamplingAuditSink::new(inner.clone(), 0.0)));
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query":"EV strategy",
+            "mode":"evidence",
+            "search_mode":"local",
+            "top_k":1
+        }"#,
+    )
+    .unwrap();
+
+    engine.execute(request.clone()).await.unwrap();
+    let principal = Principal::new("ingestor-1", "acme").with_roles(["ingestor"]);
+    let result = engine
+        .execute_authorized(
+            request,
+            &principal,
+            &Authorizer::default(),
+            &ResourceContext::new("acme"),
+        )
+        .await;
+    assert!(result.is_err());
+
+    let events = inner.events().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].outcome, AuditOutcome::Denied);
+    assert!(events[0].metadata.contains_key(REQUEST_FINGERPRINT_KEY));
+}
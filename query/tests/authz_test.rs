@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
-use alayasiki_core::auth::{Authorizer, AuthzError, JwtAuthenticator, Principal, ResourceContext};
+use alayasiki_core::auth::{
+    Authorizer, AuthzError, JwtAuthenticator, Principal, ResourceContext, RowAuthorizer,
+};
 use alayasiki_core::embedding::deterministic_embedding;
-use alayasiki_core::model::Node;
-use query::{QueryEngine, QueryError, QueryRequest};
-use storage::community::CommunitySummary;
+use alayasiki_core::model::{Edge, Node};
+use query::{ExclusionCode, QueryEngine, QueryError, QueryRequest};
+use storage::community::{CommunityEngine, CommunitySummary, TextualSummarizer};
 use storage::repo::Repository;
 use tempfile::tempdir;
 
@@ -161,8 +163,11 @@ async fn execute_authorized_global_avoids_cross_tenant_summary_synthesis() {
         level: 0,
         community_id: 1,
         top_nodes: vec![10, 11],
+        node_ids: Vec::new(),
         summary: "Cross-tenant summary mentions beta confidential program".to_string(),
         snapshot_lsn_range: None,
+        redundant_with: None,
+        tenant: None,
     }];
     let engine = QueryEngine::new(repo).with_community_summaries(summaries);
 
@@ -195,3 +200,159 @@ async fn execute_authorized_global_avoids_cross_tenant_summary_synthesis() {
         .iter()
         .any(|x| x.reason == "global_summary_disabled_by_tenant_scope"));
 }
+
+#[tokio::test]
+async fn execute_authorized_global_uses_tenant_partitioned_communities() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("query_authz_tenant_communities.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+
+    let tenant_nodes = [
+        ("acme", 1, "Acme battery supply chain"),
+        ("acme", 2, "Acme battery recycling"),
+        ("acme", 3, "Acme battery gigafactory"),
+        ("beta", 11, "Beta battery confidential program"),
+        ("beta", 12, "Beta battery pricing"),
+        ("beta", 13, "Beta battery partners"),
+    ];
+    let mut texts = std::collections::HashMap::new();
+    for (tenant, id, text) in tenant_nodes {
+        let mut node = Node::new(
+            id,
+            deterministic_embedding(text, "embedding-default-v1", 8),
+            text.to_string(),
+        );
+        node.metadata
+            .insert("tenant".to_string(), tenant.to_string());
+        repo.put_node(node).await.unwrap();
+        texts.insert(id, text.to_string());
+    }
+    for (source, target) in [(1, 2), (2, 3), (1, 3), (11, 12), (12, 13), (11, 13)] {
+        repo.put_edge(Edge::new(source, target, "related_to", 1.0))
+            .await
+            .unwrap();
+    }
+    // A heavy cross-tenant edge that whole-graph detection would merge on.
+    repo.put_edge(Edge::new(3, 11, "related_to", 5.0))
+        .await
+        .unwrap();
+
+    let mut communities = CommunityEngine::new(repo.graph_index().await)
+        .with_tenant_partitions(repo.node_tenants().await);
+    communities.rebuild_hierarchy(2, &TextualSummarizer::new(Arc::new(texts)));
+    let summaries = communities.summaries().to_vec();
+    for summary in &summaries {
+        let tenant = summary.tenant.as_deref().expect("partitioned summary");
+        let members = if tenant == "acme" { 1..=3 } else { 11..=13 };
+        assert!(summary.node_ids.iter().all(|id| members.contains(id)));
+    }
+
+    let engine = QueryEngine::new(repo).with_community_summaries(summaries);
+    let authorizer = Authorizer::default();
+    let request = || {
+        QueryRequest::parse_json(
+            r#"{"query":"battery","mode":"answer","search_mode":"global","top_k":5}"#,
+        )
+        .unwrap()
+    };
+
+    for (tenant, own, other) in [("acme", "Acme", "Beta"), ("beta", "Beta", "Acme")] {
+        let principal = Principal::new("reader-1", tenant).with_roles(["reader"]);
+        let response = engine
+            .execute_authorized(
+                request(),
+                &principal,
+                &authorizer,
+                &ResourceContext::new(tenant),
+            )
+            .await
+            .unwrap();
+
+        assert!(response
+            .explain
+            .steps
+            .contains(&"community_map_reduce".to_string()));
+        let answer = response.answer.unwrap_or_default();
+        assert!(answer.contains("Global synthesis"), "{tenant}: {answer}");
+        assert!(answer.contains(own), "{tenant}: {answer}");
+        assert!(!answer.contains(other), "{tenant}: {answer}");
+    }
+}
+
+/// Admins read everything; everyone else only reads nodes whose
+/// classification matches their `classification` attribute.
+struct ClassificationRowAuthorizer;
+
+impl RowAuthorizer for ClassificationRowAuthorizer {
+    fn can_read(&self, principal: &Principal, node: &Node) -> bool {
+        principal.roles.contains("admin")
+            || node.metadata.get("classification") == principal.attributes.get("classification")
+    }
+}
+
+#[tokio::test]
+async fn execute_authorized_applies_row_authorizer_per_principal() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("query_row_authz.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+    for (id, text, classification) in [
+        (1, "EV strategy overview", "public"),
+        (2, "EV strategy battery costs", "confidential"),
+        (3, "EV strategy charging network", "public"),
+    ] {
+        let mut node = Node::new(
+            id,
+            deterministic_embedding(text, "embedding-default-v1", 8),
+            text.to_string(),
+        );
+        node.metadata
+            .insert("tenant".to_string(), "acme".to_string());
+        node.metadata
+            .insert("classification".to_string(), classification.to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "covers", 0.9)).await.unwrap();
+    repo.put_edge(Edge::new(1, 3, "covers", 0.9)).await.unwrap();
+
+    let engine = QueryEngine::new(repo).with_row_authorizer(Arc::new(ClassificationRowAuthorizer));
+    let authorizer = Authorizer::default();
+    let resource = ResourceContext::new("acme");
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query":"EV strategy",
+            "mode":"evidence",
+            "search_mode":"local",
+            "top_k":5,
+            "traversal":{"depth":2}
+        }"#,
+    )
+    .unwrap();
+
+    let analyst = Principal::new("analyst-1", "acme")
+        .with_roles(["reader"])
+        .with_attribute("classification", "public");
+    let restricted = engine
+        .execute_authorized(request.clone(), &analyst, &authorizer, &resource)
+        .await
+        .unwrap();
+    let mut restricted_ids: Vec<u64> = restricted.evidence.nodes.iter().map(|n| n.id).collect();
+    restricted_ids.sort_unstable();
+    assert_eq!(restricted_ids, vec![1, 3]);
+    assert!(restricted.explain.exclusions.iter().any(|exclusion| {
+        exclusion.node_id == Some(2) && exclusion.code == ExclusionCode::RowAuthzDenied
+    }));
+
+    let admin = Principal::new("admin-1", "acme").with_roles(["admin"]);
+    let full = engine
+        .execute_authorized(request, &admin, &authorizer, &resource)
+        .await
+        .unwrap();
+    let mut full_ids: Vec<u64> = full.evidence.nodes.iter().map(|n| n.id).collect();
+    full_ids.sort_unstable();
+    assert_eq!(full_ids, vec![1, 2, 3]);
+    assert!(!full
+        .explain
+        .exclusions
+        .iter()
+        .any(|exclusion| exclusion.code == ExclusionCode::RowAuthzDenied));
+}
@@ -1,5 +1,5 @@
 use alayasiki_core::error::{AlayasikiError, ErrorCode};
-use query::QueryEngine;
+use query::{QueryEngine, ResultStatus};
 use std::sync::Arc;
 use storage::repo::Repository;
 use tempfile::tempdir;
@@ -18,8 +18,10 @@ async fn test_error_mapping_standard_categories() {
         Err(err) => {
             assert_eq!(err.error_code(), ErrorCode::InvalidArgument);
             let response = err.to_response();
+            assert_eq!(response.result_status, ResultStatus::Error);
             assert_eq!(response.error_code, Some(ErrorCode::InvalidArgument));
-            assert!(response.answer.unwrap().contains("invalid query"));
+            assert_eq!(response.answer, None);
+            assert!(response.error_message.unwrap().contains("invalid query"));
         }
         Ok(_) => panic!("Expected error for top_k=0"),
     }
@@ -32,7 +34,7 @@ async fn test_error_mapping_standard_categories() {
             assert_eq!(err.error_code(), ErrorCode::NotFound);
             let response = err.to_response();
             assert_eq!(response.error_code, Some(ErrorCode::NotFound));
-            assert!(response.answer.unwrap().contains("snapshot_id"));
+            assert!(response.error_message.unwrap().contains("snapshot_id"));
         }
         Ok(_) => panic!("Expected error for missing snapshot"),
     }
@@ -8,6 +8,7 @@ use query::engine::QueryEngine;
 use query::graphrag::{compute_groundedness, GroundednessInput};
 use query::{QueryRequest, SearchMode};
 use storage::community::{CommunityEngine, CommunitySummary, DeterministicSummarizer};
+use storage::index::{LinearAnnIndex, VectorIndex};
 use storage::repo::Repository;
 use tempfile::TempDir;
 
@@ -136,7 +137,7 @@ async fn graphrag_repo() -> (TempDir, Arc<Repository>, Vec<CommunitySummary>) {
     // Build community summaries using CommunityEngine
     let graph = {
         let index = repo.hyper_index.read().await;
-        index.graph_index.clone()
+        (*index.graph_index).clone()
     };
     let mut community_engine = CommunityEngine::new(graph);
     community_engine.rebuild_hierarchy(2, &DeterministicSummarizer);
@@ -324,6 +325,77 @@ async fn test_global_search_uses_community_summaries_for_answer() {
     assert!(!answer.is_empty());
 }
 
+#[tokio::test]
+async fn test_global_search_adds_top_community_nodes_as_evidence() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("global_evidence.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, data, entity_type) in [
+        (1, "Anchor document", "Company"),
+        (10, "Battery recycling plant opens", "Company"),
+        (11, "Recycling rules for battery packs", "Regulator"),
+        (20, "Weather outlook", "Company"),
+    ] {
+        let mut node = Node::new(
+            id,
+            deterministic_embedding(data, MODEL_ID, DIMS),
+            data.to_string(),
+        );
+        node.metadata
+            .insert("entity_type".to_string(), entity_type.to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    let summary = |community_id: usize, top_nodes: Vec<u64>, text: &str| CommunitySummary {
+        level: 0,
+        community_id,
+        top_nodes,
+        node_ids: Vec::new(),
+        summary: text.to_string(),
+        snapshot_lsn_range: None,
+        redundant_with: None,
+        tenant: None,
+    };
+    let engine = QueryEngine::new(repo).with_community_summaries(vec![
+        summary(0, vec![10, 11], "battery recycling theme"),
+        summary(1, vec![20], "weather theme"),
+    ]);
+    // Explicit anchors keep vector search from reaching the community nodes,
+    // so any of them in the evidence came from the community selection.
+    let request = |entity_type: Option<&str>| QueryRequest {
+        query: "battery recycling".to_string(),
+        search_mode: SearchMode::Global,
+        anchor_ids: vec![1],
+        top_k: 10,
+        filters: query::dsl::QueryFilters {
+            entity_type: entity_type.into_iter().map(str::to_string).collect(),
+            ..Default::default()
+        },
+        ..QueryRequest::default()
+    };
+
+    let response = engine.execute(request(None)).await.unwrap();
+    assert!(response.answer.is_some());
+    let community_nodes: Vec<(u64, f32)> = response
+        .evidence
+        .nodes
+        .iter()
+        .filter(|node| node.id != 1)
+        .map(|node| (node.id, node.score))
+        .collect();
+    let ids: Vec<u64> = community_nodes.iter().map(|(id, _)| *id).collect();
+    assert!(ids.contains(&10) && ids.contains(&11), "{ids:?}");
+    assert!(!ids.contains(&20), "zero-score communities add no evidence");
+    assert!(community_nodes.iter().all(|(_, score)| *score > 0.0));
+
+    let filtered = engine.execute(request(Some("Company"))).await.unwrap();
+    let ids: Vec<u64> = filtered.evidence.nodes.iter().map(|n| n.id).collect();
+    assert!(ids.contains(&10), "{ids:?}");
+    assert!(!ids.contains(&11), "filters apply to community evidence");
+}
+
 #[tokio::test]
 async fn test_global_search_without_community_data_falls_back_to_expanded_vector() {
     let (_dir, repo, _summaries) = graphrag_repo().await;
@@ -517,8 +589,11 @@ async fn test_global_search_disables_summary_when_snapshot_is_pinned() {
         level: 0,
         community_id: 0,
         top_nodes: vec![2],
+        node_ids: Vec::new(),
         summary: "Global synthesis: leaked future summary".to_string(),
         snapshot_lsn_range: None,
+        redundant_with: None,
+        tenant: None,
     }];
     let engine = QueryEngine::new(repo).with_community_summaries(summaries);
 
@@ -616,6 +691,57 @@ async fn test_drift_search_terminates_within_max_iterations() {
     // Should not hang or loop forever — response is returned
 }
 
+#[tokio::test]
+async fn test_drift_search_is_reproducible_across_runs() {
+    let mut baseline: Option<String> = None;
+    for _ in 0..8 {
+        // A fresh repository per run re-seeds every HashMap along the way.
+        let (_dir, repo, summaries) = graphrag_repo().await;
+        // Nodes tied with Toyota on similarity and fan-out exercise the
+        // node-id tie-breaking in anchor selection and candidate ranking.
+        for id in [11, 10, 12] {
+            let node = Node::new(
+                id,
+                deterministic_embedding("EV production", MODEL_ID, DIMS),
+                "EV production line".to_string(),
+            );
+            repo.put_node(node).await.unwrap();
+            repo.put_edge(Edge::new(id, 4, "regulated_by", 0.6))
+                .await
+                .unwrap();
+        }
+        // The linear index scans a HashMap, so its ties are order-sensitive.
+        let nodes = repo.get_nodes_by_ids(&repo.list_node_ids().await).await;
+        {
+            let mut linear = LinearAnnIndex::new();
+            for node in &nodes {
+                linear.insert(node.id, &node.embedding);
+            }
+            repo.hyper_index.write().await.vector_index = Box::new(linear);
+        }
+        let engine = QueryEngine::new(repo).with_community_summaries(summaries);
+
+        let request = QueryRequest::parse_json(
+            r#"{
+                "query": "EV production",
+                "mode": "answer",
+                "search_mode": "drift",
+                "top_k": 4,
+                "traversal": {"depth": 1}
+            }"#,
+        )
+        .unwrap();
+        let mut response = engine.execute(request).await.unwrap();
+        response.latency_ms = 0;
+        let serialized = serde_json::to_string(&response).unwrap();
+
+        match &baseline {
+            Some(expected) => assert_eq!(&serialized, expected),
+            None => baseline = Some(serialized),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // 7. Groundedness Scoring
 // ---------------------------------------------------------------------------
@@ -806,3 +932,237 @@ async fn test_fallback_preserves_evidence_from_vector_search() {
     assert!(!response.citations.is_empty());
     assert!(response.groundedness > 0.0);
 }
+
+#[tokio::test]
+async fn test_community_scheduler_publishes_summaries_to_running_engine() {
+    use std::time::Duration;
+    use storage::community::CommunitySummaryHandle;
+    use storage::community_scheduler::{CommunityScheduler, CommunitySchedulerConfig};
+
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("scheduler.wal"))
+            .await
+            .unwrap(),
+    );
+    let handle = CommunitySummaryHandle::default();
+    let engine = QueryEngine::new(repo.clone()).with_community_summary_handle(handle.clone());
+    let mut scheduler = CommunityScheduler::new(
+        repo.clone(),
+        handle.clone(),
+        Arc::new(DeterministicSummarizer),
+    )
+    .with_config(CommunitySchedulerConfig {
+        interval: Duration::from_secs(3600),
+        dirty_node_threshold: 4,
+        poll_interval: Duration::from_millis(10),
+        max_levels: 2,
+        partition_by_tenant: false,
+    });
+
+    let global_request = || {
+        QueryRequest::parse_json(
+            r#"{"query": "L0-C0 summary", "mode": "answer", "search_mode": "global"}"#,
+        )
+        .unwrap()
+    };
+
+    // First tick builds the (empty) baseline.
+    assert!(scheduler.tick().await);
+    let before = engine.execute(global_request()).await.unwrap();
+    assert!(!before
+        .explain
+        .steps
+        .contains(&"community_map_reduce".to_string()));
+
+    for id in 1..=6u64 {
+        let node = Node::new(
+            id,
+            deterministic_embedding(&format!("entity {id}"), MODEL_ID, DIMS),
+            format!("Entity {id}"),
+        );
+        repo.put_node(node).await.unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "links", 1.0)).await.unwrap();
+    // Two dirty nodes stay below the threshold.
+    assert!(!scheduler.tick().await);
+    assert_eq!(handle.generation(), 1);
+
+    let task = scheduler.spawn();
+    for (source, target) in [(2, 3), (4, 5), (5, 6)] {
+        repo.put_edge(Edge::new(source, target, "links", 1.0))
+            .await
+            .unwrap();
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    while handle.generation() < 2 {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "scheduler did not publish summaries in time"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    task.shutdown().await;
+
+    assert!(!handle.load().is_empty());
+    let after = engine.execute(global_request()).await.unwrap();
+    assert!(after
+        .explain
+        .steps
+        .contains(&"community_map_reduce".to_string()));
+    assert!(after.answer.unwrap().contains("L0-C0"));
+}
+
+#[tokio::test]
+async fn test_global_max_communities_widens_global_synthesis() {
+    let (_dir, repo, _summaries) = graphrag_repo().await;
+    let summaries: Vec<CommunitySummary> = (0..7)
+        .map(|community_id| CommunitySummary {
+            level: 0,
+            community_id,
+            top_nodes: vec![community_id as u64 + 1],
+            node_ids: Vec::new(),
+            summary: format!("EV battery theme number {community_id}"),
+            snapshot_lsn_range: None,
+            redundant_with: None,
+            tenant: None,
+        })
+        .collect();
+    let request = |global_max_communities: Option<usize>| QueryRequest {
+        query: "EV battery theme".to_string(),
+        search_mode: SearchMode::Global,
+        top_k: 10,
+        global_max_communities,
+        ..QueryRequest::default()
+    };
+
+    let engine = QueryEngine::new(repo.clone()).with_community_summaries(summaries.clone());
+    let default_answer = engine.execute(request(None)).await.unwrap().answer.unwrap();
+    assert!(default_answer.contains("from 5 community summaries"));
+    assert!(!default_answer.contains("L0-C6"));
+
+    let wide_answer = engine
+        .execute(request(Some(7)))
+        .await
+        .unwrap()
+        .answer
+        .unwrap();
+    assert!(wide_answer.contains("from 7 community summaries"));
+    assert!(wide_answer.contains("L0-C5") && wide_answer.contains("L0-C6"));
+
+    let narrow_engine = QueryEngine::new(repo)
+        .with_community_summaries(summaries)
+        .with_global_max_communities(2);
+    let narrow_answer = narrow_engine
+        .execute(request(None))
+        .await
+        .unwrap()
+        .answer
+        .unwrap();
+    assert!(narrow_answer.contains("from 2 community summaries"));
+
+    let invalid = narrow_engine.execute(request(Some(0))).await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_global_search_reflects_live_community_summary_swap() {
+    let (_dir, repo, summaries) = graphrag_repo().await;
+    let engine = Arc::new(QueryEngine::new(repo).with_community_summaries(summaries));
+    let request = || {
+        QueryRequest::parse_json(
+            r#"{
+                "query": "EV battery community themes",
+                "mode": "answer",
+                "search_mode": "global",
+                "top_k": 10
+            }"#,
+        )
+        .unwrap()
+    };
+
+    let before = engine.execute(request()).await.unwrap();
+    let before_answer = before.answer.unwrap();
+    assert!(!before_answer.contains("gigafactory consortium"));
+
+    engine.set_community_summaries(vec![CommunitySummary {
+        level: 0,
+        community_id: 0,
+        top_nodes: vec![1, 2, 3],
+        node_ids: Vec::new(),
+        summary: "EV battery gigafactory consortium led by Toyota".to_string(),
+        snapshot_lsn_range: None,
+        redundant_with: None,
+        tenant: None,
+    }]);
+
+    // Same request and snapshot: the earlier cached answer must not be served.
+    let after = engine.execute(request()).await.unwrap();
+    assert!(!after
+        .explain
+        .steps
+        .contains(&query::SEMANTIC_CACHE_HIT_STEP.to_string()));
+    assert!(after
+        .answer
+        .unwrap()
+        .contains("EV battery gigafactory consortium led by Toyota"));
+
+    let handle = engine.community_summary_handle();
+    assert_eq!(handle.generation(), 1);
+    assert_eq!(handle.load().len(), 1);
+}
+
+#[tokio::test]
+async fn test_evidence_nodes_carry_their_community() {
+    let (_dir, repo, summaries) = graphrag_repo().await;
+    let level0: Vec<&CommunitySummary> = summaries.iter().filter(|s| s.level == 0).collect();
+    let expected_community = |node_id: u64| {
+        level0
+            .iter()
+            .find(|summary| summary.node_ids.contains(&node_id))
+            .map(|summary| summary.community_id)
+    };
+
+    let without_communities = QueryEngine::new(repo.clone());
+    let engine = QueryEngine::new(repo).with_community_summaries(summaries.clone());
+
+    for search_mode in ["local", "global"] {
+        let request = || {
+            QueryRequest::parse_json(&format!(
+                r#"{{
+                    "query": "EV production",
+                    "mode": "evidence",
+                    "search_mode": "{search_mode}",
+                    "top_k": 10,
+                    "traversal": {{"depth": 2}}
+                }}"#
+            ))
+            .unwrap()
+        };
+
+        let response = engine.execute(request()).await.unwrap();
+        assert!(!response.evidence.nodes.is_empty());
+        for node in &response.evidence.nodes {
+            assert_eq!(
+                node.community_level,
+                Some(0),
+                "{search_mode}: node {}",
+                node.id
+            );
+            assert_eq!(
+                node.community_id,
+                expected_community(node.id),
+                "{search_mode}: node {}",
+                node.id
+            );
+        }
+
+        let response = without_communities.execute(request()).await.unwrap();
+        assert!(response
+            .evidence
+            .nodes
+            .iter()
+            .all(|node| node.community_id.is_none() && node.community_level.is_none()));
+    }
+}
@@ -159,6 +159,7 @@ fn anchored_request(query: &str, depth: u8, search_mode: SearchMode) -> QueryReq
         traversal: Traversal {
             depth,
             relation_types: Vec::new(),
+            ..Traversal::default()
         },
         top_k: 1,
         search_mode,
@@ -320,6 +321,7 @@ async fn test_local_multi_anchor_attributes_paths_to_correct_anchor() {
         traversal: Traversal {
             depth: 3,
             relation_types: Vec::new(),
+            ..Traversal::default()
         },
         top_k: 2,
         search_mode: SearchMode::Local,
@@ -454,6 +456,7 @@ async fn test_relation_filter_excludes_disallowed_edges() {
                 "uses_equipment".to_string(),
                 "optics_from".to_string(),
             ],
+            ..Traversal::default()
         },
         top_k: 1,
         search_mode: SearchMode::Local,
@@ -472,6 +475,62 @@ async fn test_relation_filter_excludes_disallowed_edges() {
     assert_excluded_with_reason(&response, "relation_filtered:competes_with");
 }
 
+#[tokio::test]
+async fn test_excluded_relations_are_skipped_while_others_are_followed() {
+    let (_dir, repo) = supply_chain_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    let request = QueryRequest {
+        query: OPENAI_TEXT.to_string(),
+        mode: QueryMode::Evidence,
+        traversal: Traversal {
+            depth: 4,
+            exclude_relation_types: vec![
+                "backed_by".to_string(),
+                "licenses_ip".to_string(),
+                "competes_with".to_string(),
+            ],
+            ..Traversal::default()
+        },
+        top_k: 1,
+        search_mode: SearchMode::Local,
+        ..QueryRequest::default()
+    };
+
+    let response = engine.execute(request).await.unwrap();
+
+    assert_eq!(path_targets(&response), vec![NVIDIA, TSMC, ASML, ZEISS]);
+    assert_excluded_with_reason(&response, "relation_excluded:backed_by");
+    assert_excluded_with_reason(&response, "relation_excluded:licenses_ip");
+    assert_excluded_with_reason(&response, "relation_excluded:competes_with");
+}
+
+#[tokio::test]
+async fn test_excluded_relation_wins_over_allow_list() {
+    let (_dir, repo) = supply_chain_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    let request = QueryRequest {
+        query: OPENAI_TEXT.to_string(),
+        mode: QueryMode::Evidence,
+        traversal: Traversal {
+            depth: 4,
+            relation_types: vec!["uses_gpus".to_string(), "fabricated_by".to_string()],
+            exclude_relation_types: vec!["fabricated_by".to_string()],
+            ..Traversal::default()
+        },
+        top_k: 1,
+        search_mode: SearchMode::Local,
+        ..QueryRequest::default()
+    };
+
+    let response = engine.execute(request).await.unwrap();
+
+    assert_eq!(path_targets(&response), vec![NVIDIA]);
+    assert_excluded_with_reason(&response, "relation_excluded:fabricated_by");
+    assert_excluded_with_reason(&response, "relation_filtered:competes_with");
+}
+
 // ---------------------------------------------------------------------------
 // 6. top_k pruning caps the evidence set while still traversing
 // ---------------------------------------------------------------------------
@@ -512,6 +571,7 @@ async fn test_drift_search_honors_mode_and_records_drift_steps() {
         traversal: Traversal {
             depth: 1,
             relation_types: Vec::new(),
+            ..Traversal::default()
         },
         top_k: 5,
         search_mode: SearchMode::Drift,
@@ -565,6 +625,7 @@ async fn test_drift_reports_exhaustion_when_index_is_empty() {
         traversal: Traversal {
             depth: 2,
             relation_types: Vec::new(),
+            ..Traversal::default()
         },
         top_k: 5,
         search_mode: SearchMode::Drift,
@@ -608,6 +669,29 @@ async fn test_auto_mode_falls_back_to_drift_for_insufficient_local_evidence() {
     );
 }
 
+#[tokio::test]
+async fn test_deterministic_auto_request_never_falls_back_to_drift() {
+    let (_dir, repo) = supply_chain_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    // Same sparse-evidence request as above, but pinned to a fixed strategy.
+    let mut request = anchored_request(OPENAI_TEXT, 1, SearchMode::Auto);
+    request.deterministic = true;
+    let response = engine.execute(request).await.unwrap();
+
+    assert_eq!(response.explain.effective_search_mode, SearchMode::Local);
+    assert!(response.evidence.nodes.len() < 2);
+    assert!(!response
+        .explain
+        .exclusions
+        .iter()
+        .any(|x| x.reason == "auto_fallback_to_drift_due_to_insufficient_evidence"));
+    assert!(!response
+        .explain
+        .steps
+        .contains(&"drift_iterative_expansion".to_string()));
+}
+
 // ---------------------------------------------------------------------------
 // 9. Reproducibility: identical requests yield identical reasoned results
 //    across two independently-opened repositories.
@@ -628,6 +712,7 @@ async fn test_identical_requests_are_reproducible_across_independent_repositorie
         traversal: Traversal {
             depth: 4,
             relation_types: Vec::new(),
+            ..Traversal::default()
         },
         top_k: 8,
         search_mode: SearchMode::Drift,
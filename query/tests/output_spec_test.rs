@@ -5,10 +5,10 @@ use std::sync::Arc;
 
 use alayasiki_core::embedding::deterministic_embedding;
 use alayasiki_core::model::{Edge, Node};
-use query::engine::{Provenance, QueryEngine};
-use query::QueryRequest;
+use query::engine::{EvidenceField, Provenance, ProvenanceMapping, QueryEngine};
+use query::{EdgeConfidenceSource, QueryRequest};
 use storage::community::{CommunityEngine, CommunitySummary, DeterministicSummarizer};
-use storage::repo::Repository;
+use storage::repo::{Repository, EDGE_CREATED_AT_KEY, EDGE_CREATED_LSN_KEY};
 use tempfile::TempDir;
 
 const DIMS: usize = 8;
@@ -100,7 +100,7 @@ async fn provenance_repo() -> (TempDir, Arc<Repository>, Vec<CommunitySummary>)
     // Build community summaries
     let graph = {
         let index = repo.hyper_index.read().await;
-        index.graph_index.clone()
+        (*index.graph_index).clone()
     };
     let mut community_engine = CommunityEngine::new(graph);
     community_engine.rebuild_hierarchy(2, &DeterministicSummarizer);
@@ -295,6 +295,46 @@ async fn test_provenance_struct_fields_are_populated_from_metadata() {
     );
 }
 
+#[tokio::test]
+async fn test_provenance_mapping_reads_custom_metadata_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("mapping.wal"))
+            .await
+            .unwrap(),
+    );
+    let mut node = Node::new(
+        1,
+        deterministic_embedding("EV production", MODEL_ID, DIMS),
+        "Toyota leads EV production".to_string(),
+    );
+    node.metadata
+        .insert("doc_uri".to_string(), "s3://docs/toyota.pdf".to_string());
+    node.metadata
+        .insert("model".to_string(), "extractor-v9".to_string());
+    node.metadata
+        .insert("source".to_string(), "ignored".to_string());
+    repo.put_node(node).await.unwrap();
+
+    let engine = QueryEngine::new(repo).with_provenance_mapping(
+        ProvenanceMapping::default()
+            .with_source_key("doc_uri")
+            .with_extraction_model_id_key("model"),
+    );
+    let request = QueryRequest::parse_json(
+        r#"{"query": "EV production", "mode": "evidence", "search_mode": "local", "top_k": 1}"#,
+    )
+    .unwrap();
+    let response = engine.execute(request).await.unwrap();
+
+    let provenance = &response.evidence.nodes[0].provenance;
+    assert_eq!(provenance.source.as_deref(), Some("s3://docs/toyota.pdf"));
+    assert_eq!(
+        provenance.extraction_model_id.as_deref(),
+        Some("extractor-v9")
+    );
+}
+
 #[tokio::test]
 async fn test_confidence_reflects_metadata_when_available() {
     let (_dir, repo, _summaries) = provenance_repo().await;
@@ -331,6 +371,59 @@ async fn test_confidence_reflects_metadata_when_available() {
     }
 }
 
+#[tokio::test]
+async fn test_edge_confidence_prefers_metadata_over_weight() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("edge_confidence.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, text) in [(1, "Toyota EV production"), (2, "Panasonic EV batteries")] {
+        repo.put_node(Node::new(
+            id,
+            deterministic_embedding(text, MODEL_ID, DIMS),
+            text.to_string(),
+        ))
+        .await
+        .unwrap();
+    }
+    let mut edge = Edge::new(1, 2, "supplied_by", 0.9);
+    edge.metadata
+        .insert("confidence".to_string(), "0.35".to_string());
+    repo.put_edge(edge).await.unwrap();
+
+    let request = || {
+        QueryRequest::parse_json(
+            r#"{
+                "query": "Toyota EV production",
+                "mode": "evidence",
+                "search_mode": "local",
+                "top_k": 5,
+                "traversal": {"depth": 1}
+            }"#,
+        )
+        .unwrap()
+    };
+    let edge_confidence = |response: &query::QueryResponse| {
+        response
+            .evidence
+            .edges
+            .iter()
+            .find(|edge| edge.source == 1 && edge.target == 2)
+            .map(|edge| (edge.weight, edge.confidence))
+            .unwrap()
+    };
+
+    let engine = QueryEngine::new(repo.clone());
+    let response = engine.execute(request()).await.unwrap();
+    assert_eq!(edge_confidence(&response), (0.9, 0.35));
+
+    let engine = QueryEngine::new(repo).with_edge_confidence_source(EdgeConfidenceSource::Weight);
+    let response = engine.execute(request()).await.unwrap();
+    assert_eq!(edge_confidence(&response), (0.9, 0.9));
+}
+
 // ---------------------------------------------------------------------------
 // 3. Citation 形式（source + span）を定義・返却
 // ---------------------------------------------------------------------------
@@ -372,6 +465,97 @@ async fn test_citations_include_node_id_and_confidence() {
     }
 }
 
+#[tokio::test]
+async fn test_citation_spans_point_into_original_document() {
+    use alayasiki_core::ingest::IngestionRequest;
+    use ingestion::chunker::{ChunkingConfig, SemanticChunker};
+    use ingestion::processor::IngestionPipeline;
+    use std::collections::HashMap;
+
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("citation_offsets.wal"))
+            .await
+            .unwrap(),
+    );
+    let pipeline = IngestionPipeline::with_chunker(
+        repo.clone(),
+        Box::new(SemanticChunker::new(ChunkingConfig {
+            max_chars: 40,
+            overlap_chars: 0,
+            ..Default::default()
+        })),
+    );
+    let document = "Toyota expands EV production in Kentucky. \
+Battery supply deals were signed with Panasonic. \
+Regulators reviewed the new emission targets.";
+    let chunk_ids = pipeline
+        .ingest(IngestionRequest::text(
+            document.to_string(),
+            HashMap::from([("source".to_string(), "s3://news/toyota.txt".to_string())]),
+        ))
+        .await
+        .unwrap();
+    assert!(chunk_ids.len() > 1, "document should split into chunks");
+
+    let engine = QueryEngine::new(repo);
+    let last_chunk = *chunk_ids.last().unwrap();
+    let request = QueryRequest {
+        query: "emission targets".to_string(),
+        mode: query::QueryMode::Evidence,
+        anchor_ids: vec![last_chunk],
+        top_k: 1,
+        ..QueryRequest::default()
+    };
+    let response = engine.execute(request).await.unwrap();
+
+    let citation = &response.citations[0];
+    assert_eq!(citation.node_id, last_chunk);
+    let node = &response.evidence.nodes[0];
+    assert!(citation.span[0] > 0, "later chunk must not start at 0");
+    assert_eq!(&document[citation.span[0]..citation.span[1]], node.data);
+}
+
+#[tokio::test]
+async fn test_answer_includes_inline_source_markers() {
+    let (_dir, repo, _summaries) = provenance_repo().await;
+    let engine = QueryEngine::new(repo.clone());
+
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query": "EV production",
+            "mode": "answer",
+            "search_mode": "local",
+            "top_k": 3,
+            "traversal": {"depth": 2}
+        }"#,
+    )
+    .unwrap();
+
+    let response = engine.execute(request.clone()).await.unwrap();
+    let answer = response.answer.clone().unwrap();
+
+    let mut marked = 0;
+    for node in &response.evidence.nodes {
+        let Some(source) = &node.provenance.source else {
+            assert!(answer.contains(&node.data));
+            continue;
+        };
+        let marker = format!("{} [{}]", node.data, source);
+        assert!(
+            answer.contains(&marker),
+            "answer must cite {source}: {answer}"
+        );
+        assert!(response.citations.iter().any(|c| &c.source == source));
+        marked += 1;
+    }
+    assert!(marked > 0, "fixture nodes carry provenance");
+
+    // Deterministic: a fresh engine (no cache) produces the same text.
+    let again = QueryEngine::new(repo).execute(request).await.unwrap();
+    assert_eq!(again.answer.unwrap(), answer);
+}
+
 #[tokio::test]
 async fn test_citations_span_covers_actual_data_range() {
     let (_dir, repo, _summaries) = provenance_repo().await;
@@ -656,13 +840,17 @@ async fn test_edge_update_with_empty_metadata_clears_old_provenance() {
     let meta = repo.get_edge_metadata(1, 2, "links").await;
     assert_eq!(meta.get("extraction_model_id").unwrap(), "old-model-v1");
 
-    // Update edge with EMPTY metadata — must clear old provenance
+    // Update edge with EMPTY metadata — must clear old provenance; only the
+    // creation stamps are carried over.
     let edge_v2 = Edge::new(1, 2, "links", 0.95);
     repo.put_edge(edge_v2).await.unwrap();
 
     let meta_after = repo.get_edge_metadata(1, 2, "links").await;
-    assert!(
-        meta_after.is_empty(),
+    let mut keys: Vec<&str> = meta_after.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(
+        keys,
+        vec![EDGE_CREATED_AT_KEY, EDGE_CREATED_LSN_KEY],
         "empty metadata update must clear stale provenance, got: {:?}",
         meta_after
     );
@@ -818,3 +1006,63 @@ async fn test_edge_upsert_survives_wal_replay() {
         );
     }
 }
+
+// ---------------------------------------------------------------------------
+// 8. CSV/TSV export of evidence nodes
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_response_csv_export_round_trips_escaped_values() {
+    let (_dir, repo, _summaries) = provenance_repo().await;
+    let tricky = "Nissan, Renault say \"battery\" deal\nspans two lines";
+    repo.put_node(Node::new(
+        9,
+        deterministic_embedding("EV production", MODEL_ID, DIMS),
+        tricky.to_string(),
+    ))
+    .await
+    .unwrap();
+    let engine = QueryEngine::new(repo);
+    let request = QueryRequest::parse_json(
+        r#"{"query": "EV production", "search_mode": "local", "top_k": 5}"#,
+    )
+    .unwrap();
+    let response = engine.execute(request).await.unwrap();
+    assert!(response
+        .evidence
+        .nodes
+        .iter()
+        .any(|node| node.data == tricky));
+
+    let csv = response.to_csv(&EvidenceField::ALL);
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .unwrap()
+        .iter()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(
+        headers,
+        vec!["id", "score", "hop", "confidence", "source", "data"]
+    );
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), response.evidence.nodes.len());
+    for (row, node) in rows.iter().zip(&response.evidence.nodes) {
+        assert_eq!(row[0].parse::<u64>().unwrap(), node.id);
+        assert_eq!(row[1].parse::<f32>().unwrap(), node.score);
+        assert_eq!(row[2].parse::<u8>().unwrap(), node.hop);
+        assert_eq!(row[3].parse::<f32>().unwrap(), node.confidence);
+        assert_eq!(&row[4], node.provenance.source.as_deref().unwrap_or(""));
+        assert_eq!(&row[5], node.data);
+    }
+
+    let tsv = response.to_tsv(&[EvidenceField::Id, EvidenceField::Data]);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_reader(tsv.as_bytes());
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    let tricky_row = rows.iter().find(|row| &row[0] == "9").unwrap();
+    assert_eq!(tricky_row.len(), 2);
+    assert_eq!(&tricky_row[1], tricky);
+}
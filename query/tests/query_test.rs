@@ -1,8 +1,15 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-use alayasiki_core::embedding::deterministic_embedding;
+use alayasiki_core::embedding::{cosine_similarity, deterministic_embedding};
 use alayasiki_core::model::{Edge, Node};
-use query::{QueryEngine, QueryMode, QueryPlanner, QueryRequest, SearchMode};
+use query::dsl::{HopPenalty, QueryValidationError, SearchAccuracy, TokenizerLanguage};
+use query::semantic_cache::SemanticCacheConfig;
+use query::Tokenizer;
+use query::{
+    ExclusionCode, LexicalFieldWeights, QueryEngine, QueryError, QueryLimits, QueryMode,
+    QueryPlanner, QueryRequest, SearchMode, QUERY_PLAN_LOG_TARGET,
+};
 use storage::repo::Repository;
 use tempfile::TempDir;
 
@@ -74,8 +81,12 @@ fn test_json_dsl_parser_defaults_and_validation() {
     assert_eq!(request.mode, QueryMode::Answer);
     assert_eq!(request.search_mode, SearchMode::Auto);
     assert_eq!(request.traversal.depth, 1);
+    assert_eq!(request.search_accuracy, SearchAccuracy::Approximate);
     assert!(request.validate().is_ok());
 
+    let exact = QueryRequest::parse_json(r#"{"query":"x","search_accuracy":"exact"}"#).unwrap();
+    assert_eq!(exact.search_accuracy, SearchAccuracy::Exact);
+
     let invalid = QueryRequest::parse_json(r#"{"query":"x","top_k":0}"#).unwrap();
     assert!(invalid.validate().is_err());
 
@@ -83,6 +94,96 @@ fn test_json_dsl_parser_defaults_and_validation() {
     assert!(unknown_mode.is_err());
 }
 
+#[test]
+fn test_query_request_builder_matches_parsed_json() {
+    use query::dsl::QueryFilters;
+
+    let built = QueryRequest::builder()
+        .query("Toyota EV strategy")
+        .mode(QueryMode::Evidence)
+        .search_mode(SearchMode::Local)
+        .top_k(5)
+        .traversal_depth(2)
+        .filters(QueryFilters {
+            entity_type: vec!["Company".to_string()],
+            ..QueryFilters::default()
+        })
+        .model_id("embedding-default-v1")
+        .snapshot_id("wal-lsn-3")
+        .build()
+        .unwrap();
+    let parsed = QueryRequest::parse_json(
+        r#"{
+            "query": "Toyota EV strategy",
+            "mode": "evidence",
+            "search_mode": "local",
+            "top_k": 5,
+            "traversal": {"depth": 2},
+            "filters": {"entity_type": ["Company"]},
+            "model_id": "embedding-default-v1",
+            "snapshot_id": "wal-lsn-3"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(built, parsed);
+
+    assert_eq!(
+        QueryRequest::builder().query("x").top_k(0).build(),
+        Err(QueryValidationError::InvalidTopK(1_000))
+    );
+}
+
+#[test]
+fn test_validation_rejects_over_length_query_and_oversized_filter_list() {
+    let long_query = QueryRequest {
+        query: "a".repeat(8_193),
+        ..QueryRequest::default()
+    };
+    assert_eq!(
+        long_query.validate(),
+        Err(QueryValidationError::QueryTooLong(8_192))
+    );
+
+    let mut many_filters = QueryRequest {
+        query: "x".to_string(),
+        ..QueryRequest::default()
+    };
+    many_filters.filters.entity_type = (0..5).map(|i| format!("type-{i}")).collect();
+    let limits = QueryLimits {
+        max_filter_values: 4,
+        ..QueryLimits::default()
+    };
+    assert!(many_filters.validate().is_ok());
+    assert_eq!(
+        many_filters.validate_with_limits(&limits),
+        Err(QueryValidationError::TooManyValues {
+            field: "filters.entity_type",
+            max: 4,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_engine_enforces_configured_request_limits() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo).with_request_limits(QueryLimits {
+        max_query_chars: 8,
+        ..QueryLimits::default()
+    });
+
+    let err = engine
+        .execute(QueryRequest {
+            query: "Toyota EV strategy".to_string(),
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap_err();
+    match err {
+        QueryError::InvalidQuery(message) => assert!(message.contains("at most 8 characters")),
+        other => panic!("expected InvalidQuery, got {other:?}"),
+    }
+}
+
 #[test]
 fn test_query_planner_auto_mode_chooses_global_for_theme_queries() {
     let request = QueryRequest::parse_json(
@@ -98,6 +199,183 @@ fn test_query_planner_auto_mode_chooses_global_for_theme_queries() {
     );
 }
 
+#[test]
+fn test_query_planner_clamps_depth_to_configured_maximum() {
+    let request = QueryRequest::parse_json(
+        r#"{"query":"EV strategy","search_mode":"local","traversal":{"depth":20}}"#,
+    )
+    .unwrap();
+
+    let plan = QueryPlanner::plan_with_max_depth(&request, 4);
+    assert_eq!(plan.expansion_depth, 4);
+    assert!(plan.depth_capped);
+
+    let shallow = QueryRequest::parse_json(
+        r#"{"query":"EV strategy","search_mode":"local","traversal":{"depth":3}}"#,
+    )
+    .unwrap();
+    let plan = QueryPlanner::plan_with_max_depth(&shallow, 4);
+    assert_eq!(plan.expansion_depth, 3);
+    assert!(!plan.depth_capped);
+}
+
+#[tokio::test]
+async fn test_engine_reports_depth_capped_for_deep_requests() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo).with_max_expansion_depth(2);
+
+    for search_mode in ["local", "drift"] {
+        let request = QueryRequest::parse_json(&format!(
+            r#"{{"query":"Toyota EV strategy","search_mode":"{search_mode}","traversal":{{"depth":20}}}}"#
+        ))
+        .unwrap();
+        let response = engine.execute(request).await.unwrap();
+        assert!(
+            response
+                .explain
+                .steps
+                .iter()
+                .any(|step| step == "depth_capped"),
+            "{search_mode}: {:?}",
+            response.explain.steps
+        );
+        assert!(response
+            .explain
+            .expansion_paths
+            .iter()
+            .all(|path| path.path.len() <= 3));
+    }
+}
+
+#[derive(Clone, Default)]
+struct CapturedLog(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl CapturedLog {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn run_with_log_level(
+    engine: &QueryEngine,
+    request: QueryRequest,
+    level: tracing::Level,
+) -> String {
+    let log = CapturedLog::default();
+    let writer = log.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+    engine.execute(request).await.unwrap();
+    log.contents()
+}
+
+#[tokio::test]
+async fn test_plan_logging_emits_fingerprinted_debug_record() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo).with_plan_logging(true);
+    let request = QueryRequest::parse_json(
+        r#"{"query":"Toyota EV strategy","search_mode":"local","top_k":3}"#,
+    )
+    .unwrap();
+
+    let debug_log = run_with_log_level(&engine, request.clone(), tracing::Level::DEBUG).await;
+    let record = debug_log
+        .lines()
+        .find(|line| line.contains(QUERY_PLAN_LOG_TARGET))
+        .unwrap_or_else(|| panic!("no plan record in {debug_log:?}"));
+    for field in [
+        "fingerprint=",
+        "search_mode=Local",
+        "vector_top_k=",
+        "expansion_depth=",
+        "anchor_count=",
+        "evidence_count=",
+        "groundedness=",
+    ] {
+        assert!(record.contains(field), "missing {field}: {record}");
+    }
+    assert!(record.contains("outcome=\"executed\""), "{record}");
+    assert!(!record.contains("Toyota EV strategy"), "{record}");
+
+    // Cache hits and dry runs are logged too, tagged with their outcome.
+    let cached_log = run_with_log_level(&engine, request.clone(), tracing::Level::DEBUG).await;
+    assert!(
+        cached_log.contains("outcome=\"semantic_cache_hit\""),
+        "{cached_log}"
+    );
+    let mut dry_run = request.clone();
+    dry_run.dry_run = true;
+    let dry_run_log = run_with_log_level(&engine, dry_run, tracing::Level::DEBUG).await;
+    assert!(dry_run_log.contains("outcome=\"dry_run\""), "{dry_run_log}");
+
+    let info_log = run_with_log_level(&engine, request, tracing::Level::INFO).await;
+    assert!(!info_log.contains(QUERY_PLAN_LOG_TARGET), "{info_log}");
+}
+
+#[tokio::test]
+async fn test_min_answer_groundedness_withholds_weak_answers() {
+    let (_dir, repo) = seeded_repo().await;
+    let strong = || {
+        QueryRequest::parse_json(
+            r#"{"query":"Toyota EV strategy","mode":"answer","top_k":10,"traversal":{"depth":2}}"#,
+        )
+        .unwrap()
+    };
+    let weak = || {
+        QueryRequest::parse_json(
+            r#"{"query":"gardening tips","mode":"answer","top_k":1,"traversal":{"depth":1}}"#,
+        )
+        .unwrap()
+    };
+
+    let ungated = QueryEngine::new(repo.clone());
+    let strong_groundedness = ungated.execute(strong()).await.unwrap().groundedness;
+    let weak_response = ungated.execute(weak()).await.unwrap();
+    assert!(weak_response.answer.is_some());
+    assert!(weak_response.groundedness < strong_groundedness);
+    let threshold = (weak_response.groundedness + strong_groundedness) / 2.0;
+
+    let engine = QueryEngine::new(repo).with_min_answer_groundedness(threshold);
+    let response = engine.execute(weak()).await.unwrap();
+    assert!(response.answer.is_none());
+    assert_eq!(response.result_status, query::ResultStatus::Found);
+    assert_eq!(response.evidence.nodes, weak_response.evidence.nodes);
+    assert!(response
+        .explain
+        .steps
+        .contains(&query::ANSWER_SUPPRESSED_STEP.to_string()));
+
+    let response = engine.execute(strong()).await.unwrap();
+    assert!(response.answer.is_some());
+
+    // A request-level threshold overrides the engine default.
+    let mut lenient = weak();
+    lenient.min_answer_groundedness = Some(0.0);
+    assert!(engine.execute(lenient).await.unwrap().answer.is_some());
+
+    let mut invalid = strong();
+    invalid.min_answer_groundedness = Some(1.5);
+    assert!(matches!(
+        engine.execute(invalid).await.unwrap_err(),
+        QueryError::InvalidQuery(_)
+    ));
+}
+
 #[tokio::test]
 async fn test_query_mode_switch_between_answer_and_evidence() {
     let (_dir, repo) = seeded_repo().await;
@@ -134,6 +412,46 @@ async fn test_query_mode_switch_between_answer_and_evidence() {
     assert!(!evidence_response.evidence.nodes.is_empty());
 }
 
+#[tokio::test]
+async fn test_count_mode_reports_matches_without_evidence() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo);
+    let request = |mode: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "EV戦略の比較",
+                "mode": "{mode}",
+                "search_mode": "local",
+                "top_k": 10,
+                "traversal": {{"depth": 3}},
+                "filters": {{"entity_type": ["Company"]}}
+            }}"#
+        ))
+        .unwrap()
+    };
+
+    let evidence = engine.execute(request("evidence")).await.unwrap();
+    assert!(evidence.counts.is_none());
+    let count = engine.execute(request("count")).await.unwrap();
+
+    assert!(count.answer.is_none());
+    assert!(count.evidence.nodes.is_empty());
+    assert!(count.evidence.edges.is_empty());
+    assert!(count.citations.is_empty());
+    let counts = count.counts.unwrap();
+    assert!(counts.matched > 0);
+    assert_eq!(counts.matched, evidence.evidence.nodes.len());
+    assert_eq!(counts.edges, evidence.evidence.edges.len());
+    assert_eq!(
+        counts.excluded.values().sum::<usize>(),
+        evidence.explain.exclusions.len()
+    );
+    assert!(counts
+        .excluded
+        .get("entity_type_filtered")
+        .is_some_and(|count| *count > 0));
+}
+
 #[tokio::test]
 async fn test_query_engine_returns_explain_plan_with_anchors_paths_and_exclusions() {
     let (_dir, repo) = seeded_repo().await;
@@ -203,6 +521,265 @@ async fn test_query_engine_applies_entity_and_time_range_filters() {
         .any(|ex| ex.node_id == Some(3)));
 }
 
+#[tokio::test]
+async fn test_query_engine_time_range_targets_configured_field() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("time_field.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, embedding, ingested_at, published_on) in [
+        (
+            1,
+            vec![1.0, 0.0],
+            Some("2025-03-01T10:00:00Z"),
+            Some("2023-06-01"),
+        ),
+        (2, vec![0.9, 0.1], Some("2024-06-01T23:30:00+09:00"), None),
+        (3, vec![0.8, 0.2], None, Some("2023-07-15")),
+    ] {
+        let mut node = Node::new(id, embedding, format!("EV market report {id}"));
+        node.metadata
+            .insert("timestamp".to_string(), "2024-02-10".to_string());
+        if let Some(value) = ingested_at {
+            node.metadata
+                .insert("ingested_at".to_string(), value.to_string());
+        }
+        if let Some(value) = published_on {
+            node.metadata
+                .insert("published_on".to_string(), value.to_string());
+        }
+        repo.put_node(node).await.unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "related_to", 0.9))
+        .await
+        .unwrap();
+    repo.put_edge(Edge::new(1, 3, "related_to", 0.9))
+        .await
+        .unwrap();
+    let engine = QueryEngine::new(repo);
+
+    let run = |time_range: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "EV market report",
+                "top_k": 10,
+                "traversal": {{"depth": 2}},
+                "filters": {{"time_range": {time_range}}}
+            }}"#
+        ))
+        .unwrap()
+    };
+    let kept_and_filtered = |response: &query::QueryResponse| {
+        let mut kept: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+        kept.sort_unstable();
+        let mut filtered: Vec<u64> = response
+            .explain
+            .exclusions
+            .iter()
+            .filter(|ex| ex.code == query::ExclusionCode::TimeRangeFiltered)
+            .filter_map(|ex| ex.node_id)
+            .collect();
+        filtered.sort_unstable();
+        (kept, filtered)
+    };
+
+    let by_ingestion = engine
+        .execute(run(
+            r#"{"from": "2025-01-01", "to": "2025-12-31", "time_field": "ingested_at"}"#,
+        ))
+        .await
+        .unwrap();
+    // Node 2 was ingested in 2024; node 3 has no `ingested_at` at all.
+    assert_eq!(kept_and_filtered(&by_ingestion), (vec![1], vec![2, 3]));
+
+    let by_custom_field = engine
+        .execute(run(
+            r#"{"from": "2023-01-01", "to": "2023-12-31", "time_field": "published_on"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(kept_and_filtered(&by_custom_field), (vec![1, 3], vec![2]));
+
+    // The default field is still `timestamp`.
+    let by_timestamp = engine
+        .execute(run(r#"{"from": "2024-01-01", "to": "2024-12-31"}"#))
+        .await
+        .unwrap();
+    assert_eq!(kept_and_filtered(&by_timestamp), (vec![1, 2, 3], vec![]));
+}
+
+#[tokio::test]
+async fn test_query_engine_time_range_compares_rfc3339_instants() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("time_instants.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, embedding, timestamp) in [
+        (1, vec![1.0, 0.0], "2024-06-01T08:00:00Z"),
+        (2, vec![0.9, 0.1], "2024-06-01T13:00:00Z"),
+        (3, vec![0.8, 0.2], "2024-06-01"),
+        (4, vec![0.7, 0.3], "2024-05-31T23:59:59Z"),
+    ] {
+        let mut node = Node::new(id, embedding, format!("EV market report {id}"));
+        node.metadata
+            .insert("timestamp".to_string(), timestamp.to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+
+    // `from` is midnight UTC written with an offset; a bare-date value is
+    // midnight UTC, and node 2 falls on the same day but after `to`.
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query": "EV market report",
+            "top_k": 10,
+            "filters": {"time_range": {"from": "2024-06-01T09:00:00+09:00", "to": "2024-06-01T12:00:00Z"}}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(request.validate(), Ok(()));
+    let response = engine.execute(request).await.unwrap();
+    let mut kept: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+    kept.sort_unstable();
+    assert_eq!(kept, vec![1, 3]);
+
+    let reversed = QueryRequest::parse_json(
+        r#"{"query": "EV market report", "filters": {"time_range": {"from": "2024-06-01T12:00:00Z", "to": "2024-06-01T11:00:00Z"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        reversed.validate(),
+        Err(QueryValidationError::InvalidTimeRangeOrder)
+    );
+}
+
+#[tokio::test]
+async fn test_query_engine_time_range_supports_open_and_exclusive_bounds() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("time_bounds.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, embedding, timestamp) in [
+        (1, vec![1.0, 0.0], "2023-12-31"),
+        (2, vec![0.9, 0.1], "2024-01-01"),
+        (3, vec![0.8, 0.2], "2024-06-30"),
+    ] {
+        let mut node = Node::new(id, embedding, format!("EV market report {id}"));
+        node.metadata
+            .insert("timestamp".to_string(), timestamp.to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+
+    let kept = |time_range: &str| {
+        let request = QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "EV market report",
+                "top_k": 10,
+                "filters": {{"time_range": {time_range}}}
+            }}"#
+        ))
+        .unwrap();
+        let engine = &engine;
+        async move {
+            let response = engine.execute(request).await.unwrap();
+            let mut ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+            ids.sort_unstable();
+            ids
+        }
+    };
+
+    assert_eq!(kept(r#"{"from": "2024-01-01"}"#).await, vec![2, 3]);
+    assert_eq!(kept(r#"{"to": "2024-01-01"}"#).await, vec![1, 2]);
+    assert_eq!(
+        kept(r#"{"to": "2024-01-01", "inclusive_end": false}"#).await,
+        vec![1]
+    );
+    assert_eq!(
+        kept(r#"{"from": "2024-01-01", "to": "2024-06-30", "inclusive_start": false}"#).await,
+        vec![3]
+    );
+
+    let empty = QueryRequest::parse_json(
+        r#"{"query": "EV market report", "filters": {"time_range": {"time_field": "timestamp"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        empty.validate(),
+        Err(QueryValidationError::MissingTimeRangeBound)
+    );
+    assert!(matches!(
+        engine.execute(empty).await.unwrap_err(),
+        QueryError::InvalidQuery(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_explain_vector_reports_dimension_contributions() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("explain_vector.wal"))
+            .await
+            .unwrap(),
+    );
+    let dims = 4;
+    let embedding = deterministic_embedding("battery supply", "embedding-default-v1", dims);
+    repo.put_node(Node::new(
+        1,
+        embedding.clone(),
+        "battery supply".to_string(),
+    ))
+    .await
+    .unwrap();
+    repo.put_node(Node::new(
+        2,
+        vec![0.5, -0.5, 0.5, -0.5],
+        "unrelated".to_string(),
+    ))
+    .await
+    .unwrap();
+    let engine = QueryEngine::new(repo);
+
+    let request = |explain_vector: bool| QueryRequest {
+        query: "battery supply chain".to_string(),
+        explain_vector,
+        ..QueryRequest::default()
+    };
+
+    let response = engine.execute(request(false)).await.unwrap();
+    assert!(response.explain.vector_explain.is_none());
+
+    let response = engine.execute(request(true)).await.unwrap();
+    let explanation = response.explain.vector_explain.expect("vector explanation");
+    assert_eq!(explanation.node_id, response.explain.anchors[0].node_id);
+    assert_eq!(explanation.dimensions.len(), dims);
+
+    let query_embedding =
+        deterministic_embedding("battery supply chain", "embedding-default-v1", dims);
+    let node_embedding = if explanation.node_id == 1 {
+        embedding
+    } else {
+        vec![0.5, -0.5, 0.5, -0.5]
+    };
+    let expected = cosine_similarity(&query_embedding, &node_embedding).unwrap();
+    assert!((explanation.similarity - expected).abs() < 1e-5);
+    let total: f32 = explanation
+        .dimensions
+        .iter()
+        .map(|dimension| dimension.contribution)
+        .sum();
+    assert!((total - expected).abs() < 1e-5);
+    for pair in explanation.dimensions.windows(2) {
+        assert!(pair[0].contribution.abs() >= pair[1].contribution.abs());
+    }
+}
+
 #[tokio::test]
 async fn test_query_engine_uses_model_id_for_vector_search() {
     let dir = tempfile::tempdir().unwrap();
@@ -259,6 +836,172 @@ async fn test_query_engine_uses_model_id_for_vector_search() {
     );
 }
 
+#[tokio::test]
+async fn test_query_embedding_cache_reuses_identical_query_embedding() {
+    let (_dir, repo) = seeded_repo().await;
+    let spy_engine = |capacity: usize| {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let spy_calls = calls.clone();
+        let engine = QueryEngine::new(repo.clone())
+            .with_semantic_cache_config(SemanticCacheConfig {
+                enabled: false,
+                ..SemanticCacheConfig::default()
+            })
+            .with_embedding_cache_capacity(capacity)
+            .with_query_embed_fn(Arc::new(move |text, model_id, dims| {
+                spy_calls.fetch_add(1, Ordering::SeqCst);
+                deterministic_embedding(text, model_id, dims)
+            }));
+        (engine, calls)
+    };
+    let request = |query: &str| QueryRequest {
+        query: query.to_string(),
+        top_k: 2,
+        ..QueryRequest::default()
+    };
+
+    let (engine, calls) = spy_engine(8);
+    let first = engine
+        .execute(request("battery partnerships"))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let second = engine
+        .execute(request("battery partnerships"))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first.evidence, second.evidence);
+    engine.execute(request("solid state cells")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let (uncached, calls) = spy_engine(0);
+    uncached
+        .execute(request("battery partnerships"))
+        .await
+        .unwrap();
+    uncached
+        .execute(request("battery partnerships"))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_language_hint_changes_lexical_scores() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("language_hint.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, data) in [
+        (1, "anchor"),
+        (2, "denki jidosha report"),
+        (3, "トヨタ電気自動車の計画"),
+    ] {
+        repo.put_node(Node::new(id, vec![1.0, 0.0], data.to_string()))
+            .await
+            .unwrap();
+    }
+    for target in [2, 3] {
+        repo.put_edge(Edge::new(1, target, "related_to", 1.0))
+            .await
+            .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+    let scores = |language: Option<TokenizerLanguage>| {
+        let engine = &engine;
+        async move {
+            let request = QueryRequest {
+                query: "denkijidosha トヨタ電気".to_string(),
+                anchor_ids: vec![1],
+                language,
+                ..QueryRequest::default()
+            };
+            let response = engine.execute(request).await.unwrap();
+            let score = |id: u64| {
+                response
+                    .evidence
+                    .nodes
+                    .iter()
+                    .find(|node| node.id == id)
+                    .unwrap()
+                    .score
+            };
+            (score(2), score(3))
+        }
+    };
+
+    let (romanized, japanese) = scores(None).await;
+    let (auto_romanized, auto_japanese) = scores(Some(TokenizerLanguage::Auto)).await;
+    assert_eq!((romanized, japanese), (auto_romanized, auto_japanese));
+    // Auto-detection leaves the romanized word whole, so it shares no token
+    // with "denki jidosha" and only the Japanese node gets lexical credit.
+    assert!(japanese > romanized);
+
+    let (cjk_romanized, _) = scores(Some(TokenizerLanguage::Cjk)).await;
+    assert!(
+        cjk_romanized > romanized,
+        "cjk bigrams should match romanized text"
+    );
+
+    let (_, whitespace_japanese) = scores(Some(TokenizerLanguage::Whitespace)).await;
+    assert!(
+        whitespace_japanese < japanese,
+        "whole-word tokens should lose the Japanese n-gram overlap"
+    );
+
+    let parsed = QueryRequest::parse_json(r#"{"query": "x", "language": "cjk"}"#).unwrap();
+    assert_eq!(parsed.language, Some(TokenizerLanguage::Cjk));
+}
+
+#[tokio::test]
+async fn test_stemming_tokenizer_credits_morphological_variants() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("stemming.wal"))
+            .await
+            .unwrap(),
+    );
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "anchor".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(
+        2,
+        vec![1.0, 0.0],
+        "solid state batteries".to_string(),
+    ))
+    .await
+    .unwrap();
+    repo.put_edge(Edge::new(1, 2, "related_to", 1.0))
+        .await
+        .unwrap();
+
+    let score = |tokenizer: Tokenizer| {
+        let engine = QueryEngine::new(repo.clone()).with_tokenizer(tokenizer);
+        async move {
+            let request = QueryRequest {
+                query: "battery".to_string(),
+                anchor_ids: vec![1],
+                ..QueryRequest::default()
+            };
+            let response = engine.execute(request).await.unwrap();
+            response
+                .evidence
+                .nodes
+                .iter()
+                .find(|node| node.id == 2)
+                .unwrap()
+                .score
+        }
+    };
+
+    let raw = score(Tokenizer::new()).await;
+    let stemmed = score(Tokenizer::new().with_stemming(true)).await;
+    assert!(stemmed > raw, "stemmed {stemmed} <= raw {raw}");
+}
+
 #[tokio::test]
 async fn test_query_engine_keeps_japanese_lexical_signal() {
     let dir = tempfile::tempdir().unwrap();
@@ -320,3 +1063,1454 @@ async fn test_query_engine_keeps_japanese_lexical_signal() {
         "Unrelated Japanese text should be pruned by top_k"
     );
 }
+
+#[tokio::test]
+async fn test_min_anchor_score_drops_weak_anchors() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("anchor_threshold.wal"))
+            .await
+            .unwrap(),
+    );
+    let query_embedding = deterministic_embedding("battery recall", "embedding-default-v1", 8);
+    let opposite: Vec<f32> = query_embedding.iter().map(|value| -value).collect();
+    repo.put_node(Node::new(
+        1,
+        query_embedding,
+        "Battery recall notice".to_string(),
+    ))
+    .await
+    .unwrap();
+    for (id, data) in [
+        (2, "Weather outlook"),
+        (3, "Travel plans"),
+        (4, "Recall FAQ"),
+    ] {
+        repo.put_node(Node::new(id, opposite.clone(), data.to_string()))
+            .await
+            .unwrap();
+    }
+    repo.put_edge(Edge::new(1, 4, "related_to", 1.0))
+        .await
+        .unwrap();
+    repo.put_edge(Edge::new(2, 3, "related_to", 1.0))
+        .await
+        .unwrap();
+    let engine = QueryEngine::new(repo);
+
+    let request = |extra: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "battery recall",
+                "mode": "evidence",
+                "search_mode": "local",
+                "top_k": 5,
+                "traversal": {{"depth": 1}}{extra}
+            }}"#
+        ))
+        .unwrap()
+    };
+    let evidence_ids = |response: &query::QueryResponse| {
+        let mut ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let unfiltered = engine.execute(request("")).await.unwrap();
+    assert_eq!(unfiltered.explain.anchors.len(), 4);
+    assert_eq!(evidence_ids(&unfiltered), vec![1, 2, 3, 4]);
+
+    let filtered = engine
+        .execute(request(r#", "min_anchor_score": 0.5"#))
+        .await
+        .unwrap();
+    let anchors: Vec<u64> = filtered
+        .explain
+        .anchors
+        .iter()
+        .map(|anchor| anchor.node_id)
+        .collect();
+    assert_eq!(anchors, vec![1]);
+    assert_eq!(evidence_ids(&filtered), vec![1, 4]);
+    let mut below: Vec<u64> = filtered
+        .explain
+        .exclusions
+        .iter()
+        .filter(|e| e.reason == "anchor_below_threshold")
+        .filter_map(|e| e.node_id)
+        .collect();
+    below.sort_unstable();
+    assert_eq!(below, vec![2, 3, 4]);
+
+    // With every hit below the threshold, the single fallback anchor is only
+    // used when `require_match` is off.
+    let fallback = engine
+        .execute(request(r#", "min_anchor_score": 1.5"#))
+        .await
+        .unwrap();
+    assert_eq!(fallback.explain.anchors.len(), 1);
+    let strict = engine
+        .execute(request(
+            r#", "min_anchor_score": 1.5, "require_match": true"#,
+        ))
+        .await
+        .unwrap();
+    assert!(strict.evidence.nodes.is_empty());
+    assert!(strict
+        .explain
+        .exclusions
+        .iter()
+        .any(|e| e.reason == "no_vector_match"));
+
+    let invalid = QueryRequest {
+        query: "x".to_string(),
+        min_anchor_score: Some(f32::NAN),
+        ..QueryRequest::default()
+    };
+    assert_eq!(
+        invalid.validate(),
+        Err(QueryValidationError::InvalidMinAnchorScore)
+    );
+}
+
+#[tokio::test]
+async fn test_path_weight_hop_penalty_favors_strong_multi_hop_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("hop_penalty.wal"))
+            .await
+            .unwrap(),
+    );
+    // 1 -(0.2)-> 2 is a weak single hop; 1 -(0.95)-> 3 -(0.95)-> 4 is a
+    // strong two-hop path.
+    for (id, data) in [(1, "alpha"), (2, "beta"), (3, "gamma"), (4, "delta")] {
+        repo.put_node(Node::new(id, vec![1.0, 0.0], data.to_string()))
+            .await
+            .unwrap();
+    }
+    for (source, target, weight) in [(1, 2, 0.2), (1, 3, 0.95), (3, 4, 0.95)] {
+        repo.put_edge(Edge::new(source, target, "related_to", weight))
+            .await
+            .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+    let rank = |hop_penalty: &'static str| {
+        let engine = &engine;
+        async move {
+            let request = QueryRequest::parse_json(&format!(
+                r#"{{
+                    "query": "unrelated words",
+                    "mode": "evidence",
+                    "search_mode": "local",
+                    "top_k": 4,
+                    "anchor_ids": [1],
+                    "traversal": {{"depth": 2, "hop_penalty": "{hop_penalty}"}}
+                }}"#
+            ))
+            .unwrap();
+            let response = engine.execute(request).await.unwrap();
+            let position = |id: u64| {
+                response
+                    .evidence
+                    .nodes
+                    .iter()
+                    .position(|node| node.id == id)
+                    .unwrap()
+            };
+            (position(2), position(4))
+        }
+    };
+
+    let (weak_one_hop, strong_two_hop) = rank("hop_count").await;
+    assert!(weak_one_hop < strong_two_hop);
+    let (weak_one_hop, strong_two_hop) = rank("path_weight").await;
+    assert!(strong_two_hop < weak_one_hop);
+
+    let default_request = QueryRequest::parse_json(r#"{"query": "x"}"#).unwrap();
+    assert_eq!(default_request.traversal.hop_penalty, HopPenalty::HopCount);
+}
+
+#[tokio::test]
+async fn test_metadata_filter_matches_nested_json_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("metadata_filter.wal"))
+            .await
+            .unwrap(),
+    );
+    let authored = |id: u64, author: Option<&str>| {
+        let mut node = Node::new(id, vec![1.0, 0.0], format!("report {id}"));
+        node.metadata
+            .insert("region".to_string(), "apac".to_string());
+        if let Some(author) = author {
+            node.metadata
+                .insert("author".to_string(), author.to_string());
+        }
+        node
+    };
+    for node in [
+        authored(
+            1,
+            Some(r#"{"name": "Sato", "country": "JP", "tags": ["ev", "battery"]}"#),
+        ),
+        authored(2, Some(r#"{"name": "Smith", "country": "US"}"#)),
+        authored(3, None),
+        authored(
+            4,
+            Some(r#"{"name": "Tanaka", "country": "JP", "papers": 12}"#),
+        ),
+    ] {
+        repo.put_node(node).await.unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+    let run = |filter: &'static str| {
+        let engine = &engine;
+        async move {
+            let request = QueryRequest::parse_json(&format!(
+                r#"{{
+                    "query": "report",
+                    "mode": "evidence",
+                    "search_mode": "local",
+                    "top_k": 10,
+                    "filters": {{"metadata": {filter}}}
+                }}"#
+            ))
+            .unwrap();
+            engine.execute(request).await.unwrap()
+        }
+    };
+    let evidence_ids = |response: &query::QueryResponse| {
+        let mut ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let japanese = run(r#"{"author.country": "JP"}"#).await;
+    assert_eq!(evidence_ids(&japanese), vec![1, 4]);
+    let mut filtered: Vec<u64> = japanese
+        .explain
+        .exclusions
+        .iter()
+        .filter(|e| e.code == ExclusionCode::MetadataFiltered)
+        .filter_map(|e| e.node_id)
+        .collect();
+    filtered.sort_unstable();
+    assert_eq!(
+        filtered,
+        vec![2, 3],
+        "missing and mismatched paths are excluded"
+    );
+
+    let nested = run(r#"{"author.tags.1": "battery", "region": "apac"}"#).await;
+    assert_eq!(evidence_ids(&nested), vec![1]);
+    let numeric = run(r#"{"author.papers": "12"}"#).await;
+    assert_eq!(evidence_ids(&numeric), vec![4]);
+    let missing = run(r#"{"author.affiliation.name": "Toyota"}"#).await;
+    assert!(missing.evidence.nodes.is_empty());
+
+    let invalid = QueryRequest::parse_json(
+        r#"{"query": "report", "filters": {"metadata": {"author..country": "JP"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        invalid.validate(),
+        Err(QueryValidationError::InvalidMetadataFilter)
+    );
+}
+
+#[tokio::test]
+async fn test_query_engine_expands_from_explicit_anchor_ids_only() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    // The query text matches Toyota (node 1) best; explicit anchors must win anyway.
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query": "Toyota EV production",
+            "mode": "evidence",
+            "search_mode": "local",
+            "traversal": {"depth": 1},
+            "anchor_ids": [2, 99]
+        }"#,
+    )
+    .unwrap();
+
+    let response = engine.execute(request).await.unwrap();
+    assert_eq!(
+        response.explain.steps,
+        vec!["explicit_anchors", "graph_expansion", "context_pruning"]
+    );
+    let anchor_ids: Vec<u64> = response
+        .explain
+        .anchors
+        .iter()
+        .map(|anchor| anchor.node_id)
+        .collect();
+    assert_eq!(anchor_ids, vec![2]);
+
+    let mut node_ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+    node_ids.sort_unstable();
+    assert_eq!(node_ids, vec![2, 3]);
+    assert!(response
+        .explain
+        .exclusions
+        .iter()
+        .any(|e| e.node_id == Some(99) && e.reason == "anchor_not_found"));
+}
+
+#[tokio::test]
+async fn test_undirected_graph_semantics_expand_through_reverse_edges() {
+    use storage::community::{CommunityEngine, DeterministicSummarizer};
+    use storage::index::GraphSemantics;
+
+    let (dir, repo) = seeded_repo().await;
+    // Edges run 1 -> 2 -> 3, so node 3 has no outgoing edges.
+    repo.update_edge_metadata(
+        2,
+        3,
+        "influenced_by",
+        std::collections::HashMap::from([
+            ("source".to_string(), "s3://policy/brief".to_string()),
+            ("confidence".to_string(), "0.4".to_string()),
+        ]),
+        Vec::new(),
+    )
+    .await
+    .unwrap();
+    let request = || {
+        QueryRequest::parse_json(
+            r#"{
+                "query": "battery recycling",
+                "mode": "evidence",
+                "search_mode": "local",
+                "traversal": {"depth": 2},
+                "anchor_ids": [3]
+            }"#,
+        )
+        .unwrap()
+    };
+    let evidence_ids = |response: query::QueryResponse| {
+        let mut ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let directed = QueryEngine::new(repo.clone());
+    assert_eq!(
+        evidence_ids(directed.execute(request()).await.unwrap()),
+        vec![3]
+    );
+    drop(directed);
+    drop(repo);
+
+    let repo = Arc::new(
+        Repository::open(dir.path().join("query.wal"))
+            .await
+            .unwrap()
+            .with_graph_semantics(GraphSemantics::Undirected),
+    );
+    let response = QueryEngine::new(repo.clone())
+        .execute(request())
+        .await
+        .unwrap();
+    // Reverse-followed edges are reported as stored, with their metadata.
+    let mut edges: Vec<(u64, u64, &str)> = response
+        .evidence
+        .edges
+        .iter()
+        .map(|edge| (edge.source, edge.target, edge.relation.as_str()))
+        .collect();
+    edges.sort_unstable();
+    assert_eq!(
+        edges,
+        vec![(1, 2, "competitor_of"), (2, 3, "influenced_by")]
+    );
+    let influenced = response
+        .evidence
+        .edges
+        .iter()
+        .find(|edge| edge.relation == "influenced_by")
+        .unwrap();
+    assert_eq!(
+        influenced.provenance.source.as_deref(),
+        Some("s3://policy/brief")
+    );
+    assert_eq!(influenced.confidence, 0.4);
+    let reached = evidence_ids(response);
+    assert_eq!(reached, vec![1, 2, 3]);
+
+    let graph = repo.graph_index().await;
+    assert_eq!(graph.semantics(), GraphSemantics::Undirected);
+    let mut communities = CommunityEngine::new(graph);
+    communities.rebuild_hierarchy(1, &DeterministicSummarizer);
+    let anchor_community = communities.hierarchy()[0]
+        .communities
+        .iter()
+        .find(|community| community.node_ids.contains(&3))
+        .unwrap();
+    assert!(anchor_community
+        .node_ids
+        .iter()
+        .all(|id| reached.contains(id)));
+}
+
+#[tokio::test]
+async fn test_query_engine_dry_run_returns_plan_without_evidence() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query": "Toyota EV production",
+            "search_mode": "local",
+            "dry_run": true
+        }"#,
+    )
+    .unwrap();
+    let response = engine.execute(request).await.unwrap();
+
+    assert_eq!(
+        response.explain.steps,
+        vec![
+            "dry_run",
+            "vector_search",
+            "graph_expansion",
+            "context_pruning"
+        ]
+    );
+    assert_eq!(response.explain.effective_search_mode, SearchMode::Local);
+    assert!(response.evidence.nodes.is_empty());
+    assert!(response.evidence.edges.is_empty());
+    assert!(response.explain.anchors.is_empty());
+    assert!(response.answer.is_none());
+    assert!(response.latency_ms < 50);
+
+    // Global dispatch selection is reported even though nothing executes.
+    let global = QueryRequest::parse_json(
+        r#"{"query": "overall themes", "search_mode": "auto", "dry_run": true}"#,
+    )
+    .unwrap();
+    let response = engine.execute(global).await.unwrap();
+    assert_eq!(response.explain.effective_search_mode, SearchMode::Global);
+    assert_eq!(
+        response.explain.steps.last().map(String::as_str),
+        Some("global_fallback_no_community_data")
+    );
+    assert!(response.evidence.nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_query_engine_caps_fanout_to_top_weighted_neighbors() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("fanout.wal"))
+            .await
+            .unwrap(),
+    );
+    const HUB: u64 = 100;
+    repo.put_node(Node::new(
+        HUB,
+        vec![1.0, 0.0],
+        "Battery hub entity".to_string(),
+    ))
+    .await
+    .unwrap();
+    for (id, weight) in [(1, 0.1), (2, 0.9), (3, 0.3), (4, 0.7), (5, 0.5)] {
+        repo.put_node(Node::new(id, vec![0.9, 0.1], format!("Battery spoke {id}")))
+            .await
+            .unwrap();
+        repo.put_edge(Edge::new(HUB, id, "supplies", weight))
+            .await
+            .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+
+    let request = |cap: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "battery hub",
+                "mode": "evidence",
+                "search_mode": "local",
+                "top_k": 10,
+                "traversal": {{"depth": 1{cap}}},
+                "anchor_ids": [{HUB}]
+            }}"#
+        ))
+        .unwrap()
+    };
+
+    let uncapped = engine.execute(request("")).await.unwrap();
+    let mut targets: Vec<u64> = uncapped
+        .explain
+        .expansion_paths
+        .iter()
+        .map(|path| path.target_id)
+        .collect();
+    targets.sort_unstable();
+    assert_eq!(targets, vec![1, 2, 3, 4, 5]);
+
+    let capped = engine
+        .execute(request(r#", "max_neighbors_per_node": 2"#))
+        .await
+        .unwrap();
+    let mut targets: Vec<u64> = capped
+        .explain
+        .expansion_paths
+        .iter()
+        .map(|path| path.target_id)
+        .collect();
+    targets.sort_unstable();
+    assert_eq!(targets, vec![2, 4]);
+
+    let mut capped_out: Vec<u64> = capped
+        .explain
+        .exclusions
+        .iter()
+        .filter(|e| e.reason == format!("fanout_capped:{HUB}"))
+        .filter_map(|e| e.node_id)
+        .collect();
+    capped_out.sort_unstable();
+    assert_eq!(capped_out, vec![1, 3, 5]);
+}
+
+#[tokio::test]
+async fn test_query_engine_caps_candidates_on_dense_graph() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("candidate_cap.wal"))
+            .await
+            .unwrap(),
+    );
+    const HUB: u64 = 100;
+    repo.put_node(Node::new(HUB, vec![1.0, 0.0], "Battery hub".to_string()))
+        .await
+        .unwrap();
+    for spoke in 1..=4u64 {
+        repo.put_node(Node::new(
+            spoke,
+            vec![0.9, 0.1],
+            format!("Battery hub spoke {spoke}"),
+        ))
+        .await
+        .unwrap();
+        repo.put_edge(Edge::new(HUB, spoke, "supplies", 0.9))
+            .await
+            .unwrap();
+    }
+    let fillers: Vec<u64> = (10..50).collect();
+    for &id in &fillers {
+        repo.put_node(Node::new(id, vec![0.0, 1.0], format!("Filler {id}")))
+            .await
+            .unwrap();
+    }
+    for spoke in 1..=4u64 {
+        for &id in &fillers {
+            repo.put_edge(Edge::new(spoke, id, "mentions", 0.5))
+                .await
+                .unwrap();
+        }
+    }
+    for &a in &fillers {
+        for &b in &fillers {
+            if a != b {
+                repo.put_edge(Edge::new(a, b, "related", 0.5))
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+    let engine = QueryEngine::new(repo);
+
+    let request = |cap: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "battery hub spoke",
+                "mode": "evidence",
+                "search_mode": "local",
+                "top_k": 3,
+                "traversal": {{"depth": 3{cap}}},
+                "anchor_ids": [{HUB}]
+            }}"#
+        ))
+        .unwrap()
+    };
+    let candidate_count = |response: &query::QueryResponse| {
+        response.evidence.nodes.len()
+            + response
+                .explain
+                .exclusions
+                .iter()
+                .filter(|e| e.reason == "pruned_by_top_k")
+                .count()
+    };
+    let top_ids = |response: &query::QueryResponse| -> Vec<u64> {
+        response.evidence.nodes.iter().map(|node| node.id).collect()
+    };
+
+    let uncapped = engine.execute(request("")).await.unwrap();
+    assert_eq!(candidate_count(&uncapped), 45);
+    assert!(!uncapped
+        .explain
+        .exclusions
+        .iter()
+        .any(|e| e.reason == "candidate_cap_reached"));
+
+    let capped = engine
+        .execute(request(r#", "max_candidates": 10"#))
+        .await
+        .unwrap();
+    assert_eq!(candidate_count(&capped), 10);
+    assert!(capped
+        .explain
+        .exclusions
+        .iter()
+        .any(|e| e.node_id.is_none() && e.reason == "candidate_cap_reached"));
+    // Lower-hop candidates are kept first, so the hub and all spokes survive.
+    let mut expanded: Vec<u64> = capped
+        .explain
+        .expansion_paths
+        .iter()
+        .map(|path| path.target_id)
+        .collect();
+    expanded.sort_unstable();
+    assert!(expanded.starts_with(&[1, 2, 3, 4]));
+    assert_eq!(top_ids(&capped), top_ids(&uncapped));
+
+    let invalid =
+        QueryRequest::parse_json(r#"{"query": "battery", "traversal": {"max_candidates": 0}}"#)
+            .unwrap();
+    assert_eq!(
+        invalid.validate(),
+        Err(QueryValidationError::InvalidMaxCandidates)
+    );
+}
+
+#[tokio::test]
+async fn test_query_engine_validates_embedding_model_dimension() {
+    use alayasiki_core::embedding::{EmbeddingModelError, EmbeddingModelRegistry};
+    use alayasiki_core::error::{AlayasikiError, ErrorCode};
+    use query::QueryError;
+
+    let (_dir, repo) = seeded_repo().await;
+    let mut registry = EmbeddingModelRegistry::new();
+    registry
+        .register("embedding-default-v1", 2, "1.0.0")
+        .register("embedding-wide-v1", 768, "1.0.0");
+    let engine = QueryEngine::new(repo).with_embedding_model_registry(registry);
+
+    let request = |model_id: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{"query": "Toyota EV production", "search_mode": "local", "model_id": "{model_id}"}}"#
+        ))
+        .unwrap()
+    };
+
+    let response = engine
+        .execute(request("embedding-default-v1"))
+        .await
+        .unwrap();
+    assert!(!response.evidence.nodes.is_empty());
+
+    let err = engine
+        .execute(request("embedding-wide-v1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        QueryError::EmbeddingModel(EmbeddingModelError::DimensionMismatch {
+            model_dimension: 768,
+            repository_dimension: 2,
+            ..
+        })
+    ));
+    assert_eq!(err.error_code(), ErrorCode::InvalidArgument);
+
+    let err = engine
+        .execute(request("embedding-unknown-v9"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        QueryError::EmbeddingModel(EmbeddingModelError::UnknownModel(ref id)) if id == "embedding-unknown-v9"
+    ));
+    assert_eq!(err.error_code(), ErrorCode::NotFound);
+}
+
+#[tokio::test]
+async fn test_query_engine_relation_weights_change_surviving_multihop_nodes() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("relation_weights.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, text) in [
+        (1, "Automaker"),
+        (2, "Rival one"),
+        (3, "Regulator one"),
+        (4, "Rival two"),
+        (5, "Regulator two"),
+    ] {
+        repo.put_node(Node::new(id, vec![1.0, 0.0], text.to_string()))
+            .await
+            .unwrap();
+    }
+    for (source, target, relation) in [
+        (1, 2, "competitor_of"),
+        (1, 3, "regulates"),
+        (2, 4, "competitor_of"),
+        (3, 5, "regulates"),
+    ] {
+        repo.put_edge(Edge::new(source, target, relation, 0.5))
+            .await
+            .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+
+    let request = |weights: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query": "supply network",
+                "mode": "evidence",
+                "search_mode": "local",
+                "top_k": 3,
+                "traversal": {{"depth": 2{weights}}},
+                "anchor_ids": [1]
+            }}"#
+        ))
+        .unwrap()
+    };
+    let evidence_ids = |response: &query::QueryResponse| {
+        let mut ids: Vec<u64> = response.evidence.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    // Unweighted: both 1-hop neighbors outrank every 2-hop node.
+    let flat = engine.execute(request("")).await.unwrap();
+    assert_eq!(evidence_ids(&flat), vec![1, 2, 3]);
+
+    // Favoring competitor_of lets the 2-hop rival displace the 1-hop regulator.
+    let weighted = engine
+        .execute(request(
+            r#", "relation_weights": {"competitor_of": 2.0, "regulates": 0.2}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(evidence_ids(&weighted), vec![1, 2, 4]);
+
+    // A zero factor prunes the relation from expansion altogether.
+    let pruned = engine
+        .execute(request(r#", "relation_weights": {"regulates": 0.0}"#))
+        .await
+        .unwrap();
+    assert_eq!(evidence_ids(&pruned), vec![1, 2, 4]);
+    assert!(pruned
+        .explain
+        .exclusions
+        .iter()
+        .any(|e| e.node_id == Some(3) && e.reason == "relation_weight_zero:regulates"));
+}
+
+#[tokio::test]
+async fn test_query_engine_exclusions_carry_structured_codes() {
+    use query::{ExclusionCode, ExclusionReason};
+
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("exclusion_codes.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, entity_type) in [
+        (100, "Company"),
+        (1, "Company"),
+        (2, "Company"),
+        (3, "Company"),
+        (4, "Policy"),
+    ] {
+        let mut node = Node::new(id, vec![1.0, 0.0], format!("Entity {id}"));
+        node.metadata
+            .insert("entity_type".to_string(), entity_type.to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    for (target, relation, weight) in [(1, "a", 0.9), (4, "a", 0.8), (2, "a", 0.5), (3, "b", 0.5)] {
+        repo.put_edge(Edge::new(100, target, relation, weight))
+            .await
+            .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+
+    let request = QueryRequest::parse_json(
+        r#"{
+            "query": "entity",
+            "mode": "evidence",
+            "search_mode": "local",
+            "top_k": 1,
+            "filters": {"entity_type": ["Company"]},
+            "traversal": {"depth": 1, "relation_types": ["a"], "max_neighbors_per_node": 2},
+            "anchor_ids": [100, 99]
+        }"#,
+    )
+    .unwrap();
+    let response = engine.execute(request).await.unwrap();
+
+    let find = |node_id: u64, code: ExclusionCode| -> &ExclusionReason {
+        response
+            .explain
+            .exclusions
+            .iter()
+            .find(|e| e.node_id == Some(node_id) && e.code == code)
+            .unwrap_or_else(|| panic!("missing {code} exclusion for node {node_id}"))
+    };
+
+    let expected = [
+        (99, ExclusionCode::AnchorNotFound, None, "anchor_not_found"),
+        (
+            3,
+            ExclusionCode::RelationFiltered,
+            Some("b"),
+            "relation_filtered:b",
+        ),
+        (
+            2,
+            ExclusionCode::FanoutCapped,
+            Some("100"),
+            "fanout_capped:100",
+        ),
+        (
+            4,
+            ExclusionCode::EntityTypeFiltered,
+            None,
+            "entity_type_filtered",
+        ),
+        (1, ExclusionCode::PrunedByTopK, None, "pruned_by_top_k"),
+    ];
+    for (node_id, code, detail, legacy) in expected {
+        let exclusion = find(node_id, code);
+        assert_eq!(exclusion.detail.as_deref(), detail);
+        assert_eq!(exclusion.reason, legacy);
+        assert_eq!(exclusion.to_string(), legacy);
+    }
+
+    let json = serde_json::to_value(find(2, ExclusionCode::FanoutCapped)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "node_id": 2,
+            "code": "fanout_capped",
+            "detail": "100",
+            "reason": "fanout_capped:100"
+        })
+    );
+    let json = serde_json::to_value(find(1, ExclusionCode::PrunedByTopK)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"node_id": 1, "code": "pruned_by_top_k", "reason": "pruned_by_top_k"})
+    );
+}
+
+#[tokio::test]
+async fn test_query_engine_debug_tokens_reports_cjk_bigrams() {
+    let (_dir, repo) = seeded_repo().await;
+    let engine = QueryEngine::new(repo);
+
+    let request = QueryRequest::parse_json(
+        r#"{"query": "トヨタ 電気自動車", "search_mode": "local", "debug_tokens": true}"#,
+    )
+    .unwrap();
+    let response = engine.execute(request).await.unwrap();
+    let tokens = response
+        .explain
+        .query_tokens
+        .expect("debug tokens requested");
+
+    for expected in [
+        "トヨタ",
+        "トヨ",
+        "ヨタ",
+        "電気自動車",
+        "電気",
+        "気自",
+        "自動",
+        "動車",
+    ] {
+        assert!(
+            tokens.iter().any(|token| token == expected),
+            "missing {expected} in {tokens:?}"
+        );
+    }
+    let mut sorted = tokens.clone();
+    sorted.sort();
+    assert_eq!(tokens, sorted);
+
+    let plain = QueryRequest::parse_json(r#"{"query": "トヨタ 電気自動車"}"#).unwrap();
+    let response = engine.execute(plain).await.unwrap();
+    assert!(response.explain.query_tokens.is_none());
+    let json = serde_json::to_value(&response).unwrap();
+    assert!(json["explain"].get("query_tokens").is_none());
+}
+
+#[tokio::test]
+async fn test_query_engine_tokenizer_is_shared_with_semantic_cache() {
+    // Bigram Jaccard between these queries is 0.7, trigram Jaccard is 6/9.
+    let first = r#"{"query": "電気自動車の生産", "search_mode": "local", "debug_tokens": true}"#;
+    let second = r#"{"query": "電気自動車の生産量", "search_mode": "local"}"#;
+    let cache_config = SemanticCacheConfig {
+        similarity_threshold: 0.68,
+        ..SemanticCacheConfig::default()
+    };
+    let is_cache_hit = |response: &query::QueryResponse| {
+        response
+            .explain
+            .steps
+            .iter()
+            .any(|step| step == query::SEMANTIC_CACHE_HIT_STEP)
+    };
+
+    let (_dir, repo) = seeded_repo().await;
+    let bigram_engine = QueryEngine::new(repo).with_semantic_cache_config(cache_config.clone());
+    let response = bigram_engine
+        .execute(QueryRequest::parse_json(first).unwrap())
+        .await
+        .unwrap();
+    assert!(response
+        .explain
+        .query_tokens
+        .unwrap()
+        .contains(&"自動".to_string()));
+    let response = bigram_engine
+        .execute(QueryRequest::parse_json(second).unwrap())
+        .await
+        .unwrap();
+    assert!(is_cache_hit(&response));
+
+    let (_dir, repo) = seeded_repo().await;
+    let trigram_engine = QueryEngine::new(repo)
+        .with_semantic_cache_config(cache_config)
+        .with_tokenizer(query::Tokenizer::new().with_ngram_size(3));
+    assert_eq!(trigram_engine.tokenizer().ngram_size(), 3);
+    let response = trigram_engine
+        .execute(QueryRequest::parse_json(first).unwrap())
+        .await
+        .unwrap();
+    let tokens = response.explain.query_tokens.unwrap();
+    assert!(tokens.contains(&"自動車".to_string()));
+    assert!(!tokens.contains(&"自動".to_string()));
+    let response = trigram_engine
+        .execute(QueryRequest::parse_json(second).unwrap())
+        .await
+        .unwrap();
+    assert!(!is_cache_hit(&response));
+}
+
+#[tokio::test]
+async fn test_query_engine_reports_empty_result_status_for_empty_repository() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("empty.wal"))
+            .await
+            .unwrap(),
+    );
+    let engine = QueryEngine::new(repo);
+
+    let response = engine
+        .execute(QueryRequest {
+            query: "Toyota EV production".to_string(),
+            mode: QueryMode::Answer,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response.result_status, query::ResultStatus::Empty);
+    assert!(response.answer.is_none());
+    assert!(response.evidence.nodes.is_empty());
+    assert_eq!(response.groundedness, 0.0);
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json["result_status"], "empty");
+
+    let (_dir, repo) = seeded_repo().await;
+    let response = QueryEngine::new(repo)
+        .execute(QueryRequest {
+            query: "Toyota EV production".to_string(),
+            mode: QueryMode::Answer,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.result_status, query::ResultStatus::Found);
+    assert!(response.answer.is_some());
+}
+
+#[tokio::test]
+async fn test_query_engine_require_match_skips_arbitrary_node_fallback() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("text_only.wal"))
+            .await
+            .unwrap(),
+    );
+    // Nodes without embeddings never produce vector hits.
+    repo.put_node(Node::new(
+        1,
+        Vec::new(),
+        "Quarterly earnings call".to_string(),
+    ))
+    .await
+    .unwrap();
+    repo.put_node(Node::new(2, Vec::new(), "Supplier audit notes".to_string()))
+        .await
+        .unwrap();
+    let engine = QueryEngine::new(repo);
+
+    let lenient = engine
+        .execute(QueryRequest {
+            query: "zxqv plorb".to_string(),
+            mode: QueryMode::Evidence,
+            search_mode: SearchMode::Local,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(lenient.evidence.nodes.len(), 1);
+
+    let strict = engine
+        .execute(QueryRequest {
+            query: "zxqv plorb".to_string(),
+            mode: QueryMode::Evidence,
+            search_mode: SearchMode::Local,
+            require_match: true,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap();
+    assert!(strict.evidence.nodes.is_empty());
+    assert!(strict
+        .explain
+        .exclusions
+        .iter()
+        .any(
+            |exclusion| exclusion.code == query::ExclusionCode::NoVectorMatch
+                && exclusion.reason == "no_vector_match"
+        ));
+}
+
+#[tokio::test]
+async fn test_query_engine_reports_empty_repository() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("empty.wal"))
+            .await
+            .unwrap(),
+    );
+    let engine = QueryEngine::new(repo.clone());
+
+    for search_mode in [SearchMode::Local, SearchMode::Auto, SearchMode::Drift] {
+        let response = engine
+            .execute(QueryRequest {
+                query: "anything at all".to_string(),
+                mode: QueryMode::Answer,
+                search_mode,
+                ..QueryRequest::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.result_status, query::ResultStatus::Empty);
+        let codes: Vec<&str> = response
+            .explain
+            .exclusions
+            .iter()
+            .map(|exclusion| exclusion.reason.as_str())
+            .collect();
+        assert!(
+            codes.contains(&"empty_repository"),
+            "{search_mode:?}: {codes:?}"
+        );
+        assert!(
+            !codes.contains(&"no_nodes_available"),
+            "{search_mode:?}: {codes:?}"
+        );
+    }
+
+    repo.put_node(Node::new(
+        1,
+        deterministic_embedding("first document", "embedding-default-v1", 8),
+        "first document".to_string(),
+    ))
+    .await
+    .unwrap();
+    let response = engine
+        .execute(QueryRequest {
+            query: "first document".to_string(),
+            mode: QueryMode::Evidence,
+            search_mode: SearchMode::Local,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.evidence.nodes.len(), 1);
+    assert!(response
+        .explain
+        .exclusions
+        .iter()
+        .all(|exclusion| exclusion.code != query::ExclusionCode::EmptyRepository));
+}
+
+#[tokio::test]
+async fn test_lexical_field_weights_rank_body_match_over_metadata_noise() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("lexical.wal"))
+            .await
+            .unwrap(),
+    );
+
+    let mut body = Node::new(1, vec![1.0, 0.0], "Toyota battery roadmap".to_string());
+    body.metadata.insert("tags".to_string(), "misc".to_string());
+    repo.put_node(body).await.unwrap();
+
+    let mut noisy = Node::new(2, vec![1.0, 0.0], "memo".to_string());
+    noisy
+        .metadata
+        .insert("notes".to_string(), "toyota battery".to_string());
+    repo.put_node(noisy).await.unwrap();
+
+    let request = QueryRequest {
+        query: "toyota battery".to_string(),
+        mode: QueryMode::Evidence,
+        anchor_ids: vec![1, 2],
+        ..QueryRequest::default()
+    };
+    let ranked_ids = |response: query::QueryResponse| -> Vec<u64> {
+        response.evidence.nodes.iter().map(|node| node.id).collect()
+    };
+
+    let unweighted = QueryEngine::new(repo.clone())
+        .execute(request.clone())
+        .await
+        .unwrap();
+    assert_eq!(ranked_ids(unweighted), vec![2, 1]);
+
+    let boosted = QueryEngine::new(repo.clone())
+        .with_lexical_field_weights(LexicalFieldWeights::default().with_data_weight(3.0))
+        .execute(request.clone())
+        .await
+        .unwrap();
+    assert_eq!(ranked_ids(boosted), vec![1, 2]);
+
+    let excluded = QueryEngine::new(repo)
+        .with_lexical_field_weights(LexicalFieldWeights::default().exclude_metadata_key("notes"))
+        .execute(request)
+        .await
+        .unwrap();
+    assert_eq!(ranked_ids(excluded), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn test_confidence_propagation_discounts_nodes_behind_weak_edges() {
+    use query::dsl::{ConfidencePropagation, Traversal};
+
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("confidence.wal"))
+            .await
+            .unwrap(),
+    );
+    for (id, data) in [(1, "hub"), (2, "strong"), (3, "weak"), (4, "far")] {
+        let mut node = Node::new(id, vec![1.0, 0.0], data.to_string());
+        node.metadata
+            .insert("confidence".to_string(), "0.8".to_string());
+        repo.put_node(node).await.unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "rel", 0.9)).await.unwrap();
+    repo.put_edge(Edge::new(1, 3, "rel", 0.2)).await.unwrap();
+    repo.put_edge(Edge::new(2, 4, "rel", 0.5)).await.unwrap();
+
+    let engine = QueryEngine::new(repo.clone());
+    let confidences = |response: query::QueryResponse| -> std::collections::HashMap<u64, f32> {
+        response
+            .evidence
+            .nodes
+            .iter()
+            .map(|node| (node.id, node.confidence))
+            .collect()
+    };
+    let request = |propagation| QueryRequest {
+        query: "hub".to_string(),
+        mode: QueryMode::Evidence,
+        anchor_ids: vec![1],
+        traversal: Traversal {
+            depth: 2,
+            confidence_propagation: propagation,
+            ..Traversal::default()
+        },
+        ..QueryRequest::default()
+    };
+
+    let metadata = confidences(
+        engine
+            .execute(request(ConfidencePropagation::Metadata))
+            .await
+            .unwrap(),
+    );
+    assert!(metadata
+        .values()
+        .all(|confidence| (confidence - 0.8).abs() < 1e-6));
+
+    let product = confidences(
+        engine
+            .execute(request(ConfidencePropagation::Product))
+            .await
+            .unwrap(),
+    );
+    assert!((product[&1] - 0.8).abs() < 1e-6);
+    assert!((product[&2] - 0.72).abs() < 1e-6);
+    assert!((product[&3] - 0.16).abs() < 1e-6);
+    assert!((product[&4] - 0.36).abs() < 1e-6);
+    assert!(product[&3] < product[&2]);
+
+    let min_edge = confidences(
+        engine
+            .execute(request(ConfidencePropagation::MinEdge))
+            .await
+            .unwrap(),
+    );
+    assert!((min_edge[&4] - 0.4).abs() < 1e-6);
+
+    // Paths fold in the same edge confidence evidence edges report: the
+    // `confidence` metadata when present, unless the engine uses weights.
+    let mut edge = Edge::new(2, 4, "rel", 0.5);
+    edge.metadata
+        .insert("confidence".to_string(), "0.25".to_string());
+    repo.put_edge(edge).await.unwrap();
+    let product = confidences(
+        engine
+            .execute(request(ConfidencePropagation::Product))
+            .await
+            .unwrap(),
+    );
+    assert!((product[&4] - 0.18).abs() < 1e-6);
+    let weight_engine =
+        QueryEngine::new(repo).with_edge_confidence_source(query::EdgeConfidenceSource::Weight);
+    let product = confidences(
+        weight_engine
+            .execute(request(ConfidencePropagation::Product))
+            .await
+            .unwrap(),
+    );
+    assert!((product[&4] - 0.36).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_answer_evidence_count_controls_synthesized_snippets() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("answer_count.wal"))
+            .await
+            .unwrap(),
+    );
+    for id in 1..=5u64 {
+        repo.put_node(Node::new(
+            id,
+            vec![1.0, id as f32 * 0.01],
+            format!("EV battery report {id}"),
+        ))
+        .await
+        .unwrap();
+    }
+    let engine = QueryEngine::new(repo);
+    let snippet_count = |answer: &str| answer.split(" | ").count();
+
+    let default_answer = engine
+        .execute(QueryRequest {
+            query: "EV battery report".to_string(),
+            mode: QueryMode::Answer,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap()
+        .answer
+        .unwrap();
+    assert_eq!(snippet_count(&default_answer), 3);
+
+    let wider_answer = engine
+        .execute(QueryRequest {
+            query: "EV battery report".to_string(),
+            mode: QueryMode::Answer,
+            answer_evidence_count: 5,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap()
+        .answer
+        .unwrap();
+    assert_eq!(snippet_count(&wider_answer), 5);
+
+    // Asking for more than the available evidence uses all of it.
+    let clamped_answer = engine
+        .execute(QueryRequest {
+            query: "EV battery report".to_string(),
+            mode: QueryMode::Answer,
+            answer_evidence_count: 50,
+            ..QueryRequest::default()
+        })
+        .await
+        .unwrap()
+        .answer
+        .unwrap();
+    assert_eq!(snippet_count(&clamped_answer), 5);
+
+    let err = QueryRequest {
+        query: "EV battery report".to_string(),
+        answer_evidence_count: 0,
+        ..QueryRequest::default()
+    }
+    .validate()
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        QueryValidationError::InvalidAnswerEvidenceCount(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_centrality_boost_promotes_connected_anchor_over_isolated_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("centrality.wal"))
+            .await
+            .unwrap(),
+    );
+    let query_text = "regional logistics hub";
+    let query_embedding = deterministic_embedding(query_text, "embedding-default-v1", 4);
+    let mut hub_embedding = query_embedding.clone();
+    hub_embedding[0] += 0.05;
+    let opposite: Vec<f32> = query_embedding.iter().map(|v| -v).collect();
+    assert!(
+        cosine_similarity(&query_embedding, &hub_embedding).unwrap()
+            < cosine_similarity(&query_embedding, &query_embedding).unwrap()
+    );
+
+    repo.put_node(Node::new(
+        10,
+        query_embedding.clone(),
+        "Isolated depot".to_string(),
+    ))
+    .await
+    .unwrap();
+    repo.put_node(Node::new(20, hub_embedding, "Central hub".to_string()))
+        .await
+        .unwrap();
+    for spoke in 21..25 {
+        repo.put_node(Node::new(spoke, opposite.clone(), format!("Spoke {spoke}")))
+            .await
+            .unwrap();
+        repo.put_edge(Edge::new(spoke, 20, "ships_to", 1.0))
+            .await
+            .unwrap();
+    }
+
+    let engine = QueryEngine::new(repo.clone());
+    let request = |centrality_boost| QueryRequest {
+        query: query_text.to_string(),
+        top_k: 1,
+        search_mode: SearchMode::Local,
+        mode: QueryMode::Evidence,
+        centrality_boost,
+        ..QueryRequest::default()
+    };
+
+    let plain = engine.execute(request(0.0)).await.unwrap();
+    assert_eq!(plain.explain.anchors[0].node_id, 10);
+
+    let boosted = engine.execute(request(0.5)).await.unwrap();
+    assert_eq!(boosted.explain.anchors[0].node_id, 20);
+
+    // Ranks are reused between queries but recomputed once the graph changes.
+    for spoke in 21..25 {
+        repo.delete_node(spoke).await.unwrap();
+    }
+    let boosted = engine.execute(request(0.5)).await.unwrap();
+    assert_eq!(boosted.explain.anchors[0].node_id, 10);
+
+    let err = request(-1.0).validate().unwrap_err();
+    assert!(matches!(err, QueryValidationError::InvalidCentralityBoost));
+}
+
+#[tokio::test]
+async fn test_query_model_differing_from_indexed_model_requires_projection() {
+    use alayasiki_core::embedding::{
+        EmbeddingModelError, EmbeddingModelRegistry, EmbeddingProjection,
+    };
+
+    let (_dir, repo) = seeded_repo().await;
+    let mut registry = EmbeddingModelRegistry::new();
+    registry
+        .register("embedding-default-v1", 2, "1.0.0")
+        .register("embedding-alt-v1", 2, "1.0.0")
+        .set_indexed_model("embedding-default-v1");
+    let request = |model_id: &str| {
+        QueryRequest::parse_json(&format!(
+            r#"{{"query": "Toyota EV production", "search_mode": "local", "model_id": "{model_id}"}}"#
+        ))
+        .unwrap()
+    };
+
+    let engine = QueryEngine::new(repo.clone()).with_embedding_model_registry(registry.clone());
+    let same_model = engine
+        .execute(request("embedding-default-v1"))
+        .await
+        .unwrap();
+    assert!(!same_model
+        .explain
+        .steps
+        .contains(&query::QUERY_EMBEDDING_PROJECTED_STEP.to_string()));
+
+    let err = engine
+        .execute(request("embedding-alt-v1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        QueryError::EmbeddingModel(EmbeddingModelError::ModelMismatch {
+            ref model_id,
+            ref indexed_model_id,
+        }) if model_id == "embedding-alt-v1" && indexed_model_id == "embedding-default-v1"
+    ));
+
+    // A projection shaped for another input dimension is rejected, not padded.
+    registry.register_projection(
+        "embedding-alt-v1",
+        "embedding-default-v1",
+        EmbeddingProjection::new(vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]).unwrap(),
+    );
+    let engine = QueryEngine::new(repo.clone()).with_embedding_model_registry(registry.clone());
+    let err = engine
+        .execute(request("embedding-alt-v1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        QueryError::EmbeddingModel(EmbeddingModelError::ProjectionDimensionMismatch {
+            model_dimension: 2,
+            projection_dimension: 3,
+            ..
+        })
+    ));
+
+    registry.register_projection(
+        "embedding-alt-v1",
+        "embedding-default-v1",
+        EmbeddingProjection::identity(2),
+    );
+    let engine = QueryEngine::new(repo).with_embedding_model_registry(registry);
+    let projected = engine.execute(request("embedding-alt-v1")).await.unwrap();
+    assert!(!projected.evidence.nodes.is_empty());
+    assert_eq!(projected.model_id.as_deref(), Some("embedding-alt-v1"));
+    assert!(projected
+        .explain
+        .steps
+        .contains(&query::QUERY_EMBEDDING_PROJECTED_STEP.to_string()));
+}
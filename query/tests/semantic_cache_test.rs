@@ -127,3 +127,65 @@ async fn semantic_cache_does_not_cross_snapshot_boundaries() {
 
     assert_ne!(first.snapshot_id, second.snapshot_id);
 }
+
+#[tokio::test]
+async fn exact_cache_is_shared_across_identical_content_and_invalidated_on_change() {
+    use query::semantic_cache::ExactResultCache;
+    use tokio::sync::Mutex;
+
+    let (_dir_a, repo_a) = seeded_repo().await;
+    let (_dir_b, repo_b) = seeded_repo().await;
+    let content_id = repo_a.content_snapshot_id().await.expect("content id");
+    assert_eq!(
+        repo_b
+            .content_snapshot_id()
+            .await
+            .expect("replica content id"),
+        content_id
+    );
+    let cache = Arc::new(Mutex::new(ExactResultCache::new(16)));
+    let engine_a = QueryEngine::new(repo_a).with_exact_result_cache(cache.clone());
+    let engine_b = QueryEngine::new(repo_b.clone()).with_exact_result_cache(cache.clone());
+
+    let pinned = QueryRequest::parse_json(&format!(
+        r#"{{"query": "Toyota EV strategy", "mode": "evidence", "search_mode": "local", "snapshot_id": "{content_id}"}}"#
+    ))
+    .expect("pinned request parse");
+    let first = engine_a.execute(pinned.clone()).await.expect("first query");
+    assert_eq!(cache.lock().await.len(), 1);
+    assert_eq!(cache.lock().await.hits(), 0);
+
+    let replica = engine_b.execute(pinned).await.expect("replica query");
+    assert_eq!(cache.lock().await.hits(), 1);
+    assert_eq!(replica.evidence, first.evidence);
+    assert_eq!(replica.explain, first.explain);
+
+    // Unpinned requests are keyed on each repository's own state.
+    let request = QueryRequest::parse_json(
+        r#"{"query": "Toyota EV strategy", "mode": "evidence", "search_mode": "local"}"#,
+    )
+    .expect("request parse");
+    engine_b
+        .execute(request.clone())
+        .await
+        .expect("unpinned query");
+    engine_b
+        .execute(request.clone())
+        .await
+        .expect("repeated unpinned query");
+    assert_eq!(cache.lock().await.len(), 2);
+    assert_eq!(cache.lock().await.hits(), 2);
+
+    repo_b
+        .put_node(Node::new(
+            3,
+            vec![1.0, 0.0],
+            "Toyota EV strategy briefing".to_string(),
+        ))
+        .await
+        .expect("put node");
+    let recomputed = engine_b.execute(request).await.expect("query after change");
+    assert_eq!(cache.lock().await.hits(), 2);
+    assert!(recomputed.evidence.nodes.iter().any(|node| node.id == 3));
+    assert_eq!(cache.lock().await.len(), 3);
+}
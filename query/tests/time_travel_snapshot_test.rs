@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use alayasiki_core::embedding::deterministic_embedding;
-use alayasiki_core::model::Node;
+use alayasiki_core::model::{Edge, Node};
 use chrono::{Duration as ChronoDuration, Utc};
 use query::{QueryEngine, QueryError, QueryRequest};
 use storage::community::CommunitySummary;
@@ -83,6 +83,65 @@ async fn pinned_snapshot_uses_historical_state() {
     );
 }
 
+#[tokio::test]
+async fn pinned_snapshot_at_current_lsn_matches_live_repository() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("time_travel_same_lsn.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+
+    for (id, text) in [
+        (1, "grid storage policy"),
+        (2, "solar subsidy policy"),
+        (3, "battery recycling rules"),
+    ] {
+        repo.put_node(Node::new(
+            id,
+            deterministic_embedding(text, MODEL_ID, DIMS),
+            text.to_string(),
+        ))
+        .await
+        .unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "related_to", 0.8))
+        .await
+        .unwrap();
+    repo.put_edge(Edge::new(2, 3, "related_to", 0.6))
+        .await
+        .unwrap();
+    let snapshot_id = repo.current_snapshot_id().await;
+
+    let query = |snapshot: Option<&str>| {
+        let snapshot = snapshot
+            .map(|id| format!(r#","snapshot_id":"{id}""#))
+            .unwrap_or_default();
+        QueryRequest::parse_json(&format!(
+            r#"{{
+                "query":"grid storage policy",
+                "mode":"evidence",
+                "search_mode":"local",
+                "top_k":10,
+                "traversal":{{"depth":2}}{snapshot}
+            }}"#
+        ))
+        .unwrap()
+    };
+
+    // Separate engines so the second run cannot be served by the semantic cache.
+    let mut live = QueryEngine::new(repo.clone())
+        .execute(query(None))
+        .await
+        .unwrap();
+    let mut pinned = QueryEngine::new(repo)
+        .execute(query(Some(&snapshot_id)))
+        .await
+        .unwrap();
+    live.latency_ms = 0;
+    pinned.latency_ms = 0;
+
+    assert_eq!(live.evidence.nodes.len(), 3);
+    assert_eq!(pinned, live);
+}
+
 #[tokio::test]
 async fn unknown_snapshot_id_returns_not_found() {
     let dir = tempdir().unwrap();
@@ -217,15 +276,21 @@ async fn global_time_travel_uses_versioned_community_summary_without_future_leak
             level: 0,
             community_id: 0,
             top_nodes: vec![1],
+            node_ids: Vec::new(),
             summary: "Global synthesis: baseline trend summary".to_string(),
             snapshot_lsn_range: Some((1, 1)),
+            redundant_with: None,
+            tenant: None,
         },
         CommunitySummary {
             level: 0,
             community_id: 1,
             top_nodes: vec![2],
+            node_ids: Vec::new(),
             summary: "Global synthesis: leaked future trend summary".to_string(),
             snapshot_lsn_range: Some((2, 2)),
+            redundant_with: None,
+            tenant: None,
         },
     ];
     let engine = QueryEngine::new(repo).with_community_summaries(summaries);
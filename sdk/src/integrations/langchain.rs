@@ -93,6 +93,7 @@ impl GraphVectorStore for LangChainAdapter {
             traversal: Traversal {
                 depth: normalize_depth(query.depth),
                 relation_types: Vec::new(),
+                ..Traversal::default()
             },
             model_id: query.model_id,
             snapshot_id: query.snapshot_id,
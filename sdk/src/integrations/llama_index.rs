@@ -88,6 +88,7 @@ impl GraphStore for LlamaIndexAdapter {
             traversal: Traversal {
                 depth: normalize_depth(query.depth),
                 relation_types: Vec::new(),
+                ..Traversal::default()
             },
             model_id: query.model_id,
             snapshot_id: query.snapshot_id,
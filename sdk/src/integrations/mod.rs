@@ -1,15 +1,14 @@
 pub mod langchain;
 pub mod llama_index;
 
-use query::dsl::{QueryValidationError, Traversal};
+use query::dsl::QueryValidationError;
+use query::planner::MAX_EXPANSION_DEPTH;
 use query::QueryRequest;
 use std::sync::OnceLock;
 
 const FALLBACK_MAX_TOP_K: usize = 1_000;
-const FALLBACK_MAX_DEPTH: u8 = 8;
 
 static QUERY_MAX_TOP_K: OnceLock<usize> = OnceLock::new();
-static QUERY_MAX_DEPTH: OnceLock<u8> = OnceLock::new();
 
 pub(crate) fn normalize_top_k(value: usize) -> usize {
     let max_top_k = *QUERY_MAX_TOP_K.get_or_init(detect_query_max_top_k);
@@ -20,13 +19,10 @@ pub(crate) fn normalize_top_k(value: usize) -> usize {
     }
 }
 
+/// Keeps depth within the engine's default expansion ceiling so integration
+/// queries are not reported as `depth_capped`.
 pub(crate) fn normalize_depth(value: u8) -> u8 {
-    let max_depth = *QUERY_MAX_DEPTH.get_or_init(detect_query_max_depth);
-    if value == 0 {
-        1
-    } else {
-        value.min(max_depth)
-    }
+    value.clamp(1, MAX_EXPANSION_DEPTH)
 }
 
 fn detect_query_max_top_k() -> usize {
@@ -41,22 +37,6 @@ fn detect_query_max_top_k() -> usize {
     }
 }
 
-fn detect_query_max_depth() -> u8 {
-    let probe = QueryRequest {
-        query: "probe".to_string(),
-        top_k: 1,
-        traversal: Traversal {
-            depth: u8::MAX,
-            relation_types: Vec::new(),
-        },
-        ..QueryRequest::default()
-    };
-    match probe.validate() {
-        Err(QueryValidationError::InvalidDepth(max)) => max,
-        _ => FALLBACK_MAX_DEPTH,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,9 +49,8 @@ mod tests {
     }
 
     #[test]
-    fn normalize_depth_uses_query_limit() {
-        let expected_max = detect_query_max_depth();
+    fn normalize_depth_uses_engine_ceiling() {
         assert_eq!(normalize_depth(0), 1);
-        assert_eq!(normalize_depth(u8::MAX), expected_max);
+        assert_eq!(normalize_depth(u8::MAX), MAX_EXPANSION_DEPTH);
     }
 }
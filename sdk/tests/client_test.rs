@@ -13,7 +13,7 @@ use alayasiki_sdk::client::{
 use async_trait::async_trait;
 use ingestion::processor::IngestionError;
 use query::engine::{Anchor, Citation, EvidenceSubgraph, ExplainPlan};
-use query::{QueryError, QueryRequest, QueryResponse, SearchMode};
+use query::{QueryError, QueryRequest, QueryResponse, ResultStatus, SearchMode};
 use storage::repo::{RepoError, Repository};
 use storage::wal::WalError;
 use tempfile::tempdir;
@@ -260,6 +260,7 @@ impl SdkTransport for FlakyQueryTransport {
         }
 
         Ok(QueryResponse {
+            result_status: ResultStatus::Found,
             answer: Some("ok".to_string()),
             evidence: EvidenceSubgraph {
                 nodes: vec![],
@@ -273,12 +274,16 @@ impl SdkTransport for FlakyQueryTransport {
                 anchors: Vec::<Anchor>::new(),
                 expansion_paths: vec![],
                 exclusions: vec![],
+                query_tokens: None,
+                vector_explain: None,
             },
             model_id: Some("embedding-default-v1".to_string()),
             snapshot_id: Some("wal-lsn-1".to_string()),
             time_travel: None,
             latency_ms: 0,
             error_code: None,
+            error_message: None,
+            counts: None,
         })
     }
 }
@@ -1,25 +1,46 @@
-use crate::index::AdjacencyGraph;
+use crate::index::{AdjacencyGraph, GraphSemantics};
+use crate::snapshot::SnapshotError;
+use arc_swap::ArcSwap;
+use rayon::prelude::*;
+use rkyv::ser::{serializers::AllocSerializer, Serializer};
+use rkyv::{Archive, Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[archive(check_bytes)]
 pub struct Community {
     pub id: usize,
     pub node_ids: Vec<u64>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[archive(check_bytes)]
 pub struct CommunityLevel {
     pub level: usize,
     pub communities: Vec<Community>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[archive(check_bytes)]
 pub struct CommunitySummary {
     pub level: usize,
     pub community_id: usize,
     pub top_nodes: Vec<u64>,
+    /// Every node in the community.
+    pub node_ids: Vec<u64>,
     pub summary: String,
     pub snapshot_lsn_range: Option<(u64, u64)>,
+    /// Id of the next-level community whose summary restates this one (see
+    /// [`SummaryConfig::redundancy_overlap_threshold`]). Flagged summaries are
+    /// kept so every level stays available.
+    pub redundant_with: Option<usize>,
+    /// Tenant whose nodes make up the community when detection is partitioned
+    /// (see [`CommunityEngine::with_tenant_partitions`]); `None` otherwise.
+    pub tenant: Option<String>,
 }
 
 impl CommunitySummary {
@@ -29,12 +50,81 @@ impl CommunitySummary {
             .unwrap_or(true)
     }
 
+    /// Whether the summary was built from `tenant`'s partition of the graph.
+    pub fn belongs_to_tenant(&self, tenant: &str) -> bool {
+        self.tenant.as_deref() == Some(tenant)
+    }
+
     pub fn with_snapshot_lsn_range(mut self, start_lsn: u64, end_lsn: u64) -> Self {
         self.snapshot_lsn_range = Some((start_lsn, end_lsn));
         self
     }
 }
 
+/// Shared, lock-free slot holding the currently published community summaries.
+///
+/// Readers take an `Arc` to the whole set, so a reader keeps a consistent view
+/// while a publisher swaps in a new one. Each publish bumps a generation counter
+/// that is swapped together with the set.
+#[derive(Clone)]
+pub struct CommunitySummaryHandle {
+    published: Arc<ArcSwap<PublishedSummaries>>,
+}
+
+struct PublishedSummaries {
+    generation: u64,
+    summaries: Arc<Vec<CommunitySummary>>,
+}
+
+impl CommunitySummaryHandle {
+    pub fn new(summaries: Vec<CommunitySummary>) -> Self {
+        Self {
+            published: Arc::new(ArcSwap::from_pointee(PublishedSummaries {
+                generation: 0,
+                summaries: Arc::new(summaries),
+            })),
+        }
+    }
+
+    pub fn load(&self) -> Arc<Vec<CommunitySummary>> {
+        self.published.load().summaries.clone()
+    }
+
+    /// The current set together with the generation it was published as.
+    pub fn load_versioned(&self) -> (u64, Arc<Vec<CommunitySummary>>) {
+        let published = self.published.load();
+        (published.generation, published.summaries.clone())
+    }
+
+    pub fn store(&self, summaries: Vec<CommunitySummary>) {
+        let summaries = Arc::new(summaries);
+        self.published.rcu(|current| PublishedSummaries {
+            generation: current.generation + 1,
+            summaries: summaries.clone(),
+        });
+    }
+
+    /// Number of publishes since the handle was created.
+    pub fn generation(&self) -> u64 {
+        self.published.load().generation
+    }
+}
+
+impl Default for CommunitySummaryHandle {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl std::fmt::Debug for CommunitySummaryHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommunitySummaryHandle")
+            .field("summaries", &self.load().len())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
+
 pub trait CommunitySummarizer: Send + Sync {
     fn summarize(
         &self,
@@ -65,6 +155,196 @@ impl CommunitySummarizer for DeterministicSummarizer {
     }
 }
 
+/// Read access to node text for summarizers that quote member content.
+pub trait NodeTextReader: Send + Sync {
+    fn node_text(&self, node_id: u64) -> Option<String>;
+}
+
+impl NodeTextReader for HashMap<u64, String> {
+    fn node_text(&self, node_id: u64) -> Option<String> {
+        self.get(&node_id).cloned()
+    }
+}
+
+/// Extractive summarizer: quotes the leading sentence of the community's
+/// highest-PageRank nodes, topping up from other members when fewer than
+/// `max_nodes` top nodes have text. Falls back to the
+/// [`DeterministicSummarizer`] label when no member text is available.
+pub struct TextualSummarizer {
+    reader: Arc<dyn NodeTextReader>,
+    max_nodes: usize,
+    max_chars_per_node: usize,
+}
+
+impl TextualSummarizer {
+    pub const DEFAULT_MAX_NODES: usize = 3;
+    pub const DEFAULT_MAX_CHARS_PER_NODE: usize = 160;
+
+    pub fn new(reader: Arc<dyn NodeTextReader>) -> Self {
+        Self {
+            reader,
+            max_nodes: Self::DEFAULT_MAX_NODES,
+            max_chars_per_node: Self::DEFAULT_MAX_CHARS_PER_NODE,
+        }
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(1);
+        self
+    }
+
+    pub fn with_max_chars_per_node(mut self, max_chars: usize) -> Self {
+        self.max_chars_per_node = max_chars.max(1);
+        self
+    }
+}
+
+impl CommunitySummarizer for TextualSummarizer {
+    fn summarize(
+        &self,
+        level: usize,
+        community_id: usize,
+        node_ids: &[u64],
+        top_nodes: &[u64],
+    ) -> String {
+        let mut seen_nodes = HashSet::new();
+        let mut snippets: Vec<String> = Vec::new();
+        for node_id in top_nodes.iter().chain(node_ids) {
+            if snippets.len() >= self.max_nodes {
+                break;
+            }
+            if !seen_nodes.insert(*node_id) {
+                continue;
+            }
+            let Some(text) = self.reader.node_text(*node_id) else {
+                continue;
+            };
+            let snippet = leading_sentence(&text, self.max_chars_per_node);
+            if !snippet.is_empty() && !snippets.contains(&snippet) {
+                snippets.push(snippet);
+            }
+        }
+
+        if snippets.is_empty() {
+            return DeterministicSummarizer.summarize(level, community_id, node_ids, top_nodes);
+        }
+        snippets.join(" ")
+    }
+}
+
+fn leading_sentence(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sentence_end = text
+        .char_indices()
+        .find(|(_, c)| matches!(c, '.' | '!' | '?' | '。' | '！' | '？'))
+        .map(|(idx, c)| idx + c.len_utf8())
+        .unwrap_or(text.len());
+    let sentence = &text[..sentence_end];
+    if sentence.chars().count() <= max_chars {
+        return sentence.to_string();
+    }
+    let truncated: String = sentence.chars().take(max_chars).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// On-disk form of a [`CommunityEngine`]'s computed state. The graph itself is
+/// not persisted; it is supplied again from the repository on load.
+#[derive(Archive, Deserialize, Serialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct CommunityEngineFile {
+    snapshot_id: Option<String>,
+    max_levels: u64,
+    hierarchy: Vec<CommunityLevel>,
+    summaries: Vec<CommunitySummary>,
+    pagerank: Vec<(u64, f64)>,
+}
+
+/// PageRank parameters used by [`CommunityEngine::rebuild_hierarchy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankConfig {
+    pub damping: f64,
+    /// Upper bound on power iterations, reached only when ranks do not converge.
+    pub max_iterations: usize,
+    /// Stop once the L1 distance between consecutive rank vectors drops below
+    /// this value. `0.0` always runs `max_iterations`.
+    pub epsilon: f64,
+}
+
+impl Default for PageRankConfig {
+    fn default() -> Self {
+        Self {
+            damping: 0.85,
+            max_iterations: 30,
+            epsilon: 1e-9,
+        }
+    }
+}
+
+/// How many PageRank-ranked nodes [`CommunityEngine::fastgraphrag_top_nodes`]
+/// selects: `ceil(node_count * fraction)`, clamped to `min_nodes..=max_nodes`.
+/// A non-empty graph always yields at least one node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopNodeSelection {
+    pub fraction: f64,
+    pub min_nodes: usize,
+    /// `None` leaves the count unbounded above.
+    pub max_nodes: Option<usize>,
+}
+
+impl Default for TopNodeSelection {
+    fn default() -> Self {
+        Self {
+            fraction: 0.10,
+            min_nodes: 1,
+            max_nodes: None,
+        }
+    }
+}
+
+impl TopNodeSelection {
+    fn count(&self, node_count: usize) -> usize {
+        let mut count = ((node_count as f64) * self.fraction.max(0.0)).ceil() as usize;
+        if let Some(max_nodes) = self.max_nodes {
+            count = count.min(max_nodes);
+        }
+        count.max(self.min_nodes).max(1).min(node_count)
+    }
+}
+
+/// Limits applied to each [`CommunitySummary`] by
+/// [`CommunityEngine::rebuild_hierarchy`]. `None` leaves a dimension unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SummaryConfig {
+    /// Representative nodes kept per community (in PageRank order) and passed
+    /// to the summarizer. At least one node is always kept.
+    pub max_top_nodes_per_community: Option<usize>,
+    /// Summary text longer than this many characters is cut and ends in `…`.
+    pub max_summary_chars: Option<usize>,
+    /// Flag a summary as redundant when at least this fraction of its words
+    /// also appear in its parent community's summary. `None` disables the
+    /// check.
+    pub redundancy_overlap_threshold: Option<f32>,
+}
+
+/// Per-relation rescaling of edge weights in the graph that community
+/// detection and PageRank run on. Stored edge weights are never changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeightNormalization {
+    /// Use stored weights as-is.
+    #[default]
+    None,
+    /// Min-max over each relation's edges, mapped into
+    /// `[MIN_NORMALIZED_WEIGHT, 1.0]` so the weakest edge still connects. A
+    /// relation whose edges share a single weight maps to `1.0`.
+    MinMax,
+    /// Logistic of each edge's z-score within its relation, in `(0, 1)`. A
+    /// relation whose edges share a single weight maps to `0.5`.
+    ZScore,
+}
+
+/// Lowest weight [`WeightNormalization::MinMax`] assigns.
+pub const MIN_NORMALIZED_WEIGHT: f32 = 0.05;
+
 pub struct CommunityEngine {
     graph: AdjacencyGraph,
     hierarchy: Vec<CommunityLevel>,
@@ -72,6 +352,12 @@ pub struct CommunityEngine {
     pagerank: HashMap<u64, f64>,
     dirty_nodes: HashSet<u64>,
     max_levels: usize,
+    snapshot_id: Option<String>,
+    pagerank_config: PageRankConfig,
+    top_node_selection: TopNodeSelection,
+    summary_config: SummaryConfig,
+    node_tenants: HashMap<u64, String>,
+    weight_normalization: WeightNormalization,
 }
 
 impl CommunityEngine {
@@ -83,16 +369,148 @@ impl CommunityEngine {
             pagerank: HashMap::new(),
             dirty_nodes: HashSet::new(),
             max_levels: 3,
+            snapshot_id: None,
+            pagerank_config: PageRankConfig::default(),
+            top_node_selection: TopNodeSelection::default(),
+            summary_config: SummaryConfig::default(),
+            node_tenants: HashMap::new(),
+            weight_normalization: WeightNormalization::None,
         }
     }
 
+    pub fn with_pagerank_config(mut self, config: PageRankConfig) -> Self {
+        self.pagerank_config = config;
+        self
+    }
+
+    pub fn with_top_node_selection(mut self, selection: TopNodeSelection) -> Self {
+        self.top_node_selection = selection;
+        self
+    }
+
+    pub fn with_summary_config(mut self, config: SummaryConfig) -> Self {
+        self.summary_config = config;
+        self
+    }
+
+    /// Partitions detection by tenant: edges between nodes of different
+    /// tenants (or between a tenant's node and an untenanted one) are ignored,
+    /// so every community holds a single tenant's nodes and its summary is
+    /// tagged with that tenant. Nodes missing from `node_tenants` form one
+    /// shared, untagged partition; an empty map keeps whole-graph detection.
+    pub fn with_tenant_partitions(mut self, node_tenants: HashMap<u64, String>) -> Self {
+        self.node_tenants = node_tenants;
+        self
+    }
+
+    /// Opt-in per-relation weight normalization, so a relation extracted on a
+    /// different weight scale does not dominate (or vanish from) detection.
+    pub fn with_weight_normalization(mut self, normalization: WeightNormalization) -> Self {
+        self.weight_normalization = normalization;
+        self
+    }
+
+    /// Restores hierarchy, PageRank and summaries written by [`Self::save`],
+    /// attaching them to `graph` without recomputation.
+    pub async fn load(
+        path: impl AsRef<Path>,
+        graph: AdjacencyGraph,
+    ) -> Result<Self, SnapshotError> {
+        let bytes = fs::read(path.as_ref()).await?;
+        let archived = rkyv::check_archived_root::<CommunityEngineFile>(&bytes[..])
+            .map_err(|_| SnapshotError::Deserialization)?;
+        let file: CommunityEngineFile = archived
+            .deserialize(&mut rkyv::Infallible)
+            .map_err(|_| SnapshotError::Deserialization)?;
+
+        Ok(Self {
+            graph,
+            hierarchy: file.hierarchy,
+            summaries: file.summaries,
+            pagerank: file.pagerank.into_iter().collect(),
+            dirty_nodes: HashSet::new(),
+            max_levels: (file.max_levels as usize).max(1),
+            snapshot_id: file.snapshot_id,
+            pagerank_config: PageRankConfig::default(),
+            top_node_selection: TopNodeSelection::default(),
+            summary_config: SummaryConfig::default(),
+            node_tenants: HashMap::new(),
+            weight_normalization: WeightNormalization::None,
+        })
+    }
+
+    /// Atomically writes the computed state, tagged with the snapshot id it was
+    /// computed at, to `path`.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let mut pagerank: Vec<(u64, f64)> = self
+            .pagerank
+            .iter()
+            .map(|(id, score)| (*id, *score))
+            .collect();
+        pagerank.sort_by_key(|(id, _)| *id);
+        let file = CommunityEngineFile {
+            snapshot_id: self.snapshot_id.clone(),
+            max_levels: self.max_levels as u64,
+            hierarchy: self.hierarchy.clone(),
+            summaries: self.summaries.clone(),
+            pagerank,
+        };
+        let mut serializer = AllocSerializer::<4096>::default();
+        serializer
+            .serialize_value(&file)
+            .map_err(|_| SnapshotError::Serialization)?;
+        let bytes = serializer.into_serializer().into_inner();
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes).await?;
+        fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    /// Snapshot id the current hierarchy was computed at, if tagged.
+    pub fn snapshot_id(&self) -> Option<&str> {
+        self.snapshot_id.as_deref()
+    }
+
+    /// Whether the computed state needs rebuilding to reflect `snapshot_id`:
+    /// true when it was never tagged, was tagged at another snapshot, or has
+    /// pending incremental edges.
+    pub fn is_stale_for(&self, snapshot_id: &str) -> bool {
+        self.hierarchy.is_empty()
+            || !self.dirty_nodes.is_empty()
+            || self.snapshot_id.as_deref() != Some(snapshot_id)
+    }
+
+    /// Rebuilds and tags the hierarchy with `snapshot_id` unless it is already
+    /// current. Returns whether a rebuild happened.
+    pub fn rebuild_if_stale(
+        &mut self,
+        snapshot_id: &str,
+        max_levels: usize,
+        summarizer: &dyn CommunitySummarizer,
+    ) -> bool {
+        if !self.is_stale_for(snapshot_id) {
+            return false;
+        }
+        self.rebuild_hierarchy(max_levels, summarizer);
+        self.snapshot_id = Some(snapshot_id.to_string());
+        true
+    }
+
     pub fn rebuild_hierarchy(&mut self, max_levels: usize, summarizer: &dyn CommunitySummarizer) {
         self.max_levels = max_levels.max(1);
+        self.snapshot_id = None;
 
-        let mut level0 = detect_leiden_level(&self.graph);
+        let graph = tenant_partitioned_graph(&self.graph, &self.node_tenants);
+        let graph = normalize_relation_weights(graph, self.weight_normalization);
+        let mut level0 = detect_leiden_level(&graph);
         if level0.is_empty() {
-            level0 = self
-                .graph
+            level0 = graph
                 .node_ids()
                 .into_iter()
                 .enumerate()
@@ -114,7 +532,7 @@ impl CommunityEngine {
                 break;
             }
 
-            let super_graph = build_super_graph(&self.graph, &current);
+            let super_graph = build_super_graph(&graph, &current);
             let super_communities = detect_leiden_level(&super_graph);
 
             if super_communities.is_empty() || super_communities.len() >= current.len() {
@@ -152,10 +570,24 @@ impl CommunityEngine {
         }
 
         self.hierarchy = levels;
-        self.pagerank = compute_pagerank(&self.graph, 30, 0.85);
+        self.pagerank = compute_pagerank(&graph, &self.pagerank_config);
 
         let top_nodes = self.fastgraphrag_top_nodes();
-        self.summaries = build_summaries(&self.hierarchy, &top_nodes, summarizer);
+        self.summaries = build_summaries(
+            &self.hierarchy,
+            &top_nodes,
+            &self.summary_config,
+            summarizer,
+        );
+        if !self.node_tenants.is_empty() {
+            for summary in &mut self.summaries {
+                summary.tenant = summary
+                    .node_ids
+                    .first()
+                    .and_then(|node_id| self.node_tenants.get(node_id))
+                    .cloned();
+            }
+        }
         self.dirty_nodes.clear();
     }
 
@@ -196,12 +628,7 @@ impl CommunityEngine {
                 .then(a.0.cmp(&b.0))
         });
 
-        let count = ranked.len();
-        let mut top_k = ((count as f64) * 0.10).ceil() as usize;
-        if top_k == 0 {
-            top_k = 1;
-        }
-        ranked.truncate(top_k);
+        ranked.truncate(self.top_node_selection.count(ranked.len()));
         ranked.into_iter().map(|(id, _)| id).collect()
     }
 
@@ -225,9 +652,15 @@ impl CommunityEngine {
 fn build_summaries(
     levels: &[CommunityLevel],
     top_nodes: &[u64],
+    config: &SummaryConfig,
     summarizer: &dyn CommunitySummarizer,
 ) -> Vec<CommunitySummary> {
-    let top_set: HashSet<u64> = top_nodes.iter().copied().collect();
+    // `top_nodes` is in PageRank order; keep that order within each community.
+    let top_rank: HashMap<u64, usize> = top_nodes
+        .iter()
+        .enumerate()
+        .map(|(rank, id)| (*id, rank))
+        .collect();
     let mut out = Vec::new();
 
     for level in levels {
@@ -236,32 +669,187 @@ fn build_summaries(
                 .node_ids
                 .iter()
                 .copied()
-                .filter(|id| top_set.contains(id))
+                .filter(|id| top_rank.contains_key(id))
                 .collect();
+            community_top.sort_by_key(|id| top_rank[id]);
             if community_top.is_empty() && !community.node_ids.is_empty() {
                 community_top.push(community.node_ids[0]);
             }
+            if let Some(max_top_nodes) = config.max_top_nodes_per_community {
+                community_top.truncate(max_top_nodes.max(1));
+            }
 
-            let summary = summarizer.summarize(
+            let mut summary = summarizer.summarize(
                 level.level,
                 community.id,
                 &community.node_ids,
                 &community_top,
             );
+            if let Some(max_chars) = config.max_summary_chars {
+                summary = truncate_summary(summary, max_chars);
+            }
 
             out.push(CommunitySummary {
                 level: level.level,
                 community_id: community.id,
                 top_nodes: community_top,
+                node_ids: community.node_ids.clone(),
                 summary,
                 snapshot_lsn_range: None,
+                redundant_with: None,
+                tenant: None,
             });
         }
     }
 
+    if let Some(threshold) = config.redundancy_overlap_threshold {
+        flag_redundant_summaries(levels, &mut out, threshold);
+    }
+
     out
 }
 
+/// Marks each summary whose words are mostly contained in the summary of the
+/// community that absorbs it at the next level.
+fn flag_redundant_summaries(
+    levels: &[CommunityLevel],
+    summaries: &mut [CommunitySummary],
+    threshold: f32,
+) {
+    let position: HashMap<(usize, usize), usize> = summaries
+        .iter()
+        .enumerate()
+        .map(|(idx, summary)| ((summary.level, summary.community_id), idx))
+        .collect();
+
+    for pair in levels.windows(2) {
+        let (child_level, parent_level) = (&pair[0], &pair[1]);
+        let parent_of: HashMap<u64, usize> = parent_level
+            .communities
+            .iter()
+            .flat_map(|parent| parent.node_ids.iter().map(move |id| (*id, parent.id)))
+            .collect();
+
+        for child in &child_level.communities {
+            let Some(parent_id) = child
+                .node_ids
+                .first()
+                .and_then(|node_id| parent_of.get(node_id))
+            else {
+                continue;
+            };
+            let (Some(&child_idx), Some(&parent_idx)) = (
+                position.get(&(child_level.level, child.id)),
+                position.get(&(parent_level.level, *parent_id)),
+            ) else {
+                continue;
+            };
+
+            let child_words = summary_words(&summaries[child_idx].summary);
+            if child_words.is_empty() {
+                continue;
+            }
+            let parent_words = summary_words(&summaries[parent_idx].summary);
+            let shared = child_words.intersection(&parent_words).count();
+            if shared as f32 / child_words.len() as f32 >= threshold {
+                summaries[child_idx].redundant_with = Some(*parent_id);
+            }
+        }
+    }
+}
+
+fn summary_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn truncate_summary(summary: String, max_chars: usize) -> String {
+    if summary.chars().count() <= max_chars {
+        return summary;
+    }
+    let truncated: String = summary.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// `graph` without the edges that cross tenant partitions. Borrowed unchanged
+/// when no tenants are known.
+fn tenant_partitioned_graph<'a>(
+    graph: &'a AdjacencyGraph,
+    node_tenants: &HashMap<u64, String>,
+) -> Cow<'a, AdjacencyGraph> {
+    if node_tenants.is_empty() {
+        return Cow::Borrowed(graph);
+    }
+    let mut partitioned = AdjacencyGraph::new().with_semantics(graph.semantics());
+    for node_id in graph.node_ids() {
+        let tenant = node_tenants.get(&node_id);
+        for (target, relation, weight) in graph.neighbors(node_id) {
+            if node_tenants.get(target) == tenant {
+                partitioned.add_edge(node_id, *target, relation.clone(), *weight);
+            }
+        }
+    }
+    Cow::Owned(partitioned)
+}
+
+fn normalize_relation_weights(
+    graph: Cow<'_, AdjacencyGraph>,
+    normalization: WeightNormalization,
+) -> Cow<'_, AdjacencyGraph> {
+    if normalization == WeightNormalization::None {
+        return graph;
+    }
+
+    let mut weights_by_relation: HashMap<&str, Vec<f32>> = HashMap::new();
+    for node_id in graph.node_ids() {
+        for (_, relation, weight) in graph.neighbors(node_id) {
+            weights_by_relation
+                .entry(relation.as_str())
+                .or_default()
+                .push(*weight);
+        }
+    }
+    let scales: HashMap<&str, (f32, f32)> = weights_by_relation
+        .into_iter()
+        .map(|(relation, weights)| {
+            let scale = match normalization {
+                WeightNormalization::MinMax => {
+                    let min = weights.iter().copied().fold(f32::INFINITY, f32::min);
+                    let max = weights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                    (min, max - min)
+                }
+                _ => {
+                    let count = weights.len() as f32;
+                    let mean = weights.iter().sum::<f32>() / count;
+                    let variance = weights.iter().map(|w| (w - mean).powi(2)).sum::<f32>() / count;
+                    (mean, variance.sqrt())
+                }
+            };
+            (relation, scale)
+        })
+        .collect();
+
+    let mut normalized = AdjacencyGraph::new().with_semantics(graph.semantics());
+    for node_id in graph.node_ids() {
+        for (target, relation, weight) in graph.neighbors(node_id) {
+            let (offset, spread) = scales[relation.as_str()];
+            let weight = match normalization {
+                WeightNormalization::MinMax if spread > f32::EPSILON => {
+                    MIN_NORMALIZED_WEIGHT
+                        + (1.0 - MIN_NORMALIZED_WEIGHT) * (weight - offset) / spread
+                }
+                WeightNormalization::MinMax => 1.0,
+                _ if spread > f32::EPSILON => 1.0 / (1.0 + (-(weight - offset) / spread).exp()),
+                _ => 0.5,
+            };
+            normalized.add_edge(node_id, *target, relation.clone(), weight);
+        }
+    }
+    Cow::Owned(normalized)
+}
+
 fn build_super_graph(graph: &AdjacencyGraph, communities: &[Community]) -> AdjacencyGraph {
     let mut node_to_community = HashMap::new();
     for (community_idx, community) in communities.iter().enumerate() {
@@ -527,11 +1115,28 @@ fn connected_components(nodes: &[u64], adj: &HashMap<u64, HashMap<u64, f64>>) ->
     components
 }
 
-fn compute_pagerank(graph: &AdjacencyGraph, iterations: usize, damping: f64) -> HashMap<u64, f64> {
+/// Graphs with at least this many nodes spread each PageRank iteration across
+/// the rayon pool; smaller graphs are not worth the scheduling overhead.
+const PARALLEL_PAGERANK_MIN_NODES: usize = 2048;
+
+/// PageRank of every node in `graph`, weighted by edge weight. Ranks sum to 1.
+/// Under [`GraphSemantics::Undirected`] every edge also links target to source.
+pub fn compute_pagerank(graph: &AdjacencyGraph, config: &PageRankConfig) -> HashMap<u64, f64> {
+    let parallel = graph.node_ids().len() >= PARALLEL_PAGERANK_MIN_NODES;
+    compute_pagerank_with(graph, config, parallel).0
+}
+
+/// Returns the ranks and the number of iterations actually run.
+fn compute_pagerank_with(
+    graph: &AdjacencyGraph,
+    config: &PageRankConfig,
+    parallel: bool,
+) -> (HashMap<u64, f64>, usize) {
+    let damping = config.damping;
     let nodes = graph.node_ids();
     let n = nodes.len();
     if n == 0 {
-        return HashMap::new();
+        return (HashMap::new(), 0);
     }
 
     let n_f64 = n as f64;
@@ -545,10 +1150,14 @@ fn compute_pagerank(graph: &AdjacencyGraph, iterations: usize, damping: f64) ->
         node_to_idx.insert(node_id, idx);
     }
 
+    let undirected = graph.semantics() == GraphSemantics::Undirected;
     let mut out_neighbors: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
     for (source, target, weight) in graph.edges() {
         if let (Some(&s_idx), Some(&t_idx)) = (node_to_idx.get(&source), node_to_idx.get(&target)) {
             out_neighbors[s_idx].push((t_idx, weight as f64));
+            if undirected && s_idx != t_idx {
+                out_neighbors[t_idx].push((s_idx, weight as f64));
+            }
         }
     }
 
@@ -566,34 +1175,67 @@ fn compute_pagerank(graph: &AdjacencyGraph, iterations: usize, damping: f64) ->
         }
     }
 
-    let mut rank = vec![1.0 / n_f64; n];
-    let mut next = vec![base; n];
-
-    for _ in 0..iterations {
-        next.fill(base);
-        let mut dangling_mass = 0.0;
-
-        for (idx, edges) in out_neighbors.iter().enumerate() {
-            let current_rank = rank[idx];
-
-            if is_dangling[idx] {
-                dangling_mass += current_rank;
-                continue;
-            }
-
+    // The parallel pass pulls contributions per target. Building the incoming
+    // lists in ascending source order keeps each target's floating-point sum in
+    // the same order as the sequential push pass.
+    let in_neighbors: Vec<Vec<(usize, f64)>> = if parallel {
+        let mut incoming = vec![Vec::new(); n];
+        for (source_idx, edges) in out_neighbors.iter().enumerate() {
             for &(target_idx, norm_weight) in edges {
-                next[target_idx] += current_rank * norm_weight;
+                incoming[target_idx].push((source_idx, norm_weight));
             }
         }
+        incoming
+    } else {
+        Vec::new()
+    };
+
+    let mut rank = vec![1.0 / n_f64; n];
+    let mut next = vec![base; n];
 
-        if dangling_mass > 0.0 {
-            let distribute = damping * dangling_mass / n_f64;
+    let mut iterations = 0;
+    while iterations < config.max_iterations {
+        iterations += 1;
+        let dangling_mass: f64 = rank
+            .iter()
+            .zip(&is_dangling)
+            .filter(|(_, dangling)| **dangling)
+            .map(|(value, _)| *value)
+            .sum();
+        let distribute = damping * dangling_mass / n_f64;
+
+        if parallel {
+            next.par_iter_mut()
+                .zip(in_neighbors.par_iter())
+                .for_each(|(value, incoming)| {
+                    let mut acc = base;
+                    for &(source_idx, norm_weight) in incoming {
+                        acc += rank[source_idx] * norm_weight;
+                    }
+                    *value = acc + distribute;
+                });
+        } else {
+            next.fill(base);
+            for (idx, edges) in out_neighbors.iter().enumerate() {
+                let current_rank = rank[idx];
+                for &(target_idx, norm_weight) in edges {
+                    next[target_idx] += current_rank * norm_weight;
+                }
+            }
             for value in next.iter_mut() {
                 *value += distribute;
             }
         }
 
+        let delta: f64 = rank
+            .iter()
+            .zip(&next)
+            .map(|(previous, current)| (current - previous).abs())
+            .sum();
         std::mem::swap(&mut rank, &mut next);
+        if delta < config.epsilon {
+            break;
+        }
     }
 
     let mut final_rank = HashMap::with_capacity(n);
@@ -601,7 +1243,7 @@ fn compute_pagerank(graph: &AdjacencyGraph, iterations: usize, damping: f64) ->
         final_rank.insert(node_id, rank[idx]);
     }
 
-    final_rank
+    (final_rank, iterations)
 }
 
 #[cfg(test)]
@@ -627,7 +1269,247 @@ mod tests {
     #[test]
     fn test_pagerank_returns_scores() {
         let graph = graph_for_test();
-        let scores = compute_pagerank(&graph, 10, 0.85);
+        let config = PageRankConfig {
+            max_iterations: 10,
+            ..PageRankConfig::default()
+        };
+        let scores = compute_pagerank(&graph, &config);
         assert!(!scores.is_empty());
     }
+
+    #[test]
+    fn test_partitioning_and_normalization_keep_graph_semantics() {
+        let graph = graph_for_test().with_semantics(GraphSemantics::Undirected);
+        let node_tenants: HashMap<u64, String> = [1, 2, 3, 10, 11, 12]
+            .into_iter()
+            .map(|id| (id, "acme".to_string()))
+            .collect();
+        let rebuilt = normalize_relation_weights(
+            tenant_partitioned_graph(&graph, &node_tenants),
+            WeightNormalization::MinMax,
+        );
+        assert!(matches!(rebuilt, Cow::Owned(_)));
+        assert_eq!(rebuilt.semantics(), GraphSemantics::Undirected);
+
+        // Undirected, the ends of the 1-2-3 path rank the same.
+        let scores = compute_pagerank(&rebuilt, &PageRankConfig::default());
+        assert!((scores[&1] - scores[&3]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parallel_pagerank_matches_sequential() {
+        // Deterministic pseudo-random graph with dangling nodes and uneven weights.
+        let mut graph = AdjacencyGraph::new();
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for source in 0..600u64 {
+            if source % 17 == 0 {
+                continue;
+            }
+            for _ in 0..4 {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let target = state % 640;
+                let weight = 0.5 + (state % 7) as f32;
+                graph.add_edge(source, target, "links", weight);
+            }
+        }
+
+        let config = PageRankConfig::default();
+        let (sequential, _) = compute_pagerank_with(&graph, &config, false);
+        let (parallel, _) = compute_pagerank_with(&graph, &config, true);
+
+        assert_eq!(sequential.len(), parallel.len());
+        for (node_id, score) in &sequential {
+            let other = parallel[node_id];
+            assert!(
+                (score - other).abs() < 1e-12,
+                "node {node_id}: sequential {score} vs parallel {other}"
+            );
+        }
+        let total: f64 = parallel.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_top_node_selection_follows_fraction_and_bounds() {
+        let mut graph = AdjacencyGraph::new();
+        for id in 1..40 {
+            graph.add_edge(id, id + 1, "links", 1.0);
+        }
+        let selected = |selection: TopNodeSelection| {
+            let mut engine = CommunityEngine::new(graph.clone()).with_top_node_selection(selection);
+            engine.rebuild_hierarchy(1, &DeterministicSummarizer);
+            engine.fastgraphrag_top_nodes().len()
+        };
+
+        assert_eq!(selected(TopNodeSelection::default()), 4);
+        assert_eq!(
+            selected(TopNodeSelection {
+                fraction: 0.25,
+                ..TopNodeSelection::default()
+            }),
+            10
+        );
+        assert_eq!(
+            selected(TopNodeSelection {
+                fraction: 0.5,
+                max_nodes: Some(6),
+                ..TopNodeSelection::default()
+            }),
+            6
+        );
+        assert_eq!(
+            selected(TopNodeSelection {
+                fraction: 0.0,
+                min_nodes: 0,
+                max_nodes: None,
+            }),
+            1
+        );
+        assert_eq!(
+            selected(TopNodeSelection {
+                fraction: 0.01,
+                min_nodes: 100,
+                max_nodes: None,
+            }),
+            40
+        );
+    }
+
+    #[test]
+    fn test_summary_config_limits_top_nodes_and_length() {
+        let mut graph = AdjacencyGraph::new();
+        for id in 1..10 {
+            graph.add_edge(id, id + 1, "links", 1.0);
+            graph.add_edge(id + 1, id, "links", 1.0);
+        }
+        let summaries = |config: SummaryConfig| {
+            let mut engine = CommunityEngine::new(graph.clone())
+                .with_top_node_selection(TopNodeSelection {
+                    fraction: 1.0,
+                    ..TopNodeSelection::default()
+                })
+                .with_summary_config(config);
+            engine.rebuild_hierarchy(1, &DeterministicSummarizer);
+            engine.summaries().to_vec()
+        };
+
+        let unbounded = summaries(SummaryConfig::default());
+        assert!(unbounded.iter().any(|summary| summary.top_nodes.len() > 1));
+
+        let single = summaries(SummaryConfig {
+            max_top_nodes_per_community: Some(1),
+            ..SummaryConfig::default()
+        });
+        for (summary, full) in single.iter().zip(&unbounded) {
+            assert_eq!(summary.top_nodes, full.top_nodes[..1]);
+            assert!(summary
+                .summary
+                .ends_with(&format!("key {:?}", summary.top_nodes)));
+        }
+
+        let short = summaries(SummaryConfig {
+            max_summary_chars: Some(12),
+            ..SummaryConfig::default()
+        });
+        for summary in &short {
+            assert!(summary.summary.chars().count() <= 12);
+            assert!(summary.summary.ends_with('…'));
+        }
+    }
+
+    #[test]
+    fn test_redundant_child_summaries_are_flagged() {
+        struct FixedSummarizer;
+        impl CommunitySummarizer for FixedSummarizer {
+            fn summarize(&self, level: usize, community_id: usize, _: &[u64], _: &[u64]) -> String {
+                match (level, community_id) {
+                    (0, 0) => "EV battery production in Japan.",
+                    (0, 1) => "Wind turbine maintenance costs.",
+                    _ => "EV battery production in Japan grows alongside solar exports.",
+                }
+                .to_string()
+            }
+        }
+
+        let levels = vec![
+            CommunityLevel {
+                level: 0,
+                communities: vec![
+                    Community {
+                        id: 0,
+                        node_ids: vec![1, 2],
+                    },
+                    Community {
+                        id: 1,
+                        node_ids: vec![3, 4],
+                    },
+                ],
+            },
+            CommunityLevel {
+                level: 1,
+                communities: vec![Community {
+                    id: 0,
+                    node_ids: vec![1, 2, 3, 4],
+                }],
+            },
+        ];
+        let build = |threshold: Option<f32>| {
+            let config = SummaryConfig {
+                redundancy_overlap_threshold: threshold,
+                ..SummaryConfig::default()
+            };
+            build_summaries(&levels, &[1], &config, &FixedSummarizer)
+        };
+
+        let flagged = build(Some(0.8));
+        assert_eq!(flagged.len(), 3);
+        let redundancy: Vec<_> = flagged
+            .iter()
+            .map(|summary| (summary.level, summary.community_id, summary.redundant_with))
+            .collect();
+        assert_eq!(
+            redundancy,
+            vec![(0, 0, Some(0)), (0, 1, None), (1, 0, None)]
+        );
+
+        assert!(build(None)
+            .iter()
+            .all(|summary| summary.redundant_with.is_none()));
+    }
+
+    #[test]
+    fn test_pagerank_stops_early_once_converged() {
+        let mut graph = graph_for_test();
+        graph.add_edge(3, 1, "links", 1.0);
+        graph.add_edge(2, 1, "links", 1.0);
+        graph.add_edge(12, 1, "links", 1.0);
+
+        let full_config = PageRankConfig {
+            max_iterations: 200,
+            epsilon: 0.0,
+            ..PageRankConfig::default()
+        };
+        let (full, full_iterations) = compute_pagerank_with(&graph, &full_config, false);
+        assert_eq!(full_iterations, 200);
+
+        let early_config = PageRankConfig {
+            epsilon: 1e-8,
+            ..full_config
+        };
+        let (early, early_iterations) = compute_pagerank_with(&graph, &early_config, false);
+        assert!(early_iterations < 200, "ran {early_iterations} iterations");
+
+        let top = |ranks: &HashMap<u64, f64>| {
+            ranks
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(b.1).then(b.0.cmp(a.0)))
+                .map(|(id, _)| *id)
+        };
+        assert_eq!(top(&early), top(&full));
+        for (node_id, score) in &full {
+            assert!((score - early[node_id]).abs() < 1e-6);
+        }
+    }
 }
@@ -0,0 +1,180 @@
+//! Background rebuilds of community summaries from the live repository graph.
+//!
+//! The scheduler polls the repository's durable snapshot id. Once the snapshot
+//! moves, it rebuilds when either enough graph nodes changed since the last
+//! rebuild (`dirty_node_threshold`) or the rebuild `interval` has elapsed. The
+//! Leiden/PageRank pass runs on the blocking pool and the result is published
+//! through a [`CommunitySummaryHandle`], so queries reading the handle never
+//! wait on a rebuild.
+
+use crate::community::{CommunityEngine, CommunitySummarizer, CommunitySummaryHandle};
+use crate::repo::Repository;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// (source, target, weight bits) of one graph edge.
+type EdgeKey = (u64, u64, u32);
+
+#[derive(Debug, Clone)]
+pub struct CommunitySchedulerConfig {
+    /// Rebuild at least this often while the snapshot keeps changing.
+    pub interval: Duration,
+    /// Rebuild early once this many graph nodes gained or lost edges.
+    pub dirty_node_threshold: usize,
+    /// How often the repository snapshot id is checked.
+    pub poll_interval: Duration,
+    pub max_levels: usize,
+    /// Detect communities within each tenant's subgraph (by node `tenant`
+    /// metadata) so tenant-scoped global search only sees its own summaries.
+    pub partition_by_tenant: bool,
+}
+
+impl Default for CommunitySchedulerConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            dirty_node_threshold: 64,
+            poll_interval: Duration::from_secs(1),
+            max_levels: 3,
+            partition_by_tenant: false,
+        }
+    }
+}
+
+pub struct CommunityScheduler {
+    repo: Arc<Repository>,
+    summaries: CommunitySummaryHandle,
+    summarizer: Arc<dyn CommunitySummarizer>,
+    config: CommunitySchedulerConfig,
+    last_snapshot_id: Option<String>,
+    last_edges: HashSet<EdgeKey>,
+    last_rebuild: Option<Instant>,
+}
+
+impl CommunityScheduler {
+    pub fn new(
+        repo: Arc<Repository>,
+        summaries: CommunitySummaryHandle,
+        summarizer: Arc<dyn CommunitySummarizer>,
+    ) -> Self {
+        Self {
+            repo,
+            summaries,
+            summarizer,
+            config: CommunitySchedulerConfig::default(),
+            last_snapshot_id: None,
+            last_edges: HashSet::new(),
+            last_rebuild: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: CommunitySchedulerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Runs one scheduling decision. Returns whether new summaries were
+    /// published.
+    pub async fn tick(&mut self) -> bool {
+        let snapshot_id = self.repo.current_snapshot_id().await;
+        if self.last_snapshot_id.as_deref() == Some(snapshot_id.as_str()) {
+            return false;
+        }
+
+        let graph = self.repo.graph_index().await;
+        let edges: HashSet<EdgeKey> = graph
+            .edges()
+            .into_iter()
+            .map(|(source, target, weight)| (source, target, weight.to_bits()))
+            .collect();
+        let dirty_nodes = dirty_node_count(&self.last_edges, &edges);
+        let interval_elapsed = self
+            .last_rebuild
+            .is_none_or(|at| at.elapsed() >= self.config.interval);
+        if dirty_nodes < self.config.dirty_node_threshold.max(1) && !interval_elapsed {
+            return false;
+        }
+
+        let node_tenants = if self.config.partition_by_tenant {
+            self.repo.node_tenants().await
+        } else {
+            HashMap::new()
+        };
+        let summarizer = self.summarizer.clone();
+        let max_levels = self.config.max_levels;
+        let rebuilt = tokio::task::spawn_blocking(move || {
+            let mut engine = CommunityEngine::new(graph).with_tenant_partitions(node_tenants);
+            engine.rebuild_hierarchy(max_levels, summarizer.as_ref());
+            engine.summaries().to_vec()
+        })
+        .await;
+        let summaries = match rebuilt {
+            Ok(summaries) => summaries,
+            Err(err) => {
+                warn!("Community rebuild at {} failed: {}", snapshot_id, err);
+                return false;
+            }
+        };
+
+        debug!(
+            "Publishing {} community summaries at {} ({} dirty nodes)",
+            summaries.len(),
+            snapshot_id,
+            dirty_nodes
+        );
+        self.summaries.store(summaries);
+        self.last_snapshot_id = Some(snapshot_id);
+        self.last_edges = edges;
+        self.last_rebuild = Some(Instant::now());
+        true
+    }
+
+    /// Runs the scheduler on the current Tokio runtime until the returned task
+    /// is shut down.
+    pub fn spawn(mut self) -> CommunitySchedulerTask {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let join = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.poll_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        self.tick().await;
+                    }
+                    _ = shutdown_rx.changed() => break,
+                }
+            }
+        });
+        CommunitySchedulerTask {
+            shutdown: shutdown_tx,
+            join,
+        }
+    }
+}
+
+/// Handle to a spawned [`CommunityScheduler`].
+pub struct CommunitySchedulerTask {
+    shutdown: watch::Sender<bool>,
+    join: JoinHandle<()>,
+}
+
+impl CommunitySchedulerTask {
+    /// Stops the scheduler after any in-progress rebuild finishes.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        let _ = self.join.await;
+    }
+}
+
+fn dirty_node_count(previous: &HashSet<EdgeKey>, current: &HashSet<EdgeKey>) -> usize {
+    let mut nodes = HashSet::new();
+    for (source, target, _) in previous.symmetric_difference(current) {
+        nodes.insert(*source);
+        nodes.insert(*target);
+    }
+    nodes.len()
+}
@@ -2,10 +2,11 @@
 use crate::index::HnswIndex;
 #[cfg(not(feature = "hnsw"))]
 use crate::index::LinearAnnIndex;
-use crate::index::{AdjacencyGraph, VectorIndex};
+use crate::index::{AdjacencyGraph, SearchAccuracy, VectorIndex};
 use crate::tiering::{StorageCapabilities, StorageProfile};
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// HyperIndex combines Vector and Graph indexes with ID mapping.
 ///
@@ -15,7 +16,9 @@ use std::collections::HashMap;
 /// swapped without changing any call-site code.
 pub struct HyperIndex {
     pub vector_index: Box<dyn VectorIndex>,
-    pub graph_index: AdjacencyGraph,
+    /// Shared copy-on-write so read handles can pin it; mutate through
+    /// [`Self::graph_mut`].
+    pub graph_index: Arc<AdjacencyGraph>,
     storage_profile: StorageProfile,
     storage_capabilities: StorageCapabilities,
     // ID mapping for cross-referencing (e.g., entity resolution)
@@ -29,7 +32,9 @@ impl HyperIndex {
 
     pub fn with_storage_profile(storage_profile: StorageProfile) -> Self {
         #[cfg(feature = "hnsw")]
-        let vector_index: Box<dyn VectorIndex> = Box::new(HnswIndex::new());
+        let vector_index: Box<dyn VectorIndex> = Box::new(HnswIndex::with_restore_order_seed(
+            storage_profile.effective_restore_order_seed(),
+        ));
         #[cfg(not(feature = "hnsw"))]
         let vector_index: Box<dyn VectorIndex> = Box::new(LinearAnnIndex::new());
 
@@ -44,7 +49,7 @@ impl HyperIndex {
 
         Self {
             vector_index,
-            graph_index: AdjacencyGraph::new(),
+            graph_index: Arc::new(AdjacencyGraph::new()),
             storage_profile,
             storage_capabilities,
             id_aliases: HashMap::new(),
@@ -64,10 +69,20 @@ impl HyperIndex {
         &self.storage_capabilities
     }
 
+    /// The graph index, copied first if a read handle still shares it.
+    pub fn graph_mut(&mut self) -> &mut AdjacencyGraph {
+        Arc::make_mut(&mut self.graph_index)
+    }
+
     pub fn insert_node(&mut self, id: u64, embedding: Vec<f32>) {
         self.vector_index.insert(id, &embedding);
     }
 
+    /// Bulk-load vectors, e.g. when restoring a backup snapshot.
+    pub fn insert_nodes(&mut self, entries: &[(u64, Vec<f32>)]) {
+        self.vector_index.insert_batch(entries);
+    }
+
     pub fn insert_edge(
         &mut self,
         source: u64,
@@ -75,18 +90,22 @@ impl HyperIndex {
         relation: impl Into<String>,
         weight: f32,
     ) {
-        self.graph_index.add_edge(source, target, relation, weight);
+        self.graph_mut().add_edge(source, target, relation, weight);
     }
 
     /// Insert or update an edge. Replaces weight if same (source, target, relation) exists.
     pub fn upsert_edge(&mut self, source: u64, target: u64, relation: &str, weight: f32) {
-        self.graph_index
+        self.graph_mut()
             .upsert_edge(source, target, relation, weight);
     }
 
+    pub fn remove_edges_by_relation(&mut self, relation: &str) -> usize {
+        self.graph_mut().remove_edges_by_relation(relation)
+    }
+
     pub fn remove_node(&mut self, id: u64) {
         self.vector_index.delete(id);
-        self.graph_index.remove_node(id);
+        self.graph_mut().remove_node(id);
         // Remove any aliases pointing to this ID
         self.id_aliases.retain(|_, v| *v != id);
     }
@@ -101,9 +120,34 @@ impl HyperIndex {
         self.id_aliases.get(alias).copied()
     }
 
-    /// Vector search: find top-k similar nodes
+    /// Top-`k` vector hits. A query whose length differs from the indexed
+    /// dimension yields no hits rather than index-specific garbage.
     pub fn search_vector(&self, query: &[f32], k: usize) -> Vec<(u64, f32)> {
-        self.vector_index.search(query, k)
+        self.search_vector_with_accuracy(query, k, SearchAccuracy::Approximate)
+    }
+
+    /// [`Self::search_vector`], routed to the ANN structure or to an
+    /// exhaustive scan.
+    pub fn search_vector_with_accuracy(
+        &self,
+        query: &[f32],
+        k: usize,
+        accuracy: SearchAccuracy,
+    ) -> Vec<(u64, f32)> {
+        if let Some(dim) = self.vector_index.dim() {
+            if dim != query.len() {
+                tracing::warn!(
+                    "HyperIndex::search_vector: query dimension {} does not match indexed dimension {}",
+                    query.len(),
+                    dim
+                );
+                return Vec::new();
+            }
+        }
+        match accuracy {
+            SearchAccuracy::Approximate => self.vector_index.search(query, k),
+            SearchAccuracy::Exact => self.vector_index.exact_search(query, k),
+        }
     }
 
     /// Graph expansion: get neighbors up to max_hops
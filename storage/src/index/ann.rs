@@ -1,6 +1,18 @@
 use alayasiki_core::embedding::cosine_similarity;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Whether a vector search may trade recall for speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchAccuracy {
+    /// Use the index's own (possibly approximate) search structure.
+    #[default]
+    Approximate,
+    /// Brute-force scan over every stored vector: the true top-k.
+    Exact,
+}
+
 /// Abstraction over ANN vector index implementations.
 ///
 /// Implementations must be `Send + Sync` so that `Box<dyn VectorIndex>` can
@@ -13,6 +25,12 @@ pub trait VectorIndex: Send + Sync {
     /// Return the top-`k` most similar nodes to `query`, sorted descending by
     /// cosine similarity score (higher = more similar).
     fn search(&self, query: &[f32], k: usize) -> Vec<(u64, f32)>;
+    /// Like [`Self::search`], but guaranteed to return the true top-`k` by
+    /// scanning every vector. Indexes whose `search` is already exhaustive
+    /// keep the default.
+    fn exact_search(&self, query: &[f32], k: usize) -> Vec<(u64, f32)> {
+        self.search(query, k)
+    }
     /// Number of vectors currently stored.
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool {
@@ -20,6 +38,16 @@ pub trait VectorIndex: Send + Sync {
     }
     /// Dimension of stored vectors, or `None` if the index is empty.
     fn dim(&self) -> Option<usize>;
+    /// Insert many vectors at once. Indexes whose structure depends on
+    /// insertion order may reorder the batch; the default inserts in order.
+    fn insert_batch(&mut self, entries: &[(u64, Vec<f32>)]) {
+        for (id, embedding) in entries {
+            self.insert(*id, embedding);
+        }
+    }
+    /// Touch lazily built or paged-out structures so the first real search
+    /// does not pay for them. No-op by default.
+    fn warmup(&self) {}
 }
 
 /// Simple O(n·d) linear scan ANN index.
@@ -55,7 +83,12 @@ impl VectorIndex for LinearAnnIndex {
             .filter_map(|(id, emb)| cosine_similarity(query, emb).map(|score| (*id, score)))
             .collect();
 
-        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Break score ties by id so the top-k cut does not depend on HashMap order.
+        scores.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
         scores.truncate(k);
         scores
     }
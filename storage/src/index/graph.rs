@@ -1,26 +1,80 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Edge representation: (target_id, relation, weight)
 pub type EdgeData = (u64, String, f32);
 
+/// An edge traversal may follow from a node. `reversed` marks a stored
+/// `neighbor -> node` edge followed against its direction under
+/// [`GraphSemantics::Undirected`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraversalEdge {
+    pub neighbor: u64,
+    pub relation: String,
+    pub weight: f32,
+    pub reversed: bool,
+}
+
+impl TraversalEdge {
+    /// Stored `(source, target)` of this edge when followed from `from`.
+    pub fn endpoints(&self, from: u64) -> (u64, u64) {
+        if self.reversed {
+            (self.neighbor, from)
+        } else {
+            (from, self.neighbor)
+        }
+    }
+}
+
+/// Whether edges are followed only from source to target, or both ways.
+///
+/// Consulted by query traversal ([`AdjacencyGraph::traversal_neighbors`],
+/// [`AdjacencyGraph::expand`]) and PageRank. Leiden community detection always
+/// treats edges as undirected, so `Undirected` makes traversal agree with
+/// community membership.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphSemantics {
+    #[default]
+    Directed,
+    Undirected,
+}
+
 /// Simple adjacency list graph index
 #[derive(Clone, Debug)]
 pub struct AdjacencyGraph {
     adjacency: HashMap<u64, Vec<EdgeData>>,
+    /// Sources with at least one edge into each target.
+    incoming: HashMap<u64, HashSet<u64>>,
+    semantics: GraphSemantics,
 }
 
 impl AdjacencyGraph {
     pub fn new() -> Self {
         Self {
             adjacency: HashMap::new(),
+            incoming: HashMap::new(),
+            semantics: GraphSemantics::default(),
         }
     }
 
+    pub fn with_semantics(mut self, semantics: GraphSemantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    pub fn set_semantics(&mut self, semantics: GraphSemantics) {
+        self.semantics = semantics;
+    }
+
+    pub fn semantics(&self) -> GraphSemantics {
+        self.semantics
+    }
+
     pub fn add_edge(&mut self, source: u64, target: u64, relation: impl Into<String>, weight: f32) {
         self.adjacency
             .entry(source)
             .or_default()
             .push((target, relation.into(), weight));
+        self.incoming.entry(target).or_default().insert(source);
     }
 
     /// Insert or update an edge. All existing entries matching (source, target, relation)
@@ -30,26 +84,85 @@ impl AdjacencyGraph {
         let edges = self.adjacency.entry(source).or_default();
         edges.retain(|(t, r, _)| !(*t == target && r == relation));
         edges.push((target, relation.to_string(), weight));
+        self.incoming.entry(target).or_default().insert(source);
     }
 
     pub fn remove_edge(&mut self, source: u64, target: u64) -> bool {
         if let Some(edges) = self.adjacency.get_mut(&source) {
             let len_before = edges.len();
             edges.retain(|(t, _, _)| *t != target);
-            return edges.len() < len_before;
+            if edges.len() < len_before {
+                self.unlink_incoming(source, target);
+                return true;
+            }
         }
         false
     }
 
+    /// Remove every edge labeled `relation` and return how many were removed.
+    pub fn remove_edges_by_relation(&mut self, relation: &str) -> usize {
+        let mut removed = 0;
+        let mut unlinked = Vec::new();
+        for (source, edges) in self.adjacency.iter_mut() {
+            let targets: Vec<u64> = edges
+                .iter()
+                .filter(|(_, r, _)| r == relation)
+                .map(|(t, _, _)| *t)
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            edges.retain(|(_, r, _)| r != relation);
+            removed += targets.len();
+            for target in targets {
+                if !edges.iter().any(|(t, _, _)| *t == target) {
+                    unlinked.push((*source, target));
+                }
+            }
+        }
+        for (source, target) in unlinked {
+            self.unlink_incoming(source, target);
+        }
+        removed
+    }
+
+    fn unlink_incoming(&mut self, source: u64, target: u64) {
+        if let Some(sources) = self.incoming.get_mut(&target) {
+            sources.remove(&source);
+            if sources.is_empty() {
+                self.incoming.remove(&target);
+            }
+        }
+    }
+
+    pub fn relation_edge_count(&self, relation: &str) -> usize {
+        self.adjacency
+            .values()
+            .map(|edges| edges.iter().filter(|(_, r, _)| r == relation).count())
+            .sum()
+    }
+
     pub fn remove_node(&mut self, id: u64) {
         // Remove outgoing edges
-        self.adjacency.remove(&id);
+        if let Some(edges) = self.adjacency.remove(&id) {
+            for (target, _, _) in edges {
+                self.unlink_incoming(id, target);
+            }
+        }
         // Remove incoming edges
-        for edges in self.adjacency.values_mut() {
-            edges.retain(|(t, _, _)| *t != id);
+        for source in self.incoming.remove(&id).unwrap_or_default() {
+            if let Some(edges) = self.adjacency.get_mut(&source) {
+                edges.retain(|(t, _, _)| *t != id);
+            }
         }
     }
 
+    pub fn contains_edge(&self, source: u64, target: u64, relation: &str) -> bool {
+        self.adjacency
+            .get(&source)
+            .is_some_and(|edges| edges.iter().any(|(t, r, _)| *t == target && r == relation))
+    }
+
     /// Get 1-hop neighbors
     pub fn neighbors(&self, id: u64) -> Vec<&EdgeData> {
         self.adjacency
@@ -58,6 +171,54 @@ impl AdjacencyGraph {
             .unwrap_or_default()
     }
 
+    /// Edges pointing at `id`, as `(source, relation, weight)`, ordered by
+    /// source id.
+    pub fn in_neighbors(&self, id: u64) -> Vec<EdgeData> {
+        let Some(sources) = self.incoming.get(&id) else {
+            return Vec::new();
+        };
+        let mut sources: Vec<u64> = sources.iter().copied().collect();
+        sources.sort_unstable();
+        sources
+            .into_iter()
+            .flat_map(|source| {
+                self.adjacency[&source]
+                    .iter()
+                    .filter(move |(target, _, _)| *target == id)
+                    .map(move |(_, relation, weight)| (source, relation.clone(), *weight))
+            })
+            .collect()
+    }
+
+    /// Edges traversal may follow from `id`: outgoing edges, plus reversed
+    /// incoming edges under [`GraphSemantics::Undirected`].
+    pub fn traversal_neighbors(&self, id: u64) -> Vec<TraversalEdge> {
+        let outgoing = self
+            .neighbors(id)
+            .into_iter()
+            .map(|(target, relation, weight)| TraversalEdge {
+                neighbor: *target,
+                relation: relation.clone(),
+                weight: *weight,
+                reversed: false,
+            });
+        let mut out: Vec<TraversalEdge> = outgoing.collect();
+        if self.semantics == GraphSemantics::Undirected {
+            out.extend(
+                self.in_neighbors(id)
+                    .into_iter()
+                    .filter(|(source, _, _)| *source != id)
+                    .map(|(source, relation, weight)| TraversalEdge {
+                        neighbor: source,
+                        relation,
+                        weight,
+                        reversed: true,
+                    }),
+            );
+        }
+        out
+    }
+
     /// Get neighbors within max_hops (BFS)
     /// Returns a list of (node_id, distance)
     pub fn expand(&self, start_id: u64, max_hops: u8) -> Vec<(u64, u8)> {
@@ -83,13 +244,12 @@ impl AdjacencyGraph {
                 continue;
             }
 
-            if let Some(edges) = self.adjacency.get(&curr_id) {
-                for (target, _, _) in edges {
-                    if !visited.contains_key(target) {
-                        visited.insert(*target, dist + 1);
-                        result.push((*target, dist + 1));
-                        queue.push_back((*target, dist + 1));
-                    }
+            for edge in self.traversal_neighbors(curr_id) {
+                let target = edge.neighbor;
+                if let std::collections::hash_map::Entry::Vacant(entry) = visited.entry(target) {
+                    entry.insert(dist + 1);
+                    result.push((target, dist + 1));
+                    queue.push_back((target, dist + 1));
                 }
             }
         }
@@ -266,4 +426,47 @@ mod tests {
             "expand from nonexistent node should return empty vector"
         );
     }
+
+    #[test]
+    fn test_in_neighbors_track_edge_removals() {
+        let mut graph = AdjacencyGraph::new();
+        graph.add_edge(1, 3, "knows", 1.0);
+        graph.add_edge(2, 3, "likes", 0.5);
+        graph.add_edge(2, 3, "knows", 0.7);
+        assert_eq!(
+            graph.in_neighbors(3),
+            vec![
+                (1, "knows".to_string(), 1.0),
+                (2, "likes".to_string(), 0.5),
+                (2, "knows".to_string(), 0.7),
+            ]
+        );
+
+        graph.remove_edges_by_relation("likes");
+        assert_eq!(graph.in_neighbors(3).len(), 2);
+        graph.remove_edge(2, 3);
+        assert_eq!(graph.in_neighbors(3), vec![(1, "knows".to_string(), 1.0)]);
+        graph.remove_node(1);
+        assert!(graph.in_neighbors(3).is_empty());
+    }
+
+    #[test]
+    fn test_undirected_semantics_traverse_reverse_edges() {
+        let mut graph = AdjacencyGraph::new();
+        graph.add_edge(2, 1, "knows", 1.0);
+        graph.add_edge(3, 2, "knows", 1.0);
+        assert!(graph.traversal_neighbors(1).is_empty());
+        assert!(graph.expand(1, 2).is_empty());
+
+        let graph = graph.with_semantics(GraphSemantics::Undirected);
+        let reversed = TraversalEdge {
+            neighbor: 2,
+            relation: "knows".to_string(),
+            weight: 1.0,
+            reversed: true,
+        };
+        assert_eq!(graph.traversal_neighbors(1), vec![reversed.clone()]);
+        assert_eq!(reversed.endpoints(1), (2, 1));
+        assert_eq!(graph.expand(1, 2), vec![(2, 1), (3, 2)]);
+    }
 }
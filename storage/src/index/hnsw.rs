@@ -1,4 +1,7 @@
 use super::ann::VectorIndex;
+use super::DEFAULT_RESTORE_ORDER_SEED;
+#[cfg(not(target_os = "macos"))]
+use std::collections::HashMap;
 
 #[cfg(target_os = "macos")]
 use super::ann::LinearAnnIndex;
@@ -15,6 +18,14 @@ use usearch::{Index, IndexOptions, MetricKind, ScalarKind};
 /// `LinearAnnIndex` interface). Any subsequent `insert` with a mismatched
 /// dimension is silently ignored (same behaviour as `cosine_similarity`).
 ///
+/// # Restore order
+/// usearch draws node levels from a generator it seeds internally and does not
+/// expose, so the graph depends only on insertion order. Incremental
+/// [`VectorIndex::insert`] calls keep the caller's order; only
+/// [`VectorIndex::insert_batch`], which restoring a backup snapshot uses,
+/// reorders by a hash of each id and the restore order seed, so a restore is
+/// reproducible for a given seed and snapshot.
+///
 /// # Thread safety
 /// `usearch::Index` wraps a C++ object via a raw pointer and therefore does
 /// not derive `Send`/`Sync` automatically. The underlying USearch library is
@@ -45,6 +56,7 @@ pub struct HnswIndex {
     /// Tracks logical element count so `len()` doesn't need to call into C++
     /// after every mutating operation.
     count: usize,
+    restore_order_seed: u64,
 }
 
 #[cfg(target_os = "macos")]
@@ -53,6 +65,7 @@ pub struct HnswIndex {
 pub struct HnswIndex {
     inner: LinearAnnIndex,
     dim: Option<usize>,
+    restore_order_seed: u64,
 }
 
 #[cfg(not(target_os = "macos"))]
@@ -65,10 +78,15 @@ unsafe impl Sync for HnswIndex {}
 #[cfg(not(target_os = "macos"))]
 impl HnswIndex {
     pub fn new() -> Self {
+        Self::with_restore_order_seed(DEFAULT_RESTORE_ORDER_SEED)
+    }
+
+    pub fn with_restore_order_seed(seed: u64) -> Self {
         Self {
             inner: None,
             dim: None,
             count: 0,
+            restore_order_seed: seed,
         }
     }
 
@@ -137,14 +155,62 @@ impl HnswIndex {
             }
         }
     }
+
+    fn run_search(&self, query: &[f32], k: usize, exact: bool) -> Vec<(u64, f32)> {
+        let Some(idx) = &self.inner else {
+            return vec![];
+        };
+        if k == 0 || query.is_empty() {
+            return vec![];
+        }
+        if let Some(d) = self.dim {
+            if d != query.len() {
+                return vec![];
+            }
+        }
+        let count = k.min(self.count);
+        if count == 0 {
+            return vec![];
+        }
+        let found = if exact {
+            idx.exact_search(query, count)
+        } else {
+            idx.search(query, count)
+        };
+        match found {
+            Ok(matches) => {
+                let mut results: Vec<(u64, f32)> = matches
+                    .keys
+                    .into_iter()
+                    .zip(matches.distances)
+                    .map(|(key, dist)| (key, 1.0_f32 - dist))
+                    .collect();
+                results.sort_by(|a, b| {
+                    b.1.partial_cmp(&a.1)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(a.0.cmp(&b.0))
+                });
+                results
+            }
+            Err(e) => {
+                tracing::error!("HnswIndex::search: {e}");
+                vec![]
+            }
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
 impl HnswIndex {
     pub fn new() -> Self {
+        Self::with_restore_order_seed(DEFAULT_RESTORE_ORDER_SEED)
+    }
+
+    pub fn with_restore_order_seed(seed: u64) -> Self {
         Self {
             inner: LinearAnnIndex::new(),
             dim: None,
+            restore_order_seed: seed,
         }
     }
 
@@ -157,6 +223,33 @@ impl HnswIndex {
     }
 }
 
+impl HnswIndex {
+    pub fn restore_order_seed(&self) -> u64 {
+        self.restore_order_seed
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+/// Orders a batch by a seeded hash of each id, keeping only the last entry per
+/// id so upsert semantics match sequential inserts.
+fn restore_batch_order(entries: &[(u64, Vec<f32>)], seed: u64) -> Vec<&(u64, Vec<f32>)> {
+    let mut last: HashMap<u64, usize> = HashMap::with_capacity(entries.len());
+    for (position, (id, _)) in entries.iter().enumerate() {
+        last.insert(*id, position);
+    }
+    let mut ordered: Vec<&(u64, Vec<f32>)> = last.into_values().map(|i| &entries[i]).collect();
+    ordered.sort_by_key(|(id, _)| (splitmix64(id ^ seed), *id));
+    ordered
+}
+
+#[cfg(not(target_os = "macos"))]
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
 impl Default for HnswIndex {
     fn default() -> Self {
         Self::new()
@@ -192,40 +285,28 @@ impl VectorIndex for HnswIndex {
         self.remove_existing(id)
     }
 
-    fn search(&self, query: &[f32], k: usize) -> Vec<(u64, f32)> {
-        let Some(idx) = &self.inner else {
-            return vec![];
-        };
-        if k == 0 || query.is_empty() {
-            return vec![];
-        }
-        if let Some(d) = self.dim {
-            if d != query.len() {
-                return vec![];
+    fn insert_batch(&mut self, entries: &[(u64, Vec<f32>)]) {
+        let ordered = restore_batch_order(entries, self.restore_order_seed);
+        let needed = ordered.iter().filter(|(_, v)| !v.is_empty()).count();
+        if let Some((_, first)) = ordered.iter().find(|(_, v)| !v.is_empty()) {
+            if self.ensure_index(first.len()) {
+                self.maybe_reserve(self.count + needed);
             }
         }
-        let count = k.min(self.count);
-        if count == 0 {
-            return vec![];
-        }
-        match idx.search(query, count) {
-            Ok(matches) => {
-                let mut results: Vec<(u64, f32)> = matches
-                    .keys
-                    .into_iter()
-                    .zip(matches.distances)
-                    .map(|(key, dist)| (key, 1.0_f32 - dist))
-                    .collect();
-                results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                results
-            }
-            Err(e) => {
-                tracing::error!("HnswIndex::search: {e}");
-                vec![]
-            }
+        for (id, embedding) in ordered {
+            self.insert(*id, embedding);
         }
     }
 
+    fn search(&self, query: &[f32], k: usize) -> Vec<(u64, f32)> {
+        self.run_search(query, k, false)
+    }
+
+    /// Brute-force scan through usearch, bypassing the HNSW graph.
+    fn exact_search(&self, query: &[f32], k: usize) -> Vec<(u64, f32)> {
+        self.run_search(query, k, true)
+    }
+
     fn len(&self) -> usize {
         self.count
     }
@@ -237,6 +318,18 @@ impl VectorIndex for HnswIndex {
             self.dim
         }
     }
+
+    /// Runs one probe search so the graph's upper layers are resident before
+    /// the first query.
+    fn warmup(&self) {
+        let Some(dim) = self.dim() else { return };
+        if self.search(&vec![1.0; dim], 1).is_empty() {
+            tracing::warn!(
+                "HnswIndex::warmup: probe search over {} vectors returned nothing",
+                self.count
+            );
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
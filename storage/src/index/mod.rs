@@ -3,7 +3,11 @@ pub mod graph;
 #[cfg(feature = "hnsw")]
 pub mod hnsw;
 
-pub use ann::{LinearAnnIndex, VectorIndex};
-pub use graph::AdjacencyGraph;
+pub use ann::{LinearAnnIndex, SearchAccuracy, VectorIndex};
+pub use graph::{AdjacencyGraph, GraphSemantics, TraversalEdge};
 #[cfg(feature = "hnsw")]
 pub use hnsw::HnswIndex;
+
+/// Seed for the HNSW restore order when the storage profile does not set one,
+/// so two restores of the same snapshot build identical graphs.
+pub const DEFAULT_RESTORE_ORDER_SEED: u64 = 0x5EED_A1A7_A51C_0001;
@@ -1,11 +1,16 @@
 use super::replay::{apply_replayed_entry, load_materialized_state_from_backup};
 use super::{
-    collect_backup_edges, current_unix_timestamp_ms, parse_wal_snapshot_lsn, RepoError, Repository,
+    collect_backup_edges, current_unix_timestamp_ms, RepoError, Repository,
     RepositoryBackupSnapshot, SnapshotView,
 };
+use crate::snapshot::{SnapshotError, SnapshotReport};
+use alayasiki_core::audit::{AuditEvent, AuditOperation, AuditOutcome};
 use rkyv::ser::serializers::AllocSerializer;
 use rkyv::ser::Serializer;
-use rkyv::Deserialize;
+use rkyv::{AlignedVec, Deserialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 impl Repository {
     pub(super) async fn record_durable_snapshot(&self, durable_lsn: u64) -> Result<(), RepoError> {
@@ -18,6 +23,12 @@ impl Repository {
 
     /// Create a durable backup snapshot file at the current WAL LSN.
     pub async fn create_backup_snapshot(&self) -> Result<String, RepoError> {
+        let result = self.write_backup_snapshot().await;
+        self.emit_backup_audit_event(AuditOperation::Snapshot, &result);
+        result
+    }
+
+    async fn write_backup_snapshot(&self) -> Result<String, RepoError> {
         let snapshot_manager = self
             .snapshot_manager
             .as_ref()
@@ -89,11 +100,17 @@ impl Repository {
             .create_snapshot(snapshot.lsn, &encoded)
             .await?;
 
-        Ok(format!("wal-lsn-{}", snapshot.lsn))
+        Ok(self.snapshot_id_codec.encode(snapshot.lsn))
     }
 
     /// Rebuild in-memory state from the latest backup snapshot plus WAL delta replay.
     pub async fn restore_from_latest_backup(&self) -> Result<String, RepoError> {
+        let result = self.restore_latest_backup().await;
+        self.emit_backup_audit_event(AuditOperation::Restore, &result);
+        result
+    }
+
+    async fn restore_latest_backup(&self) -> Result<String, RepoError> {
         if self.snapshot_manager.is_none() {
             return Err(RepoError::SnapshotNotConfigured);
         }
@@ -135,19 +152,49 @@ impl Repository {
             .await?;
         }
 
-        *self.nodes.write().await = materialized.nodes;
+        materialized
+            .hyper_index
+            .graph_mut()
+            .set_semantics(self.graph_semantics);
+        *self.nodes.write().await = Arc::new(materialized.nodes);
         *self.hyper_index.write().await = materialized.hyper_index;
         *self.idempotency_index.write().await = materialized.idempotency_index;
-        *self.edge_metadata.write().await = materialized.edge_metadata;
+        *self.edge_metadata.write().await = Arc::new(materialized.edge_metadata);
+        self.bump_state_generation();
 
-        Ok(format!("wal-lsn-{target_lsn}"))
+        Ok(self.snapshot_id_codec.encode(target_lsn))
+    }
+
+    fn emit_backup_audit_event(
+        &self,
+        operation: AuditOperation,
+        result: &Result<String, RepoError>,
+    ) {
+        let Some(sink) = &self.audit_sink else {
+            return;
+        };
+        let event = match result {
+            Ok(snapshot_id) => {
+                let mut event = AuditEvent::new(operation, AuditOutcome::Succeeded);
+                event.snapshot_id = Some(snapshot_id.clone());
+                event
+            }
+            Err(err) => {
+                let mut event = AuditEvent::new(operation, AuditOutcome::Failed);
+                event.metadata.insert("error".to_string(), err.to_string());
+                event
+            }
+        };
+        if let Err(err) = sink.record(event) {
+            tracing::warn!("Recording {:?} audit event failed: {}", operation, err);
+        }
     }
 
     /// Materialize an immutable read view at the specified snapshot.
-    /// Supported format: `wal-lsn-<number>`.
+    /// Accepts ids from the configured snapshot id codec and legacy
+    /// `wal-lsn-<number>` ids.
     pub async fn load_snapshot_view(&self, snapshot_id: &str) -> Result<SnapshotView, RepoError> {
-        let target_lsn = parse_wal_snapshot_lsn(snapshot_id)
-            .ok_or_else(|| RepoError::InvalidSnapshotId(snapshot_id.to_string()))?;
+        let target_lsn = self.resolve_snapshot_id(snapshot_id)?.lsn();
 
         let current_lsn = {
             let wal = self.wal.lock().await;
@@ -186,6 +233,10 @@ impl Repository {
         })
         .await?;
 
+        materialized
+            .hyper_index
+            .graph_mut()
+            .set_semantics(self.graph_semantics);
         Ok(SnapshotView {
             snapshot_id: snapshot_id.to_string(),
             nodes: materialized.nodes,
@@ -195,10 +246,135 @@ impl Repository {
     }
 }
 
+/// Structural checks behind [`crate::snapshot::SnapshotManager::verify`].
+pub(crate) fn verify_backup_snapshot(
+    bytes: &[u8],
+    expected_lsn: u64,
+    path: PathBuf,
+) -> Result<SnapshotReport, SnapshotError> {
+    let payload = decode_backup_file(bytes)?;
+    let archived = rkyv::check_archived_root::<RepositoryBackupSnapshot>(&payload[..])
+        .map_err(|err| SnapshotError::Corrupt(format!("invalid archive: {err}")))?;
+    let snapshot: RepositoryBackupSnapshot = archived
+        .deserialize(&mut rkyv::Infallible)
+        .map_err(|_| SnapshotError::Deserialization)?;
+
+    if snapshot.lsn != expected_lsn {
+        return Err(SnapshotError::Corrupt(format!(
+            "snapshot records lsn {} but is stored as lsn {expected_lsn}",
+            snapshot.lsn
+        )));
+    }
+
+    let mut node_ids = HashSet::with_capacity(snapshot.nodes.len());
+    for node in &snapshot.nodes {
+        if !node_ids.insert(node.id) {
+            return Err(SnapshotError::Corrupt(format!(
+                "duplicate node {}",
+                node.id
+            )));
+        }
+    }
+
+    let mut edge_keys = HashSet::with_capacity(snapshot.edges.len());
+    for edge in &snapshot.edges {
+        if let Some(missing) = [edge.source, edge.target]
+            .into_iter()
+            .find(|id| !node_ids.contains(id))
+        {
+            return Err(SnapshotError::Corrupt(format!(
+                "edge {} -> {} ({}) references missing node {missing}",
+                edge.source, edge.target, edge.relation
+            )));
+        }
+        edge_keys.insert((edge.source, edge.target, edge.relation.as_str()));
+    }
+
+    for record in &snapshot.edge_metadata {
+        if record.relation.is_empty()
+            || !edge_keys.contains(&(record.source, record.target, record.relation.as_str()))
+        {
+            return Err(SnapshotError::Corrupt(format!(
+                "edge metadata for {} -> {} ({:?}) has no matching edge",
+                record.source, record.target, record.relation
+            )));
+        }
+    }
+
+    Ok(SnapshotReport {
+        lsn: snapshot.lsn,
+        path,
+        node_count: snapshot.nodes.len(),
+        edge_count: snapshot.edges.len(),
+        idempotency_key_count: snapshot.idempotency.len(),
+        edge_metadata_count: snapshot.edge_metadata.len(),
+    })
+}
+
+/// Magic bytes opening every backup snapshot file.
+const BACKUP_MAGIC: [u8; 4] = *b"ALBK";
+/// Bumped whenever the archived `RepositoryBackupSnapshot` layout changes.
+pub(super) const BACKUP_FORMAT_VERSION: u32 = 1;
+/// Magic, format version (u32), payload length (u64) and payload CRC32 (u32),
+/// all little-endian.
+const BACKUP_HEADER_LEN: usize = 4 + 4 + 8 + 4;
+
 fn serialize_backup_snapshot(snapshot: &RepositoryBackupSnapshot) -> Result<Vec<u8>, RepoError> {
     let mut serializer = AllocSerializer::<4096>::default();
     serializer
         .serialize_value(snapshot)
         .map_err(|_| RepoError::Serialization)?;
-    Ok(serializer.into_serializer().into_inner().to_vec())
+    let payload = serializer.into_serializer().into_inner();
+
+    let mut out = Vec::with_capacity(BACKUP_HEADER_LEN + payload.len());
+    out.extend_from_slice(&BACKUP_MAGIC);
+    out.extend_from_slice(&BACKUP_FORMAT_VERSION.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+/// Validate the header of a backup snapshot file and return its payload,
+/// copied into an aligned buffer for rkyv validation. Files without the
+/// magic predate the header (format version 0) and are the bare payload;
+/// they carry no checksum, so only rkyv validation guards them.
+pub(super) fn decode_backup_file(bytes: &[u8]) -> Result<AlignedVec, SnapshotError> {
+    if !bytes.starts_with(&BACKUP_MAGIC) {
+        let mut aligned = AlignedVec::with_capacity(bytes.len());
+        aligned.extend_from_slice(bytes);
+        return Ok(aligned);
+    }
+    if bytes.len() < BACKUP_HEADER_LEN {
+        return Err(SnapshotError::Corrupt(
+            "truncated backup snapshot header".to_string(),
+        ));
+    }
+    let version = u32::from_le_bytes(bytes[4..8].try_into().expect("4-byte slice"));
+    if version != BACKUP_FORMAT_VERSION {
+        return Err(SnapshotError::VersionMismatch {
+            found: version,
+            expected: BACKUP_FORMAT_VERSION,
+        });
+    }
+    let payload_len = u64::from_le_bytes(bytes[8..16].try_into().expect("8-byte slice"));
+    let expected_crc = u32::from_le_bytes(bytes[16..20].try_into().expect("4-byte slice"));
+    let payload = &bytes[BACKUP_HEADER_LEN..];
+    if payload.len() as u64 != payload_len {
+        return Err(SnapshotError::Corrupt(format!(
+            "payload is {} bytes but header records {payload_len}",
+            payload.len()
+        )));
+    }
+    let actual_crc = crc32fast::hash(payload);
+    if actual_crc != expected_crc {
+        return Err(SnapshotError::ChecksumMismatch {
+            expected: expected_crc,
+            actual: actual_crc,
+        });
+    }
+
+    let mut aligned = AlignedVec::with_capacity(payload.len());
+    aligned.extend_from_slice(payload);
+    Ok(aligned)
 }
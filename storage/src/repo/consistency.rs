@@ -0,0 +1,188 @@
+use super::replay::{apply_tx_operation, serialize_wal_entry};
+use super::{EdgeMetaKey, RepoError, Repository, TxOperation, WalEntry};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+/// A cross-index inconsistency found by [`Repository::validate_consistency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    /// A graph edge whose source or target has no stored node.
+    DanglingEdge {
+        source: u64,
+        target: u64,
+        relation: String,
+        missing_node: u64,
+    },
+    /// Edge metadata with no matching edge in the graph index.
+    OrphanEdgeMetadata {
+        source: u64,
+        target: u64,
+        relation: String,
+    },
+    /// An idempotency key recording node ids that are no longer stored.
+    DanglingIdempotencyEntry {
+        key: String,
+        missing_node_ids: Vec<u64>,
+    },
+}
+
+impl Repository {
+    /// Report dangling edges, orphan edge metadata and idempotency entries that
+    /// point at missing nodes. Does not mutate state.
+    ///
+    /// Takes `tx_lock` so the scan neither observes a half-applied write nor
+    /// queues its read locks against a writer's, which would deadlock.
+    pub async fn validate_consistency(&self) -> Vec<ConsistencyIssue> {
+        let _tx_guard = self.tx_lock.lock().await;
+        self.collect_consistency_issues().await
+    }
+
+    /// Body of [`Self::validate_consistency`]; the caller holds `tx_lock`.
+    async fn collect_consistency_issues(&self) -> Vec<ConsistencyIssue> {
+        let nodes = self.nodes.read().await;
+        let index = self.hyper_index.read().await;
+        let edge_meta = self.edge_metadata.read().await;
+        let idempotency = self.idempotency_index.read().await;
+
+        let mut issues = Vec::new();
+        let mut graph_edges: HashSet<EdgeMetaKey> = HashSet::new();
+        for source in index.graph_index.node_ids() {
+            let mut outgoing: Vec<(u64, String)> = index
+                .graph_index
+                .neighbors(source)
+                .into_iter()
+                .map(|(target, relation, _)| (*target, relation.clone()))
+                .collect();
+            outgoing.sort();
+            for (target, relation) in outgoing {
+                let missing_node = [source, target]
+                    .into_iter()
+                    .find(|id| !nodes.contains_key(id));
+                if let Some(missing_node) = missing_node {
+                    issues.push(ConsistencyIssue::DanglingEdge {
+                        source,
+                        target,
+                        relation: relation.clone(),
+                        missing_node,
+                    });
+                }
+                graph_edges.insert((source, target, relation));
+            }
+        }
+
+        let mut orphan_metadata: Vec<&EdgeMetaKey> = edge_meta
+            .keys()
+            .filter(|key| !graph_edges.contains(*key))
+            .collect();
+        orphan_metadata.sort();
+        issues.extend(
+            orphan_metadata
+                .into_iter()
+                .map(
+                    |(source, target, relation)| ConsistencyIssue::OrphanEdgeMetadata {
+                        source: *source,
+                        target: *target,
+                        relation: relation.clone(),
+                    },
+                ),
+        );
+
+        let mut keys: Vec<&String> = idempotency.keys().collect();
+        keys.sort();
+        for key in keys {
+            let missing_node_ids: Vec<u64> = idempotency[key]
+                .iter()
+                .copied()
+                .filter(|id| !nodes.contains_key(id))
+                .collect();
+            if !missing_node_ids.is_empty() {
+                issues.push(ConsistencyIssue::DanglingIdempotencyEntry {
+                    key: key.clone(),
+                    missing_node_ids,
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Touch the vector index after open so the first query does not pay for
+    /// lazy initialization. A no-op for the linear index.
+    pub async fn warmup(&self) {
+        self.hyper_index.read().await.vector_index.warmup();
+    }
+
+    /// [`Self::warmup`], then fail with [`RepoError::Inconsistent`] if
+    /// [`Self::validate_consistency`] reports any issue, so a damaged
+    /// repository is caught at startup rather than by a query. Safe to run
+    /// while writes are accepted; validation waits for in-flight writes.
+    pub async fn warmup_and_validate(&self) -> Result<(), RepoError> {
+        self.warmup().await;
+        let issues = self.validate_consistency().await;
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(RepoError::Inconsistent(issues))
+        }
+    }
+
+    /// Remove every entry reported by [`Self::validate_consistency`] in one WAL
+    /// transaction and return the issues that were repaired.
+    ///
+    /// Dangling edges are dropped by deleting their missing endpoints, which
+    /// also clears any metadata attached to those edges.
+    pub async fn repair(&self) -> Result<Vec<ConsistencyIssue>, RepoError> {
+        let _tx_guard = self.tx_lock.lock().await;
+
+        let issues = self.collect_consistency_issues().await;
+        if issues.is_empty() {
+            return Ok(issues);
+        }
+
+        let mut missing_nodes = BTreeSet::new();
+        let mut tx_operations = Vec::new();
+        for issue in &issues {
+            match issue {
+                ConsistencyIssue::DanglingEdge { missing_node, .. } => {
+                    missing_nodes.insert(*missing_node);
+                }
+                ConsistencyIssue::OrphanEdgeMetadata {
+                    source,
+                    target,
+                    relation,
+                } => tx_operations.push(TxOperation::RemoveEdgeMetadata {
+                    source: *source,
+                    target: *target,
+                    relation: relation.clone(),
+                }),
+                ConsistencyIssue::DanglingIdempotencyEntry { key, .. } => {
+                    tx_operations.push(TxOperation::RemoveIdempotency { key: key.clone() })
+                }
+            }
+        }
+        tx_operations.extend(missing_nodes.into_iter().map(TxOperation::Delete));
+
+        let tx_entry = WalEntry::Transaction(tx_operations.clone());
+        let tx_bytes = serialize_wal_entry(&tx_entry)?;
+
+        let durable_lsn = {
+            let mut wal = self.wal.lock().await;
+            wal.append(&tx_bytes).await?;
+            wal.durable_lsn()
+        };
+        self.record_durable_snapshot(durable_lsn).await?;
+
+        let mut nodes_guard = self.nodes.write().await;
+        let nodes = Arc::make_mut(&mut nodes_guard);
+        let mut index = self.hyper_index.write().await;
+        let mut idempotency = self.idempotency_index.write().await;
+        let mut edge_meta_guard = self.edge_metadata.write().await;
+        let edge_meta = Arc::make_mut(&mut edge_meta_guard);
+        for operation in &tx_operations {
+            apply_tx_operation(operation, nodes, &mut index, &mut idempotency, edge_meta);
+        }
+        self.bump_state_generation();
+
+        Ok(issues)
+    }
+}
@@ -0,0 +1,187 @@
+//! Content-addressed snapshot ids.
+//!
+//! A content id is a SHA-256 over the materialized state (nodes, edges and
+//! edge metadata, each in a canonical order), so two repositories, or two
+//! LSNs of one repository, holding identical data share the same id. Edge
+//! creation stamps record when data arrived rather than what it is, so they
+//! are left out of the hash. Ids are
+//! remembered in memory together with the LSN they were computed at, which is
+//! what lets `load_snapshot_view` and query pinning resolve them; after a
+//! restart an id resolves again once `content_snapshot_id` is called with the
+//! same content. Only the most recent ids are remembered.
+
+use super::{
+    collect_backup_edges, EdgeMetaKey, RepoError, Repository, EDGE_CREATED_AT_KEY,
+    EDGE_CREATED_LSN_KEY,
+};
+use crate::hyper_index::HyperIndex;
+use alayasiki_core::model::Node;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+pub const CONTENT_SNAPSHOT_ID_PREFIX: &str = "content-sha256-";
+
+/// Content ids kept resolvable; the ones observed at the oldest LSN are
+/// forgotten first.
+pub(super) const MAX_CONTENT_SNAPSHOT_IDS: usize = 1024;
+
+impl Repository {
+    /// Hash of the current materialized state, as `content-sha256-<hex>`.
+    /// Pending WAL appends are flushed first so the id can be pinned. The
+    /// hash is reused until a write changes nodes, edges or edge metadata.
+    pub async fn content_snapshot_id(&self) -> Result<String, RepoError> {
+        if let Some(content_id) = self.cached_content_snapshot_id() {
+            return Ok(content_id);
+        }
+
+        let _tx_guard = self.tx_lock.lock().await;
+        if let Some(content_id) = self.cached_content_snapshot_id() {
+            return Ok(content_id);
+        }
+        let generation = self.state_generation.load(Ordering::Acquire);
+        let lsn = {
+            let mut wal = self.wal.lock().await;
+            wal.flush().await?;
+            wal.durable_lsn()
+        };
+        let content_id = {
+            let nodes = self.nodes.read().await;
+            let index = self.hyper_index.read().await;
+            let edge_metadata = self.edge_metadata.read().await;
+            hash_materialized_state(&nodes, &index, &edge_metadata)
+        };
+
+        {
+            let mut content_ids = self
+                .content_snapshot_ids
+                .write()
+                .expect("content snapshot id lock poisoned");
+            content_ids.insert(content_id.clone(), lsn);
+            if content_ids.len() > MAX_CONTENT_SNAPSHOT_IDS {
+                if let Some(oldest) = content_ids
+                    .iter()
+                    .min_by_key(|(_, lsn)| **lsn)
+                    .map(|(id, _)| id.clone())
+                {
+                    content_ids.remove(&oldest);
+                }
+            }
+        }
+        *self
+            .latest_content_snapshot_id
+            .lock()
+            .expect("content snapshot id lock poisoned") = Some((generation, content_id.clone()));
+        Ok(content_id)
+    }
+
+    /// Cheap id of the in-memory state, as `state-<instance>-<generation>`.
+    /// It changes after every write that alters nodes, edges or edge
+    /// metadata and never repeats across repositories in one process, but
+    /// unlike a content id it does not survive a restart or match a replica.
+    /// Takes no locks and never touches the WAL, so per-query cache keys can
+    /// use it.
+    pub fn local_state_id(&self) -> String {
+        format!(
+            "state-{}-{}",
+            self.instance_id,
+            self.state_generation.load(Ordering::Acquire)
+        )
+    }
+
+    /// Marks the in-memory state as changed. Writers call this after
+    /// applying, so a reader that sees the new generation also sees the data.
+    pub(super) fn bump_state_generation(&self) {
+        self.state_generation.fetch_add(1, Ordering::Release);
+    }
+
+    fn cached_content_snapshot_id(&self) -> Option<String> {
+        let generation = self.state_generation.load(Ordering::Acquire);
+        self.latest_content_snapshot_id
+            .lock()
+            .expect("content snapshot id lock poisoned")
+            .as_ref()
+            .filter(|(cached_generation, _)| *cached_generation == generation)
+            .map(|(_, content_id)| content_id.clone())
+    }
+
+    /// LSN a previously computed content id was observed at.
+    pub(super) fn resolve_content_snapshot_id(&self, snapshot_id: &str) -> Option<u64> {
+        if !snapshot_id.starts_with(CONTENT_SNAPSHOT_ID_PREFIX) {
+            return None;
+        }
+        self.content_snapshot_ids
+            .read()
+            .expect("content snapshot id lock poisoned")
+            .get(snapshot_id)
+            .copied()
+    }
+}
+
+fn hash_materialized_state(
+    nodes: &HashMap<u64, Node>,
+    index: &HyperIndex,
+    edge_metadata: &HashMap<EdgeMetaKey, HashMap<String, String>>,
+) -> String {
+    let mut hasher = Sha256::new();
+
+    let mut node_ids: Vec<u64> = nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    hasher.update(b"nodes");
+    hasher.update((node_ids.len() as u64).to_le_bytes());
+    for id in node_ids {
+        let node = &nodes[&id];
+        hasher.update(id.to_le_bytes());
+        hasher.update((node.embedding.len() as u64).to_le_bytes());
+        for value in &node.embedding {
+            hasher.update(value.to_bits().to_le_bytes());
+        }
+        hash_str(&mut hasher, &node.data);
+        hash_map(&mut hasher, &node.metadata, &[]);
+    }
+
+    let edges = collect_backup_edges(index);
+    hasher.update(b"edges");
+    hasher.update((edges.len() as u64).to_le_bytes());
+    for edge in edges {
+        hasher.update(edge.source.to_le_bytes());
+        hasher.update(edge.target.to_le_bytes());
+        hash_str(&mut hasher, &edge.relation);
+        hasher.update(edge.weight.to_bits().to_le_bytes());
+    }
+
+    let mut meta_keys: Vec<&EdgeMetaKey> = edge_metadata.keys().collect();
+    meta_keys.sort();
+    hasher.update(b"edge_metadata");
+    hasher.update((meta_keys.len() as u64).to_le_bytes());
+    for key in meta_keys {
+        hasher.update(key.0.to_le_bytes());
+        hasher.update(key.1.to_le_bytes());
+        hash_str(&mut hasher, &key.2);
+        hash_map(
+            &mut hasher,
+            &edge_metadata[key],
+            &[EDGE_CREATED_LSN_KEY, EDGE_CREATED_AT_KEY],
+        );
+    }
+
+    format!("{CONTENT_SNAPSHOT_ID_PREFIX}{:x}", hasher.finalize())
+}
+
+fn hash_str(hasher: &mut Sha256, value: &str) {
+    hasher.update((value.len() as u64).to_le_bytes());
+    hasher.update(value.as_bytes());
+}
+
+fn hash_map(hasher: &mut Sha256, map: &HashMap<String, String>, skip_keys: &[&str]) {
+    let mut entries: Vec<(&String, &String)> = map
+        .iter()
+        .filter(|(key, _)| !skip_keys.contains(&key.as_str()))
+        .collect();
+    entries.sort();
+    hasher.update((entries.len() as u64).to_le_bytes());
+    for (key, value) in entries {
+        hash_str(hasher, key);
+        hash_str(hasher, value);
+    }
+}
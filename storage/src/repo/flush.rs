@@ -0,0 +1,82 @@
+use super::{flush_pending_and_record, Repository};
+use crate::snapshot::SnapshotCatalog;
+use crate::wal::{Wal, WalFlushPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+/// Periodic WAL flush task owned by a [`Repository`]. Dropping it stops the
+/// task after a final flush of whatever is still buffered.
+pub(super) struct BackgroundFlush {
+    stop: watch::Sender<bool>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl BackgroundFlush {
+    pub(super) fn spawn(
+        wal: Arc<Mutex<Wal>>,
+        snapshot_catalog: Arc<Mutex<SnapshotCatalog>>,
+        interval: Duration,
+    ) -> Self {
+        let (stop, mut stopped) = watch::channel(false);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = stopped.changed() => break,
+                }
+                flush_pending_and_record(&wal, &snapshot_catalog).await;
+            }
+            flush_pending_and_record(&wal, &snapshot_catalog).await;
+        });
+        Self {
+            stop,
+            task: Some(task),
+        }
+    }
+}
+
+impl Drop for BackgroundFlush {
+    fn drop(&mut self) {
+        let _ = self.stop.send(true);
+        let Some(task) = self.task.take() else {
+            return;
+        };
+        // On a multi-threaded runtime, wait for the final flush so writes are
+        // durable once the repository is gone. A current-thread runtime cannot
+        // block here; the task then flushes the next time it is polled.
+        if let Ok(handle) = Handle::try_current() {
+            if handle.runtime_flavor() == RuntimeFlavor::MultiThread {
+                let _ = tokio::task::block_in_place(|| handle.block_on(task));
+            }
+        }
+    }
+}
+
+impl Repository {
+    /// Trade per-write fsync for throughput: appends are buffered (flushed
+    /// inline only when `interval` has passed since the last flush) and a
+    /// background task flushes the WAL every `interval`.
+    ///
+    /// A crash loses at most the writes of the last `interval`. Dropping the
+    /// repository flushes pending writes; on a current-thread runtime that
+    /// final flush runs asynchronously, so call [`Repository::flush`] before
+    /// shutting such a runtime down. A zero `interval` keeps an fsync per
+    /// write. Replaces the flush task started on open under an interval
+    /// flush policy, so the WAL is never flushed by two tasks.
+    pub async fn with_background_flush(mut self, interval: Duration) -> Self {
+        self.wal
+            .lock()
+            .await
+            .set_flush_policy(WalFlushPolicy::Interval(interval));
+        self.background_flush = (!interval.is_zero()).then(|| {
+            BackgroundFlush::spawn(self.wal.clone(), self.snapshot_catalog.clone(), interval)
+        });
+        self
+    }
+}
@@ -0,0 +1,32 @@
+use super::{RepoError, Repository};
+
+/// Sizes of the WAL and in-memory structures, for capacity planning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Footprint {
+    /// WAL size including appends not yet flushed to the file.
+    pub wal_bytes: u64,
+    pub node_count: usize,
+    /// Estimated as `node_count * dimension * 4` (f32 components); nodes
+    /// without embeddings are not distinguished.
+    pub approx_vector_bytes: u64,
+    pub edge_count: usize,
+}
+
+impl Repository {
+    /// Reports storage sizes without scanning embeddings: the WAL size comes
+    /// from file metadata and vector bytes are estimated from the dimension
+    /// of a single node.
+    pub async fn storage_footprint(&self) -> Result<Footprint, RepoError> {
+        let wal_bytes = self.wal.lock().await.size_bytes().await?;
+        let node_count = self.nodes.read().await.len();
+        let dimension = self.embedding_dimension().await.unwrap_or(0);
+        let edge_count = self.hyper_index.read().await.graph_index.edge_count();
+
+        Ok(Footprint {
+            wal_bytes,
+            node_count,
+            approx_vector_bytes: (node_count * dimension * std::mem::size_of::<f32>()) as u64,
+            edge_count,
+        })
+    }
+}
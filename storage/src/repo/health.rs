@@ -0,0 +1,126 @@
+use super::Repository;
+use std::future::Future;
+use std::time::Duration;
+
+/// How long [`Repository::health_check`] waits for each lock before
+/// reporting the component as unhealthy.
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    Unhealthy,
+}
+
+/// Status of one repository subsystem, with the failure reason when unhealthy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentHealth {
+    pub component: &'static str,
+    pub status: HealthStatus,
+    pub detail: Option<String>,
+}
+
+impl ComponentHealth {
+    fn healthy(component: &'static str) -> Self {
+        Self {
+            component,
+            status: HealthStatus::Healthy,
+            detail: None,
+        }
+    }
+
+    fn unhealthy(component: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            component,
+            status: HealthStatus::Unhealthy,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Result of [`Repository::health_check`]: one entry each for `wal`, `nodes`
+/// and `index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    pub components: Vec<ComponentHealth>,
+    /// WAL LSN read during the check; `None` when the WAL lock timed out.
+    pub current_lsn: Option<u64>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.components
+            .iter()
+            .all(|component| component.status == HealthStatus::Healthy)
+    }
+
+    pub fn component(&self, name: &str) -> Option<&ComponentHealth> {
+        self.components
+            .iter()
+            .find(|component| component.component == name)
+    }
+}
+
+impl Repository {
+    /// Readiness probe: checks that the WAL lock can be taken and its file is
+    /// writable, that the node store can be read, and that the vector index
+    /// answers a lookup for a stored node. Nothing is written.
+    pub async fn health_check(&self) -> HealthReport {
+        self.health_check_with_timeout(DEFAULT_HEALTH_CHECK_TIMEOUT)
+            .await
+    }
+
+    /// [`Self::health_check`] with a custom per-lock timeout.
+    pub async fn health_check_with_timeout(&self, timeout: Duration) -> HealthReport {
+        let (wal, current_lsn) = match with_timeout(timeout, self.wal.lock()).await {
+            None => (
+                ComponentHealth::unhealthy("wal", lock_timeout(timeout)),
+                None,
+            ),
+            Some(wal) => match wal.check_writable().await {
+                Ok(()) => (ComponentHealth::healthy("wal"), Some(wal.current_lsn())),
+                Err(err) => (
+                    ComponentHealth::unhealthy("wal", err.to_string()),
+                    Some(wal.current_lsn()),
+                ),
+            },
+        };
+
+        let (nodes, probe) = match with_timeout(timeout, self.nodes.read()).await {
+            None => (
+                ComponentHealth::unhealthy("nodes", lock_timeout(timeout)),
+                None,
+            ),
+            Some(nodes) => {
+                let probe = nodes
+                    .values()
+                    .find(|node| !node.embedding.is_empty())
+                    .map(|node| node.embedding.clone());
+                (ComponentHealth::healthy("nodes"), probe)
+            }
+        };
+
+        let index = match with_timeout(timeout, self.hyper_index.read()).await {
+            None => ComponentHealth::unhealthy("index", lock_timeout(timeout)),
+            Some(index) => match probe {
+                Some(embedding) if index.search_vector(&embedding, 1).is_empty() => {
+                    ComponentHealth::unhealthy("index", "vector search returned no results")
+                }
+                _ => ComponentHealth::healthy("index"),
+            },
+        };
+
+        HealthReport {
+            components: vec![wal, nodes, index],
+            current_lsn,
+        }
+    }
+}
+
+async fn with_timeout<T>(timeout: Duration, future: impl Future<Output = T>) -> Option<T> {
+    tokio::time::timeout(timeout, future).await.ok()
+}
+
+fn lock_timeout(timeout: Duration) -> String {
+    format!("lock not acquired within {}ms", timeout.as_millis())
+}
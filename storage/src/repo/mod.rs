@@ -1,20 +1,41 @@
 mod backup;
+mod consistency;
+mod content_id;
+mod flush;
+mod footprint;
+mod health;
+mod read;
 mod replay;
 mod search;
+mod source;
 mod transaction;
 
+pub(crate) use backup::verify_backup_snapshot;
+pub use consistency::ConsistencyIssue;
+pub use content_id::CONTENT_SNAPSHOT_ID_PREFIX;
+pub use footprint::Footprint;
+pub use health::{ComponentHealth, HealthReport, HealthStatus, DEFAULT_HEALTH_CHECK_TIMEOUT};
+pub use read::ReadHandle;
+pub use source::ReadSource;
+pub use transaction::Transaction;
+
 use crate::crypto::{AtRestCipher, NoOpCipher};
 use crate::hyper_index::HyperIndex;
-use crate::index::AdjacencyGraph;
+use crate::index::{AdjacencyGraph, GraphSemantics, SearchAccuracy, TraversalEdge};
 use crate::session::{SessionGraph, SessionManager, SessionOwner};
-use crate::snapshot::{SnapshotCatalog, SnapshotCatalogEntry, SnapshotError, SnapshotManager};
+use crate::snapshot::{
+    SnapshotCatalog, SnapshotCatalogEntry, SnapshotError, SnapshotId, SnapshotIdCodec,
+    SnapshotManager, WalLsnSnapshotIdCodec,
+};
 use crate::tiering::{StorageCapabilities, StorageProfile};
-use crate::wal::{Wal, WalError, WalOptions};
+use crate::wal::{ReplayProgressReporter, Wal, WalError, WalFlushPolicy, WalOptions};
+use alayasiki_core::audit::AuditSink;
 use alayasiki_core::error::{AlayasikiError, ErrorCode};
 use alayasiki_core::model::{Edge, Node};
 use rkyv::{Archive, Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
@@ -42,6 +63,8 @@ pub enum RepoError {
     Snapshot(#[from] SnapshotError),
     #[error("Session access denied: {0}")]
     SessionAccessDenied(String),
+    #[error("Repository is inconsistent: {} issue(s)", .0.len())]
+    Inconsistent(Vec<ConsistencyIssue>),
 }
 
 impl AlayasikiError for RepoError {
@@ -57,6 +80,7 @@ impl AlayasikiError for RepoError {
             RepoError::SnapshotNotConfigured => ErrorCode::Internal,
             RepoError::Snapshot(err) => err.error_code(),
             RepoError::SessionAccessDenied(_) => ErrorCode::PermissionDenied,
+            RepoError::Inconsistent(_) => ErrorCode::Internal,
         }
     }
 }
@@ -78,7 +102,38 @@ pub enum TxOperation {
     Put(Node),
     PutEdge(Edge),
     Delete(u64),
-    RecordIdempotency { key: String, node_ids: Vec<u64> },
+    RecordIdempotency {
+        key: String,
+        node_ids: Vec<u64>,
+    },
+    RemoveEdgeMetadata {
+        source: u64,
+        target: u64,
+        relation: String,
+    },
+    RemoveIdempotency {
+        key: String,
+    },
+    /// Merge `patch` into an edge's metadata after dropping `remove_keys`;
+    /// weight and graph structure are untouched.
+    UpdateEdgeMetadata(Box<EdgeMetadataUpdate>),
+    /// Drop every edge labeled `relation`, with its metadata.
+    RemoveEdgesByRelation {
+        relation: String,
+    },
+}
+
+/// Payload of [`TxOperation::UpdateEdgeMetadata`]. Boxed so the archived
+/// `TxOperation` keeps the 48-byte layout WALs were written with before the
+/// variant existed; growing it makes older WALs fail to replay.
+#[derive(Archive, Deserialize, Serialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct EdgeMetadataUpdate {
+    pub source: u64,
+    pub target: u64,
+    pub relation: String,
+    pub patch: HashMap<String, String>,
+    pub remove_keys: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -88,8 +143,27 @@ pub enum IndexMutation {
     DeleteNode(u64),
 }
 
+/// How [`IndexMutation::PutNode`] treats an id that already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Replace the existing node.
+    #[default]
+    Upsert,
+    /// Reject the whole transaction, so an accidental id collision between
+    /// unrelated documents cannot overwrite a node.
+    InsertOnly,
+}
+
+/// Edge metadata key holding the WAL LSN of the transaction that created the
+/// edge. Reserved: written once by the repository, and re-putting the edge
+/// keeps the original value; callers cannot set or remove it.
+pub const EDGE_CREATED_LSN_KEY: &str = "created_lsn";
+/// Edge metadata key holding the creation time in Unix milliseconds.
+pub const EDGE_CREATED_AT_KEY: &str = "created_at_unix_ms";
+
 /// Key for edge metadata lookup: (source, target, relation)
 pub type EdgeMetaKey = (u64, u64, String);
+type EdgeMetadataMap = HashMap<EdgeMetaKey, HashMap<String, String>>;
 
 #[derive(Archive, Deserialize, Serialize, Debug, Clone)]
 #[archive(check_bytes)]
@@ -143,19 +217,43 @@ pub struct SnapshotView {
 pub struct Repository {
     wal: Arc<Mutex<Wal>>,
     tx_lock: Arc<Mutex<()>>,
-    nodes: Arc<RwLock<HashMap<u64, Node>>>,
+    /// Shared with any pinned [`ReadHandle`]; writers copy on write.
+    nodes: Arc<RwLock<Arc<HashMap<u64, Node>>>>,
     pub hyper_index: Arc<RwLock<HyperIndex>>,
     idempotency_index: Arc<RwLock<HashMap<String, Vec<u64>>>>,
-    edge_metadata: Arc<RwLock<HashMap<EdgeMetaKey, HashMap<String, String>>>>,
+    edge_metadata: Arc<RwLock<Arc<EdgeMetadataMap>>>,
     snapshot_manager: Option<SnapshotManager>,
     snapshot_catalog: Arc<Mutex<SnapshotCatalog>>,
     pub session_manager: Arc<SessionManager>,
     storage_profile: StorageProfile,
     storage_capabilities: StorageCapabilities,
+    snapshot_id_codec: Arc<dyn SnapshotIdCodec>,
+    /// Content ids handed out by `content_snapshot_id`, with their LSN.
+    content_snapshot_ids: Arc<std::sync::RwLock<HashMap<String, u64>>>,
+    /// Last computed content id and the state generation it was computed at.
+    latest_content_snapshot_id: Arc<std::sync::Mutex<Option<(u64, String)>>>,
+    /// Process-unique id of this repository, part of `local_state_id`.
+    instance_id: u64,
+    /// Bumped after every write that changes nodes, edges or edge metadata.
+    state_generation: Arc<AtomicU64>,
+    /// Receives `Snapshot`/`Restore` events for backup operations.
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    graph_semantics: GraphSemantics,
+    /// Relations edges may be written with; `None` accepts any relation.
+    allowed_relations: Option<HashSet<String>>,
+    /// Periodic flush task, started on open under an interval flush policy
+    /// or by [`Repository::with_background_flush`]. At most one exists.
+    background_flush: Option<flush::BackgroundFlush>,
 }
 
 const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
 
+static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_instance_id() -> u64 {
+    NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 impl Repository {
     /// Create a new empty Repository (no replay)
     pub fn new(wal: Arc<Mutex<Wal>>) -> Self {
@@ -168,17 +266,26 @@ impl Repository {
         Self {
             wal,
             tx_lock: Arc::new(Mutex::new(())),
-            nodes: Arc::new(RwLock::new(HashMap::new())),
+            nodes: Arc::default(),
             hyper_index: Arc::new(RwLock::new(HyperIndex::with_storage_profile(
                 storage_profile.clone(),
             ))),
             idempotency_index: Arc::new(RwLock::new(HashMap::new())),
-            edge_metadata: Arc::new(RwLock::new(HashMap::new())),
+            edge_metadata: Arc::default(),
             snapshot_manager: None,
             snapshot_catalog: Arc::new(Mutex::new(SnapshotCatalog::new_in_memory())),
             session_manager: Arc::new(SessionManager::new(DEFAULT_SESSION_TTL)),
             storage_profile,
             storage_capabilities,
+            snapshot_id_codec: Arc::new(WalLsnSnapshotIdCodec),
+            content_snapshot_ids: Arc::default(),
+            latest_content_snapshot_id: Arc::default(),
+            instance_id: next_instance_id(),
+            state_generation: Arc::default(),
+            audit_sink: None,
+            graph_semantics: GraphSemantics::default(),
+            allowed_relations: None,
+            background_flush: None,
         }
     }
 
@@ -218,6 +325,24 @@ impl Repository {
             None,
             wal_options,
             storage_profile,
+            None,
+        )
+        .await
+    }
+
+    /// Open a repository, reporting WAL replay progress through `progress`.
+    pub async fn open_with_replay_progress(
+        wal_path: impl AsRef<Path>,
+        wal_options: WalOptions,
+        progress: ReplayProgressReporter,
+    ) -> Result<Self, RepoError> {
+        Self::open_internal(
+            wal_path.as_ref().to_path_buf(),
+            Arc::new(NoOpCipher),
+            None,
+            wal_options,
+            StorageProfile::default(),
+            Some(progress),
         )
         .await
     }
@@ -242,6 +367,7 @@ impl Repository {
             None,
             wal_options,
             StorageProfile::default(),
+            None,
         )
         .await
     }
@@ -289,6 +415,7 @@ impl Repository {
             Some(snapshot_manager),
             wal_options,
             StorageProfile::default(),
+            None,
         )
         .await
     }
@@ -299,6 +426,7 @@ impl Repository {
         snapshot_manager: Option<SnapshotManager>,
         wal_options: WalOptions,
         storage_profile: StorageProfile,
+        progress: Option<ReplayProgressReporter>,
     ) -> Result<Self, RepoError> {
         let wal_instance =
             Wal::open_with_cipher_and_options(&wal_path, cipher, wal_options).await?;
@@ -314,26 +442,19 @@ impl Repository {
         // Replay WAL entries newer than the snapshot baseline.
         {
             let mut wal_lock = wal.lock().await;
-            let last_replayed_lsn = wal_lock
-                .replay(|lsn, data| {
-                    if lsn <= base_lsn {
-                        return Ok(());
-                    }
-                    let archived = rkyv::check_archived_root::<WalEntry>(&data[..])
-                        .map_err(|_| WalError::CorruptEntry)?;
-                    let entry: WalEntry = archived
-                        .deserialize(&mut rkyv::Infallible)
-                        .expect("infallible deserializer");
-                    replay::apply_replayed_entry(
-                        &entry,
-                        &mut materialized.nodes,
-                        &mut materialized.hyper_index,
-                        &mut materialized.idempotency_index,
-                        &mut materialized.edge_metadata,
-                    );
-                    Ok(())
-                })
-                .await?;
+            let mut pipeline =
+                replay::PipelinedReplay::new(&mut materialized, wal_options.replay_batch_entries);
+            let apply = |lsn: u64, data: Vec<u8>| {
+                if lsn <= base_lsn {
+                    return Ok(());
+                }
+                pipeline.push(data)
+            };
+            let last_replayed_lsn = match &progress {
+                Some(progress) => wal_lock.replay_with_progress(apply, progress).await?,
+                None => wal_lock.replay(apply).await?,
+            };
+            pipeline.finish()?;
 
             if base_lsn > last_replayed_lsn {
                 return Err(RepoError::SnapshotNotFound(format!("wal-lsn-{base_lsn}")));
@@ -349,24 +470,89 @@ impl Repository {
         snapshot_catalog
             .record_snapshot(durable_lsn, current_unix_timestamp_ms())
             .await?;
+        let snapshot_catalog = Arc::new(Mutex::new(snapshot_catalog));
+
+        // Flush buffered appends every `interval` until the repository is
+        // dropped, so writes become durable even when no append follows.
+        let background_flush = match wal.lock().await.flush_policy() {
+            WalFlushPolicy::Interval(interval) if !interval.is_zero() => Some(
+                flush::BackgroundFlush::spawn(wal.clone(), snapshot_catalog.clone(), interval),
+            ),
+            _ => None,
+        };
 
         let storage_capabilities = storage_profile.resolve_capabilities();
 
         Ok(Self {
             wal,
             tx_lock,
-            nodes: Arc::new(RwLock::new(materialized.nodes)),
+            nodes: Arc::new(RwLock::new(Arc::new(materialized.nodes))),
             hyper_index: Arc::new(RwLock::new(materialized.hyper_index)),
             idempotency_index: Arc::new(RwLock::new(materialized.idempotency_index)),
-            edge_metadata: Arc::new(RwLock::new(materialized.edge_metadata)),
+            edge_metadata: Arc::new(RwLock::new(Arc::new(materialized.edge_metadata))),
             snapshot_manager,
-            snapshot_catalog: Arc::new(Mutex::new(snapshot_catalog)),
+            snapshot_catalog,
             session_manager: Arc::new(SessionManager::new(DEFAULT_SESSION_TTL)),
             storage_profile,
             storage_capabilities,
+            snapshot_id_codec: Arc::new(WalLsnSnapshotIdCodec),
+            content_snapshot_ids: Arc::default(),
+            latest_content_snapshot_id: Arc::default(),
+            instance_id: next_instance_id(),
+            state_generation: Arc::default(),
+            audit_sink: None,
+            graph_semantics: GraphSemantics::default(),
+            allowed_relations: None,
+            background_flush,
         })
     }
 
+    /// Replace the codec used to emit snapshot ids. Legacy `wal-lsn-<lsn>`
+    /// ids keep resolving regardless of the codec.
+    pub fn with_snapshot_id_codec(mut self, codec: Arc<dyn SnapshotIdCodec>) -> Self {
+        self.snapshot_id_codec = codec;
+        self
+    }
+
+    /// Record an audit event for every backup snapshot and restore.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Edge direction used by traversal, snapshot views and graph clones
+    /// handed to community detection. Not persisted; set it on every open.
+    pub fn with_graph_semantics(mut self, semantics: GraphSemantics) -> Self {
+        self.graph_semantics = semantics;
+        self.hyper_index
+            .try_write()
+            .expect("graph semantics are set before the repository is shared")
+            .graph_mut()
+            .set_semantics(semantics);
+        self
+    }
+
+    pub fn graph_semantics(&self) -> GraphSemantics {
+        self.graph_semantics
+    }
+
+    /// Restrict the relations new edges may carry. `put_edge`, transactions
+    /// and extraction jobs writing any other relation fail with
+    /// [`RepoError::InvalidTransaction`]; edges already stored (or replayed
+    /// from the WAL) are left alone. Not persisted; set it on every open.
+    pub fn with_allowed_relations<I, S>(mut self, relations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_relations = Some(relations.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn allowed_relations(&self) -> Option<&HashSet<String>> {
+        self.allowed_relations.as_ref()
+    }
+
     pub fn storage_profile(&self) -> &StorageProfile {
         &self.storage_profile
     }
@@ -380,6 +566,21 @@ impl Repository {
             .await
     }
 
+    /// Like [`Self::put_node`], but appends nothing to the WAL when the stored
+    /// node already has the same data, metadata and embedding. Returns whether
+    /// a write happened.
+    pub async fn ensure_node(&self, node: Node) -> Result<bool, RepoError> {
+        // Compare under `tx_lock` so a concurrent write cannot land between
+        // the check and the put.
+        let _tx_guard = self.tx_lock.lock().await;
+        if self.nodes.read().await.get(&node.id) == Some(&node) {
+            return Ok(false);
+        }
+        self.apply_index_transaction_locked(vec![IndexMutation::PutNode(node)], WriteMode::Upsert)
+            .await?;
+        Ok(true)
+    }
+
     pub async fn put_edge(&self, edge: Edge) -> Result<(), RepoError> {
         self.apply_index_transaction(vec![IndexMutation::PutEdge(edge)])
             .await
@@ -411,9 +612,17 @@ impl Repository {
             .find_map(|node| (!node.embedding.is_empty()).then_some(node.embedding.len()))
     }
 
+    /// `tenant` metadata of every node that has one, keyed by node id.
+    pub async fn node_tenants(&self) -> HashMap<u64, String> {
+        let nodes = self.nodes.read().await;
+        nodes
+            .values()
+            .filter_map(|node| Some((node.id, node.metadata.get("tenant")?.clone())))
+            .collect()
+    }
+
     pub async fn graph_index(&self) -> AdjacencyGraph {
-        let index = self.hyper_index.read().await;
-        index.graph_index.clone()
+        AdjacencyGraph::clone(&self.hyper_index.read().await.graph_index)
     }
 
     pub async fn delete_node(&self, id: u64) -> Result<(), RepoError> {
@@ -462,10 +671,11 @@ impl Repository {
         query: &[f32],
         k: usize,
         session: Option<&SessionGraph>,
+        accuracy: SearchAccuracy,
     ) -> Vec<(u64, f32)> {
         let mut results = {
             let index = self.hyper_index.read().await;
-            index.search_vector(query, k)
+            index.search_vector_with_accuracy(query, k, accuracy)
         };
 
         if let Some(session) = session {
@@ -490,6 +700,26 @@ impl Repository {
         results
     }
 
+    /// Every node whose cosine similarity to `query` is at least
+    /// `min_score`, sorted by descending score with ties broken by ascending
+    /// id. Scans all stored embeddings, so the result is exact and not capped
+    /// by any `k`; nodes whose dimension differs from the query are skipped.
+    pub async fn search_vector_threshold(&self, query: &[f32], min_score: f32) -> Vec<(u64, f32)> {
+        use alayasiki_core::embedding::cosine_similarity;
+        let mut results: Vec<(u64, f32)> = {
+            let nodes = self.nodes.read().await;
+            nodes
+                .values()
+                .filter_map(|node| {
+                    let score = cosine_similarity(query, &node.embedding)?;
+                    (score >= min_score).then_some((node.id, score))
+                })
+                .collect()
+        };
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        results
+    }
+
     pub async fn search_vector_with_session(
         &self,
         query: &[f32],
@@ -497,30 +727,28 @@ impl Repository {
         session_id: Option<&str>,
     ) -> Vec<(u64, f32)> {
         let session = session_id.and_then(|sid| self.session_manager.get(sid).map(|s| s.clone()));
-        self.search_vector_with_session_graph(query, k, session.as_ref())
-            .await
+        self.search_vector_with_session_graph(
+            query,
+            k,
+            session.as_ref(),
+            SearchAccuracy::Approximate,
+        )
+        .await
     }
 
     pub async fn neighbors_with_session_graph(
         &self,
         node_id: u64,
         session: Option<&SessionGraph>,
-    ) -> Vec<(u64, String, f32)> {
-        let mut results: Vec<(u64, String, f32)> = {
-            let index = self.hyper_index.read().await;
-            index
-                .graph_index
-                .neighbors(node_id)
-                .into_iter()
-                .cloned()
-                .collect()
-        };
+    ) -> Vec<TraversalEdge> {
+        let mut results = self
+            .hyper_index
+            .read()
+            .await
+            .graph_index
+            .traversal_neighbors(node_id);
         if let Some(session) = session {
-            for edge in &session.edges {
-                if edge.source == node_id {
-                    results.push((edge.target, edge.relation.clone(), edge.weight));
-                }
-            }
+            push_session_edges(&mut results, node_id, session, self.graph_semantics);
         }
         results
     }
@@ -529,7 +757,7 @@ impl Repository {
         &self,
         node_id: u64,
         session_id: Option<&str>,
-    ) -> Vec<(u64, String, f32)> {
+    ) -> Vec<TraversalEdge> {
         let session = session_id.and_then(|sid| self.session_manager.get(sid).map(|s| s.clone()));
         self.neighbors_with_session_graph(node_id, session.as_ref())
             .await
@@ -665,7 +893,19 @@ impl Repository {
     /// Return the latest durable WAL snapshot id.
     pub async fn current_snapshot_id(&self) -> String {
         let wal = self.wal.lock().await;
-        format!("wal-lsn-{}", wal.durable_lsn())
+        self.snapshot_id_codec.encode(wal.durable_lsn())
+    }
+
+    /// Resolve a snapshot id produced by the configured codec, a legacy
+    /// `wal-lsn-<lsn>` id, or a content id from `content_snapshot_id`, to its
+    /// LSN.
+    pub fn resolve_snapshot_id(&self, snapshot_id: &str) -> Result<SnapshotId, RepoError> {
+        self.snapshot_id_codec
+            .decode(snapshot_id)
+            .or_else(|| parse_wal_snapshot_lsn(snapshot_id))
+            .or_else(|| self.resolve_content_snapshot_id(snapshot_id))
+            .map(|lsn| SnapshotId::new(snapshot_id, lsn))
+            .ok_or_else(|| RepoError::InvalidSnapshotId(snapshot_id.to_string()))
     }
 
     pub async fn resolve_snapshot_id_at_or_before(
@@ -675,7 +915,7 @@ impl Repository {
         let catalog = self.snapshot_catalog.lock().await;
         catalog
             .resolve_as_of(as_of_unix_ms)
-            .map(|entry| entry.snapshot_id.clone())
+            .map(|entry| self.snapshot_id_codec.encode(entry.lsn))
             .ok_or_else(|| RepoError::SnapshotNotFound(format!("as-of-{as_of_unix_ms}")))
     }
 
@@ -686,7 +926,30 @@ impl Repository {
 }
 
 pub fn parse_wal_snapshot_lsn(snapshot_id: &str) -> Option<u64> {
-    snapshot_id.strip_prefix("wal-lsn-")?.parse::<u64>().ok()
+    WalLsnSnapshotIdCodec.decode(snapshot_id)
+}
+
+/// Flushes buffered WAL entries, if any, and records the new durable LSN in
+/// the snapshot catalog. Failures are logged; the next attempt retries.
+async fn flush_pending_and_record(wal: &Mutex<Wal>, snapshot_catalog: &Mutex<SnapshotCatalog>) {
+    let durable_lsn = {
+        let mut wal = wal.lock().await;
+        match wal.flush_pending().await {
+            Ok(true) => wal.durable_lsn(),
+            Ok(false) => return,
+            Err(err) => {
+                tracing::warn!("Background WAL flush failed: {}", err);
+                return;
+            }
+        }
+    };
+    let mut catalog = snapshot_catalog.lock().await;
+    if let Err(err) = catalog
+        .record_snapshot(durable_lsn, current_unix_timestamp_ms())
+        .await
+    {
+        tracing::warn!("Recording snapshot {} failed: {}", durable_lsn, err);
+    }
 }
 
 fn snapshot_catalog_path(wal_path: &Path) -> PathBuf {
@@ -722,5 +985,29 @@ fn collect_backup_edges(index: &HyperIndex) -> Vec<BackupEdgeRecord> {
     edges
 }
 
+/// Appends the session edges traversal may follow from `node_id`.
+fn push_session_edges(
+    results: &mut Vec<TraversalEdge>,
+    node_id: u64,
+    session: &SessionGraph,
+    semantics: GraphSemantics,
+) {
+    for edge in &session.edges {
+        let (neighbor, reversed) = if edge.source == node_id {
+            (edge.target, false)
+        } else if semantics == GraphSemantics::Undirected && edge.target == node_id {
+            (edge.source, true)
+        } else {
+            continue;
+        };
+        results.push(TraversalEdge {
+            neighbor,
+            relation: edge.relation.clone(),
+            weight: edge.weight,
+            reversed,
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests;
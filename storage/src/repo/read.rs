@@ -0,0 +1,116 @@
+use super::{EdgeMetadataMap, Repository};
+use crate::community::NodeTextReader;
+use crate::index::{AdjacencyGraph, TraversalEdge};
+use alayasiki_core::model::Node;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A read-only view of the repository pinned to the LSN it was opened at.
+///
+/// The handle shares the node map, edge-metadata map and graph index with the
+/// repository instead of copying them; a writer that commits while the handle
+/// is alive copies the structure it mutates, so the handle keeps seeing the
+/// pinned state. Dropping the handle releases the pinned state.
+pub struct ReadHandle {
+    lsn: u64,
+    snapshot_id: String,
+    nodes: Arc<HashMap<u64, Node>>,
+    edge_metadata: Arc<EdgeMetadataMap>,
+    graph: Arc<AdjacencyGraph>,
+}
+
+impl ReadHandle {
+    pub fn lsn(&self) -> u64 {
+        self.lsn
+    }
+
+    pub fn snapshot_id(&self) -> &str {
+        &self.snapshot_id
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get_node(&self, id: u64) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    pub fn list_node_ids(&self) -> Vec<u64> {
+        let mut out: Vec<u64> = self.nodes.keys().copied().collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Iterate the pinned nodes in ascending id order.
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &Node> {
+        let mut nodes: Vec<&Node> = self.nodes.values().collect();
+        nodes.sort_unstable_by_key(|node| node.id);
+        nodes.into_iter()
+    }
+
+    pub fn edge_metadata(
+        &self,
+        source: u64,
+        target: u64,
+        relation: &str,
+    ) -> Option<&HashMap<String, String>> {
+        self.edge_metadata
+            .get(&(source, target, relation.to_string()))
+    }
+
+    /// The pinned graph index.
+    pub fn graph(&self) -> &AdjacencyGraph {
+        &self.graph
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Edges traversal may follow from `node_id` in the pinned graph,
+    /// honouring its [`crate::index::GraphSemantics`].
+    pub fn neighbors(&self, node_id: u64) -> Vec<TraversalEdge> {
+        self.graph.traversal_neighbors(node_id)
+    }
+
+    /// Iterate the pinned edges as `(source, target, relation, weight)`,
+    /// ordered by source id.
+    pub fn iter_edges(&self) -> impl Iterator<Item = (u64, u64, &str, f32)> {
+        self.graph.node_ids().into_iter().flat_map(move |source| {
+            self.graph
+                .neighbors(source)
+                .into_iter()
+                .map(move |(target, relation, weight)| {
+                    (source, *target, relation.as_str(), *weight)
+                })
+        })
+    }
+}
+
+impl NodeTextReader for ReadHandle {
+    fn node_text(&self, node_id: u64) -> Option<String> {
+        self.nodes.get(&node_id).map(|node| node.data.clone())
+    }
+}
+
+impl Repository {
+    /// Open a [`ReadHandle`] pinned to the current durable LSN. Long-running
+    /// analytics can iterate it while writers keep committing.
+    pub async fn begin_read(&self) -> ReadHandle {
+        // Every writer holds `tx_lock` from WAL append through apply, so the
+        // LSN and the maps read under it describe the same state.
+        let _tx_guard = self.tx_lock.lock().await;
+        let lsn = self.wal.lock().await.durable_lsn();
+        let nodes = Arc::clone(&*self.nodes.read().await);
+        let edge_metadata = Arc::clone(&*self.edge_metadata.read().await);
+        let graph = Arc::clone(&self.hyper_index.read().await.graph_index);
+        ReadHandle {
+            lsn,
+            snapshot_id: self.snapshot_id_codec.encode(lsn),
+            nodes,
+            edge_metadata,
+            graph,
+        }
+    }
+}
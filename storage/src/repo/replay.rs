@@ -1,15 +1,20 @@
+use super::backup::decode_backup_file;
 use super::{
-    EdgeMetaKey, MaterializedState, RepoError, RepositoryBackupSnapshot, TxOperation, WalEntry,
+    EdgeMetaKey, EdgeMetadataUpdate, MaterializedState, RepoError, RepositoryBackupSnapshot,
+    TxOperation, WalEntry,
 };
 use crate::hyper_index::HyperIndex;
 use crate::snapshot::{SnapshotError, SnapshotManager};
 use crate::tiering::StorageProfile;
+use crate::wal::WalError;
 use alayasiki_core::model::Node;
+use rayon::prelude::*;
 use rkyv::ser::serializers::AllocSerializer;
 use rkyv::ser::Serializer;
 use rkyv::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::mpsc;
 
 pub(super) fn apply_replayed_entry(
     entry: &WalEntry,
@@ -50,6 +55,100 @@ pub(super) fn apply_replayed_entry(
     }
 }
 
+type DeserializedBatch = Result<Vec<WalEntry>, WalError>;
+
+/// Replays WAL payloads into `state` in batches: a full batch is deserialized
+/// on the rayon pool while the caller keeps reading the next one, and batches
+/// are applied in arrival (LSN) order on the caller's thread. With a batch
+/// size of 1 every entry is deserialized and applied inline.
+pub(super) struct PipelinedReplay<'a> {
+    state: &'a mut MaterializedState,
+    batch_entries: usize,
+    batch: Vec<Vec<u8>>,
+    in_flight: Option<mpsc::Receiver<DeserializedBatch>>,
+}
+
+impl<'a> PipelinedReplay<'a> {
+    pub(super) fn new(state: &'a mut MaterializedState, batch_entries: usize) -> Self {
+        let batch_entries = batch_entries.max(1);
+        Self {
+            state,
+            batch_entries,
+            batch: Vec::with_capacity(batch_entries),
+            in_flight: None,
+        }
+    }
+
+    pub(super) fn push(&mut self, payload: Vec<u8>) -> Result<(), WalError> {
+        self.batch.push(payload);
+        if self.batch.len() >= self.batch_entries {
+            self.dispatch()?;
+        }
+        Ok(())
+    }
+
+    /// Applies everything pushed so far.
+    pub(super) fn finish(mut self) -> Result<(), WalError> {
+        self.dispatch()?;
+        self.apply_in_flight()
+    }
+
+    fn dispatch(&mut self) -> Result<(), WalError> {
+        // The previous batch must be applied before this one to keep LSN order.
+        self.apply_in_flight()?;
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_entries));
+        if self.batch_entries == 1 {
+            let entries = deserialize_wal_batch(batch)?;
+            self.apply(&entries);
+            return Ok(());
+        }
+        let (tx, rx) = mpsc::sync_channel(1);
+        rayon::spawn(move || {
+            let _ = tx.send(deserialize_wal_batch(batch));
+        });
+        self.in_flight = Some(rx);
+        Ok(())
+    }
+
+    fn apply_in_flight(&mut self) -> Result<(), WalError> {
+        let Some(rx) = self.in_flight.take() else {
+            return Ok(());
+        };
+        let entries = rx.recv().map_err(|_| WalError::CorruptEntry)??;
+        self.apply(&entries);
+        Ok(())
+    }
+
+    fn apply(&mut self, entries: &[WalEntry]) {
+        let state = &mut *self.state;
+        for entry in entries {
+            apply_replayed_entry(
+                entry,
+                &mut state.nodes,
+                &mut state.hyper_index,
+                &mut state.idempotency_index,
+                &mut state.edge_metadata,
+            );
+        }
+    }
+}
+
+fn deserialize_wal_batch(batch: Vec<Vec<u8>>) -> DeserializedBatch {
+    batch
+        .par_iter()
+        .map(|payload| {
+            let archived = rkyv::check_archived_root::<WalEntry>(&payload[..])
+                .map_err(|_| WalError::CorruptEntry)?;
+            Ok(archived
+                .deserialize(&mut rkyv::Infallible)
+                .expect("infallible deserializer"))
+        })
+        .collect()
+}
+
 pub(super) fn apply_tx_operation(
     operation: &TxOperation,
     node_map: &mut HashMap<u64, Node>,
@@ -81,6 +180,46 @@ pub(super) fn apply_tx_operation(
         TxOperation::RecordIdempotency { key, node_ids } => {
             record_idempotency_if_absent(idem_map, key, node_ids);
         }
+        TxOperation::RemoveEdgeMetadata {
+            source,
+            target,
+            relation,
+        } => {
+            edge_meta.remove(&(*source, *target, relation.clone()));
+        }
+        TxOperation::RemoveIdempotency { key } => {
+            idem_map.remove(key);
+        }
+        TxOperation::UpdateEdgeMetadata(update) => {
+            let EdgeMetadataUpdate {
+                source,
+                target,
+                relation,
+                patch,
+                remove_keys,
+            } = update.as_ref();
+            // The edge may have been removed by a later entry before a crash;
+            // never leave metadata without its edge.
+            if !h_index
+                .graph_index
+                .contains_edge(*source, *target, relation)
+            {
+                return;
+            }
+            let key = (*source, *target, relation.clone());
+            let metadata = edge_meta.entry(key.clone()).or_default();
+            for removed in remove_keys {
+                metadata.remove(removed);
+            }
+            metadata.extend(patch.iter().map(|(k, v)| (k.clone(), v.clone())));
+            if metadata.is_empty() {
+                edge_meta.remove(&key);
+            }
+        }
+        TxOperation::RemoveEdgesByRelation { relation } => {
+            h_index.remove_edges_by_relation(relation);
+            edge_meta.retain(|(_, _, edge_relation), _| edge_relation != relation);
+        }
     }
 }
 
@@ -135,10 +274,14 @@ pub(super) async fn load_materialized_state_from_backup(
 
     let mut nodes = HashMap::new();
     let mut hyper_index = HyperIndex::with_storage_profile(storage_profile);
+    let embeddings: Vec<(u64, Vec<f32>)> = snapshot
+        .nodes
+        .iter()
+        .map(|node| (node.id, node.embedding.clone()))
+        .collect();
+    hyper_index.insert_nodes(&embeddings);
     for node in snapshot.nodes {
-        let id = node.id;
-        hyper_index.insert_node(id, node.embedding.clone());
-        nodes.insert(id, node);
+        nodes.insert(node.id, node);
     }
 
     for edge in snapshot.edges {
@@ -173,7 +316,8 @@ async fn deserialize_backup_snapshot(path: &Path) -> Result<RepositoryBackupSnap
     let bytes = tokio::fs::read(path)
         .await
         .map_err(|err| RepoError::Snapshot(SnapshotError::Io(err)))?;
-    let archived = rkyv::check_archived_root::<RepositoryBackupSnapshot>(&bytes[..])
+    let payload = decode_backup_file(&bytes)?;
+    let archived = rkyv::check_archived_root::<RepositoryBackupSnapshot>(&payload[..])
         .map_err(|_| RepoError::Deserialization)?;
     archived
         .deserialize(&mut rkyv::Infallible)
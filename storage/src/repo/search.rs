@@ -1,4 +1,6 @@
-use super::{EdgeMetaKey, SnapshotView};
+use super::{push_session_edges, EdgeMetaKey, SnapshotView};
+use crate::community::NodeTextReader;
+use crate::index::{SearchAccuracy, TraversalEdge};
 use crate::session::SessionGraph;
 use alayasiki_core::embedding::cosine_similarity;
 use alayasiki_core::model::Node;
@@ -13,6 +15,10 @@ impl SnapshotView {
         self.hyper_index.storage_capabilities()
     }
 
+    pub fn graph_index(&self) -> &crate::index::AdjacencyGraph {
+        &self.hyper_index.graph_index
+    }
+
     pub fn list_node_ids(&self) -> Vec<u64> {
         let mut out: Vec<u64> = self.nodes.keys().copied().collect();
         out.sort_unstable();
@@ -43,8 +49,11 @@ impl SnapshotView {
         query: &[f32],
         k: usize,
         session: Option<&SessionGraph>,
+        accuracy: SearchAccuracy,
     ) -> Vec<(u64, f32)> {
-        let mut results = self.search_vector(query, k);
+        let mut results = self
+            .hyper_index
+            .search_vector_with_accuracy(query, k, accuracy);
         if let Some(session) = session {
             let mut session_results: Vec<(u64, f32)> = session
                 .nodes
@@ -66,27 +75,25 @@ impl SnapshotView {
         results
     }
 
-    pub fn neighbors(&self, node_id: u64) -> Vec<(u64, String, f32)> {
-        self.hyper_index
-            .graph_index
-            .neighbors(node_id)
-            .into_iter()
-            .map(|(target, relation, weight)| (*target, relation.clone(), *weight))
-            .collect()
+    /// Edges traversal may follow from `node_id` under the view's
+    /// [`crate::index::GraphSemantics`].
+    pub fn neighbors(&self, node_id: u64) -> Vec<TraversalEdge> {
+        self.hyper_index.graph_index.traversal_neighbors(node_id)
     }
 
     pub fn neighbors_with_session(
         &self,
         node_id: u64,
         session: Option<&SessionGraph>,
-    ) -> Vec<(u64, String, f32)> {
+    ) -> Vec<TraversalEdge> {
         let mut results = self.neighbors(node_id);
         if let Some(session) = session {
-            for edge in &session.edges {
-                if edge.source == node_id {
-                    results.push((edge.target, edge.relation.clone(), edge.weight));
-                }
-            }
+            push_session_edges(
+                &mut results,
+                node_id,
+                session,
+                self.hyper_index.graph_index.semantics(),
+            );
         }
         results
     }
@@ -104,3 +111,9 @@ impl SnapshotView {
             .collect()
     }
 }
+
+impl NodeTextReader for SnapshotView {
+    fn node_text(&self, node_id: u64) -> Option<String> {
+        self.nodes.get(&node_id).map(|node| node.data.clone())
+    }
+}
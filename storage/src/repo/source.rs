@@ -0,0 +1,159 @@
+use super::{EdgeMetaKey, Repository, SnapshotView};
+use crate::index::{AdjacencyGraph, SearchAccuracy, TraversalEdge};
+use crate::session::SessionGraph;
+use alayasiki_core::model::Node;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Read-only view of graph state that query execution runs against.
+///
+/// [`Repository`] answers from the live state; [`SnapshotView`] answers from
+/// a state pinned at a past snapshot. Both give the same results at the same
+/// LSN.
+#[async_trait::async_trait]
+pub trait ReadSource: Send + Sync {
+    /// Top-`k` vector hits, merged with `session` nodes when given.
+    /// [`SearchAccuracy::Exact`] scans every vector instead of the ANN index.
+    async fn search_vector(
+        &self,
+        query: &[f32],
+        k: usize,
+        session: Option<&SessionGraph>,
+        accuracy: SearchAccuracy,
+    ) -> Vec<(u64, f32)>;
+
+    /// Edges traversal may follow from `node_id`, including `session` edges
+    /// when given. Edges followed against their stored direction are flagged
+    /// `reversed`.
+    async fn neighbors(&self, node_id: u64, session: Option<&SessionGraph>) -> Vec<TraversalEdge>;
+
+    /// Nodes with the given ids, sorted by id. Unknown ids are skipped.
+    async fn get_nodes_by_ids(&self, ids: &[u64]) -> Vec<Node>;
+
+    /// All node ids in ascending order.
+    async fn list_node_ids(&self) -> Vec<u64>;
+
+    async fn node_count(&self) -> usize;
+
+    async fn embedding_dimension(&self) -> Option<usize>;
+
+    async fn get_edge_metadata_bulk(
+        &self,
+        keys: &[EdgeMetaKey],
+    ) -> HashMap<EdgeMetaKey, HashMap<String, String>>;
+
+    /// Snapshot id that identifies the state this source reads.
+    async fn current_snapshot_id(&self) -> String;
+
+    /// Cheap id that changes whenever the data this source reads changes,
+    /// for caching results derived from it.
+    async fn state_id(&self) -> String;
+
+    async fn graph_index(&self) -> Cow<'_, AdjacencyGraph>;
+}
+
+#[async_trait::async_trait]
+impl ReadSource for Repository {
+    async fn search_vector(
+        &self,
+        query: &[f32],
+        k: usize,
+        session: Option<&SessionGraph>,
+        accuracy: SearchAccuracy,
+    ) -> Vec<(u64, f32)> {
+        self.search_vector_with_session_graph(query, k, session, accuracy)
+            .await
+    }
+
+    async fn neighbors(&self, node_id: u64, session: Option<&SessionGraph>) -> Vec<TraversalEdge> {
+        self.neighbors_with_session_graph(node_id, session).await
+    }
+
+    async fn get_nodes_by_ids(&self, ids: &[u64]) -> Vec<Node> {
+        Repository::get_nodes_by_ids(self, ids).await
+    }
+
+    async fn list_node_ids(&self) -> Vec<u64> {
+        Repository::list_node_ids(self).await
+    }
+
+    async fn node_count(&self) -> usize {
+        self.nodes.read().await.len()
+    }
+
+    async fn embedding_dimension(&self) -> Option<usize> {
+        Repository::embedding_dimension(self).await
+    }
+
+    async fn get_edge_metadata_bulk(
+        &self,
+        keys: &[EdgeMetaKey],
+    ) -> HashMap<EdgeMetaKey, HashMap<String, String>> {
+        Repository::get_edge_metadata_bulk(self, keys).await
+    }
+
+    async fn current_snapshot_id(&self) -> String {
+        Repository::current_snapshot_id(self).await
+    }
+
+    async fn state_id(&self) -> String {
+        self.local_state_id()
+    }
+
+    async fn graph_index(&self) -> Cow<'_, AdjacencyGraph> {
+        Cow::Owned(Repository::graph_index(self).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl ReadSource for SnapshotView {
+    async fn search_vector(
+        &self,
+        query: &[f32],
+        k: usize,
+        session: Option<&SessionGraph>,
+        accuracy: SearchAccuracy,
+    ) -> Vec<(u64, f32)> {
+        self.search_vector_with_session(query, k, session, accuracy)
+    }
+
+    async fn neighbors(&self, node_id: u64, session: Option<&SessionGraph>) -> Vec<TraversalEdge> {
+        self.neighbors_with_session(node_id, session)
+    }
+
+    async fn get_nodes_by_ids(&self, ids: &[u64]) -> Vec<Node> {
+        SnapshotView::get_nodes_by_ids(self, ids)
+    }
+
+    async fn list_node_ids(&self) -> Vec<u64> {
+        SnapshotView::list_node_ids(self)
+    }
+
+    async fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    async fn embedding_dimension(&self) -> Option<usize> {
+        SnapshotView::embedding_dimension(self)
+    }
+
+    async fn get_edge_metadata_bulk(
+        &self,
+        keys: &[EdgeMetaKey],
+    ) -> HashMap<EdgeMetaKey, HashMap<String, String>> {
+        SnapshotView::get_edge_metadata_bulk(self, keys)
+    }
+
+    async fn current_snapshot_id(&self) -> String {
+        self.snapshot_id().to_string()
+    }
+
+    /// A view is pinned, so its snapshot id never covers two states.
+    async fn state_id(&self) -> String {
+        self.snapshot_id().to_string()
+    }
+
+    async fn graph_index(&self) -> Cow<'_, AdjacencyGraph> {
+        Cow::Borrowed(SnapshotView::graph_index(self))
+    }
+}
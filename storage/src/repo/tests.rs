@@ -1,6 +1,7 @@
 use super::*;
 use crate::crypto::NoOpCipher;
 use crate::hyper_index::HyperIndex;
+use crate::index::SearchAccuracy;
 use crate::session::SessionOwner;
 use crate::wal::{Wal, WalError, WalFlushPolicy, WalOptions};
 use alayasiki_core::model::{Edge, Node};
@@ -23,6 +24,13 @@ async fn test_repo_put_get() {
     assert_eq!(retrieved, node);
 }
 
+#[test]
+fn test_archived_tx_operation_keeps_wal_layout() {
+    // Existing WALs archive transactions as arrays of 48-byte operations;
+    // new variants must box larger payloads to keep them readable.
+    assert_eq!(std::mem::size_of::<ArchivedTxOperation>(), 48);
+}
+
 #[tokio::test]
 async fn test_repo_replay_on_restart() {
     let dir = tempdir().unwrap();
@@ -166,6 +174,188 @@ async fn test_index_transaction_rollback_on_validation_error() {
     );
 }
 
+#[tokio::test]
+async fn test_ensure_node_skips_wal_write_for_unchanged_node() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::open(dir.path().join("ensure.wal"))
+        .await
+        .unwrap();
+    let mut node = Node::new(1, vec![1.0, 0.0], "N1".to_string());
+    node.metadata.insert("k".to_string(), "v".to_string());
+
+    assert!(repo.ensure_node(node.clone()).await.unwrap());
+    let written = repo.current_snapshot_id().await;
+    assert!(!repo.ensure_node(node.clone()).await.unwrap());
+    assert_eq!(repo.current_snapshot_id().await, written);
+
+    let mut changed = node.clone();
+    changed.metadata.insert("k".to_string(), "v2".to_string());
+    assert!(repo.ensure_node(changed.clone()).await.unwrap());
+    assert_ne!(repo.current_snapshot_id().await, written);
+    assert_eq!(repo.get_node(1).await.unwrap(), changed);
+
+    let mut moved = changed.clone();
+    moved.embedding = vec![0.0, 1.0];
+    assert!(repo.ensure_node(moved).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_allowed_relations_reject_unlisted_edges() {
+    let dir = tempdir().unwrap();
+    let open = |name: &str| Repository::open(dir.path().join(name));
+    async fn seed(repo: &Repository) {
+        for id in [1, 2] {
+            repo.put_node(Node::new(id, vec![1.0], format!("N{id}")))
+                .await
+                .unwrap();
+        }
+    }
+
+    let restricted = open("restricted.wal")
+        .await
+        .unwrap()
+        .with_allowed_relations(["works_at", "located_in"]);
+    seed(&restricted).await;
+    restricted
+        .put_edge(Edge::new(1, 2, "works_at", 1.0))
+        .await
+        .unwrap();
+    let err = restricted
+        .put_edge(Edge::new(1, 2, "wroks_at", 1.0))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RepoError::InvalidTransaction(_)), "{err:?}");
+    let mut tx = restricted.transaction();
+    tx.put_node(Node::new(3, vec![1.0], "N3".to_string()))
+        .put_edge(Edge::new(1, 3, "unknown", 1.0));
+    assert!(matches!(
+        tx.commit().await,
+        Err(RepoError::InvalidTransaction(_))
+    ));
+    assert!(matches!(
+        restricted.get_node(3).await,
+        Err(RepoError::NotFound)
+    ));
+
+    let open_repo = open("open.wal").await.unwrap();
+    assert!(open_repo.allowed_relations().is_none());
+    seed(&open_repo).await;
+    open_repo
+        .put_edge(Edge::new(1, 2, "wroks_at", 1.0))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_transaction_handle_stages_reads_and_commits_atomically() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("txn_handle.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "old".to_string()))
+        .await
+        .unwrap();
+
+    let mut tx = repo.transaction();
+    tx.put_node(Node::new(2, vec![0.0, 1.0], "N2".to_string()));
+    if tx.get_node(1).await.unwrap().data == "old" {
+        tx.put_node(Node::new(1, vec![1.0, 0.0], "new".to_string()))
+            .put_edge(Edge::new(1, 2, "links", 1.0));
+    }
+    // Staged writes are visible through the handle only.
+    assert_eq!(tx.get_node(1).await.unwrap().data, "new");
+    assert_eq!(tx.get_node(2).await.unwrap().data, "N2");
+    assert_eq!(repo.get_node(1).await.unwrap().data, "old");
+    assert!(repo.get_node(2).await.is_err());
+    assert_eq!(tx.mutations().len(), 3);
+    tx.commit().await.unwrap();
+
+    assert_eq!(repo.get_node(1).await.unwrap().data, "new");
+    assert_eq!(repo.neighbors_with_session(1, None).await.len(), 1);
+
+    // A failing mutation rolls back the whole handle, like the vector API.
+    let mut tx = repo.transaction();
+    tx.put_node(Node::new(3, vec![1.0, 1.0], "N3".to_string()))
+        .delete_node(2);
+    assert!(matches!(tx.get_node(2).await, Err(RepoError::NotFound)));
+    tx.put_edge(Edge::new(3, 999, "links", 1.0));
+    assert!(matches!(
+        tx.commit().await,
+        Err(RepoError::InvalidTransaction(_))
+    ));
+    assert!(repo.get_node(3).await.is_err());
+    assert!(repo.get_node(2).await.is_ok());
+
+    let mut tx = repo.transaction().with_write_mode(WriteMode::InsertOnly);
+    tx.put_node(Node::new(1, vec![0.0, 1.0], "clobber".to_string()));
+    assert!(tx.commit().await.is_err());
+
+    let reopened = Repository::open(&wal_path).await.unwrap();
+    assert_eq!(reopened.get_node(1).await.unwrap().data, "new");
+    assert!(reopened.get_node(3).await.is_err());
+}
+
+#[tokio::test]
+async fn test_insert_only_transaction_rejects_existing_node_id() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("txn_insert_only.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+    repo.put_node(Node::new(1, vec![1.0], "original".to_string()))
+        .await
+        .unwrap();
+
+    let result = repo
+        .apply_index_transaction_with_mode(
+            vec![
+                IndexMutation::PutNode(Node::new(2, vec![2.0], "N2".to_string())),
+                IndexMutation::PutNode(Node::new(1, vec![9.0], "colliding".to_string())),
+            ],
+            WriteMode::InsertOnly,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(RepoError::InvalidTransaction(ref message)) if message == "node id 1 exists")
+    );
+    assert_eq!(repo.get_node(1).await.unwrap().data, "original");
+    assert!(repo.get_node(2).await.is_err());
+
+    let duplicate_in_batch = repo
+        .apply_index_transaction_with_mode(
+            vec![
+                IndexMutation::PutNode(Node::new(3, vec![3.0], "first".to_string())),
+                IndexMutation::PutNode(Node::new(3, vec![3.0], "second".to_string())),
+            ],
+            WriteMode::InsertOnly,
+        )
+        .await;
+    assert!(matches!(
+        duplicate_in_batch,
+        Err(RepoError::InvalidTransaction(_))
+    ));
+
+    repo.apply_index_transaction_with_mode(
+        vec![IndexMutation::PutNode(Node::new(
+            4,
+            vec![4.0],
+            "N4".to_string(),
+        ))],
+        WriteMode::InsertOnly,
+    )
+    .await
+    .unwrap();
+    repo.apply_index_transaction(vec![IndexMutation::PutNode(Node::new(
+        1,
+        vec![1.0],
+        "updated".to_string(),
+    ))])
+    .await
+    .unwrap();
+
+    let reopened = Repository::open(&wal_path).await.unwrap();
+    assert_eq!(reopened.get_node(1).await.unwrap().data, "updated");
+    assert!(reopened.get_node(3).await.is_err());
+    assert_eq!(reopened.get_node(4).await.unwrap().data, "N4");
+}
+
 #[tokio::test]
 async fn test_index_transaction_persists_single_wal_record() {
     let dir = tempdir().unwrap();
@@ -449,6 +639,133 @@ async fn test_load_snapshot_view_reconstructs_historical_state() {
     assert_eq!(view_at_lsn_3.list_node_ids(), vec![2]);
 }
 
+#[tokio::test]
+async fn test_read_source_matches_between_repository_and_snapshot_view() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("read_source.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "N1".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![0.6, 0.8], "N2".to_string()))
+        .await
+        .unwrap();
+    let mut edge = Edge::new(1, 2, "cites", 0.7);
+    edge.metadata
+        .insert("source".to_string(), "doc.pdf".to_string());
+    repo.put_edge(edge).await.unwrap();
+    let view = repo
+        .load_snapshot_view(&repo.current_snapshot_id().await)
+        .await
+        .unwrap();
+
+    let keys = vec![(1, 2, "cites".to_string())];
+    let sources: [&dyn ReadSource; 2] = [&repo, &view];
+    let [live, pinned] = sources;
+    assert_eq!(
+        live.current_snapshot_id().await,
+        pinned.current_snapshot_id().await
+    );
+    assert_eq!(live.list_node_ids().await, pinned.list_node_ids().await);
+    assert_eq!(
+        live.get_nodes_by_ids(&[2, 1, 9]).await,
+        pinned.get_nodes_by_ids(&[2, 1, 9]).await
+    );
+    assert_eq!(
+        live.embedding_dimension().await,
+        pinned.embedding_dimension().await
+    );
+    for accuracy in [SearchAccuracy::Approximate, SearchAccuracy::Exact] {
+        assert_eq!(
+            live.search_vector(&[1.0, 0.0], 2, None, accuracy).await,
+            pinned.search_vector(&[1.0, 0.0], 2, None, accuracy).await
+        );
+    }
+    assert_eq!(
+        live.neighbors(1, None).await,
+        pinned.neighbors(1, None).await
+    );
+    assert_eq!(
+        live.get_edge_metadata_bulk(&keys).await,
+        pinned.get_edge_metadata_bulk(&keys).await
+    );
+    assert_eq!(
+        live.graph_index().await.edge_count(),
+        pinned.graph_index().await.edge_count()
+    );
+}
+
+#[tokio::test]
+async fn test_search_vector_ignores_queries_with_mismatched_dimension() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("snapshot_view_dims.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "N1".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![0.0, 1.0], "N2".to_string()))
+        .await
+        .unwrap();
+    let view = repo
+        .load_snapshot_view(&repo.current_snapshot_id().await)
+        .await
+        .unwrap();
+
+    assert_eq!(view.embedding_dimension(), Some(2));
+    assert_eq!(view.search_vector(&[1.0, 0.0], 1)[0].0, 1);
+    assert!(view.search_vector(&[1.0, 0.0, 0.0], 2).is_empty());
+    assert!(view.search_vector(&[1.0], 2).is_empty());
+    assert!(repo
+        .search_vector_with_session(&[1.0, 0.0, 0.0], 2, None)
+        .await
+        .is_empty());
+    assert_eq!(
+        repo.search_vector_with_session(&[0.0, 1.0], 1, None).await[0].0,
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_search_vector_threshold_returns_every_node_at_or_above_min_score() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("threshold.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+    // Nodes 3 and 5 share an embedding, so their scores tie.
+    for (id, embedding) in [
+        (1, vec![1.0, 0.0]),
+        (2, vec![0.0, 1.0]),
+        (3, vec![0.9, 0.1]),
+        (4, vec![-1.0, 0.0]),
+        (5, vec![0.9, 0.1]),
+        (6, vec![0.8, 0.6]),
+    ] {
+        repo.put_node(Node::new(id, embedding, format!("N{id}")))
+            .await
+            .unwrap();
+    }
+    for id in 10..40u64 {
+        repo.put_node(Node::new(id, vec![0.1, 1.0], format!("far{id}")))
+            .await
+            .unwrap();
+    }
+
+    let hits = repo.search_vector_threshold(&[1.0, 0.0], 0.8).await;
+    let ids: Vec<u64> = hits.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![1, 3, 5, 6]);
+    assert!(hits.iter().all(|(_, score)| *score >= 0.8));
+    assert!(hits.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    assert!((hits[3].1 - 0.8).abs() < 1e-6);
+
+    let everything = repo.search_vector_threshold(&[1.0, 0.0], -1.0).await;
+    assert_eq!(everything.len(), 36);
+    assert!(repo
+        .search_vector_threshold(&[1.0, 0.0, 0.0], 0.0)
+        .await
+        .is_empty());
+}
+
 #[tokio::test]
 async fn test_load_snapshot_view_rejects_missing_or_invalid_snapshot_id() {
     let dir = tempdir().unwrap();
@@ -494,6 +811,158 @@ async fn test_current_snapshot_id_tracks_durable_lsn_for_buffered_policies() {
     assert_eq!(reopened.list_node_ids().await, vec![1]);
 }
 
+/// Hex-encoded ids under an opaque prefix, standing in for a deployment that
+/// does not want to expose raw LSNs.
+struct OpaqueSnapshotIdCodec;
+
+impl SnapshotIdCodec for OpaqueSnapshotIdCodec {
+    fn encode(&self, lsn: u64) -> String {
+        format!("snap_{:016x}", lsn ^ 0x5a5a_5a5a)
+    }
+
+    fn decode(&self, snapshot_id: &str) -> Option<u64> {
+        let hex = snapshot_id.strip_prefix("snap_")?;
+        u64::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| value ^ 0x5a5a_5a5a)
+    }
+}
+
+#[tokio::test]
+async fn test_custom_snapshot_id_codec_round_trips_and_accepts_legacy_ids() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("snapshot_codec.wal");
+    let repo = Repository::open(&wal_path)
+        .await
+        .unwrap()
+        .with_snapshot_id_codec(Arc::new(OpaqueSnapshotIdCodec));
+
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![2.0], "N2".to_string()))
+        .await
+        .unwrap();
+
+    let current = repo.current_snapshot_id().await;
+    assert_eq!(current, OpaqueSnapshotIdCodec.encode(2));
+    assert!(!current.contains("wal-lsn"));
+    assert_eq!(repo.resolve_snapshot_id(&current).unwrap().lsn(), 2);
+
+    let opaque_view = repo
+        .load_snapshot_view(&OpaqueSnapshotIdCodec.encode(1))
+        .await
+        .unwrap();
+    assert_eq!(opaque_view.list_node_ids(), vec![1]);
+
+    let legacy = repo.resolve_snapshot_id("wal-lsn-1").unwrap();
+    assert_eq!(legacy.lsn(), 1);
+    assert_eq!(legacy.as_str(), "wal-lsn-1");
+    let legacy_view = repo.load_snapshot_view("wal-lsn-1").await.unwrap();
+    assert_eq!(legacy_view.list_node_ids(), vec![1]);
+
+    assert_eq!(
+        repo.resolve_snapshot_id_at_or_before(i64::MAX)
+            .await
+            .unwrap(),
+        current
+    );
+    assert!(matches!(
+        repo.resolve_snapshot_id("not-a-snapshot"),
+        Err(RepoError::InvalidSnapshotId(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_content_snapshot_id_matches_for_identical_content_and_pins_views() {
+    async fn build(wal_path: &std::path::Path, extra: bool) -> Repository {
+        let repo = Repository::open(wal_path).await.unwrap();
+        let mut node = Node::new(1, vec![1.0, 0.0], "N1".to_string());
+        node.metadata
+            .insert("source".to_string(), "s3://a".to_string());
+        repo.put_node(node).await.unwrap();
+        repo.put_node(Node::new(2, vec![0.0, 1.0], "N2".to_string()))
+            .await
+            .unwrap();
+        repo.put_edge(Edge::new(1, 2, "rel", 0.5)).await.unwrap();
+        if extra {
+            repo.put_node(Node::new(3, vec![1.0, 1.0], "N3".to_string()))
+                .await
+                .unwrap();
+        }
+        repo
+    }
+
+    let dir = tempdir().unwrap();
+    let first = build(&dir.path().join("first.wal"), false).await;
+    let second = build(&dir.path().join("second.wal"), false).await;
+    let content_id = first.content_snapshot_id().await.unwrap();
+    assert!(content_id.starts_with(CONTENT_SNAPSHOT_ID_PREFIX));
+    assert_eq!(second.content_snapshot_id().await.unwrap(), content_id);
+    assert_eq!(first.current_snapshot_id().await, "wal-lsn-3");
+
+    let third = build(&dir.path().join("third.wal"), true).await;
+    assert_ne!(third.content_snapshot_id().await.unwrap(), content_id);
+
+    first
+        .put_node(Node::new(4, vec![0.5, 0.5], "N4".to_string()))
+        .await
+        .unwrap();
+    assert_ne!(first.content_snapshot_id().await.unwrap(), content_id);
+    assert_eq!(first.resolve_snapshot_id(&content_id).unwrap().lsn(), 3);
+    let pinned = first.load_snapshot_view(&content_id).await.unwrap();
+    assert_eq!(pinned.list_node_ids(), vec![1, 2]);
+
+    assert!(matches!(
+        first.resolve_snapshot_id(&format!("{CONTENT_SNAPSHOT_ID_PREFIX}unknown")),
+        Err(RepoError::InvalidSnapshotId(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_local_state_id_changes_only_when_data_changes() {
+    let dir = tempdir().unwrap();
+    let first = Repository::open(dir.path().join("first.wal"))
+        .await
+        .unwrap();
+    let second = Repository::open(dir.path().join("second.wal"))
+        .await
+        .unwrap();
+    assert_ne!(first.local_state_id(), second.local_state_id());
+
+    let before = first.local_state_id();
+    first.content_snapshot_id().await.unwrap();
+    first.record_idempotency("key", vec![]).await.unwrap();
+    assert_eq!(first.local_state_id(), before);
+
+    first
+        .put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    assert_ne!(first.local_state_id(), before);
+}
+
+#[tokio::test]
+async fn test_content_snapshot_ids_are_bounded() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::open(dir.path().join("bounded.wal"))
+        .await
+        .unwrap();
+    let first_id = repo.content_snapshot_id().await.unwrap();
+    for id in 0..content_id::MAX_CONTENT_SNAPSHOT_IDS as u64 {
+        repo.put_node(Node::new(id, vec![1.0], format!("N{id}")))
+            .await
+            .unwrap();
+        repo.content_snapshot_id().await.unwrap();
+    }
+
+    assert_eq!(
+        repo.content_snapshot_ids.read().unwrap().len(),
+        content_id::MAX_CONTENT_SNAPSHOT_IDS
+    );
+    assert!(repo.resolve_snapshot_id(&first_id).is_err());
+}
+
 #[tokio::test]
 async fn test_resolve_snapshot_id_at_or_before_uses_persisted_catalog() {
     let before_repo = current_unix_timestamp_ms() - 1;
@@ -592,6 +1061,256 @@ async fn test_open_with_snapshots_restores_snapshot_and_wal_delta() {
     assert_eq!(reopened.list_node_ids().await, vec![1, 2, 3]);
 }
 
+#[tokio::test]
+async fn test_edge_creation_stamps_survive_wal_replay_and_backup_restore() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("edge_stamps.wal");
+    let snapshot_dir = dir.path().join("snapshots");
+
+    let stamps = |metadata: &HashMap<String, String>| {
+        (
+            metadata.get(EDGE_CREATED_LSN_KEY).cloned(),
+            metadata.get(EDGE_CREATED_AT_KEY).cloned(),
+        )
+    };
+
+    let original = {
+        let repo = Repository::open_with_snapshots(&wal_path, &snapshot_dir)
+            .await
+            .unwrap();
+        repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+            .await
+            .unwrap();
+        repo.put_node(Node::new(2, vec![2.0], "N2".to_string()))
+            .await
+            .unwrap();
+        repo.put_edge(Edge::new(1, 2, "links", 1.0)).await.unwrap();
+        let original = stamps(&repo.get_edge_metadata(1, 2, "links").await);
+        assert_eq!(original.0.as_deref(), Some("3"));
+        assert!(original.1.is_some());
+
+        // Re-putting an existing edge keeps its creation stamps, even when
+        // the caller supplies its own.
+        let mut edge = Edge::new(1, 2, "links", 0.5);
+        edge.metadata = HashMap::from([(EDGE_CREATED_LSN_KEY.to_string(), "999".to_string())]);
+        repo.put_edge(edge).await.unwrap();
+        assert_eq!(
+            stamps(&repo.get_edge_metadata(1, 2, "links").await),
+            original
+        );
+
+        repo.create_backup_snapshot().await.unwrap();
+        original
+    };
+
+    let replayed = Repository::open(&wal_path).await.unwrap();
+    assert_eq!(
+        stamps(&replayed.get_edge_metadata(1, 2, "links").await),
+        original
+    );
+    drop(replayed);
+
+    let restored = Repository::open_with_snapshots(&wal_path, &snapshot_dir)
+        .await
+        .unwrap();
+    restored.restore_from_latest_backup().await.unwrap();
+    assert_eq!(
+        stamps(&restored.get_edge_metadata(1, 2, "links").await),
+        original
+    );
+}
+
+#[tokio::test]
+async fn test_backup_operations_emit_audit_events() {
+    use alayasiki_core::audit::{AuditOperation, AuditOutcome, InMemoryAuditSink};
+
+    let dir = tempdir().unwrap();
+    let sink = Arc::new(InMemoryAuditSink::default());
+    let repo = Repository::open_with_snapshots(
+        dir.path().join("backup_audit.wal"),
+        dir.path().join("snapshots"),
+    )
+    .await
+    .unwrap()
+    .with_audit_sink(sink.clone());
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+
+    let backup_id = repo.create_backup_snapshot().await.unwrap();
+    let restored_id = repo.restore_from_latest_backup().await.unwrap();
+
+    let events = sink.events().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].operation, AuditOperation::Snapshot);
+    assert_eq!(events[0].outcome, AuditOutcome::Succeeded);
+    assert_eq!(events[0].snapshot_id.as_deref(), Some(backup_id.as_str()));
+    assert_eq!(events[1].operation, AuditOperation::Restore);
+    assert_eq!(events[1].outcome, AuditOutcome::Succeeded);
+    assert_eq!(events[1].snapshot_id.as_deref(), Some(restored_id.as_str()));
+
+    let unconfigured = Repository::open(dir.path().join("no_snapshots.wal"))
+        .await
+        .unwrap()
+        .with_audit_sink(sink.clone());
+    assert!(unconfigured.create_backup_snapshot().await.is_err());
+    let failed = sink.events().unwrap().pop().unwrap();
+    assert_eq!(failed.operation, AuditOperation::Snapshot);
+    assert_eq!(failed.outcome, AuditOutcome::Failed);
+    assert!(failed.snapshot_id.is_none());
+}
+
+#[tokio::test]
+async fn test_health_check_reports_component_status() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("health.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "N1".to_string()))
+        .await
+        .unwrap();
+
+    let report = repo.health_check().await;
+    assert!(report.is_healthy(), "{report:?}");
+    assert_eq!(report.current_lsn, Some(1));
+    assert_eq!(
+        report
+            .components
+            .iter()
+            .map(|component| component.component)
+            .collect::<Vec<_>>(),
+        vec!["wal", "nodes", "index"]
+    );
+
+    let timeout = Duration::from_millis(20);
+    {
+        let _stuck_index = repo.hyper_index.write().await;
+        let report = repo.health_check_with_timeout(timeout).await;
+        assert!(!report.is_healthy());
+        let index = report.component("index").unwrap();
+        assert_eq!(index.status, HealthStatus::Unhealthy);
+        assert!(index.detail.as_deref().unwrap().contains("lock"));
+        assert_eq!(
+            report.component("wal").unwrap().status,
+            HealthStatus::Healthy
+        );
+    }
+
+    *repo.hyper_index.write().await = HyperIndex::new();
+    let report = repo.health_check_with_timeout(timeout).await;
+    assert_eq!(
+        report.component("index").unwrap().status,
+        HealthStatus::Unhealthy
+    );
+
+    let mut permissions = std::fs::metadata(&wal_path).unwrap().permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(&wal_path, permissions).unwrap();
+    let report = repo.health_check_with_timeout(timeout).await;
+    assert_eq!(
+        report.component("wal").unwrap().status,
+        HealthStatus::Unhealthy
+    );
+    assert_eq!(report.current_lsn, Some(1));
+}
+
+#[tokio::test]
+async fn test_storage_footprint_reports_wal_size_and_vector_estimate() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("footprint.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+    for id in 1..=5u64 {
+        repo.put_node(Node::new(id, vec![0.5; 8], format!("N{id}")))
+            .await
+            .unwrap();
+    }
+    repo.put_edge(Edge::new(1, 2, "links", 1.0)).await.unwrap();
+    repo.put_edge(Edge::new(2, 3, "links", 1.0)).await.unwrap();
+    repo.flush().await.unwrap();
+
+    let footprint = repo.storage_footprint().await.unwrap();
+    assert_eq!(
+        footprint.wal_bytes,
+        std::fs::metadata(&wal_path).unwrap().len()
+    );
+    assert!(footprint.wal_bytes > 0);
+    assert_eq!(footprint.node_count, 5);
+    assert_eq!(footprint.approx_vector_bytes, 5 * 8 * 4);
+    assert_eq!(footprint.edge_count, 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_background_flush_makes_unflushed_writes_durable() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("background_flush.wal");
+    let wal_len = || std::fs::metadata(&wal_path).unwrap().len();
+
+    // Flushed by the background task once the interval elapses.
+    {
+        let repo = Repository::open(&wal_path)
+            .await
+            .unwrap()
+            .with_background_flush(Duration::from_millis(50))
+            .await;
+        repo.put_node(Node::new(1, vec![1.0, 0.0], "N1".to_string()))
+            .await
+            .unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while wal_len() != repo.storage_footprint().await.unwrap().wal_bytes || wal_len() == 0 {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "background flush never ran"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    // Flushed when the repository is dropped, long before the interval.
+    let flushed_len = wal_len();
+    {
+        let repo = Repository::open(&wal_path)
+            .await
+            .unwrap()
+            .with_background_flush(Duration::from_secs(3600))
+            .await;
+        repo.put_node(Node::new(2, vec![0.0, 1.0], "N2".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(wal_len(), flushed_len, "write must still be buffered");
+    }
+    assert!(wal_len() > flushed_len);
+
+    let reopened = Repository::open(&wal_path).await.unwrap();
+    assert_eq!(reopened.get_node(1).await.unwrap().data, "N1");
+    assert_eq!(reopened.get_node(2).await.unwrap().data, "N2");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_background_flush_replaces_flusher_started_on_open() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("single_flusher.wal");
+    let repo = Repository::open_with_options(
+        &wal_path,
+        WalOptions {
+            flush_policy: WalFlushPolicy::Interval(Duration::from_millis(20)),
+            ..WalOptions::default()
+        },
+    )
+    .await
+    .unwrap()
+    .with_background_flush(Duration::from_secs(3600))
+    .await;
+
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(
+        std::fs::metadata(&wal_path).unwrap().len(),
+        0,
+        "the 20ms flusher from open must be stopped"
+    );
+}
+
 #[tokio::test]
 async fn test_restore_from_latest_backup_rebuilds_in_memory_state() {
     let dir = tempdir().unwrap();
@@ -611,10 +1330,10 @@ async fn test_restore_from_latest_backup_rebuilds_in_memory_state() {
     repo.delete_node(2).await.unwrap();
 
     // Simulate transient in-memory corruption and verify restore recovers from durable state.
-    repo.nodes.write().await.clear();
+    Arc::make_mut(&mut *repo.nodes.write().await).clear();
     *repo.hyper_index.write().await = HyperIndex::new();
     repo.idempotency_index.write().await.clear();
-    repo.edge_metadata.write().await.clear();
+    Arc::make_mut(&mut *repo.edge_metadata.write().await).clear();
 
     assert!(repo.list_node_ids().await.is_empty());
 
@@ -732,3 +1451,524 @@ async fn test_session_owner_enforced_for_ingest_and_query() {
     assert_eq!(allowed_read.nodes.len(), 1);
     assert!(allowed_read.nodes.contains_key(&1));
 }
+
+#[tokio::test]
+async fn test_validate_consistency_reports_and_repairs_dangling_entries() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("consistency.wal");
+    let repo = Repository::open(&wal_path).await.unwrap();
+
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![2.0], "N2".to_string()))
+        .await
+        .unwrap();
+    repo.put_edge(Edge::new(1, 2, "links", 1.0)).await.unwrap();
+    repo.record_idempotency("doc-1", vec![1, 2]).await.unwrap();
+    assert!(repo.validate_consistency().await.is_empty());
+
+    repo.hyper_index
+        .write()
+        .await
+        .upsert_edge(1, 99, "mentions", 1.0);
+    Arc::make_mut(&mut *repo.edge_metadata.write().await).insert(
+        (2, 1, "ghost".to_string()),
+        HashMap::from([("k".to_string(), "v".to_string())]),
+    );
+    repo.idempotency_index
+        .write()
+        .await
+        .insert("doc-2".to_string(), vec![2, 42]);
+
+    let issues = repo.validate_consistency().await;
+    assert_eq!(
+        issues,
+        vec![
+            ConsistencyIssue::DanglingEdge {
+                source: 1,
+                target: 99,
+                relation: "mentions".to_string(),
+                missing_node: 99,
+            },
+            ConsistencyIssue::OrphanEdgeMetadata {
+                source: 2,
+                target: 1,
+                relation: "ghost".to_string(),
+            },
+            ConsistencyIssue::DanglingIdempotencyEntry {
+                key: "doc-2".to_string(),
+                missing_node_ids: vec![42],
+            },
+        ]
+    );
+    // Validation is read-only.
+    assert_eq!(repo.validate_consistency().await, issues);
+
+    assert_eq!(repo.repair().await.unwrap(), issues);
+    assert!(repo.validate_consistency().await.is_empty());
+    assert_eq!(repo.list_node_ids().await, vec![1, 2]);
+    assert_eq!(repo.check_idempotency("doc-1").await, Some(vec![1, 2]));
+    assert_eq!(repo.check_idempotency("doc-2").await, None);
+    let neighbors: Vec<u64> = repo
+        .hyper_index
+        .read()
+        .await
+        .graph_index
+        .neighbors(1)
+        .into_iter()
+        .map(|(target, _, _)| *target)
+        .collect();
+    assert_eq!(neighbors, vec![2]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_validation_and_warmup_run_alongside_ingest_batches() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("consistency_concurrent.wal");
+    let repo = Arc::new(Repository::open(&wal_path).await.unwrap());
+
+    let writer = {
+        let repo = repo.clone();
+        tokio::spawn(async move {
+            for id in 0..200u64 {
+                repo.persist_ingest_batch(
+                    vec![Node::new(id, vec![1.0], format!("N{id}"))],
+                    vec![(format!("doc-{id}"), vec![id])],
+                )
+                .await
+                .unwrap();
+            }
+        })
+    };
+    let validator = {
+        let repo = repo.clone();
+        tokio::spawn(async move {
+            for round in 0..200 {
+                if round % 2 == 0 {
+                    assert!(repo.validate_consistency().await.is_empty());
+                } else {
+                    repo.warmup_and_validate().await.unwrap();
+                }
+            }
+        })
+    };
+
+    tokio::time::timeout(Duration::from_secs(30), async {
+        writer.await.unwrap();
+        validator.await.unwrap();
+    })
+    .await
+    .expect("validation deadlocked against ingest");
+}
+
+#[tokio::test]
+async fn test_remove_edges_by_relation_drops_only_that_relation_across_reopen() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("remove_edges_by_relation.wal");
+    {
+        let repo = Repository::open(&wal_path).await.unwrap();
+        for id in 1..=3 {
+            repo.put_node(Node::new(id, vec![id as f32], format!("N{id}")))
+                .await
+                .unwrap();
+        }
+        for (source, target, relation) in [
+            (1, 2, "mentions"),
+            (2, 3, "mentions"),
+            (1, 3, "mentions"),
+            (1, 2, "links"),
+            (3, 1, "links"),
+        ] {
+            repo.put_edge(Edge::new(source, target, relation, 1.0))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(repo.remove_edges_by_relation("mentions").await.unwrap(), 3);
+        assert_eq!(repo.remove_edges_by_relation("mentions").await.unwrap(), 0);
+        assert!(repo.get_edge_metadata(1, 2, "mentions").await.is_empty());
+        assert!(!repo.get_edge_metadata(1, 2, "links").await.is_empty());
+    }
+
+    let repo = Repository::open(&wal_path).await.unwrap();
+    let graph = repo.graph_index().await;
+    let mut remaining: Vec<(u64, u64, String)> = (1..=3)
+        .flat_map(|source| {
+            graph
+                .neighbors(source)
+                .into_iter()
+                .map(move |(target, relation, _)| (source, *target, relation.clone()))
+        })
+        .collect();
+    remaining.sort();
+    assert_eq!(
+        remaining,
+        vec![(1, 2, "links".to_string()), (3, 1, "links".to_string())]
+    );
+    assert!(repo.get_edge_metadata(2, 3, "mentions").await.is_empty());
+    assert!(repo.validate_consistency().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_update_edge_metadata_patches_keys_and_survives_reopen() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("edge_meta_patch.wal");
+    {
+        let repo = Repository::open(&wal_path).await.unwrap();
+        repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+            .await
+            .unwrap();
+        repo.put_node(Node::new(2, vec![2.0], "N2".to_string()))
+            .await
+            .unwrap();
+        let mut edge = Edge::new(1, 2, "links", 0.7);
+        edge.metadata = HashMap::from([
+            ("source".to_string(), "doc-1".to_string()),
+            (
+                "extraction_model_id".to_string(),
+                "extractor-v1".to_string(),
+            ),
+            ("stale".to_string(), "yes".to_string()),
+        ]);
+        repo.put_edge(edge).await.unwrap();
+
+        repo.update_edge_metadata(
+            1,
+            2,
+            "links",
+            HashMap::from([
+                (
+                    "extraction_model_id".to_string(),
+                    "extractor-v2".to_string(),
+                ),
+                (EDGE_CREATED_LSN_KEY.to_string(), "999".to_string()),
+            ]),
+            vec!["stale".to_string(), EDGE_CREATED_AT_KEY.to_string()],
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            repo.update_edge_metadata(2, 1, "links", HashMap::new(), Vec::new())
+                .await,
+            Err(RepoError::NotFound)
+        ));
+    }
+
+    let repo = Repository::open(&wal_path).await.unwrap();
+    let mut metadata = repo.get_edge_metadata(1, 2, "links").await;
+    assert_eq!(metadata.remove(EDGE_CREATED_LSN_KEY).as_deref(), Some("3"));
+    assert!(metadata.remove(EDGE_CREATED_AT_KEY).is_some());
+    assert_eq!(
+        metadata,
+        HashMap::from([
+            ("source".to_string(), "doc-1".to_string()),
+            (
+                "extraction_model_id".to_string(),
+                "extractor-v2".to_string()
+            ),
+        ])
+    );
+    let graph = repo.graph_index().await;
+    let edges: Vec<_> = graph.neighbors(1).into_iter().cloned().collect();
+    assert_eq!(edges, vec![(2, "links".to_string(), 0.7)]);
+    assert!(repo.validate_consistency().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_warmup_after_open_and_validation_surfaces_issues() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("warmup.wal");
+    {
+        let repo = Repository::open(&wal_path).await.unwrap();
+        for id in 1..=8u64 {
+            repo.put_node(Node::new(id, vec![id as f32, 1.0], format!("N{id}")))
+                .await
+                .unwrap();
+        }
+        repo.put_edge(Edge::new(1, 2, "links", 1.0)).await.unwrap();
+    }
+
+    let repo = Repository::open(&wal_path).await.unwrap();
+    repo.warmup().await;
+    repo.warmup_and_validate().await.unwrap();
+    let hits = repo.search_vector_with_session(&[8.0, 1.0], 1, None).await;
+    assert_eq!(hits[0].0, 8);
+
+    repo.hyper_index
+        .write()
+        .await
+        .upsert_edge(1, 99, "mentions", 1.0);
+    match repo.warmup_and_validate().await {
+        Err(RepoError::Inconsistent(issues)) => assert_eq!(
+            issues,
+            vec![ConsistencyIssue::DanglingEdge {
+                source: 1,
+                target: 99,
+                relation: "mentions".to_string(),
+                missing_node: 99,
+            }]
+        ),
+        other => panic!("expected inconsistency, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_snapshot_verify_accepts_good_snapshot_and_rejects_corrupt_file() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("snapshot_verify.wal");
+    let snapshot_dir = dir.path().join("snapshots");
+    let repo = Repository::open_with_snapshots(&wal_path, &snapshot_dir)
+        .await
+        .unwrap();
+
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![2.0], "N2".to_string()))
+        .await
+        .unwrap();
+    let mut edge = Edge::new(1, 2, "links", 1.0);
+    edge.metadata
+        .insert("source".to_string(), "doc".to_string());
+    repo.put_edge(edge).await.unwrap();
+    let snapshot_id = repo.create_backup_snapshot().await.unwrap();
+    let lsn = parse_wal_snapshot_lsn(&snapshot_id).unwrap();
+
+    let manager = SnapshotManager::new(&snapshot_dir);
+    let report = manager.verify(lsn).await.unwrap();
+    assert_eq!(report.lsn, lsn);
+    assert_eq!(report.node_count, 2);
+    assert_eq!(report.edge_count, 1);
+    assert_eq!(report.edge_metadata_count, 1);
+
+    assert!(matches!(
+        manager.verify(lsn + 10).await,
+        Err(SnapshotError::NotFound(missing)) if missing == lsn + 10
+    ));
+
+    let bytes = tokio::fs::read(&report.path).await.unwrap();
+    tokio::fs::write(&report.path, &bytes[..bytes.len() / 2])
+        .await
+        .unwrap();
+    let err = manager.verify(lsn).await.unwrap_err();
+    assert!(matches!(err, SnapshotError::Corrupt(_)), "{err}");
+}
+
+#[tokio::test]
+async fn test_backup_snapshot_header_rejects_version_bump_and_corrupt_payload() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("snapshot_header.wal");
+    let snapshot_dir = dir.path().join("snapshots");
+    let repo = Repository::open_with_snapshots(&wal_path, &snapshot_dir)
+        .await
+        .unwrap();
+    repo.put_node(Node::new(1, vec![1.0], "N1".to_string()))
+        .await
+        .unwrap();
+    let snapshot_id = repo.create_backup_snapshot().await.unwrap();
+    let lsn = parse_wal_snapshot_lsn(&snapshot_id).unwrap();
+    let manager = SnapshotManager::new(&snapshot_dir);
+    let path = manager.verify(lsn).await.unwrap().path;
+    let original = tokio::fs::read(&path).await.unwrap();
+
+    let mut bumped = original.clone();
+    bumped[4..8].copy_from_slice(&(backup::BACKUP_FORMAT_VERSION + 1).to_le_bytes());
+    tokio::fs::write(&path, &bumped).await.unwrap();
+    assert!(matches!(
+        manager.verify(lsn).await,
+        Err(SnapshotError::VersionMismatch { found, expected })
+            if found == backup::BACKUP_FORMAT_VERSION + 1
+                && expected == backup::BACKUP_FORMAT_VERSION
+    ));
+    assert!(matches!(
+        repo.restore_from_latest_backup().await,
+        Err(RepoError::Snapshot(SnapshotError::VersionMismatch { .. }))
+    ));
+
+    let mut corrupted = original.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0xFF;
+    tokio::fs::write(&path, &corrupted).await.unwrap();
+    assert!(matches!(
+        manager.verify(lsn).await,
+        Err(SnapshotError::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        repo.restore_from_latest_backup().await,
+        Err(RepoError::Snapshot(SnapshotError::ChecksumMismatch { .. }))
+    ));
+
+    tokio::fs::write(&path, &original).await.unwrap();
+    repo.restore_from_latest_backup().await.unwrap();
+    assert_eq!(repo.list_node_ids().await, vec![1]);
+}
+
+#[tokio::test]
+async fn test_parallel_replay_matches_serial_replay() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("parallel_replay.wal");
+    let options = |replay_batch_entries| WalOptions {
+        flush_policy: WalFlushPolicy::Batch { max_entries: 64 },
+        replay_batch_entries,
+        ..WalOptions::default()
+    };
+
+    {
+        let repo = Repository::open_with_options(&wal_path, options(1))
+            .await
+            .unwrap();
+        for id in 0..200u64 {
+            repo.put_node(Node::new(id, vec![id as f32, 1.0], format!("node {id}")))
+                .await
+                .unwrap();
+            if id > 0 {
+                let mut edge = Edge::new(id - 1, id, "next", 0.5);
+                edge.metadata.insert("seq".to_string(), id.to_string());
+                repo.put_edge(edge).await.unwrap();
+            }
+            if id % 7 == 0 {
+                // Overwrite with a new embedding so the latest write must win.
+                repo.put_node(Node::new(id, vec![0.0, id as f32], format!("node {id} v2")))
+                    .await
+                    .unwrap();
+            }
+            if id % 17 == 0 && id > 0 {
+                repo.update_edge_metadata(
+                    id - 1,
+                    id,
+                    "next",
+                    HashMap::from([("patched".to_string(), "yes".to_string())]),
+                    vec!["seq".to_string()],
+                )
+                .await
+                .unwrap();
+            }
+            if id % 11 == 0 && id > 0 {
+                repo.delete_node(id - 1).await.unwrap();
+            }
+            if id % 13 == 0 {
+                let batch = vec![Node::new(
+                    1_000 + id,
+                    vec![1.0, 0.0],
+                    format!("ingested {id}"),
+                )];
+                repo.persist_ingest_batch(batch, vec![(format!("key-{id}"), vec![1_000 + id])])
+                    .await
+                    .unwrap();
+            }
+        }
+        repo.flush().await.unwrap();
+    }
+
+    let serial = Repository::open_with_options(&wal_path, options(1))
+        .await
+        .unwrap();
+    let parallel = Repository::open_with_options(&wal_path, options(7))
+        .await
+        .unwrap();
+
+    assert_eq!(*parallel.nodes.read().await, *serial.nodes.read().await);
+    assert_eq!(
+        *parallel.idempotency_index.read().await,
+        *serial.idempotency_index.read().await
+    );
+    assert_eq!(
+        *parallel.edge_metadata.read().await,
+        *serial.edge_metadata.read().await
+    );
+    assert_eq!(
+        parallel.graph_index().await.edges(),
+        serial.graph_index().await.edges()
+    );
+    assert_eq!(
+        parallel.current_snapshot_id().await,
+        serial.current_snapshot_id().await
+    );
+    assert!(!serial.nodes.read().await.contains_key(&10));
+    assert_eq!(serial.get_node(14).await.unwrap().data, "node 14 v2");
+}
+
+#[tokio::test]
+async fn test_read_handle_stays_pinned_while_writers_advance() {
+    let dir = tempdir().unwrap();
+    let repo = Arc::new(
+        Repository::open(dir.path().join("read_handle.wal"))
+            .await
+            .unwrap(),
+    );
+    repo.put_node(Node::new(1, vec![1.0, 0.0], "alpha".to_string()))
+        .await
+        .unwrap();
+    repo.put_node(Node::new(2, vec![0.0, 1.0], "beta".to_string()))
+        .await
+        .unwrap();
+    let mut edge = Edge::new(1, 2, "links", 1.0);
+    edge.metadata
+        .insert("source".to_string(), "seed".to_string());
+    repo.put_edge(edge).await.unwrap();
+
+    let handle = repo.begin_read().await;
+    assert_eq!(handle.snapshot_id(), repo.current_snapshot_id().await);
+
+    let writer = {
+        let repo = Arc::clone(&repo);
+        tokio::spawn(async move {
+            for id in 3..50u64 {
+                repo.put_node(Node::new(id, vec![1.0, 1.0], format!("node {id}")))
+                    .await
+                    .unwrap();
+            }
+            repo.update_edge_metadata(
+                1,
+                2,
+                "links",
+                HashMap::from([("source".to_string(), "patched".to_string())]),
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+            repo.put_node(Node::new(2, vec![0.0, 1.0], "beta v2".to_string()))
+                .await
+                .unwrap();
+            repo.delete_node(1).await.unwrap();
+        })
+    };
+
+    // Iterate the pinned view while the writer runs.
+    let mut seen = Vec::new();
+    for _ in 0..10 {
+        seen = handle.iter_nodes().map(|node| node.id).collect();
+        tokio::task::yield_now().await;
+    }
+    writer.await.unwrap();
+
+    assert_eq!(seen, vec![1, 2]);
+    assert_eq!(handle.list_node_ids(), vec![1, 2]);
+    assert_eq!(handle.get_node(2).unwrap().data, "beta");
+    assert_eq!(
+        handle
+            .edge_metadata(1, 2, "links")
+            .and_then(|meta| meta.get("source"))
+            .map(String::as_str),
+        Some("seed")
+    );
+
+    let pinned_edges: Vec<(u64, u64, String, f32)> = handle
+        .iter_edges()
+        .map(|(source, target, relation, weight)| (source, target, relation.to_string(), weight))
+        .collect();
+    assert_eq!(pinned_edges, vec![(1, 2, "links".to_string(), 1.0)]);
+    assert_eq!(handle.edge_count(), 1);
+    let pinned_neighbors: Vec<u64> = handle.neighbors(1).iter().map(|e| e.neighbor).collect();
+    assert_eq!(pinned_neighbors, vec![2]);
+
+    assert_ne!(repo.current_snapshot_id().await, handle.snapshot_id());
+    let latest = repo.begin_read().await;
+    assert!(latest.lsn() > handle.lsn());
+    assert_eq!(latest.get_node(2).unwrap().data, "beta v2");
+    assert!(latest.get_node(1).is_none());
+    assert_eq!(latest.node_count(), 48);
+    assert_eq!(latest.edge_count(), 0);
+    assert!(latest.neighbors(1).is_empty());
+}
@@ -1,36 +1,82 @@
 use super::replay::{apply_tx_operation, mutations_to_tx_operations, serialize_wal_entry};
-use super::{IndexMutation, RepoError, Repository, TxOperation, WalEntry};
+use super::{
+    current_unix_timestamp_ms, EdgeMetadataMap, EdgeMetadataUpdate, IndexMutation, RepoError,
+    Repository, TxOperation, WalEntry, WriteMode, EDGE_CREATED_AT_KEY, EDGE_CREATED_LSN_KEY,
+};
+use crate::index::AdjacencyGraph;
+use alayasiki_core::model::{Edge, Node};
 use rkyv::ser::serializers::AllocSerializer;
 use rkyv::ser::Serializer;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 impl Repository {
+    /// Start a [`Transaction`] that stages mutations until committed.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            repo: self,
+            mutations: Vec::new(),
+            mode: WriteMode::Upsert,
+        }
+    }
+
     pub async fn apply_index_transaction(
         &self,
         mutations: Vec<IndexMutation>,
+    ) -> Result<(), RepoError> {
+        self.apply_index_transaction_with_mode(mutations, WriteMode::Upsert)
+            .await
+    }
+
+    /// Like [`Repository::apply_index_transaction`]; with
+    /// [`WriteMode::InsertOnly`] a `PutNode` whose id already exists (or was
+    /// put earlier in the same transaction) fails with
+    /// `RepoError::InvalidTransaction` and nothing is written.
+    pub async fn apply_index_transaction_with_mode(
+        &self,
+        mutations: Vec<IndexMutation>,
+        mode: WriteMode,
     ) -> Result<(), RepoError> {
         if mutations.is_empty() {
             return Ok(());
         }
 
         let _tx_guard = self.tx_lock.lock().await;
+        self.apply_index_transaction_locked(mutations, mode).await
+    }
 
-        self.validate_index_transaction(&mutations).await?;
+    /// Body of [`Repository::apply_index_transaction_with_mode`]; the caller
+    /// holds `tx_lock`.
+    pub(super) async fn apply_index_transaction_locked(
+        &self,
+        mut mutations: Vec<IndexMutation>,
+        mode: WriteMode,
+    ) -> Result<(), RepoError> {
+        self.validate_index_transaction(&mutations, mode).await?;
 
-        let tx_operations = mutations_to_tx_operations(&mutations);
-        let tx_entry = WalEntry::Transaction(tx_operations);
-        let tx_bytes = serialize_wal_entry(&tx_entry)?;
+        // Every WAL writer holds `tx_lock`, and the transaction is a single
+        // append, so it lands at the next LSN.
+        let next_lsn = self.wal.lock().await.current_lsn() + 1;
+        stamp_edge_creation(
+            &mut mutations,
+            &self.hyper_index.read().await.graph_index,
+            self.edge_metadata.read().await.as_ref(),
+            next_lsn,
+        );
 
         let durable_lsn = {
             let mut wal = self.wal.lock().await;
-            wal.append(&tx_bytes).await?;
+            let tx_entry = WalEntry::Transaction(mutations_to_tx_operations(&mutations));
+            wal.append(&serialize_wal_entry(&tx_entry)?).await?;
             wal.durable_lsn()
         };
         self.record_durable_snapshot(durable_lsn).await?;
 
-        let mut nodes = self.nodes.write().await;
+        let mut nodes_guard = self.nodes.write().await;
+        let nodes = Arc::make_mut(&mut nodes_guard);
         let mut index = self.hyper_index.write().await;
-        let mut edge_meta = self.edge_metadata.write().await;
+        let mut edge_meta_guard = self.edge_metadata.write().await;
+        let edge_meta = Arc::make_mut(&mut edge_meta_guard);
 
         for mutation in mutations {
             match mutation {
@@ -56,11 +102,14 @@ impl Repository {
                 }
             }
         }
+        self.bump_state_generation();
 
         Ok(())
     }
 
     /// Persist a batch of ingested nodes and their idempotency keys in one WAL transaction.
+    /// As with [`Repository::ensure_node`], nodes identical to the stored ones
+    /// are skipped, so re-ingesting unchanged content does not grow the WAL.
     pub async fn persist_ingest_batch(
         &self,
         nodes_to_put: Vec<alayasiki_core::model::Node>,
@@ -72,12 +121,16 @@ impl Repository {
 
         let _tx_guard = self.tx_lock.lock().await;
 
-        let node_mutations: Vec<IndexMutation> = nodes_to_put
-            .iter()
-            .cloned()
-            .map(IndexMutation::PutNode)
-            .collect();
-        self.validate_index_transaction(&node_mutations).await?;
+        let node_mutations: Vec<IndexMutation> = {
+            let nodes = self.nodes.read().await;
+            nodes_to_put
+                .into_iter()
+                .filter(|node| nodes.get(&node.id) != Some(node))
+                .map(IndexMutation::PutNode)
+                .collect()
+        };
+        self.validate_index_transaction(&node_mutations, WriteMode::Upsert)
+            .await?;
 
         let mut idempotency_index = self.idempotency_index.write().await;
         let new_idempotency_records: Vec<(String, Vec<u64>)> = idempotency_records
@@ -107,24 +160,119 @@ impl Repository {
         };
         self.record_durable_snapshot(durable_lsn).await?;
 
-        let mut nodes = self.nodes.write().await;
+        let mut nodes_guard = self.nodes.write().await;
+        let nodes = Arc::make_mut(&mut nodes_guard);
         let mut index = self.hyper_index.write().await;
-        let mut edge_meta = self.edge_metadata.write().await;
+        let mut edge_meta_guard = self.edge_metadata.write().await;
+        let edge_meta = Arc::make_mut(&mut edge_meta_guard);
 
         for operation in &tx_operations {
             apply_tx_operation(
                 operation,
-                &mut nodes,
+                nodes,
                 &mut index,
                 &mut idempotency_index,
-                &mut edge_meta,
+                edge_meta,
             );
         }
+        self.bump_state_generation();
 
         Ok(())
     }
 
+    /// Set the keys in `patch` and drop `remove_keys` on one edge's metadata in
+    /// a single WAL transaction, leaving its weight and the graph unchanged.
+    /// The creation stamps ([`EDGE_CREATED_LSN_KEY`], [`EDGE_CREATED_AT_KEY`])
+    /// are ignored in both. Fails with [`RepoError::NotFound`] when the edge
+    /// does not exist.
+    pub async fn update_edge_metadata(
+        &self,
+        source: u64,
+        target: u64,
+        relation: &str,
+        patch: HashMap<String, String>,
+        remove_keys: Vec<String>,
+    ) -> Result<(), RepoError> {
+        let _tx_guard = self.tx_lock.lock().await;
+        if !self
+            .hyper_index
+            .read()
+            .await
+            .graph_index
+            .contains_edge(source, target, relation)
+        {
+            return Err(RepoError::NotFound);
+        }
+
+        let mut patch = patch;
+        let mut remove_keys = remove_keys;
+        patch.retain(|key, _| !is_edge_creation_stamp(key));
+        remove_keys.retain(|key| !is_edge_creation_stamp(key));
+        let operation = TxOperation::UpdateEdgeMetadata(Box::new(EdgeMetadataUpdate {
+            source,
+            target,
+            relation: relation.to_string(),
+            patch,
+            remove_keys,
+        }));
+        let tx_bytes = serialize_wal_entry(&WalEntry::Transaction(vec![operation.clone()]))?;
+        let durable_lsn = {
+            let mut wal = self.wal.lock().await;
+            wal.append(&tx_bytes).await?;
+            wal.durable_lsn()
+        };
+        self.record_durable_snapshot(durable_lsn).await?;
+
+        let mut nodes_guard = self.nodes.write().await;
+        let nodes = Arc::make_mut(&mut nodes_guard);
+        let mut index = self.hyper_index.write().await;
+        let mut idempotency = self.idempotency_index.write().await;
+        let mut edge_meta_guard = self.edge_metadata.write().await;
+        let edge_meta = Arc::make_mut(&mut edge_meta_guard);
+        apply_tx_operation(&operation, nodes, &mut index, &mut idempotency, edge_meta);
+        self.bump_state_generation();
+        Ok(())
+    }
+
+    /// Remove every edge labeled `relation`, and its metadata, in a single WAL
+    /// transaction; e.g. to undo a mislabeled extraction run. Returns the
+    /// number of edges removed; nothing is written when there are none.
+    pub async fn remove_edges_by_relation(&self, relation: &str) -> Result<usize, RepoError> {
+        let _tx_guard = self.tx_lock.lock().await;
+        let removed = self
+            .hyper_index
+            .read()
+            .await
+            .graph_index
+            .relation_edge_count(relation);
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        let operation = TxOperation::RemoveEdgesByRelation {
+            relation: relation.to_string(),
+        };
+        let tx_bytes = serialize_wal_entry(&WalEntry::Transaction(vec![operation.clone()]))?;
+        let durable_lsn = {
+            let mut wal = self.wal.lock().await;
+            wal.append(&tx_bytes).await?;
+            wal.durable_lsn()
+        };
+        self.record_durable_snapshot(durable_lsn).await?;
+
+        let mut nodes_guard = self.nodes.write().await;
+        let nodes = Arc::make_mut(&mut nodes_guard);
+        let mut index = self.hyper_index.write().await;
+        let mut idempotency = self.idempotency_index.write().await;
+        let mut edge_meta_guard = self.edge_metadata.write().await;
+        let edge_meta = Arc::make_mut(&mut edge_meta_guard);
+        apply_tx_operation(&operation, nodes, &mut index, &mut idempotency, edge_meta);
+        self.bump_state_generation();
+        Ok(removed)
+    }
+
     pub async fn record_idempotency(&self, key: &str, node_ids: Vec<u64>) -> Result<(), RepoError> {
+        let _tx_guard = self.tx_lock.lock().await;
         {
             let mut index = self.idempotency_index.write().await;
             if index.contains_key(key) {
@@ -157,6 +305,7 @@ impl Repository {
     async fn validate_index_transaction(
         &self,
         mutations: &[IndexMutation],
+        mode: WriteMode,
     ) -> Result<(), RepoError> {
         let nodes = self.nodes.read().await;
         let mut visible_nodes: HashSet<u64> = nodes.keys().copied().collect();
@@ -164,9 +313,24 @@ impl Repository {
         for mutation in mutations {
             match mutation {
                 IndexMutation::PutNode(node) => {
-                    visible_nodes.insert(node.id);
+                    if !visible_nodes.insert(node.id) && mode == WriteMode::InsertOnly {
+                        return Err(RepoError::InvalidTransaction(format!(
+                            "node id {} exists",
+                            node.id
+                        )));
+                    }
                 }
                 IndexMutation::PutEdge(edge) => {
+                    if self
+                        .allowed_relations
+                        .as_ref()
+                        .is_some_and(|allowed| !allowed.contains(&edge.relation))
+                    {
+                        return Err(RepoError::InvalidTransaction(format!(
+                            "relation {:?} is not allowed",
+                            edge.relation
+                        )));
+                    }
                     if !visible_nodes.contains(&edge.source) {
                         return Err(RepoError::InvalidTransaction(format!(
                             "edge source {} does not exist",
@@ -191,3 +355,108 @@ impl Repository {
         Ok(())
     }
 }
+
+/// Handle for assembling a transaction step by step, returned by
+/// [`Repository::transaction`]. Nothing is written until
+/// [`Transaction::commit`], which applies every staged mutation atomically
+/// through [`Repository::apply_index_transaction_with_mode`]; dropping the
+/// handle discards them.
+pub struct Transaction<'a> {
+    repo: &'a Repository,
+    mutations: Vec<IndexMutation>,
+    mode: WriteMode,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn with_write_mode(mut self, mode: WriteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn put_node(&mut self, node: Node) -> &mut Self {
+        self.mutations.push(IndexMutation::PutNode(node));
+        self
+    }
+
+    pub fn put_edge(&mut self, edge: Edge) -> &mut Self {
+        self.mutations.push(IndexMutation::PutEdge(edge));
+        self
+    }
+
+    pub fn delete_node(&mut self, id: u64) -> &mut Self {
+        self.mutations.push(IndexMutation::DeleteNode(id));
+        self
+    }
+
+    /// Node `id` as this transaction would leave it: the latest staged put or
+    /// delete wins, otherwise the committed node is returned.
+    pub async fn get_node(&self, id: u64) -> Result<Node, RepoError> {
+        for mutation in self.mutations.iter().rev() {
+            match mutation {
+                IndexMutation::PutNode(node) if node.id == id => return Ok(node.clone()),
+                IndexMutation::DeleteNode(deleted) if *deleted == id => {
+                    return Err(RepoError::NotFound)
+                }
+                _ => {}
+            }
+        }
+        self.repo.get_node(id).await
+    }
+
+    pub fn mutations(&self) -> &[IndexMutation] {
+        &self.mutations
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    /// Validates and applies the staged mutations as one WAL record, with
+    /// the same semantics as [`Repository::apply_index_transaction`].
+    pub async fn commit(self) -> Result<(), RepoError> {
+        self.repo
+            .apply_index_transaction_with_mode(self.mutations, self.mode)
+            .await
+    }
+}
+
+fn is_edge_creation_stamp(key: &str) -> bool {
+    key == EDGE_CREATED_LSN_KEY || key == EDGE_CREATED_AT_KEY
+}
+
+/// Record [`EDGE_CREATED_LSN_KEY`] and [`EDGE_CREATED_AT_KEY`] on every edge in
+/// `mutations` before it is written to the WAL, so replay and backups restore
+/// them verbatim. Edges that already exist keep their original stamps; any
+/// stamps supplied by the caller are discarded.
+fn stamp_edge_creation(
+    mutations: &mut [IndexMutation],
+    graph: &AdjacencyGraph,
+    edge_metadata: &EdgeMetadataMap,
+    lsn: u64,
+) {
+    let created_at = current_unix_timestamp_ms().to_string();
+    for mutation in mutations {
+        let IndexMutation::PutEdge(edge) = mutation else {
+            continue;
+        };
+        edge.metadata.retain(|key, _| !is_edge_creation_stamp(key));
+        let key = (edge.source, edge.target, edge.relation.clone());
+        let existing = graph
+            .contains_edge(edge.source, edge.target, &edge.relation)
+            .then(|| edge_metadata.get(&key))
+            .flatten();
+        for (stamp, fresh) in [
+            (EDGE_CREATED_LSN_KEY, lsn.to_string()),
+            (EDGE_CREATED_AT_KEY, created_at.clone()),
+        ] {
+            let value = match existing {
+                Some(metadata) => match metadata.get(stamp) {
+                    Some(original) => original.clone(),
+                    None => continue,
+                },
+                None => fresh,
+            };
+            edge.metadata.insert(stamp.to_string(), value);
+        }
+    }
+}
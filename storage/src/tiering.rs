@@ -29,6 +29,12 @@ pub struct StorageProfile {
     pub gpu_runtime: GpuRuntime,
     pub vram_budget_bytes: Option<u64>,
     pub spillback_to_cpu: bool,
+    /// Seed for the order in which a backup snapshot's vectors are bulk-loaded
+    /// into HNSW on restore; `None` uses
+    /// [`crate::index::DEFAULT_RESTORE_ORDER_SEED`]. Incremental inserts do not
+    /// consume it.
+    #[serde(default, alias = "seed")]
+    pub restore_order_seed: Option<u64>,
 }
 
 impl StorageProfile {
@@ -40,6 +46,7 @@ impl StorageProfile {
             gpu_runtime: GpuRuntime::Disabled,
             vram_budget_bytes: None,
             spillback_to_cpu: true,
+            restore_order_seed: None,
         }
     }
 
@@ -51,6 +58,7 @@ impl StorageProfile {
             gpu_runtime: GpuRuntime::Disabled,
             vram_budget_bytes: Some(vram_budget_bytes),
             spillback_to_cpu: true,
+            restore_order_seed: None,
         }
     }
 
@@ -64,6 +72,16 @@ impl StorageProfile {
         self
     }
 
+    pub fn with_restore_order_seed(mut self, seed: u64) -> Self {
+        self.restore_order_seed = Some(seed);
+        self
+    }
+
+    pub fn effective_restore_order_seed(&self) -> u64 {
+        self.restore_order_seed
+            .unwrap_or(crate::index::DEFAULT_RESTORE_ORDER_SEED)
+    }
+
     pub fn resolve_capabilities(&self) -> StorageCapabilities {
         let gpu_resident =
             self.hot_tier == StorageTier::GpuVram && self.gpu_runtime != GpuRuntime::Disabled;
@@ -19,6 +19,8 @@ pub enum WalError {
     CorruptEntry,
     #[error("At-rest encryption error: {0}")]
     Encryption(String),
+    #[error("WAL compression error: {0}")]
+    Compression(String),
 }
 
 impl AlayasikiError for WalError {
@@ -28,6 +30,7 @@ impl AlayasikiError for WalError {
             WalError::CrcMismatch => ErrorCode::Internal,
             WalError::CorruptEntry => ErrorCode::Internal,
             WalError::Encryption(_) => ErrorCode::Internal,
+            WalError::Compression(_) => ErrorCode::Internal,
         }
     }
 }
@@ -45,10 +48,18 @@ pub enum WalRecoveryMode {
     RecoverToLastGoodOffset,
 }
 
+/// When appended entries are flushed and fsynced.
+///
+/// Anything not yet flushed is lost on a crash, so the relaxed policies trade a
+/// bounded window of recent writes for fewer fsyncs. [`Wal::flush`] is always
+/// durable regardless of the policy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum WalFlushPolicy {
     #[default]
     Always,
+    /// Flush on the first append after the interval has elapsed. A repository
+    /// opened with this policy also flushes from a background task every
+    /// interval, so an idle WAL loses at most one interval of writes.
     Interval(Duration),
     Batch {
         max_entries: usize,
@@ -68,19 +79,145 @@ impl WalFlushPolicy {
     }
 }
 
+/// Codec applied to WAL payloads before encryption and CRC.
+///
+/// Compressed records carry their codec, so a log written under one setting
+/// replays under any other, and logs mixing codecs (or predating compression)
+/// replay correctly.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalCompression {
+    #[default]
+    None,
+    Lz4,
+    /// zstd at the given level (`0` selects zstd's default).
+    Zstd(i32),
+}
+
+impl WalCompression {
+    fn codec(self) -> Option<u8> {
+        match self {
+            Self::None => None,
+            Self::Lz4 => Some(CODEC_LZ4),
+            Self::Zstd(_) => Some(CODEC_ZSTD),
+        }
+    }
+
+    fn compress(self, payload: &[u8]) -> Result<Vec<u8>, WalError> {
+        match self {
+            Self::None => Ok(payload.to_vec()),
+            Self::Lz4 => Ok(lz4_flex::compress_prepend_size(payload)),
+            Self::Zstd(level) => zstd::bulk::compress(payload, level)
+                .map_err(|err| WalError::Compression(err.to_string())),
+        }
+    }
+}
+
+/// Set in a record's length field when the payload starts with a codec byte.
+const COMPRESSED_FLAG: u32 = 1 << 31;
+const CODEC_LZ4: u8 = 1;
+const CODEC_ZSTD: u8 = 2;
+
+fn decompress(codec: u8, payload: &[u8]) -> Result<Vec<u8>, WalError> {
+    match codec {
+        CODEC_LZ4 => lz4_flex::decompress_size_prepended(payload)
+            .map_err(|err| WalError::Compression(err.to_string())),
+        CODEC_ZSTD => {
+            zstd::stream::decode_all(payload).map_err(|err| WalError::Compression(err.to_string()))
+        }
+        _ => Err(WalError::CorruptEntry),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct WalOptions {
     pub recovery_mode: WalRecoveryMode,
     pub flush_policy: WalFlushPolicy,
+    /// Capacity of the in-process write buffer in front of the WAL file.
+    pub buffer_bytes: usize,
+    /// Entries per batch that repository replay deserializes in parallel while
+    /// the next batch is read. `1` replays serially.
+    pub replay_batch_entries: usize,
+    /// Codec for newly appended payloads; existing records are unaffected.
+    pub compression: WalCompression,
 }
 
 impl WalOptions {
+    pub const DEFAULT_BUFFER_BYTES: usize = 8 * 1024;
+    pub const DEFAULT_REPLAY_BATCH_ENTRIES: usize = 1024;
+
     fn normalized(self) -> Self {
         Self {
             recovery_mode: self.recovery_mode,
             flush_policy: self.flush_policy.normalized(),
+            buffer_bytes: self.buffer_bytes,
+            replay_batch_entries: self.replay_batch_entries.max(1),
+            compression: self.compression,
+        }
+    }
+}
+
+impl Default for WalOptions {
+    fn default() -> Self {
+        Self {
+            recovery_mode: WalRecoveryMode::default(),
+            flush_policy: WalFlushPolicy::default(),
+            buffer_bytes: Self::DEFAULT_BUFFER_BYTES,
+            replay_batch_entries: Self::DEFAULT_REPLAY_BATCH_ENTRIES,
+            compression: WalCompression::default(),
+        }
+    }
+}
+
+/// Counters reported while a WAL is being replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayProgress {
+    pub records_replayed: u64,
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+}
+
+/// Optional progress hook for long replays (e.g. to drive a CLI progress bar).
+///
+/// The callback runs inline on the replay path, so it should be cheap. It fires
+/// whenever `record_interval` records or `byte_interval` bytes have been read
+/// since the previous report, and once more when the replay finishes.
+#[derive(Clone)]
+pub struct ReplayProgressReporter {
+    callback: Arc<dyn Fn(ReplayProgress) + Send + Sync>,
+    record_interval: u64,
+    byte_interval: u64,
+}
+
+impl ReplayProgressReporter {
+    pub const DEFAULT_RECORD_INTERVAL: u64 = 10_000;
+    pub const DEFAULT_BYTE_INTERVAL: u64 = 16 * 1024 * 1024;
+
+    pub fn new(callback: impl Fn(ReplayProgress) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+            record_interval: Self::DEFAULT_RECORD_INTERVAL,
+            byte_interval: Self::DEFAULT_BYTE_INTERVAL,
         }
     }
+
+    pub fn with_record_interval(mut self, records: u64) -> Self {
+        self.record_interval = records.max(1);
+        self
+    }
+
+    pub fn with_byte_interval(mut self, bytes: u64) -> Self {
+        self.byte_interval = bytes.max(1);
+        self
+    }
+}
+
+impl std::fmt::Debug for ReplayProgressReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplayProgressReporter")
+            .field("record_interval", &self.record_interval)
+            .field("byte_interval", &self.byte_interval)
+            .finish_non_exhaustive()
+    }
 }
 
 pub struct Wal {
@@ -90,6 +227,7 @@ pub struct Wal {
     cipher: Arc<dyn AtRestCipher>,
     recovery_mode: WalRecoveryMode,
     flush_policy: WalFlushPolicy,
+    compression: WalCompression,
     pending_appends: usize,
     last_flush_at: Instant,
 }
@@ -139,28 +277,45 @@ impl Wal {
             .await?;
 
         let mut wal = Self {
-            file: BufWriter::new(file),
+            file: BufWriter::with_capacity(options.buffer_bytes, file),
             current_lsn: AtomicU64::new(0),
             durable_lsn: AtomicU64::new(0),
             cipher,
             recovery_mode: options.recovery_mode,
             flush_policy: options.flush_policy,
+            compression: options.compression,
             pending_appends: 0,
             last_flush_at: Instant::now(),
         };
 
         // Recover the latest committed LSN at startup so new appends remain monotonic.
-        wal.scan_entries(|_lsn, _payload| Ok(())).await?;
+        wal.scan_entries(|_lsn, _payload| Ok(()), None).await?;
 
         Ok(wal)
     }
 
     /// Append an entry to the WAL. Returns the assigned LSN.
     /// Format: [LSN: 8 bytes][CRC: 4 bytes][Len: 4 bytes][Payload: Len bytes]
+    ///
+    /// With compression enabled the payload is compressed, then encrypted,
+    /// and stored behind a one-byte codec id; the top bit of `Len` marks such
+    /// records.
     pub async fn append(&mut self, payload: &[u8]) -> Result<u64, WalError> {
-        let encrypted_payload = self.cipher.encrypt(payload)?;
+        let encrypted_payload = match self.compression.codec() {
+            None => self.cipher.encrypt(payload)?,
+            Some(codec) => {
+                let encrypted = self.cipher.encrypt(&self.compression.compress(payload)?)?;
+                let mut framed = Vec::with_capacity(encrypted.len() + 1);
+                framed.push(codec);
+                framed.extend_from_slice(&encrypted);
+                framed
+            }
+        };
         let lsn = self.current_lsn.fetch_add(1, Ordering::SeqCst) + 1;
-        let len = encrypted_payload.len() as u32;
+        let mut len = encrypted_payload.len() as u32;
+        if self.compression.codec().is_some() {
+            len |= COMPRESSED_FLAG;
+        }
 
         let mut hasher = Hasher::new();
         hasher.update(&encrypted_payload);
@@ -185,6 +340,37 @@ impl Wal {
         self.durable_flush().await
     }
 
+    /// Flush only if entries were appended since the last flush. Returns
+    /// whether a flush happened.
+    pub async fn flush_pending(&mut self) -> Result<bool, WalError> {
+        if self.pending_appends == 0 {
+            return Ok(false);
+        }
+        self.durable_flush().await?;
+        Ok(true)
+    }
+
+    /// Fails when the WAL file can no longer be written, e.g. after it was
+    /// made read-only. Does not append or flush.
+    pub async fn check_writable(&self) -> Result<(), WalError> {
+        let metadata = self.file.get_ref().metadata().await?;
+        if metadata.permissions().readonly() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "wal file is read-only",
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Size of the WAL in bytes: the file on disk plus appends still held in
+    /// the write buffer.
+    pub async fn size_bytes(&self) -> Result<u64, WalError> {
+        let metadata = self.file.get_ref().metadata().await?;
+        Ok(metadata.len() + self.file.buffer().len() as u64)
+    }
+
     pub fn durable_lsn(&self) -> u64 {
         self.durable_lsn.load(Ordering::SeqCst)
     }
@@ -193,6 +379,12 @@ impl Wal {
         self.flush_policy
     }
 
+    /// Change when subsequent appends are flushed. Already-buffered entries
+    /// stay pending until the next flush.
+    pub fn set_flush_policy(&mut self, policy: WalFlushPolicy) {
+        self.flush_policy = policy.normalized();
+    }
+
     pub fn recovery_mode(&self) -> WalRecoveryMode {
         self.recovery_mode
     }
@@ -217,7 +409,19 @@ impl Wal {
     where
         F: FnMut(u64, Vec<u8>) -> Result<(), WalError>,
     {
-        self.scan_entries(&mut callback).await
+        self.scan_entries(&mut callback, None).await
+    }
+
+    /// Like [`Wal::replay`], additionally reporting progress through `progress`.
+    pub async fn replay_with_progress<F>(
+        &mut self,
+        mut callback: F,
+        progress: &ReplayProgressReporter,
+    ) -> Result<u64, WalError>
+    where
+        F: FnMut(u64, Vec<u8>) -> Result<(), WalError>,
+    {
+        self.scan_entries(&mut callback, Some(progress)).await
     }
 
     async fn flush_if_needed(&mut self) -> Result<(), WalError> {
@@ -234,7 +438,11 @@ impl Wal {
         Ok(())
     }
 
-    async fn scan_entries<F>(&mut self, mut callback: F) -> Result<u64, WalError>
+    async fn scan_entries<F>(
+        &mut self,
+        mut callback: F,
+        progress: Option<&ReplayProgressReporter>,
+    ) -> Result<u64, WalError>
     where
         F: FnMut(u64, Vec<u8>) -> Result<(), WalError>,
     {
@@ -245,6 +453,12 @@ impl Wal {
         let mut last_lsn = 0;
         let mut last_good_offset = 0;
         let total_len = file.metadata().await?.len();
+        let mut records_replayed = 0u64;
+        let mut reported = ReplayProgress {
+            records_replayed: 0,
+            bytes_read: 0,
+            total_bytes: total_len,
+        };
 
         loop {
             let entry_start = file.stream_position().await?;
@@ -268,8 +482,8 @@ impl Wal {
                 Err(e) => return Err(WalError::Io(e)),
             };
 
-            let len = match file.read_u32().await {
-                Ok(v) => v as usize,
+            let (len, compressed) = match file.read_u32().await {
+                Ok(v) => ((v & !COMPRESSED_FLAG) as usize, v & COMPRESSED_FLAG != 0),
                 Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
                     truncate_tail(file, last_good_offset).await?;
                     break;
@@ -303,10 +517,32 @@ impl Wal {
                 return Err(WalError::CrcMismatch);
             }
 
-            let decrypted_payload = self.cipher.decrypt(&payload)?;
+            let decrypted_payload = if compressed {
+                let (&codec, body) = payload.split_first().ok_or(WalError::CorruptEntry)?;
+                decompress(codec, &self.cipher.decrypt(body)?)?
+            } else {
+                self.cipher.decrypt(&payload)?
+            };
             callback(lsn, decrypted_payload)?;
             last_lsn = lsn;
             last_good_offset = file.stream_position().await?;
+            records_replayed += 1;
+
+            if let Some(progress) = progress {
+                if records_replayed - reported.records_replayed >= progress.record_interval
+                    || last_good_offset - reported.bytes_read >= progress.byte_interval
+                {
+                    reported.records_replayed = records_replayed;
+                    reported.bytes_read = last_good_offset;
+                    (progress.callback)(reported);
+                }
+            }
+        }
+
+        if let Some(progress) = progress {
+            reported.records_replayed = records_replayed;
+            reported.bytes_read = last_good_offset;
+            (progress.callback)(reported);
         }
 
         file.seek(std::io::SeekFrom::End(0)).await?;
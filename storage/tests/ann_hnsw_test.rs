@@ -92,6 +92,42 @@ fn test_hnsw_recall_medium() {
     recall_experiment(1_000, 128, 10, 50, 0.85);
 }
 
+#[cfg(feature = "hnsw")]
+#[test]
+fn test_exact_search_accuracy_matches_linear_scan() {
+    use storage::hyper_index::HyperIndex;
+    use storage::index::SearchAccuracy;
+
+    let (n, dim, k) = (1_000usize, 128usize, 10usize);
+    let mut linear = LinearAnnIndex::new();
+    let mut hyper = HyperIndex::with_vector_index(Box::new(HnswIndex::new()));
+    for i in 0..n as u64 {
+        let v = make_vector(i, dim);
+        linear.insert(i, &v);
+        hyper.insert_node(i, v);
+    }
+
+    let ids = |hits: &[(u64, f32)]| hits.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    let mut approximate_recall = 0.0_f32;
+    for qi in 0..50u64 {
+        let query = make_vector(qi + n as u64 * 17, dim);
+        let gt = linear.search(&query, k);
+        let exact = hyper.search_vector_with_accuracy(&query, k, SearchAccuracy::Exact);
+        assert_eq!(
+            ids(&exact),
+            ids(&gt),
+            "exact search must return the true top-{k}"
+        );
+        let approximate = hyper.search_vector_with_accuracy(&query, k, SearchAccuracy::Approximate);
+        approximate_recall += recall_at_k(&gt, &approximate);
+    }
+    let approximate_recall = approximate_recall / 50.0;
+    assert!(
+        approximate_recall >= 0.85,
+        "approximate recall@{k} = {approximate_recall:.3} < 0.85"
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Correctness / API parity with LinearAnnIndex
 // ---------------------------------------------------------------------------
@@ -156,6 +192,45 @@ fn test_hnsw_dimension_mismatch_skipped() {
     assert_eq!(hnsw.len(), 1, "mismatched-dim insert must be ignored");
 }
 
+// ---------------------------------------------------------------------------
+// Seeded restore order
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "hnsw")]
+#[test]
+fn test_hnsw_restore_order_seed_makes_batch_builds_reproducible() {
+    let (n, dim, k) = (500u64, 32, 10);
+    let entries: Vec<(u64, Vec<f32>)> = (0..n).map(|i| (i, make_vector(i, dim))).collect();
+    let mut reversed = entries.clone();
+    reversed.reverse();
+    let queries: Vec<Vec<f32>> = (0..20u64).map(|q| make_vector(q + n * 17, dim)).collect();
+
+    // Same seed: identical orderings even when the batch arrives in a
+    // different order.
+    let mut first = HnswIndex::with_restore_order_seed(7);
+    first.insert_batch(&entries);
+    let mut second = HnswIndex::with_restore_order_seed(7);
+    second.insert_batch(&reversed);
+    assert_eq!(first.len(), n as usize);
+    for query in &queries {
+        assert_eq!(first.search(query, k), second.search(query, k));
+    }
+
+    // Other seeds may build a different graph, but recall stays bounded.
+    let mut linear = LinearAnnIndex::new();
+    linear.insert_batch(&entries);
+    for seed in [7, 8, 9] {
+        let mut hnsw = HnswIndex::with_restore_order_seed(seed);
+        hnsw.insert_batch(&entries);
+        let recall: f32 = queries
+            .iter()
+            .map(|query| recall_at_k(&linear.search(query, k), &hnsw.search(query, k)))
+            .sum::<f32>()
+            / queries.len() as f32;
+        assert!(recall >= 0.9, "seed {seed}: recall@{k} = {recall:.3}");
+    }
+}
+
 // ---------------------------------------------------------------------------
 // HyperIndex integration (feature-agnostic)
 // ---------------------------------------------------------------------------
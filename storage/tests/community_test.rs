@@ -1,4 +1,4 @@
-use storage::community::{CommunityEngine, DeterministicSummarizer};
+use storage::community::{CommunityEngine, DeterministicSummarizer, WeightNormalization};
 use storage::index::AdjacencyGraph;
 
 fn sample_graph_two_clusters() -> AdjacencyGraph {
@@ -29,6 +29,44 @@ fn test_leiden_detects_multiple_communities() {
     assert!(level0.communities.len() >= 2);
 }
 
+/// Two dense clusters whose relations use different weight scales: cluster
+/// B's `mentions` edges are all tiny, so the `works_with` bridges outweigh them.
+fn mixed_scale_graph() -> AdjacencyGraph {
+    let mut graph = AdjacencyGraph::new();
+    for (source, target) in [(1, 2), (2, 3), (1, 3), (3, 4), (2, 4)] {
+        graph.add_edge(source, target, "works_with", 0.9);
+    }
+    for (source, target) in [(10, 11), (11, 12), (10, 12), (12, 13), (11, 13)] {
+        graph.add_edge(source, target, "mentions", 0.01);
+    }
+    graph.add_edge(4, 10, "works_with", 0.4);
+    graph.add_edge(4, 11, "works_with", 0.4);
+    graph
+}
+
+fn level0_sizes(normalization: WeightNormalization) -> Vec<usize> {
+    let mut engine =
+        CommunityEngine::new(mixed_scale_graph()).with_weight_normalization(normalization);
+    engine.rebuild_hierarchy(1, &DeterministicSummarizer);
+    let mut sizes: Vec<usize> = engine.hierarchy()[0]
+        .communities
+        .iter()
+        .map(|community| community.node_ids.len())
+        .collect();
+    sizes.sort_unstable();
+    sizes
+}
+
+#[test]
+fn test_relation_weight_normalization_balances_communities() {
+    // Raw weights let the tiny-scale cluster dissolve into its neighbour.
+    assert_eq!(level0_sizes(WeightNormalization::None), vec![8]);
+
+    for normalization in [WeightNormalization::MinMax, WeightNormalization::ZScore] {
+        assert_eq!(level0_sizes(normalization), vec![4, 4], "{normalization:?}");
+    }
+}
+
 #[test]
 fn test_hierarchical_levels_are_generated() {
     let graph = sample_graph_two_clusters();
@@ -66,3 +104,104 @@ fn test_incremental_update_refreshes_summaries() {
         .any(|community| community.node_ids.contains(&13));
     assert!(contains_new_node);
 }
+
+#[test]
+fn test_textual_summarizer_quotes_member_node_text() {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use storage::community::TextualSummarizer;
+
+    let texts: HashMap<u64, String> = [
+        (
+            1,
+            "Battery supply deals anchor the EV roadmap. Later details.",
+        ),
+        (2, "Cell chemistry research lowers cost."),
+        (3, "Gigafactory expansion continues in Nevada."),
+        (10, "Recycling policy tightens across the EU."),
+        (11, "Subsidies favour domestic recyclers."),
+        (12, "Audits target cobalt sourcing."),
+    ]
+    .into_iter()
+    .map(|(id, text)| (id, text.to_string()))
+    .collect();
+
+    let mut engine = CommunityEngine::new(sample_graph_two_clusters());
+    engine.rebuild_hierarchy(1, &TextualSummarizer::new(Arc::new(texts.clone())));
+
+    let level0 = &engine.hierarchy()[0];
+    for community in &level0.communities {
+        let summary = engine
+            .summaries()
+            .iter()
+            .find(|s| s.level == 0 && s.community_id == community.id)
+            .unwrap();
+        assert!(!summary.summary.starts_with("L0-C"));
+        // The summary quotes the leading sentence of at least one member.
+        assert!(community.node_ids.iter().any(|id| {
+            let leading = texts[id].split_inclusive('.').next().unwrap();
+            summary.summary.contains(leading)
+        }));
+        // Text from the other cluster never leaks in.
+        assert!(texts
+            .iter()
+            .filter(|(id, _)| !community.node_ids.contains(id))
+            .all(|(_, text)| !summary.summary.contains(text.split('.').next().unwrap())));
+    }
+    assert!(!engine
+        .summaries()
+        .iter()
+        .any(|s| s.summary.contains("Later details")));
+}
+
+#[tokio::test]
+async fn test_community_engine_save_and_load_skips_recompute() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use storage::community::CommunitySummarizer;
+
+    struct CountingSummarizer(AtomicUsize);
+
+    impl CommunitySummarizer for CountingSummarizer {
+        fn summarize(
+            &self,
+            level: usize,
+            community_id: usize,
+            node_ids: &[u64],
+            top_nodes: &[u64],
+        ) -> String {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            DeterministicSummarizer.summarize(level, community_id, node_ids, top_nodes)
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("communities.rkyv");
+    let summarizer = CountingSummarizer(AtomicUsize::new(0));
+
+    let mut engine = CommunityEngine::new(sample_graph_two_clusters());
+    assert!(engine.rebuild_if_stale("wal-lsn-7", 3, &summarizer));
+    assert_eq!(engine.snapshot_id(), Some("wal-lsn-7"));
+    let calls_after_build = summarizer.0.load(Ordering::SeqCst);
+    assert!(calls_after_build > 0);
+    engine.save(&path).await.unwrap();
+
+    let mut reloaded = CommunityEngine::load(&path, sample_graph_two_clusters())
+        .await
+        .unwrap();
+    assert_eq!(reloaded.snapshot_id(), Some("wal-lsn-7"));
+    assert_eq!(reloaded.hierarchy(), engine.hierarchy());
+    assert_eq!(reloaded.summaries(), engine.summaries());
+    assert_eq!(
+        reloaded.fastgraphrag_top_nodes(),
+        engine.fastgraphrag_top_nodes()
+    );
+
+    assert!(!reloaded.is_stale_for("wal-lsn-7"));
+    assert!(!reloaded.rebuild_if_stale("wal-lsn-7", 3, &summarizer));
+    assert_eq!(summarizer.0.load(Ordering::SeqCst), calls_after_build);
+
+    assert!(reloaded.is_stale_for("wal-lsn-8"));
+    assert!(reloaded.rebuild_if_stale("wal-lsn-8", 3, &summarizer));
+    assert!(summarizer.0.load(Ordering::SeqCst) > calls_after_build);
+    assert_eq!(reloaded.snapshot_id(), Some("wal-lsn-8"));
+}
@@ -0,0 +1,66 @@
+//! Repositories written by earlier releases must keep opening.
+
+use std::path::Path;
+
+use storage::repo::{RepoError, Repository};
+use storage::snapshot::SnapshotManager;
+use tempfile::tempdir;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// Copies a fixture so opening it (which may append to the WAL) leaves the
+/// checked-in file untouched.
+fn copy_fixture(name: &str, dest: &Path) {
+    std::fs::copy(Path::new(FIXTURES).join(name), dest).unwrap();
+}
+
+#[tokio::test]
+async fn test_replays_wal_written_by_baseline_format() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("baseline.wal");
+    copy_fixture("baseline.wal", &wal_path);
+
+    let repo = Repository::open(&wal_path).await.unwrap();
+
+    assert_eq!(repo.list_node_ids().await, vec![1, 2, 3, 5]);
+    let toyota = repo.get_node(1).await.unwrap();
+    assert_eq!(toyota.data, "Toyota");
+    assert_eq!(toyota.metadata["entity_type"], "Company");
+    assert!(matches!(repo.get_node(4).await, Err(RepoError::NotFound)));
+    assert_eq!(
+        repo.get_edge_metadata(1, 2, "develops").await["source"],
+        "report.pdf"
+    );
+    let graph = repo.graph_index().await;
+    assert!(graph.neighbors(3).iter().any(|(target, _, _)| *target == 2));
+    assert_eq!(repo.check_idempotency("hash-4").await, Some(vec![4]));
+}
+
+#[tokio::test]
+async fn test_restores_backup_written_before_the_snapshot_header() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("baseline.wal");
+    let snapshot_dir = dir.path().join("snapshots");
+    std::fs::create_dir(&snapshot_dir).unwrap();
+    copy_fixture("baseline.wal", &wal_path);
+    copy_fixture(
+        "baseline_backup.rkyv",
+        &snapshot_dir.join("snapshot_00000000000000000005.rkyv"),
+    );
+
+    let report = SnapshotManager::new(&snapshot_dir).verify(5).await.unwrap();
+    assert_eq!(report.node_count, 4);
+    assert_eq!(report.edge_count, 2);
+
+    let repo = Repository::open_with_snapshots(&wal_path, &snapshot_dir)
+        .await
+        .unwrap();
+    repo.restore_from_latest_backup().await.unwrap();
+
+    assert_eq!(repo.list_node_ids().await, vec![1, 2, 3, 5]);
+    assert_eq!(
+        repo.get_edge_metadata(1, 2, "develops").await["source"],
+        "report.pdf"
+    );
+    assert_eq!(repo.check_idempotency("hash-4").await, Some(vec![4]));
+}
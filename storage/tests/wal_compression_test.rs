@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use alayasiki_core::model::Node;
+use storage::crypto::{InMemoryKmsKeyProvider, KmsHookCipher};
+use storage::repo::Repository;
+use storage::wal::{Wal, WalCompression, WalOptions};
+use tempfile::tempdir;
+
+fn compression(compression: WalCompression) -> WalOptions {
+    WalOptions {
+        compression,
+        ..WalOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn mixed_codec_wal_replays_compressed_and_encrypted_entries() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("compressed.wal");
+    let kms = Arc::new(InMemoryKmsKeyProvider::from_keys([(
+        "kms-key-acme",
+        vec![0x42, 0x99, 0x11, 0xA7],
+    )]));
+    let cipher = Arc::new(KmsHookCipher::new("kms-key-acme", kms));
+    let text = "the quick brown fox jumps over the lazy dog. ".repeat(200);
+
+    let codecs = [
+        WalCompression::None,
+        WalCompression::Lz4,
+        WalCompression::Zstd(3),
+    ];
+    let mut sizes = Vec::new();
+    for codec in codecs {
+        let before = tokio::fs::metadata(&path).await.map_or(0, |m| m.len());
+        let mut wal = Wal::open_with_cipher_and_options(&path, cipher.clone(), compression(codec))
+            .await
+            .unwrap();
+        wal.append(format!("{codec:?}:{text}").as_bytes())
+            .await
+            .unwrap();
+        wal.flush().await.unwrap();
+        sizes.push(tokio::fs::metadata(&path).await.unwrap().len() - before);
+    }
+    assert!(
+        sizes[1] < sizes[0] / 4,
+        "lz4 record not compressed: {sizes:?}"
+    );
+    assert!(
+        sizes[2] < sizes[0] / 4,
+        "zstd record not compressed: {sizes:?}"
+    );
+
+    let mut recovered = Vec::new();
+    let mut wal = Wal::open_with_cipher(&path, cipher).await.unwrap();
+    let last_lsn = wal
+        .replay(|_lsn, payload| {
+            recovered.push(String::from_utf8(payload).unwrap());
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(last_lsn, 3);
+    let expected: Vec<String> = codecs
+        .iter()
+        .map(|codec| format!("{codec:?}:{text}"))
+        .collect();
+    assert_eq!(recovered, expected);
+}
+
+#[tokio::test]
+async fn repository_recovers_nodes_from_compressed_wal() {
+    let dir = tempdir().unwrap();
+    let wal_path = dir.path().join("repo_compressed.wal");
+    let data = "text heavy node body ".repeat(100);
+
+    {
+        let repo = Repository::open_with_options(&wal_path, compression(WalCompression::Zstd(0)))
+            .await
+            .unwrap();
+        repo.put_node(Node::new(7, vec![0.25, 0.5], data.clone()))
+            .await
+            .unwrap();
+        repo.flush().await.unwrap();
+    }
+
+    let repo = Repository::open(&wal_path).await.unwrap();
+    let node = repo.get_node(7).await.unwrap();
+    assert_eq!(node.data, data);
+    assert_eq!(node.embedding, vec![0.25, 0.5]);
+}
@@ -1,7 +1,9 @@
 use std::thread;
 use std::time::Duration;
 
-use storage::wal::{Wal, WalFlushPolicy, WalOptions, WalRecoveryMode};
+use storage::wal::{
+    ReplayProgress, ReplayProgressReporter, Wal, WalFlushPolicy, WalOptions, WalRecoveryMode,
+};
 use tempfile::tempdir;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
@@ -151,3 +153,78 @@ async fn wal_interval_flush_policy_flushes_on_next_append_after_interval() {
     wal.append(b"Entry 2").await.unwrap();
     assert!(tokio::fs::metadata(&path).await.unwrap().len() > 0);
 }
+
+#[tokio::test]
+async fn repository_open_reports_monotonic_replay_progress() {
+    use alayasiki_core::model::Node;
+    use std::sync::{Arc, Mutex};
+    use storage::repo::Repository;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("replay_progress.wal");
+    {
+        let repo = Repository::open(&path).await.unwrap();
+        for id in 0..10u64 {
+            repo.put_node(Node::new(id, vec![id as f32, 1.0], format!("node-{id}")))
+                .await
+                .unwrap();
+        }
+    }
+    let total_bytes = tokio::fs::metadata(&path).await.unwrap().len();
+
+    let reports: Arc<Mutex<Vec<ReplayProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let progress =
+        ReplayProgressReporter::new(move |p| sink.lock().unwrap().push(p)).with_record_interval(3);
+
+    let repo = Repository::open_with_replay_progress(&path, WalOptions::default(), progress)
+        .await
+        .unwrap();
+    assert_eq!(repo.list_node_ids().await.len(), 10);
+
+    let reports = reports.lock().unwrap();
+    // Every 3 records (3, 6, 9) plus the final report.
+    let records: Vec<u64> = reports.iter().map(|p| p.records_replayed).collect();
+    assert_eq!(records, vec![3, 6, 9, 10]);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].bytes_read < pair[1].bytes_read));
+    assert!(reports.iter().all(|p| p.total_bytes == total_bytes));
+    assert_eq!(reports.last().unwrap().bytes_read, total_bytes);
+}
+
+#[tokio::test]
+async fn repository_interval_policy_flushes_in_background_without_explicit_flush() {
+    use alayasiki_core::model::Node;
+    use storage::repo::Repository;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("background_flush.wal");
+    {
+        let repo = Repository::open_with_options(
+            &path,
+            WalOptions {
+                flush_policy: WalFlushPolicy::Interval(Duration::from_millis(50)),
+                buffer_bytes: 64 * 1024,
+                ..WalOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        repo.put_node(Node::new(1, vec![1.0], "buffered".to_string()))
+            .await
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while repo.current_snapshot_id().await != "wal-lsn-1" {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "background flush never ran"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    let reopened = Repository::open(&path).await.unwrap();
+    assert_eq!(reopened.list_node_ids().await, vec![1]);
+}